@@ -0,0 +1,238 @@
+use near_primitives::types::AccountId;
+use rocket::State;
+use rocket::data::{Data, Limits};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+use std::time::Instant;
+use tracing::{error, info};
+
+use crate::auth::AdminAuth;
+use crate::cache::{
+    BountyClaimsCache, BountyStore, DAO_CACHE_COUNTER, FtMetadataCache, HitCounter, LockupCache,
+    PROPOSAL_CACHE_COUNTER, ProposalCache, ProposalStore, PurgedEntries, StakingPoolCache,
+    force_refresh_dao_cache, get_latest_dao_cache, missing_dao_count, purge_dao_caches,
+};
+use crate::config::get_config;
+use crate::fetch_error_status;
+use crate::rpc_client;
+use crate::snapshot::{DaoSnapshot, ImportedEntries, dao_snapshot, restore_dao_snapshot};
+
+#[derive(Serialize)]
+pub struct HitRate {
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of hits, null before the first lookup
+    pub hit_rate: Option<f64>,
+}
+
+impl From<&HitCounter> for HitRate {
+    fn from(counter: &HitCounter) -> Self {
+        let (hits, misses) = counter.snapshot();
+        let total = hits + misses;
+        HitRate {
+            hits,
+            misses,
+            hit_rate: (total > 0).then(|| hits as f64 / total as f64),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct DaoCacheEntry {
+    pub dao_id: String,
+    pub proposals: usize,
+    pub age_secs: u64,
+    /// Size of the proposals and policy serialized as JSON, a rough memory estimate
+    pub estimated_bytes: usize,
+}
+
+#[derive(Serialize)]
+pub struct CacheStats {
+    pub daos: Vec<DaoCacheEntry>,
+    pub estimated_bytes: usize,
+    pub dao_cache: HitRate,
+    pub proposal_cache: HitRate,
+    pub proposal_cache_entries: usize,
+    pub bounty_entries: usize,
+    pub bounty_claims_entries: usize,
+    pub ft_metadata_entries: usize,
+    pub staking_pool_entries: usize,
+    pub lockup_entries: usize,
+    /// Accounts currently answered with 404 without asking RPC
+    pub missing_daos: usize,
+}
+
+#[derive(Serialize)]
+pub struct RefreshResult {
+    pub dao_id: String,
+    pub proposals: usize,
+    pub elapsed_ms: u64,
+}
+
+#[post("/admin/cache/purge/<dao_id>")]
+pub fn purge_dao_cache(
+    _admin: AdminAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    proposal_cache: &State<ProposalCache>,
+    bounty_store: &State<BountyStore>,
+    bounty_claims_cache: &State<BountyClaimsCache>,
+) -> Result<Json<PurgedEntries>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+
+    let purged = purge_dao_caches(
+        dao_id.as_str(),
+        store,
+        proposal_cache,
+        bounty_store,
+        bounty_claims_cache,
+    );
+    info!(%dao_id, ?purged, "Purged DAO caches");
+
+    Ok(Json(purged))
+}
+
+#[post("/admin/cache/refresh/<dao_id>")]
+pub async fn refresh_dao_cache(
+    _admin: AdminAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+) -> Result<Json<RefreshResult>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let started = Instant::now();
+    let refreshed = force_refresh_dao_cache(&client, store, &dao_id)
+        .await
+        .map_err(|e| {
+            error!(%dao_id, error = ?e, "Forced refresh failed");
+            fetch_error_status(&e)
+        })?;
+
+    Ok(Json(RefreshResult {
+        dao_id: dao_id.to_string(),
+        proposals: refreshed.proposals.len(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
+#[get("/admin/cache/stats")]
+#[allow(clippy::too_many_arguments)]
+pub async fn cache_stats(
+    _admin: AdminAuth,
+    store: &State<ProposalStore>,
+    proposal_cache: &State<ProposalCache>,
+    bounty_store: &State<BountyStore>,
+    bounty_claims_cache: &State<BountyClaimsCache>,
+    ft_metadata_cache: &State<FtMetadataCache>,
+    staking_pool_cache: &State<StakingPoolCache>,
+    lockup_cache: &State<LockupCache>,
+) -> Json<CacheStats> {
+    let mut daos: Vec<DaoCacheEntry> = store
+        .iter()
+        .map(|cached| DaoCacheEntry {
+            dao_id: cached.key().clone(),
+            proposals: cached.proposals.len(),
+            age_secs: cached.last_updated.elapsed().as_secs(),
+            estimated_bytes: serde_json::to_vec(&*cached.proposals).map_or(0, |json| json.len())
+                + serde_json::to_vec(&cached.policy).map_or(0, |json| json.len()),
+        })
+        .collect();
+    daos.sort_by_key(|dao| std::cmp::Reverse(dao.estimated_bytes));
+
+    Json(CacheStats {
+        estimated_bytes: daos.iter().map(|dao| dao.estimated_bytes).sum(),
+        daos,
+        dao_cache: HitRate::from(&*DAO_CACHE_COUNTER),
+        proposal_cache: HitRate::from(&*PROPOSAL_CACHE_COUNTER),
+        proposal_cache_entries: proposal_cache.len(),
+        bounty_entries: bounty_store.len(),
+        bounty_claims_entries: bounty_claims_cache.len(),
+        ft_metadata_entries: ft_metadata_cache.len(),
+        staking_pool_entries: staking_pool_cache.0.count().await,
+        lockup_entries: lockup_cache.0.count().await,
+        missing_daos: missing_dao_count(),
+    })
+}
+
+#[get("/admin/export/<dao_id>")]
+pub async fn export_dao(
+    _admin: AdminAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    proposal_cache: &State<ProposalCache>,
+    ft_metadata_cache: &State<FtMetadataCache>,
+) -> Result<Json<DaoSnapshot>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "");
+    let cached = get_latest_dao_cache(&client, store, &dao_id, life_time)
+        .await
+        .map_err(|e| {
+            error!(%dao_id, error = ?e, "Failed to fetch DAO for export");
+            fetch_error_status(&e)
+        })?;
+
+    let snapshot = dao_snapshot(&dao_id, &cached, proposal_cache, ft_metadata_cache);
+    info!(
+        %dao_id,
+        proposals = snapshot.proposals.len(),
+        txs_logs = snapshot.txs_logs.len(),
+        "Exported DAO snapshot"
+    );
+    Ok(Json(snapshot))
+}
+
+/// Name of the data limit for snapshots sent to POST /admin/import, set from
+/// server.import_limit_mb
+pub const SNAPSHOT_LIMIT: &str = "dao-snapshot";
+
+#[post("/admin/import", format = "json", data = "<data>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_dao(
+    _admin: AdminAuth,
+    data: Data<'_>,
+    limits: &Limits,
+    store: &State<ProposalStore>,
+    proposal_cache: &State<ProposalCache>,
+    bounty_store: &State<BountyStore>,
+    bounty_claims_cache: &State<BountyClaimsCache>,
+    ft_metadata_cache: &State<FtMetadataCache>,
+) -> Result<Json<ImportedEntries>, Status> {
+    // Only snapshots get the larger limit, other JSON bodies keep the default one
+    let limit = limits.get(SNAPSHOT_LIMIT).unwrap_or(Limits::JSON);
+    let body = data
+        .open(limit)
+        .into_bytes()
+        .await
+        .map_err(|_| Status::BadRequest)?;
+    if !body.is_complete() {
+        return Err(Status::PayloadTooLarge);
+    }
+    let snapshot: DaoSnapshot = serde_json::from_slice(&body).map_err(|e| {
+        error!(error = %e, "Invalid DAO snapshot");
+        Status::UnprocessableEntity
+    })?;
+    // Checked before anything is purged, a snapshot that can't be loaded leaves the
+    // cache as it was
+    let dao_id = snapshot.validate().map_err(|e| {
+        error!(dao_id = %snapshot.dao_id, error = %e, "Refusing DAO snapshot");
+        Status::UnprocessableEntity
+    })?;
+
+    // Nothing from before the import is mixed with the snapshot
+    purge_dao_caches(
+        dao_id.as_str(),
+        store,
+        proposal_cache,
+        bounty_store,
+        bounty_claims_cache,
+    );
+    let imported =
+        restore_dao_snapshot(&dao_id, snapshot, store, proposal_cache, ft_metadata_cache);
+    info!(%dao_id, ?imported, "Imported DAO snapshot");
+
+    Ok(Json(imported))
+}
@@ -0,0 +1,85 @@
+/// Whole token amount like "1.5" in the smallest unit. None for malformed amounts, negative
+/// amounts, more significant fraction digits than `decimals`, or values beyond u128.
+pub fn parse_units(amount: &str, decimals: u8) -> Option<u128> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return None;
+    }
+
+    let scale = 10u128.checked_pow(decimals.into())?;
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u128>().ok()?
+    };
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        let padded = format!("{:0<width$}", fraction, width = decimals as usize);
+        padded.parse::<u128>().ok()?
+    };
+    whole.checked_mul(scale)?.checked_add(fraction)
+}
+
+/// Raw token amount as a decimal string in whole units, without trailing zeros
+pub fn format_units(raw: u128, decimals: u8) -> String {
+    let Some(scale) = 10u128.checked_pow(decimals.into()) else {
+        return raw.to_string();
+    };
+    let fraction = format!("{:0width$}", raw % scale, width = decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (raw / scale).to_string()
+    } else {
+        format!("{}.{}", raw / scale, fraction)
+    }
+}
+
+/// Raw token amount in whole units with exactly `precision` fraction digits, rounded half
+/// up. Empty for amounts that aren't raw integers.
+pub fn format_units_fixed(raw: &str, decimals: u8, precision: u8) -> String {
+    let Ok(raw) = raw.parse::<u128>() else {
+        return String::new();
+    };
+    let Some(scale) = 10u128.checked_pow(decimals.into()) else {
+        return raw.to_string();
+    };
+
+    // Drop the digits beyond the precision, rounding half up
+    let (mut whole, mut fraction) = (raw / scale, raw % scale);
+    let kept = precision.min(decimals);
+    let dropped = 10u128.pow((decimals - kept).into());
+    let remainder = fraction % dropped;
+    fraction /= dropped;
+    if remainder > 0 && remainder >= dropped - remainder {
+        fraction += 1;
+        if fraction == 10u128.pow(kept.into()) {
+            fraction = 0;
+            whole += 1;
+        }
+    }
+
+    if precision == 0 {
+        return whole.to_string();
+    }
+    // Tokens with fewer decimals than the precision are padded with zeros
+    let fraction = match kept {
+        0 => String::new(),
+        _ => format!("{:0width$}", fraction, width = kept as usize),
+    };
+    format!(
+        "{}.{:0<width$}",
+        whole,
+        fraction,
+        width = precision as usize
+    )
+}
@@ -0,0 +1,629 @@
+use chrono::{Datelike, TimeZone, Utc};
+use rocket::form::FromFormField;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use utoipa::ToSchema;
+
+use crate::amounts::format_units;
+use crate::filters::proposal_categories;
+use crate::policy::proposal_kind_label;
+use crate::scraper::{
+    Action, PaymentInfo, Policy, Proposal, ProposalStatus, ProposalType, TxMetadata, Vote,
+};
+
+const NANOS_PER_SECOND: f64 = 1_000_000_000.0;
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct DaoStats {
+    pub total_proposals: usize,
+    /// Keyed by display status, so InProgress proposals past the voting period count as Expired
+    pub by_status: BTreeMap<String, usize>,
+    /// A proposal can belong to several categories, e.g. lockup payments
+    #[schema(value_type = BTreeMap<String, usize>)]
+    pub by_category: BTreeMap<&'static str, usize>,
+    /// Keyed by submission month as "YYYY-MM" (UTC)
+    pub by_month: BTreeMap<String, usize>,
+    pub unique_proposers: usize,
+    pub unique_voters: usize,
+    /// Average seconds between submission and the last vote of approved proposals
+    pub average_time_to_approval_secs: Option<f64>,
+    /// Approved proposals whose transaction log was cached and used for the average
+    pub approval_time_samples: usize,
+}
+
+fn submission_month(submission_time: u64) -> String {
+    Utc.timestamp_nanos(submission_time as i64)
+        .format("%Y-%m")
+        .to_string()
+}
+
+// Time of the last successful call in the proposal's transaction log
+fn approved_at(proposal: &Proposal, txs_logs: &HashMap<u64, Vec<TxMetadata>>) -> Option<u64> {
+    txs_logs.get(&proposal.id).and_then(|txs| {
+        txs.iter()
+            .filter(|tx| tx.succeeded())
+            .map(|tx| tx.timestamp)
+            .max()
+    })
+}
+
+/// Aggregates statistics over a DAO's cached proposals.
+/// `txs_logs` holds the transaction logs of proposals already fetched individually;
+/// approval times can only be computed for those.
+pub fn compute_stats(proposals: &[Proposal], txs_logs: &HashMap<u64, Vec<TxMetadata>>) -> DaoStats {
+    let mut by_status: BTreeMap<String, usize> = BTreeMap::new();
+    let mut by_category: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut by_month: BTreeMap<String, usize> = BTreeMap::new();
+    let mut proposers: HashSet<&str> = HashSet::new();
+    let mut voters: HashSet<&str> = HashSet::new();
+    let mut approval_times: Vec<u64> = Vec::new();
+
+    for proposal in proposals {
+        let status = proposal.current_status().as_str().to_string();
+        *by_status.entry(status).or_default() += 1;
+
+        for category in proposal_categories(proposal) {
+            *by_category.entry(category).or_default() += 1;
+        }
+
+        *by_month
+            .entry(submission_month(proposal.submission_time.0))
+            .or_default() += 1;
+
+        proposers.insert(proposal.proposer.as_str());
+        voters.extend(proposal.votes.keys().map(|voter| voter.as_str()));
+
+        if proposal.status == ProposalStatus::Approved
+            && let Some(approved_at) = approved_at(proposal, txs_logs)
+        {
+            approval_times.push(approved_at.saturating_sub(proposal.submission_time.0));
+        }
+    }
+
+    let average_time_to_approval_secs = (!approval_times.is_empty()).then(|| {
+        approval_times.iter().map(|t| *t as f64).sum::<f64>()
+            / approval_times.len() as f64
+            / NANOS_PER_SECOND
+    });
+
+    DaoStats {
+        total_proposals: proposals.len(),
+        by_status,
+        by_category,
+        by_month,
+        unique_proposers: proposers.len(),
+        unique_voters: voters.len(),
+        average_time_to_approval_secs,
+        approval_time_samples: approval_times.len(),
+    }
+}
+
+/// One call on a proposal, in block order
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TimelineEntry {
+    /// Account that called the contract
+    pub account: String,
+    /// AddProposal or the vote action, null for calls that can't be told apart from
+    /// the transaction log alone, e.g. finalize
+    #[schema(value_type = Option<String>)]
+    pub action: Option<Action>,
+    pub vote: Option<Vote>,
+    pub block_height: u64,
+    /// Block timestamp in nanoseconds
+    pub timestamp: u64,
+}
+
+/// Labels the transaction log of a proposal. The log only holds who called the contract
+/// and when, so the creation is the proposer's call at the submission time and each
+/// voter's first call is their vote from the votes map. Failed calls are left out.
+pub fn vote_timeline(proposal: &Proposal, txs_log: &[TxMetadata]) -> Vec<TimelineEntry> {
+    let mut txs: Vec<&TxMetadata> = txs_log.iter().filter(|tx| tx.succeeded()).collect();
+    txs.sort_by_key(|tx| tx.block_height);
+
+    let mut created = false;
+    let mut voted: HashSet<&str> = HashSet::new();
+    txs.into_iter()
+        .map(|tx| {
+            let account = tx.predecessor_id.as_str();
+            let (action, vote) = if !created
+                && account == proposal.proposer
+                && tx.timestamp == proposal.submission_time.0
+            {
+                created = true;
+                (Some(Action::AddProposal), None)
+            } else if let Some(vote) = proposal
+                .votes
+                .get(account)
+                .filter(|_| voted.insert(account))
+            {
+                let action = match vote {
+                    Vote::Approve => Action::VoteApprove,
+                    Vote::Reject => Action::VoteReject,
+                    Vote::Remove => Action::VoteRemove,
+                };
+                (Some(action), Some(vote.clone()))
+            } else {
+                (None, None)
+            };
+
+            TimelineEntry {
+                account: account.to_string(),
+                action,
+                vote,
+                block_height: tx.block_height,
+                timestamp: tx.timestamp,
+            }
+        })
+        .collect()
+}
+
+/// One proposal an account voted on
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AccountVote {
+    pub proposal_id: u64,
+    pub vote: Vote,
+    /// Display status, InProgress proposals past the voting period are Expired
+    pub status: String,
+    #[schema(value_type = Vec<String>)]
+    pub categories: Vec<&'static str>,
+    /// Nanosecond timestamp of the vote, null when the proposal's transaction log isn't cached
+    pub voted_at: Option<u64>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct VotingHistory {
+    pub account_id: String,
+    /// Ordered by proposal id
+    pub votes: Vec<AccountVote>,
+    pub total: usize,
+    pub approve: usize,
+    pub reject: usize,
+    pub remove: usize,
+}
+
+/// Every cached proposal the account voted on. Vote times come from the timelines of
+/// proposals whose transaction log is in `txs_logs`.
+pub fn voting_history(
+    account_id: &str,
+    proposals: &[Proposal],
+    txs_logs: &HashMap<u64, Vec<TxMetadata>>,
+) -> VotingHistory {
+    let mut votes: Vec<AccountVote> = proposals
+        .iter()
+        .filter_map(|proposal| {
+            let vote = proposal.votes.get(account_id)?;
+            let voted_at = txs_logs.get(&proposal.id).and_then(|txs_log| {
+                vote_timeline(proposal, txs_log)
+                    .into_iter()
+                    .find(|entry| entry.account == account_id && entry.vote.is_some())
+                    .map(|entry| entry.timestamp)
+            });
+            Some(AccountVote {
+                proposal_id: proposal.id,
+                vote: vote.clone(),
+                status: proposal.current_status().as_str().to_string(),
+                categories: proposal_categories(proposal),
+                voted_at,
+            })
+        })
+        .collect();
+    votes.sort_by_key(|vote| vote.proposal_id);
+
+    let count = |kind: Vote| votes.iter().filter(|vote| vote.vote == kind).count();
+    VotingHistory {
+        account_id: account_id.to_string(),
+        total: votes.len(),
+        approve: count(Vote::Approve),
+        reject: count(Vote::Reject),
+        remove: count(Vote::Remove),
+        votes,
+    }
+}
+
+/// Voting activity of a group member
+#[derive(Serialize, Debug, ToSchema)]
+pub struct VoterParticipation {
+    pub account_id: String,
+    /// Roles the member belongs to
+    pub roles: Vec<String>,
+    /// Proposals whose kind one of the member's roles can vote on
+    pub eligible: usize,
+    /// Votes cast on any proposal
+    pub votes: usize,
+    pub approve: usize,
+    pub reject: usize,
+    pub remove: usize,
+    /// Share of eligible proposals voted on, null without eligible proposals
+    pub participation_rate: Option<f64>,
+}
+
+#[derive(FromFormField, Clone, Copy, Debug, Default, PartialEq)]
+pub enum VoterSortBy {
+    #[default]
+    Participation,
+    Votes,
+    Eligible,
+    Account,
+}
+
+/// Participation of every group member in the policy, sorted by account. Eligibility
+/// uses the current policy, so members who joined later count older proposals too.
+pub fn voter_participation(proposals: &[Proposal], policy: &Policy) -> Vec<VoterParticipation> {
+    policy
+        .members()
+        .into_iter()
+        .map(|(account_id, roles)| {
+            let mut participation = VoterParticipation {
+                account_id,
+                roles,
+                eligible: 0,
+                votes: 0,
+                approve: 0,
+                reject: 0,
+                remove: 0,
+                participation_rate: None,
+            };
+            let mut eligible_votes = 0;
+            for proposal in proposals {
+                let eligible = proposal_kind_label(&proposal.kind)
+                    .is_some_and(|label| policy.can_vote(&participation.account_id, label));
+                let vote = proposal.votes.get(&participation.account_id);
+                participation.eligible += usize::from(eligible);
+                eligible_votes += usize::from(eligible && vote.is_some());
+                match vote {
+                    Some(Vote::Approve) => participation.approve += 1,
+                    Some(Vote::Reject) => participation.reject += 1,
+                    Some(Vote::Remove) => participation.remove += 1,
+                    None => continue,
+                }
+                participation.votes += 1;
+            }
+            participation.participation_rate = (participation.eligible > 0)
+                .then(|| eligible_votes as f64 / participation.eligible as f64);
+            participation
+        })
+        .collect()
+}
+
+/// Orders the leaderboard, ties keep the account order
+pub fn sort_voters(voters: &mut [VoterParticipation], sort_by: VoterSortBy, ascending: bool) {
+    voters.sort_by(|a, b| {
+        let order = match sort_by {
+            VoterSortBy::Participation => a
+                .participation_rate
+                .unwrap_or(0.0)
+                .total_cmp(&b.participation_rate.unwrap_or(0.0)),
+            VoterSortBy::Votes => a.votes.cmp(&b.votes),
+            VoterSortBy::Eligible => a.eligible.cmp(&b.eligible),
+            VoterSortBy::Account => a.account_id.cmp(&b.account_id),
+        };
+        if ascending { order } else { order.reverse() }
+    });
+}
+
+/// Proposals of one proposer
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ProposerStats {
+    pub account_id: String,
+    pub proposals: usize,
+    pub approved: usize,
+    /// Approved share of the proposer's proposals that are no longer in progress, null
+    /// while all are
+    pub approval_rate: Option<f64>,
+    /// Amount of approved payments per token in whole units, with native NEAR as "near"
+    pub approved_payments: BTreeMap<String, String>,
+    /// Submission time of the first and last proposal in nanoseconds
+    pub first_proposal_at: u64,
+    pub last_proposal_at: u64,
+}
+
+/// Statistics per proposer, most active first. `decimals` holds the decimals of every
+/// requested token, payments in tokens missing from it are left out of the totals.
+pub fn proposer_stats(
+    proposals: &[Proposal],
+    decimals: &HashMap<String, u8>,
+) -> Vec<ProposerStats> {
+    struct Totals {
+        proposals: usize,
+        approved: usize,
+        decided: usize,
+        payments: BTreeMap<String, u128>,
+        first: u64,
+        last: u64,
+    }
+
+    let mut totals: BTreeMap<&str, Totals> = BTreeMap::new();
+    for proposal in proposals {
+        let submitted = proposal.submission_time.0;
+        let entry = totals
+            .entry(proposal.proposer.as_str())
+            .or_insert_with(|| Totals {
+                proposals: 0,
+                approved: 0,
+                decided: 0,
+                payments: BTreeMap::new(),
+                first: submitted,
+                last: submitted,
+            });
+        entry.proposals += 1;
+        entry.first = entry.first.min(submitted);
+        entry.last = entry.last.max(submitted);
+
+        let status = proposal.current_status();
+        if *status != ProposalStatus::InProgress {
+            entry.decided += 1;
+        }
+        if *status != ProposalStatus::Approved {
+            continue;
+        }
+        entry.approved += 1;
+        if let Some(payment) = PaymentInfo::from_proposal(proposal) {
+            let token = if payment.token.is_empty() {
+                "near".to_string()
+            } else {
+                payment.token
+            };
+            if let Ok(amount) = payment.amount.parse::<u128>() {
+                let sum = entry.payments.entry(token).or_default();
+                *sum = sum.saturating_add(amount);
+            }
+        }
+    }
+
+    let mut stats: Vec<ProposerStats> = totals
+        .into_iter()
+        .map(|(account_id, totals)| ProposerStats {
+            account_id: account_id.to_string(),
+            proposals: totals.proposals,
+            approved: totals.approved,
+            approval_rate: (totals.decided > 0)
+                .then(|| totals.approved as f64 / totals.decided as f64),
+            approved_payments: totals
+                .payments
+                .into_iter()
+                .filter_map(|(token, raw)| {
+                    let decimals = *decimals.get(&token)?;
+                    Some((token, format_units(raw, decimals)))
+                })
+                .collect(),
+            first_proposal_at: totals.first,
+            last_proposal_at: totals.last,
+        })
+        .collect();
+    // Stable, so equal counts stay ordered by account
+    stats.sort_by_key(|stats| std::cmp::Reverse(stats.proposals));
+    stats
+}
+
+#[derive(FromFormField, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Granularity {
+    #[default]
+    Month,
+    Quarter,
+}
+
+#[derive(FromFormField, Clone, Copy, Debug, Default, PartialEq)]
+pub enum TimeseriesMetric {
+    /// Proposals submitted
+    #[default]
+    Proposals,
+    /// Approved payments of one token, in whole units
+    #[field(value = "approved_outflow")]
+    ApprovedOutflow,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct TimeseriesPoint {
+    /// "YYYY-MM" or "YYYY-Qn" (UTC)
+    pub bucket: String,
+    pub value: f64,
+}
+
+impl Granularity {
+    // Buckets counted from year 0, so consecutive buckets have consecutive indices
+    fn bucket_index(self, submission_time: u64) -> i64 {
+        let date = Utc.timestamp_nanos(submission_time as i64);
+        let year = i64::from(date.year());
+        match self {
+            Granularity::Month => year * 12 + i64::from(date.month0()),
+            Granularity::Quarter => year * 4 + i64::from(date.month0() / 3),
+        }
+    }
+
+    fn bucket_label(self, index: i64) -> String {
+        match self {
+            Granularity::Month => format!("{}-{:02}", index / 12, index % 12 + 1),
+            Granularity::Quarter => format!("{}-Q{}", index / 4, index % 4 + 1),
+        }
+    }
+}
+
+/// Metric bucketed by submission time, from the first to the last bucket with data and
+/// including empty buckets in between. `outflow_token` is the token contract ("near" for
+/// native NEAR) and its decimals, required for ApprovedOutflow which is empty without it.
+pub fn timeseries(
+    proposals: &[Proposal],
+    granularity: Granularity,
+    metric: TimeseriesMetric,
+    outflow_token: Option<(&str, u8)>,
+) -> Vec<TimeseriesPoint> {
+    let mut buckets: BTreeMap<i64, u128> = BTreeMap::new();
+    for proposal in proposals {
+        let value = match metric {
+            TimeseriesMetric::Proposals => 1,
+            TimeseriesMetric::ApprovedOutflow => {
+                let Some((token, _)) = outflow_token else {
+                    return Vec::new();
+                };
+                if *proposal.current_status() != ProposalStatus::Approved {
+                    continue;
+                }
+                let Some(payment) = PaymentInfo::from_proposal(proposal) else {
+                    continue;
+                };
+                let payment_token = if payment.token.is_empty() {
+                    "near"
+                } else {
+                    payment.token.as_str()
+                };
+                if payment_token != token {
+                    continue;
+                }
+                payment.amount.parse::<u128>().unwrap_or(0)
+            }
+        };
+        let sum = buckets
+            .entry(granularity.bucket_index(proposal.submission_time.0))
+            .or_default();
+        *sum = sum.saturating_add(value);
+    }
+
+    let scale = match (metric, outflow_token) {
+        (TimeseriesMetric::ApprovedOutflow, Some((_, decimals))) => 10f64.powi(decimals.into()),
+        _ => 1.0,
+    };
+    let (Some(first), Some(last)) = (buckets.keys().next(), buckets.keys().next_back()) else {
+        return Vec::new();
+    };
+    (*first..=*last)
+        .map(|index| TimeseriesPoint {
+            bucket: granularity.bucket_label(index),
+            value: buckets.get(&index).map_or(0.0, |sum| *sum as f64 / scale),
+        })
+        .collect()
+}
+
+/// Proposals created per proposer
+pub fn proposer_counts(proposals: &[Proposal]) -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for proposal in proposals {
+        *counts.entry(proposal.proposer.clone()).or_default() += 1;
+    }
+    counts
+}
+
+/// Proposals voted on per voter, whatever the vote
+pub fn voter_counts(proposals: &[Proposal]) -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for proposal in proposals {
+        for voter in proposal.votes.keys() {
+            *counts.entry(voter.clone()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Votes of one account across the DAO's proposals
+#[derive(Default, Debug)]
+pub struct ApproverVotes {
+    pub approve: usize,
+    pub reject: usize,
+    pub remove: usize,
+    /// Nanoseconds, None when no voted proposal has its transaction log in `txs_logs`
+    pub last_vote_at: Option<u64>,
+}
+
+/// Vote splits per voter. Vote times come from the timelines of proposals whose
+/// transaction log is in `txs_logs`.
+pub fn approver_votes(
+    proposals: &[Proposal],
+    txs_logs: &HashMap<u64, Vec<TxMetadata>>,
+) -> BTreeMap<String, ApproverVotes> {
+    let mut approvers: BTreeMap<String, ApproverVotes> = BTreeMap::new();
+    for proposal in proposals {
+        for (voter, vote) in &proposal.votes {
+            let entry = approvers.entry(voter.clone()).or_default();
+            match vote {
+                Vote::Approve => entry.approve += 1,
+                Vote::Reject => entry.reject += 1,
+                Vote::Remove => entry.remove += 1,
+            }
+        }
+        let Some(txs_log) = txs_logs.get(&proposal.id) else {
+            continue;
+        };
+        for entry in vote_timeline(proposal, txs_log) {
+            if entry.vote.is_none() {
+                continue;
+            }
+            if let Some(approver) = approvers.get_mut(&entry.account) {
+                approver.last_vote_at = approver.last_vote_at.max(Some(entry.timestamp));
+            }
+        }
+    }
+    approvers
+}
+
+/// Payment proposals per recipient
+pub fn recipient_counts(proposals: &[Proposal]) -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for payment in proposals.iter().filter_map(PaymentInfo::from_proposal) {
+        *counts.entry(payment.receiver).or_default() += 1;
+    }
+    counts
+}
+
+/// Payments to one recipient
+#[derive(Default, Debug)]
+pub struct RecipientPayments {
+    pub proposals: usize,
+    /// Raw amounts of approved payments per token, with native NEAR as "near"
+    pub approved_amounts: BTreeMap<String, u128>,
+    /// Nanoseconds, None without approved payments
+    pub last_payment_at: Option<u64>,
+}
+
+/// Payment proposals and approved totals per recipient. Payments are dated by the approval
+/// when the proposal's transaction log is in `txs_logs`, by the submission otherwise.
+pub fn recipient_payments(
+    proposals: &[Proposal],
+    txs_logs: &HashMap<u64, Vec<TxMetadata>>,
+) -> BTreeMap<String, RecipientPayments> {
+    let mut recipients: BTreeMap<String, RecipientPayments> = BTreeMap::new();
+    for proposal in proposals {
+        let Some(payment) = PaymentInfo::from_proposal(proposal) else {
+            continue;
+        };
+        let entry = recipients.entry(payment.receiver).or_default();
+        entry.proposals += 1;
+        if proposal.status != ProposalStatus::Approved {
+            continue;
+        }
+        let token = if payment.token.is_empty() {
+            "near".to_string()
+        } else {
+            payment.token
+        };
+        let total = entry.approved_amounts.entry(token).or_default();
+        *total = total.saturating_add(payment.amount.parse::<u128>().unwrap_or(0));
+        let paid_at = approved_at(proposal, txs_logs).unwrap_or(proposal.submission_time.0);
+        entry.last_payment_at = entry.last_payment_at.max(Some(paid_at));
+    }
+    recipients
+}
+
+/// Payment proposals per requested token, with native NEAR as "near"
+pub fn requested_token_counts(proposals: &[Proposal]) -> BTreeMap<String, usize> {
+    requested_token_totals(proposals)
+        .into_iter()
+        .map(|(token, (count, _))| (token, count))
+        .collect()
+}
+
+/// Payment proposals and the sum of their raw amounts per requested token, whatever the
+/// proposal status
+pub fn requested_token_totals(proposals: &[Proposal]) -> BTreeMap<String, (usize, u128)> {
+    let mut totals: BTreeMap<String, (usize, u128)> = BTreeMap::new();
+    for payment in proposals.iter().filter_map(PaymentInfo::from_proposal) {
+        let amount = payment.amount.parse::<u128>().unwrap_or(0);
+        let token = if payment.token.is_empty() {
+            "near".to_string()
+        } else {
+            payment.token
+        };
+        let (count, total) = totals.entry(token).or_default();
+        *count += 1;
+        *total = total.saturating_add(amount);
+    }
+    totals
+}
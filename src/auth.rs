@@ -0,0 +1,88 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use rocket::Request;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use std::time::{Duration, Instant};
+
+use crate::config::get_config;
+
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+// Start of the current window and requests made in it, per key
+static REQUEST_COUNTS: Lazy<DashMap<String, (Instant, u32)>> = Lazy::new(DashMap::new);
+
+#[derive(Debug)]
+pub enum AuthError {
+    MissingKey,
+    InvalidKey,
+    RateLimited,
+    NotAdmin,
+}
+
+/// Guard of every API route. Passes everything when no keys are configured, otherwise
+/// requires a known X-API-Key within its rate limit unless the path is public.
+pub struct ApiAuth;
+
+// Counts the request and tells whether the key is still within its limit
+fn within_rate_limit(key: &str, requests_per_minute: u32) -> bool {
+    let mut entry = REQUEST_COUNTS
+        .entry(key.to_string())
+        .or_insert_with(|| (Instant::now(), 0));
+    let (window_start, count) = entry.value_mut();
+    if window_start.elapsed() >= RATE_LIMIT_WINDOW {
+        *window_start = Instant::now();
+        *count = 0;
+    }
+    *count += 1;
+    *count <= requests_per_minute
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiAuth {
+    type Error = AuthError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let auth = &get_config().auth;
+        if !auth.is_enabled() || auth.is_public(request.uri().path().as_str()) {
+            return Outcome::Success(ApiAuth);
+        }
+
+        let Some(key) = request.headers().get_one(API_KEY_HEADER) else {
+            return Outcome::Error((Status::Unauthorized, AuthError::MissingKey));
+        };
+        let Some(api_key) = auth.find_key(key) else {
+            return Outcome::Error((Status::Unauthorized, AuthError::InvalidKey));
+        };
+
+        match api_key.requests_per_minute {
+            Some(limit) if !within_rate_limit(&api_key.key, limit) => {
+                Outcome::Error((Status::TooManyRequests, AuthError::RateLimited))
+            }
+            _ => Outcome::Success(ApiAuth),
+        }
+    }
+}
+
+/// Guard of the admin routes: requires a configured key with `admin = true`. Admin
+/// routes are unreachable while no such key exists, public paths don't apply.
+pub struct AdminAuth;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminAuth {
+    type Error = AuthError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(key) = request.headers().get_one(API_KEY_HEADER) else {
+            return Outcome::Error((Status::Unauthorized, AuthError::MissingKey));
+        };
+        let auth = &get_config().auth;
+        match auth.find_key(key) {
+            Some(api_key) if api_key.admin => Outcome::Success(AdminAuth),
+            Some(_) => Outcome::Error((Status::Forbidden, AuthError::NotAdmin)),
+            None => Outcome::Error((Status::Unauthorized, AuthError::InvalidKey)),
+        }
+    }
+}
@@ -1,30 +1,40 @@
 use anyhow::Result;
 use borsh::{BorshDeserialize, BorshSerialize};
 use dashmap::DashMap;
-use near_jsonrpc_client::JsonRpcClient;
+use futures::stream::{self, StreamExt};
 use near_primitives::types::AccountId;
 use near_sdk::json_types::U64;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use std::time::{Duration, Instant};
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio;
 
+use crate::config::get_config;
+use crate::rpc_client::{RpcPool, is_missing_contract};
 use crate::scraper::{
-    FtMetadata, Policy, Proposal, ProposalStatus, StateVersion, TxMetadata, fetch_contract_version,
-    fetch_ft_metadata, fetch_policy, fetch_proposal, fetch_proposal_log_txs, fetch_proposals,
+    AccountBountyClaim, Bounty, BountyClaim, DaoConfig, FtMetadata, Policy, Proposal,
+    ProposalStateChange, ProposalStatus, StakeDelegationInfo, StateVersion, TokenId, TxMetadata,
+    fetch_bounties, fetch_bounty_claims, fetch_bounty_number_of_claims, fetch_ft_metadata,
+    fetch_proposal_history,
 };
-
-const CACHE_LIFE_TIME: Duration = Duration::from_secs(5);
-const FT_CACHE_LIFETIME: Duration = Duration::from_secs(60 * 60); // 60 minutes
+use crate::source::{ProposalSource, proposal_source};
+use tracing::{debug, error, info, instrument, warn};
 
 #[derive(Clone, Debug)]
 pub struct CachedProposals {
-    pub proposals: Vec<Proposal>,
+    /// Shared with the requests reading it, so serving an entry doesn't copy the proposals
+    pub proposals: Arc<[Proposal]>,
     pub policy: Policy,
+    pub config: DaoConfig,
     pub last_updated: Instant,
     pub version: StateVersion,
+    // Value of get_last_proposal_id at the time of the last fetch
+    pub last_proposal_id: u64,
 }
 
 #[derive(Clone, BorshSerialize)]
@@ -34,6 +44,8 @@ pub struct CachedProposal {
     #[borsh(skip)]
     pub last_updated: Instant,
     pub txs_log: Vec<TxMetadata>,
+    /// Block to continue fetching older transactions from, None once the log is complete
+    pub backfill_block: Option<u64>,
 }
 
 pub struct CachedFtMetadata {
@@ -41,101 +53,592 @@ pub struct CachedFtMetadata {
     pub last_updated: Instant,
 }
 
-pub type FtMetadataCache = Arc<RwLock<HashMap<AccountId, CachedFtMetadata>>>;
+pub type FtMetadataCache = Arc<DashMap<AccountId, CachedFtMetadata>>;
 
 // Required to store in storage
 impl BorshDeserialize for CachedProposal {
     fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
         let txs_log = Vec::<TxMetadata>::deserialize_reader(reader)?;
+        let backfill_block = Option::<u64>::deserialize_reader(reader)?;
+        Ok(CachedProposal::restored(txs_log, backfill_block))
+    }
+}
 
+impl CachedProposal {
+    /// Entry read back from disk, expired so the proposal itself is fetched on first use
+    pub fn restored(txs_log: Vec<TxMetadata>, backfill_block: Option<u64>) -> Self {
         // Create the struct with default values for skipped fields
-
-        Ok(CachedProposal {
+        CachedProposal {
             proposal: Proposal {
                 id: 0,
                 proposer: "".parse().unwrap(),
                 description: "".to_string(),
                 kind: Value::default(),
                 status: ProposalStatus::InProgress,
+                effective_status: None,
                 vote_counts: HashMap::new(),
                 votes: HashMap::new(),
                 submission_time: U64(0),
                 last_actions_log: None,
             },
-            last_updated: Instant::now() - CACHE_LIFE_TIME,
+            last_updated: Instant::now()
+                .checked_sub(get_config().cache.life_time())
+                .unwrap_or_else(Instant::now),
             txs_log,
+            backfill_block,
+        }
+    }
+}
+
+const STATUS_RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+impl CachedProposals {
+    /// Brings the effective status of every proposal up to date, returns how many changed.
+    /// The proposals are only copied when one of them changes.
+    pub fn reconcile_statuses(&mut self) -> usize {
+        let proposal_period = self.policy.proposal_period.0;
+        let outdated = self.proposals.iter().any(|proposal| {
+            proposal.effective_status.as_ref() != Some(&proposal.reconciled_status(proposal_period))
+        });
+        if !outdated {
+            return 0;
+        }
+        let mut proposals = self.proposals.to_vec();
+        let changed = proposals
+            .iter_mut()
+            .map(|proposal| proposal.reconcile_status(proposal_period))
+            .filter(|changed| *changed)
+            .count();
+        self.proposals = proposals.into();
+        changed
+    }
+}
+
+// Copy of a cache entry with statuses as of now, entries can outlive voting periods
+fn reconciled(cached: &CachedProposals) -> CachedProposals {
+    let mut cached = cached.clone();
+    cached.reconcile_statuses();
+    cached
+}
+
+/// Periodically marks cached proposals whose voting period ran out as Expired, so the
+/// stored entries don't depend on being read to stay correct
+pub fn spawn_status_reconciliation(store: ProposalStore) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(STATUS_RECONCILE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let expired: usize = store
+                .iter_mut()
+                .map(|mut cached| cached.reconcile_statuses())
+                .sum();
+            if expired > 0 {
+                debug!(expired, "Reconciled proposal statuses");
+            }
+        }
+    });
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+// Proposals, policy and config hold arbitrary JSON, so they are stored as JSON inside
+// the Borsh encoding. Instants can't be stored, the update time is kept as a unix timestamp.
+impl BorshSerialize for CachedProposals {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let json = serde_json::to_vec(&(&*self.proposals, &self.policy, &self.config))
+            .map_err(std::io::Error::other)?;
+        let fetched_at = unix_now().saturating_sub(self.last_updated.elapsed().as_secs());
+        BorshSerialize::serialize(&json, writer)?;
+        BorshSerialize::serialize(&fetched_at, writer)?;
+        BorshSerialize::serialize(&self.version, writer)?;
+        BorshSerialize::serialize(&self.last_proposal_id, writer)
+    }
+}
+
+impl BorshDeserialize for CachedProposals {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let json = Vec::<u8>::deserialize_reader(reader)?;
+        let (proposals, policy, config): (Vec<Proposal>, Policy, DaoConfig) =
+            serde_json::from_slice(&json).map_err(std::io::Error::other)?;
+        let age = Duration::from_secs(unix_now().saturating_sub(u64::deserialize_reader(reader)?));
+
+        Ok(CachedProposals {
+            proposals: proposals.into(),
+            policy,
+            config,
+            // Keeps the age the entry had when it was saved plus the downtime
+            last_updated: Instant::now().checked_sub(age).unwrap_or_else(Instant::now),
+            version: StateVersion::deserialize_reader(reader)?,
+            last_proposal_id: u64::deserialize_reader(reader)?,
         })
     }
 }
 
-pub type ProposalStore = Arc<RwLock<HashMap<String, CachedProposals>>>;
-pub type ProposalCache = Arc<RwLock<HashMap<(String, u64), CachedProposal>>>;
+// Entries are never held across an await, a DashMap shard stays locked while one is
+pub type ProposalStore = Arc<DashMap<String, CachedProposals>>;
+pub type ProposalCache = Arc<DashMap<(String, u64), CachedProposal>>;
+
+pub struct CachedBounties {
+    pub bounties: Vec<Bounty>,
+    pub last_updated: Instant,
+}
+
+#[derive(Clone)]
+pub struct CachedBountyClaims {
+    pub number_of_claims: u32,
+    pub claims: Vec<AccountBountyClaim>,
+    pub last_updated: Instant,
+}
+
+pub type BountyStore = Arc<DashMap<String, CachedBounties>>;
+pub type BountyClaimsCache = Arc<DashMap<(String, u64), CachedBountyClaims>>;
 
 static FETCH_LOCKS: Lazy<DashMap<String, Arc<tokio::sync::Mutex<()>>>> = Lazy::new(DashMap::new);
 
+// Same for single proposals, keyed by DAO and proposal id
+type ProposalFetchLocks = DashMap<(String, u64), Arc<tokio::sync::Mutex<()>>>;
+static PROPOSAL_FETCH_LOCKS: Lazy<ProposalFetchLocks> = Lazy::new(DashMap::new);
+
+// Failure maps below keep at most this many entries, so a client asking for random
+// accounts can't grow them without bound
+const MAX_REMEMBERED_FAILURES: usize = 10_000;
+
+// Accounts that turned out not to be Sputnik DAOs, with the time of the failed fetch
+static MISSING_DAOS: Lazy<DashMap<String, Instant>> = Lazy::new(DashMap::new);
+
+// Tokens whose ft_metadata call failed, with the time of the failure
+static FT_METADATA_FAILURES: Lazy<DashMap<AccountId, Instant>> = Lazy::new(DashMap::new);
+
+// Histories of proposals keyed by DAO and proposal id, valid while the proposal's last
+// action is still the one they end with
+static PROPOSAL_HISTORIES: Lazy<DashMap<(String, u64), CachedHistory>> = Lazy::new(DashMap::new);
+
+// At most this many histories are kept, the least recently fetched make room
+const MAX_CACHED_HISTORIES: usize = 10_000;
+
+struct CachedHistory {
+    last_action_block: Option<u64>,
+    history: Vec<ProposalStateChange>,
+    fetched_at: Instant,
+}
+
+// Timestamps of the blocks looked up by updated_from / updated_to, a block's timestamp
+// never changes
+static BLOCK_TIMESTAMPS: Lazy<DashMap<u64, u64>> = Lazy::new(DashMap::new);
+
+const MAX_CACHED_BLOCK_TIMESTAMPS: usize = 100_000;
+
+// When a DAO refresh last saw a proposal change, so its ProposalCache entry is
+// refetched before its life time runs out
+static PROPOSAL_CHANGES: Lazy<DashMap<(String, u64), Instant>> = Lazy::new(DashMap::new);
+
+/// Error for accounts remembered as not being Sputnik DAOs
+#[derive(Debug)]
+pub struct NotADao(pub String);
+
+impl std::fmt::Display for NotADao {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is not a Sputnik DAO", self.0)
+    }
+}
+
+impl std::error::Error for NotADao {}
+
+/// Whether a failed DAO lookup means the account isn't a Sputnik DAO, as opposed to RPC
+/// failing
+pub fn is_unknown_dao(error: &anyhow::Error) -> bool {
+    is_missing_contract(error) || error.is::<NotADao>()
+}
+
+/// Lookups answered from memory vs. ones that had to fetch from RPC
+#[derive(Default)]
+pub struct HitCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl HitCounter {
+    fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// (hits, misses) since startup
+    pub fn snapshot(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+pub static DAO_CACHE_COUNTER: Lazy<HitCounter> = Lazy::new(HitCounter::default);
+pub static PROPOSAL_CACHE_COUNTER: Lazy<HitCounter> = Lazy::new(HitCounter::default);
+
+// Records a failure, dropping the ones older than `life_time` first. When the map is
+// still full the oldest failure makes room.
+fn remember_failure<K: Eq + Hash + Clone>(
+    failures: &DashMap<K, Instant>,
+    key: K,
+    life_time: Duration,
+) {
+    failures.retain(|_, failed_at| failed_at.elapsed() <= life_time);
+    if failures.len() >= MAX_REMEMBERED_FAILURES && !failures.contains_key(&key) {
+        let oldest = failures
+            .iter()
+            .min_by_key(|entry| *entry.value())
+            .map(|entry| entry.key().clone());
+        if let Some(oldest) = oldest {
+            failures.remove(&oldest);
+        }
+    }
+    failures.insert(key, Instant::now());
+}
+
+/// Failed ft_metadata lookups that haven't expired yet, with the unix time of the failure
+pub fn ft_metadata_failures() -> HashMap<String, u64> {
+    let life_time = get_config().cache.ft_failure_life_time();
+    let now = unix_now();
+    FT_METADATA_FAILURES
+        .iter()
+        .filter(|entry| entry.value().elapsed() <= life_time)
+        .map(|entry| {
+            let failed_at = now.saturating_sub(entry.value().elapsed().as_secs());
+            (entry.key().to_string(), failed_at)
+        })
+        .collect()
+}
+
+/// Restores failed ft_metadata lookups saved by ft_metadata_failures, skipping the ones
+/// that expired during the downtime
+pub fn restore_ft_metadata_failures(failures: HashMap<String, u64>) {
+    let life_time = get_config().cache.ft_failure_life_time();
+    for (token_id, failed_at) in failures {
+        let age = Duration::from_secs(unix_now().saturating_sub(failed_at));
+        let (Ok(token_id), Some(failed_at)) = (
+            token_id.parse::<AccountId>(),
+            Instant::now().checked_sub(age),
+        ) else {
+            continue;
+        };
+        if age <= life_time && FT_METADATA_FAILURES.len() < MAX_REMEMBERED_FAILURES {
+            FT_METADATA_FAILURES.insert(token_id, failed_at);
+        }
+    }
+}
+
+fn fetch_lock(dao_id: &AccountId) -> Arc<tokio::sync::Mutex<()>> {
+    FETCH_LOCKS
+        .entry(dao_id.to_string())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+        .clone()
+}
+
+// Held while a proposal is fetched. Dropping it removes the lock from PROPOSAL_FETCH_LOCKS
+// unless another request is waiting on it, so the map only holds proposals being fetched.
+struct ProposalFetchGuard {
+    cache_key: (String, u64),
+    guard: Option<tokio::sync::OwnedMutexGuard<()>>,
+}
+
+impl Drop for ProposalFetchGuard {
+    fn drop(&mut self) {
+        self.guard.take();
+        // Waiters clone the lock while holding the map shard, the count can't grow meanwhile
+        PROPOSAL_FETCH_LOCKS.remove_if(&self.cache_key, |_, lock| Arc::strong_count(lock) == 1);
+    }
+}
+
+async fn lock_proposal_fetch(cache_key: &(String, u64)) -> ProposalFetchGuard {
+    // Created before waiting, so a request cancelled while waiting cleans up as well
+    let mut fetch_guard = ProposalFetchGuard {
+        cache_key: cache_key.clone(),
+        guard: None,
+    };
+    let lock = PROPOSAL_FETCH_LOCKS
+        .entry(cache_key.clone())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+        .clone();
+    fetch_guard.guard = Some(lock.lock_owned().await);
+    fetch_guard
+}
+
 pub async fn get_latest_dao_cache(
-    client: &Arc<JsonRpcClient>,
+    client: &Arc<RpcPool>,
     store: &ProposalStore,
     dao_id: &AccountId,
+    life_time: Duration,
 ) -> Result<CachedProposals> {
-    // First check cache
+    // Unknown accounts are remembered so repeated requests don't hit RPC every time
+    if MISSING_DAOS
+        .get(dao_id.as_str())
+        .is_some_and(|failed_at| failed_at.elapsed() <= get_config().cache.missing_dao_life_time())
     {
-        let store_read = store
-            .read()
-            .map_err(|_| anyhow::anyhow!("Failed to acquire read lock on proposal store"))?;
+        return Err(NotADao(dao_id.to_string()).into());
+    }
 
-        if let Some(c) = store_read.get(dao_id.as_str()) {
-            if c.last_updated.elapsed() <= CACHE_LIFE_TIME {
-                return Ok(c.clone());
-            }
+    // First check cache
+    if let Some(c) = store.get(dao_id.as_str()) {
+        let age = c.last_updated.elapsed();
+        if age <= life_time {
+            DAO_CACHE_COUNTER.hit();
+            return Ok(reconciled(&c));
+        }
+        // Stale-while-revalidate: serve the expired entry and refresh it in the background
+        if get_config()
+            .cache
+            .max_staleness()
+            .is_some_and(|max_staleness| age <= max_staleness)
+        {
+            spawn_background_refresh(client.clone(), store.clone(), dao_id.clone(), life_time);
+            DAO_CACHE_COUNTER.hit();
+            return Ok(reconciled(&c));
+        }
+        // RPC is failing, an outdated answer beats waiting for a doomed refresh
+        if !client.is_available() {
+            warn!(%dao_id, "RPC unavailable, serving stale cache");
+            DAO_CACHE_COUNTER.hit();
+            return Ok(reconciled(&c));
         }
     }
 
     // Use lock to prevent multiple concurrent fetches for the same DAO
-    let dao_lock = FETCH_LOCKS
-        .entry(dao_id.to_string())
-        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
-        .clone();
-
+    let dao_lock = fetch_lock(dao_id);
     let _guard = dao_lock.lock().await;
 
     // Check cache again after acquiring lock (another request might have populated it)
-    {
-        let store_read = store
-            .read()
-            .map_err(|_| anyhow::anyhow!("Failed to acquire read lock on proposal store"))?;
-
-        if let Some(c) = store_read.get(dao_id.as_str()) {
-            if c.last_updated.elapsed() <= CACHE_LIFE_TIME {
-                println!("Cache hit for DAO ID: {}", dao_id);
-                return Ok(c.clone());
+    let previous = match store.get(dao_id.as_str()) {
+        Some(c) if c.last_updated.elapsed() <= life_time => {
+            debug!(%dao_id, "Cache hit after waiting for the fetch lock");
+            DAO_CACHE_COUNTER.hit();
+            return Ok(reconciled(&c));
+        }
+        stale => stale.map(|c| c.value().clone()),
+    };
+
+    DAO_CACHE_COUNTER.miss();
+    refresh_dao_cache(client, store, dao_id, previous.as_ref())
+        .await
+        .inspect_err(|e| {
+            if is_missing_contract(e) {
+                remember_failure(
+                    &MISSING_DAOS,
+                    dao_id.to_string(),
+                    get_config().cache.missing_dao_life_time(),
+                );
             }
+        })
+}
+
+/// Refetches all proposals, policy and config of a DAO, ignoring what is cached
+pub async fn force_refresh_dao_cache(
+    client: &Arc<RpcPool>,
+    store: &ProposalStore,
+    dao_id: &AccountId,
+) -> Result<CachedProposals> {
+    MISSING_DAOS.remove(dao_id.as_str());
+    let dao_lock = fetch_lock(dao_id);
+    let _guard = dao_lock.lock().await;
+
+    let previous = store.get(dao_id.as_str()).map(|c| c.value().clone());
+
+    let refreshed = refresh_dao_cache(client, store, dao_id, None).await?;
+    if let Some(previous) = previous {
+        crate::updates::publish_changes(dao_id, &previous.proposals, &refreshed.proposals);
+    }
+    Ok(refreshed)
+}
+
+/// Entries removed by purge_dao_caches, per cache
+#[derive(Serialize, Debug, Default)]
+pub struct PurgedEntries {
+    pub dao: usize,
+    pub proposals: usize,
+    pub bounties: usize,
+    pub bounty_claims: usize,
+}
+
+/// Drops everything cached about a DAO, the next request fetches it from scratch
+pub fn purge_dao_caches(
+    dao_id: &str,
+    store: &ProposalStore,
+    proposal_cache: &ProposalCache,
+    bounty_store: &BountyStore,
+    bounty_claims_cache: &BountyClaimsCache,
+) -> PurgedEntries {
+    MISSING_DAOS.remove(dao_id);
+    PROPOSAL_CHANGES.retain(|(changed_dao_id, _), _| changed_dao_id != dao_id);
+    PROPOSAL_FETCH_LOCKS.retain(|(locked_dao_id, _), _| locked_dao_id != dao_id);
+    PROPOSAL_HISTORIES.retain(|(history_dao_id, _), _| history_dao_id != dao_id);
+
+    let dao = store.remove(dao_id).is_some() as usize;
+    let bounties = bounty_store.remove(dao_id).is_some() as usize;
+
+    let before = proposal_cache.len();
+    proposal_cache.retain(|(cached_dao_id, _), _| cached_dao_id != dao_id);
+    let proposals = before - proposal_cache.len();
+
+    let before = bounty_claims_cache.len();
+    bounty_claims_cache.retain(|(cached_dao_id, _), _| cached_dao_id != dao_id);
+    let bounty_claims = before - bounty_claims_cache.len();
+
+    PurgedEntries {
+        dao,
+        proposals,
+        bounties,
+        bounty_claims,
+    }
+}
+
+/// Accounts currently answered with 404 without asking RPC
+pub fn missing_dao_count() -> usize {
+    let life_time = get_config().cache.missing_dao_life_time();
+    MISSING_DAOS
+        .iter()
+        .filter(|entry| entry.value().elapsed() <= life_time)
+        .count()
+}
+
+fn spawn_background_refresh(
+    client: Arc<RpcPool>,
+    store: ProposalStore,
+    dao_id: AccountId,
+    life_time: Duration,
+) {
+    tokio::spawn(async move { refresh_if_expired(&client, &store, &dao_id, life_time).await });
+}
+
+/// Brings DAOs restored from the cache file up to date one after another, they are
+/// served from the restored data meanwhile
+pub fn spawn_restored_refresh(client: Arc<RpcPool>, store: ProposalStore) {
+    let dao_ids: Vec<AccountId> = store
+        .iter()
+        .filter_map(|cached| cached.key().parse().ok())
+        .collect();
+    if dao_ids.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        info!(daos = dao_ids.len(), "Refreshing DAOs restored from disk");
+        for dao_id in dao_ids {
+            let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "");
+            refresh_if_expired(&client, &store, &dao_id, life_time).await;
         }
+    });
+}
+
+async fn refresh_if_expired(
+    client: &Arc<RpcPool>,
+    store: &ProposalStore,
+    dao_id: &AccountId,
+    life_time: Duration,
+) {
+    let dao_lock = fetch_lock(dao_id);
+    // Another request is already refreshing this DAO
+    let Ok(_guard) = dao_lock.try_lock() else {
+        return;
+    };
+
+    let previous = store.get(dao_id.as_str()).map(|c| c.value().clone());
+    if previous
+        .as_ref()
+        .is_some_and(|c| c.last_updated.elapsed() <= life_time)
+    {
+        return;
     }
 
-    // Fetch fresh data
-    let (proposals, policy, version) = tokio::try_join!(
-        fetch_proposals(&client, &dao_id),
-        fetch_policy(&client, &dao_id),
-        fetch_contract_version(&client, &dao_id)
+    if let Err(e) = refresh_dao_cache(client, store, dao_id, previous.as_ref()).await {
+        error!(%dao_id, error = ?e, "Background refresh failed");
+    }
+}
+
+#[instrument(skip_all, fields(%dao_id))]
+async fn refresh_dao_cache(
+    client: &Arc<RpcPool>,
+    store: &ProposalStore,
+    dao_id: &AccountId,
+    previous: Option<&CachedProposals>,
+) -> Result<CachedProposals> {
+    let started = Instant::now();
+    // Fetch fresh data, only re-downloading proposals that could have changed
+    let (previous_proposals, previous_last_id) =
+        previous.map_or((&[][..], 0), |c| (&c.proposals[..], c.last_proposal_id));
+    let source = proposal_source(client);
+    let ((proposals, last_proposal_id), policy, config, version) = tokio::try_join!(
+        source.proposals(dao_id, previous_proposals, previous_last_id),
+        source.policy(dao_id),
+        source.config(dao_id),
+        source.version(dao_id)
     )?;
 
     // Update cache
-    let mut store_write = store
-        .write()
-        .map_err(|_| anyhow::anyhow!("Failed to acquire write lock on proposal store"))?;
-    let new_cache = CachedProposals {
-        proposals,
+    let mut new_cache = CachedProposals {
+        proposals: proposals.into(),
         policy,
+        config,
         last_updated: Instant::now(),
         version,
+        last_proposal_id,
     };
-    store_write.insert(dao_id.to_string(), new_cache.clone());
+    new_cache.reconcile_statuses();
+    store.insert(dao_id.to_string(), new_cache.clone());
+
+    info!(
+        proposals = new_cache.proposals.len(),
+        incremental = previous.is_some(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "DAO cache refreshed"
+    );
+
+    if let Some(previous) = previous {
+        record_proposal_changes(dao_id, &previous.proposals, &new_cache.proposals);
+        crate::updates::publish_changes(dao_id, &previous.proposals, &new_cache.proposals);
+    }
     Ok(new_cache)
 }
 
+fn record_proposal_changes(dao_id: &AccountId, previous: &[Proposal], current: &[Proposal]) {
+    let previous_by_id: HashMap<u64, &Proposal> = previous.iter().map(|p| (p.id, p)).collect();
+    let now = Instant::now();
+    for proposal in current {
+        let changed = previous_by_id.get(&proposal.id).is_some_and(|old| {
+            old.status != proposal.status
+                || old.votes != proposal.votes
+                || old.last_actions_log != proposal.last_actions_log
+        });
+        if changed {
+            PROPOSAL_CHANGES.insert((dao_id.to_string(), proposal.id), now);
+        }
+    }
+}
+
+// Cached entry of a proposal and whether it is still fresh
+fn cached_proposal(
+    cache: &ProposalCache,
+    cache_key: &(String, u64),
+) -> (Option<CachedProposal>, bool) {
+    match cache.get(cache_key) {
+        Some(cached) => {
+            let changed = PROPOSAL_CHANGES
+                .get(cache_key)
+                .is_some_and(|changed_at| *changed_at > cached.last_updated);
+            let fresh = !changed && cached.last_updated.elapsed() <= get_config().cache.life_time();
+            (Some(cached.value().clone()), fresh)
+        }
+        None => (None, false),
+    }
+}
+
 pub async fn get_latest_proposal_cache(
-    client: &Arc<JsonRpcClient>,
+    client: &Arc<RpcPool>,
     cache: &ProposalCache,
     dao_id: &AccountId,
     proposal_id: u64,
@@ -143,87 +646,221 @@ pub async fn get_latest_proposal_cache(
     let cache_key = (dao_id.to_string(), proposal_id);
 
     // Check existing cache
-    let last_cached_proposal: Option<CachedProposal> = {
-        let cache_read = cache
-            .read()
-            .map_err(|_| anyhow::anyhow!("Failed to acquire read lock on proposal cache"))?;
-
-        if let Some(cached) = cache_read.get(&cache_key) {
-            if cached.last_updated.elapsed() <= CACHE_LIFE_TIME {
-                return Ok(cached.clone());
-            }
-            Some(cached.clone())
-        } else {
-            None
+    let (cached, fresh) = cached_proposal(cache, &cache_key);
+    if let Some(cached) = cached.filter(|cached| fresh && cached.backfill_block.is_none()) {
+        PROPOSAL_CACHE_COUNTER.hit();
+        return Ok(cached);
+    }
+
+    // Concurrent requests for the same proposal wait for one fetch of its transaction
+    // log instead of each walking the blocks
+    let _guard = lock_proposal_fetch(&cache_key).await;
+
+    // Check cache again after acquiring lock (another request might have fetched the
+    // proposal or continued its backfill)
+    let (last_cached_proposal, fresh) = match cached_proposal(cache, &cache_key) {
+        (Some(cached), true) if cached.backfill_block.is_none() => {
+            debug!(%dao_id, proposal_id, "Cache hit after waiting for the fetch lock");
+            PROPOSAL_CACHE_COUNTER.hit();
+            return Ok(cached);
         }
+        looked_up => looked_up,
     };
 
-    // Fetch new data
-    let block_height_limit = last_cached_proposal
-        .as_ref()
-        .map_or(0, |c| c.txs_log.last().map(|l| l.block_height).unwrap_or(0));
+    let backfill_pages = get_config().scraper.backfill_pages;
+    // A fresh entry only continues its backfill
+    let (proposal, last_updated, new_txs_log, mut backfill_block) = match &last_cached_proposal {
+        Some(c) if fresh => (c.proposal.clone(), c.last_updated, Vec::new(), None),
+        _ => {
+            PROPOSAL_CACHE_COUNTER.miss();
+            let block_height_limit = last_cached_proposal.as_ref().map_or(0, |c| {
+                c.txs_log
+                    .iter()
+                    .map(|tx| tx.block_height)
+                    .max()
+                    .or(c.backfill_block)
+                    .unwrap_or(0)
+            });
+            // Only a first fetch walks back into old history, later ones catch up in full
+            let max_pages = if last_cached_proposal.is_some() {
+                usize::MAX
+            } else {
+                backfill_pages
+            };
+            let source = proposal_source(client);
+            let (proposal, (txs_log, resume_at)) = tokio::try_join!(
+                source.proposal(dao_id, proposal_id),
+                source.proposal_txs(dao_id, proposal_id, block_height_limit, None, max_pages)
+            )?;
+            (proposal, Instant::now(), txs_log, resume_at)
+        }
+    };
 
-    let (proposal, new_txs_log) = tokio::try_join!(
-        fetch_proposal(&client, &dao_id, proposal_id),
-        fetch_proposal_log_txs(&client, dao_id, proposal_id, block_height_limit)
-    )?;
+    // Continue walking back from where the previous request stopped
+    let mut older_txs_log = Vec::new();
+    if let Some(resume_at) = last_cached_proposal.as_ref().and_then(|c| c.backfill_block) {
+        let (txs_log, next_resume_at) = proposal_source(client)
+            .proposal_txs(dao_id, proposal_id, 0, Some(resume_at), backfill_pages)
+            .await?;
+        debug!(
+            %dao_id,
+            proposal_id,
+            txs = txs_log.len(),
+            complete = next_resume_at.is_none(),
+            "Backfilled proposal transaction log"
+        );
+        older_txs_log = txs_log;
+        backfill_block = next_resume_at;
+    }
 
     // Combine transaction logs
-    let combined_txs_log = last_cached_proposal.map_or(new_txs_log.clone(), |c| {
-        [&c.txs_log[..], &new_txs_log[..]].concat()
-    });
+    let combined_txs_log = match last_cached_proposal {
+        Some(c) => [&older_txs_log[..], &c.txs_log[..], &new_txs_log[..]].concat(),
+        None => new_txs_log,
+    };
 
     // Update cache
     let updated = CachedProposal {
-        proposal: proposal.clone(),
-        last_updated: Instant::now(),
-        txs_log: combined_txs_log.clone(),
+        proposal,
+        last_updated,
+        txs_log: combined_txs_log,
+        backfill_block,
     };
 
-    let mut cache_write = cache
-        .write()
-        .map_err(|_| anyhow::anyhow!("Failed to acquire write lock on proposal cache"))?;
-    cache_write.insert(cache_key, updated.clone());
+    cache.insert(cache_key.clone(), updated.clone());
+    PROPOSAL_CHANGES.remove(&cache_key);
 
     Ok(updated)
 }
 
-pub async fn get_ft_metadata_cache(
-    client: &Arc<JsonRpcClient>,
-    cache: &FtMetadataCache,
-    contract_id: &str,
-) -> Result<FtMetadata> {
-    // Check if token is empty or NEAR (case-insensitive)
-    if contract_id.is_empty() || contract_id.eq_ignore_ascii_case("near") {
-        return Ok(FtMetadata::near());
-    }
+/// The proposal's history as of its cached state. A proposal acted on since the history
+/// was fetched only has its new blocks read.
+pub async fn get_proposal_history_cache(
+    client: &Arc<RpcPool>,
+    cache: &ProposalCache,
+    dao_id: &AccountId,
+    proposal_id: u64,
+) -> Result<Vec<ProposalStateChange>> {
+    let cached = get_latest_proposal_cache(client, cache, dao_id, proposal_id).await?;
+    let last_action_block = cached
+        .proposal
+        .last_actions_log
+        .as_ref()
+        .and_then(|log| log.iter().map(|l| l.block_height.0).max());
+    let cache_key = (dao_id.to_string(), proposal_id);
 
-    let token_id = contract_id.parse::<AccountId>()?;
+    let known = match PROPOSAL_HISTORIES.get(&cache_key) {
+        Some(entry) if entry.last_action_block == last_action_block => {
+            return Ok(entry.history.clone());
+        }
+        Some(entry) => entry.history.clone(),
+        None => Vec::new(),
+    };
+    let history = fetch_proposal_history(client, dao_id, &cached.proposal, &known).await?;
 
-    // Acquire read lock and check cache
+    if PROPOSAL_HISTORIES.len() >= MAX_CACHED_HISTORIES
+        && !PROPOSAL_HISTORIES.contains_key(&cache_key)
     {
-        let cache_read = match cache.read() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+        let oldest = PROPOSAL_HISTORIES
+            .iter()
+            .min_by_key(|entry| entry.fetched_at)
+            .map(|entry| entry.key().clone());
+        if let Some(oldest) = oldest {
+            PROPOSAL_HISTORIES.remove(&oldest);
+        }
+    }
+    PROPOSAL_HISTORIES.insert(
+        cache_key,
+        CachedHistory {
+            last_action_block,
+            history: history.clone(),
+            fetched_at: Instant::now(),
+        },
+    );
+    Ok(history)
+}
 
-        if let Some(cached) = cache_read.get(&token_id) {
-            if cached.last_updated.elapsed() <= FT_CACHE_LIFETIME {
-                return Ok(cached.metadata.clone());
-            }
+/// Timestamp of a block in nanoseconds, read from `source` once
+pub async fn get_block_timestamp_cache(
+    source: &dyn ProposalSource,
+    block_height: u64,
+) -> Result<u64> {
+    if let Some(timestamp) = BLOCK_TIMESTAMPS.get(&block_height) {
+        return Ok(*timestamp);
+    }
+    let timestamp = source.block_timestamp(block_height).await?;
+    if BLOCK_TIMESTAMPS.len() >= MAX_CACHED_BLOCK_TIMESTAMPS {
+        let evicted = BLOCK_TIMESTAMPS.iter().next().map(|entry| *entry.key());
+        if let Some(evicted) = evicted {
+            BLOCK_TIMESTAMPS.remove(&evicted);
         }
     }
+    BLOCK_TIMESTAMPS.insert(block_height, timestamp);
+    Ok(timestamp)
+}
 
-    // Fetch fresh metadata
-    let metadata = fetch_ft_metadata(client, &token_id).await?;
+pub async fn get_ft_metadata_cache(
+    client: &Arc<RpcPool>,
+    cache: &FtMetadataCache,
+    contract_id: &str,
+) -> Result<FtMetadata> {
+    let token_id = match TokenId::parse(contract_id)? {
+        TokenId::Near => return Ok(FtMetadata::near()),
+        TokenId::Bridged(token_id) => {
+            return get_config()
+                .tokens
+                .bridged(&token_id)
+                .map(FtMetadata::bridged)
+                .ok_or_else(|| anyhow::anyhow!("Unknown bridged token {}", token_id));
+        }
+        TokenId::Nep141(token_id) => token_id,
+    };
 
-    // Acquire write lock to update cache
-    let mut cache_write = match cache.write() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
+    // Check cache
+    let stale = match cache.get(&token_id) {
+        Some(cached) if cached.last_updated.elapsed() <= get_config().cache.ft_life_time() => {
+            return Ok(cached.metadata.clone());
+        }
+        cached => cached.map(|cached| cached.metadata.clone()),
     };
 
-    cache_write.insert(
+    // Failed tokens aren't asked again until the failure expires, so a deleted or rate
+    // limited contract doesn't cost an RPC call per proposal
+    let recently_failed = FT_METADATA_FAILURES
+        .get(&token_id)
+        .is_some_and(|failed_at| failed_at.elapsed() <= get_config().cache.ft_failure_life_time());
+    let fetched = if recently_failed {
+        Err(anyhow::anyhow!(
+            "Fetching ft_metadata of {} failed recently",
+            token_id
+        ))
+    } else {
+        fetch_ft_metadata(client, &token_id).await.inspect_err(|_| {
+            remember_failure(
+                &FT_METADATA_FAILURES,
+                token_id.clone(),
+                get_config().cache.ft_failure_life_time(),
+            );
+        })
+    };
+
+    // Expired metadata beats the registry, which is only a fallback
+    let metadata = match fetched {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return stale
+                .or_else(|| {
+                    get_config()
+                        .tokens
+                        .registered(token_id.as_str())
+                        .map(FtMetadata::bridged)
+                })
+                .ok_or(e);
+        }
+    };
+    FT_METADATA_FAILURES.remove(&token_id);
+
+    cache.insert(
         token_id.clone(),
         CachedFtMetadata {
             metadata: metadata.clone(),
@@ -233,47 +870,226 @@ pub async fn get_ft_metadata_cache(
     Ok(metadata)
 }
 
-#[derive(Clone)]
-pub struct StakingPoolCache {
-    cache: Arc<tokio::sync::RwLock<HashMap<String, String>>>,
+pub async fn get_latest_bounties_cache(
+    client: &Arc<RpcPool>,
+    store: &BountyStore,
+    dao_id: &AccountId,
+) -> Result<Vec<Bounty>> {
+    if let Some(cached) = store.get(dao_id.as_str())
+        && cached.last_updated.elapsed() <= get_config().cache.life_time()
+    {
+        return Ok(cached.bounties.clone());
+    }
+
+    let bounties = fetch_bounties(client, dao_id).await?;
+
+    store.insert(
+        dao_id.to_string(),
+        CachedBounties {
+            bounties: bounties.clone(),
+            last_updated: Instant::now(),
+        },
+    );
+    Ok(bounties)
 }
 
-impl Default for StakingPoolCache {
-    fn default() -> Self {
-        Self::new()
+/// Claims can only be looked up per account, so `candidates` lists the accounts to check
+pub async fn get_latest_bounty_claims_cache(
+    client: &Arc<RpcPool>,
+    cache: &BountyClaimsCache,
+    dao_id: &AccountId,
+    bounty_id: u64,
+    candidates: &[String],
+) -> Result<CachedBountyClaims> {
+    let cache_key = (dao_id.to_string(), bounty_id);
+
+    if let Some(cached) = cache.get(&cache_key)
+        && cached.last_updated.elapsed() <= get_config().cache.life_time()
+    {
+        return Ok(cached.clone());
     }
+
+    let claims_by_account = async {
+        let results: Vec<Result<(String, Vec<BountyClaim>)>> =
+            stream::iter(candidates.iter().cloned())
+                .map(move |account_id| async move {
+                    let claims = fetch_bounty_claims(client, dao_id, &account_id).await?;
+                    Ok((account_id, claims))
+                })
+                .buffer_unordered(get_config().scraper.bounty_claims_concurrency)
+                .collect()
+                .await;
+        results.into_iter().collect::<Result<Vec<_>>>()
+    };
+
+    let (number_of_claims, claims_by_account) = tokio::try_join!(
+        fetch_bounty_number_of_claims(client, dao_id, bounty_id),
+        claims_by_account
+    )?;
+
+    let mut claims: Vec<AccountBountyClaim> = claims_by_account
+        .into_iter()
+        .flat_map(|(account_id, claims)| {
+            claims
+                .into_iter()
+                .filter(|claim| claim.bounty_id == bounty_id)
+                .map(move |claim| AccountBountyClaim {
+                    account_id: account_id.clone(),
+                    claim,
+                })
+        })
+        .collect();
+    claims.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+
+    let updated = CachedBountyClaims {
+        number_of_claims,
+        claims,
+        last_updated: Instant::now(),
+    };
+
+    cache.insert(cache_key, updated.clone());
+
+    Ok(updated)
 }
 
-impl StakingPoolCache {
-    pub fn new() -> Self {
+/// Result of an RPC lookup that rarely changes, timestamped as unix seconds so it
+/// survives restarts
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct CachedLookup {
+    pub value: Option<String>,
+    pub fetched_at: u64,
+}
+
+/// Bounded lookup results expiring after cache.lookup_life_time_secs. Once
+/// cache.lookup_max_entries is reached the oldest entry makes room for a new one.
+#[derive(Clone, Default)]
+pub struct LookupCache {
+    entries: Arc<tokio::sync::RwLock<HashMap<String, CachedLookup>>>,
+}
+
+impl LookupCache {
+    pub fn from_entries(entries: HashMap<String, CachedLookup>) -> Self {
         Self {
-            cache: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            entries: Arc::new(tokio::sync::RwLock::new(entries)),
         }
     }
 
+    pub async fn entries(&self) -> HashMap<String, CachedLookup> {
+        self.entries.read().await.clone()
+    }
+
+    pub async fn count(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    // Failed lookups are not cached so they are retried on the next request
+    async fn get_or_fetch<F>(&self, key: &str, fetch: F) -> Option<String>
+    where
+        F: Future<Output = Result<Option<String>>>,
+    {
+        let cache_config = &get_config().cache;
+        {
+            let entries = self.entries.read().await;
+            if let Some(cached) = entries.get(key)
+                && unix_now().saturating_sub(cached.fetched_at)
+                    <= cache_config.lookup_life_time_secs
+            {
+                return cached.value.clone();
+            }
+        }
+
+        let value = match fetch.await {
+            Ok(value) => value,
+            Err(e) => {
+                debug!(%key, error = ?e, "Lookup failed");
+                return None;
+            }
+        };
+
+        let mut entries = self.entries.write().await;
+        if entries.len() >= cache_config.lookup_max_entries && !entries.contains_key(key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.fetched_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key.to_string(),
+            CachedLookup {
+                value: value.clone(),
+                fetched_at: unix_now(),
+            },
+        );
+        value
+    }
+}
+
+/// Staking pool each lockup contract delegates to
+#[derive(Clone, Default)]
+pub struct StakingPoolCache(pub LookupCache);
+
+impl StakingPoolCache {
     pub async fn get_staking_pool_account_id(
         &self,
-        client: &JsonRpcClient,
+        client: &RpcPool,
         lockup_account: &str,
     ) -> Option<String> {
-        // Check cache first
-        {
-            let cache = self.cache.read().await;
-            if let Some(pool_id) = cache.get(lockup_account) {
-                return Some(pool_id.clone());
+        self.0
+            .get_or_fetch(lockup_account, async {
+                // A lockup without a pool may get one later, so only found pools are cached
+                crate::rpc_client::get_staking_pool_account_id(client, lockup_account)
+                    .await
+                    .map(Some)
+                    .ok_or_else(|| anyhow::anyhow!("No staking pool for {}", lockup_account))
+            })
+            .await
+    }
+
+    /// Staking pool a stake delegation proposal delegates to. Lockup calls other than
+    /// select_staking_pool go to the lockup's selected pool, None while it has none.
+    pub async fn resolve_validator(
+        &self,
+        client: &RpcPool,
+        info: &StakeDelegationInfo,
+    ) -> Option<String> {
+        match info.lockup_account() {
+            Some(lockup_account) => {
+                self.get_staking_pool_account_id(client, lockup_account)
+                    .await
             }
+            None => Some(info.validator.clone()),
         }
+    }
 
-        // Make RPC call if not in cache
-        if let Some(pool_id) =
-            crate::rpc_client::get_staking_pool_account_id(client, lockup_account).await
-        {
-            // Store in cache - lockup_account is the key, pool_id is the value
-            let mut cache = self.cache.write().await;
-            cache.insert(lockup_account.to_string(), pool_id.clone());
-            Some(pool_id)
-        } else {
-            None
-        }
+    /// resolve_validator for several proposals, in their order. Up to
+    /// scraper.fetch_concurrency lockups are looked up at once.
+    pub async fn resolve_validators(
+        &self,
+        client: &RpcPool,
+        infos: &[StakeDelegationInfo],
+    ) -> Vec<Option<String>> {
+        stream::iter(infos.iter().cloned())
+            .map(move |info| async move { self.resolve_validator(client, &info).await })
+            .buffered(get_config().scraper.fetch_concurrency)
+            .collect()
+            .await
+    }
+}
+
+/// Lockup account of each account, including the accounts without one
+#[derive(Clone, Default)]
+pub struct LockupCache(pub LookupCache);
+
+impl LockupCache {
+    pub async fn get_lockup_account(&self, client: &RpcPool, account_id: &str) -> Option<String> {
+        self.0
+            .get_or_fetch(
+                account_id,
+                crate::rpc_client::find_lockup_account(client, account_id),
+            )
+            .await
     }
 }
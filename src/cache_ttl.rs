@@ -0,0 +1,74 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Request, Response};
+use std::convert::Infallible;
+use std::time::Duration;
+
+use crate::cache::ProposalStore;
+use crate::config::get_config;
+
+/// Life time of the requested DAO's cache entry for the requested endpoint
+pub struct CacheTtl(pub Duration);
+
+// Remembered per request so the response headers report what the route used
+struct ResolvedTtl {
+    dao_id: String,
+    life_time: Duration,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CacheTtl {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let endpoint = request.uri().path().segments().next().unwrap_or_default();
+        // The DAO is the first dynamic segment on every DAO route, /calendar adds .ics
+        let dao_id = request
+            .param::<&str>(0)
+            .and_then(Result::ok)
+            .map(|dao_id| dao_id.trim_end_matches(".ics"))
+            .unwrap_or_default();
+
+        let life_time = get_config().cache.dao_life_time(dao_id, endpoint);
+        request.local_cache(|| {
+            Some(ResolvedTtl {
+                dao_id: dao_id.to_string(),
+                life_time,
+            })
+        });
+        Outcome::Success(CacheTtl(life_time))
+    }
+}
+
+/// Adds X-Cache-TTL and X-Cache-Age to responses of routes using the CacheTtl guard
+pub struct CacheHeaders;
+
+#[rocket::async_trait]
+impl Fairing for CacheHeaders {
+    fn info(&self) -> Info {
+        Info {
+            name: "Cache TTL headers",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(resolved) = request.local_cache(|| None::<ResolvedTtl>) else {
+            return;
+        };
+        response.set_header(Header::new(
+            "X-Cache-TTL",
+            resolved.life_time.as_secs().to_string(),
+        ));
+
+        let age = request.rocket().state::<ProposalStore>().and_then(|store| {
+            store
+                .get(&resolved.dao_id)
+                .map(|cached| cached.last_updated.elapsed())
+        });
+        if let Some(age) = age {
+            response.set_header(Header::new("X-Cache-Age", age.as_secs().to_string()));
+        }
+    }
+}
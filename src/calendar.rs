@@ -0,0 +1,95 @@
+use chrono::{TimeZone, Utc};
+
+use crate::scraper::{Policy, Proposal, extract_from_description};
+
+const ICS_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+// RFC 5545 limits content lines to 75 octets, longer ones are folded
+const MAX_LINE_OCTETS: usize = 75;
+const MAX_SUMMARY_CHARS: usize = 80;
+
+fn ics_timestamp(ns: u64) -> String {
+    Utc.timestamp_nanos(ns as i64)
+        .format(ICS_DATE_FORMAT)
+        .to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Continuation lines start with a space, splits never fall inside a UTF-8 character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn proposal_summary(proposal: &Proposal) -> String {
+    let title = extract_from_description(&proposal.description, "title")
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| proposal.description.clone());
+    let title: String = title
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_SUMMARY_CHARS)
+        .collect();
+    format!("Voting ends: #{} {}", proposal.id, title)
+}
+
+/// Calendar with one event at the end of the voting period of every open proposal
+pub fn voting_deadlines_ics(dao_id: &str, proposals: &[Proposal], policy: &Policy) -> String {
+    let now = ics_timestamp(Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64);
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//sputnik-dao-caching-api-server//Voting deadlines//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{} voting deadlines", escape_text(dao_id)),
+    ];
+
+    for proposal in proposals {
+        let status = proposal.current_status().as_str().to_string();
+        if status != "InProgress" {
+            continue;
+        }
+        let expiry = ics_timestamp(proposal.submission_time.0 + policy.proposal_period.0);
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:{}-{}@sputnik-dao-caching-api-server",
+                dao_id, proposal.id
+            ),
+            format!("DTSTAMP:{}", now),
+            format!("DTSTART:{}", expiry),
+            format!("DTEND:{}", expiry),
+            format!("SUMMARY:{}", escape_text(&proposal_summary(proposal))),
+            format!("DESCRIPTION:{}", escape_text(&proposal.description)),
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            "TRIGGER:-PT24H".to_string(),
+            format!("DESCRIPTION:{}", escape_text(&proposal_summary(proposal))),
+            "END:VALARM".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
@@ -1,5 +1,7 @@
 use crate::scraper::{Proposal, ProposalStatus};
+use rocket::form::FromFormField;
 use serde::{Deserialize, Serialize};
+use tracing::error;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ProposalCsvView {
@@ -32,3 +34,92 @@ impl From<Proposal> for ProposalCsvView {
         }
     }
 }
+
+#[derive(FromFormField, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CsvDelimiter {
+    #[default]
+    Comma,
+    Semicolon,
+    Tab,
+}
+
+#[derive(FromFormField, Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Column layout of accounting software imports for the payments CSV
+#[derive(FromFormField, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountingProfile {
+    Quickbooks,
+    Xero,
+}
+
+impl AccountingProfile {
+    /// Date pattern the import expects, unless overridden with date_format
+    pub fn date_pattern(&self) -> &'static str {
+        match self {
+            AccountingProfile::Quickbooks => "%m/%d/%Y",
+            AccountingProfile::Xero => "%d/%m/%Y",
+        }
+    }
+}
+
+pub const UTF8_BOM: &str = "\u{FEFF}";
+
+/// Output options for CSV exports. European Excel expects semicolons and a BOM.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CsvDialect {
+    pub delimiter: CsvDelimiter,
+    pub line_ending: LineEnding,
+    pub bom: bool,
+}
+
+impl CsvDialect {
+    /// Dialect from the optional query parameters, defaulting to plain comma separated LF
+    pub fn new(
+        delimiter: Option<CsvDelimiter>,
+        line_ending: Option<LineEnding>,
+        bom: Option<bool>,
+    ) -> Self {
+        CsvDialect {
+            delimiter: delimiter.unwrap_or_default(),
+            line_ending: line_ending.unwrap_or_default(),
+            bom: bom.unwrap_or(false),
+        }
+    }
+
+    fn writer_builder(&self) -> csv::WriterBuilder {
+        let mut builder = csv::WriterBuilder::new();
+        builder.delimiter(match self.delimiter {
+            CsvDelimiter::Comma => b',',
+            CsvDelimiter::Semicolon => b';',
+            CsvDelimiter::Tab => b'\t',
+        });
+        builder.terminator(match self.line_ending {
+            LineEnding::Lf => csv::Terminator::Any(b'\n'),
+            LineEnding::Crlf => csv::Terminator::CRLF,
+        });
+        builder
+    }
+
+    /// Serializes a single CSV record, so rows can be streamed one at a time
+    pub fn line<I, T>(&self, record: I) -> String
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let mut wtr = self.writer_builder().from_writer(vec![]);
+        if let Err(e) = wtr.write_record(record) {
+            error!(error = %e, "Error writing CSV record");
+            return String::new();
+        }
+        wtr.into_inner()
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .unwrap_or_default()
+    }
+}
@@ -1,22 +1,28 @@
-use crate::cache::{FtMetadataCache, StakingPoolCache, get_ft_metadata_cache};
+use crate::amounts::parse_units;
+use crate::cache::{
+    FtMetadataCache, StakingPoolCache, get_block_timestamp_cache, get_ft_metadata_cache,
+};
+use crate::kind_decoder::parse_args;
+use crate::rpc_client::RpcPool;
 use crate::scraper::{
-    AssetExchangeInfo, LockupInfo, PaymentInfo, Policy, Proposal, ProposalType,
-    StakeDelegationInfo, get_status_display,
+    AssetExchangeInfo, GovernanceOpsInfo, LockupInfo, MemberChangeInfo, PaymentInfo, Policy,
+    Proposal, ProposalStatus, ProposalType, StakeDelegationInfo, extract_from_description,
+    get_current_time_nanos, last_action_block,
 };
+use crate::source::{ProposalSource, proposal_source};
 
-use near_jsonrpc_client::JsonRpcClient;
-use rocket::form::{FromForm, FromFormField};
-use rocket::serde::Deserialize;
+use base64::{Engine as _, engine::general_purpose};
+use regex::{Regex, RegexBuilder};
+use rocket::form::FromForm;
+use rocket::serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::sync::Arc;
+use tracing::warn;
+use utoipa::{IntoParams, ToSchema};
 
-// Helper function to convert human-readable amount to smallest unit
-fn convert_to_smallest_unit(amount: &str, decimals: u8) -> Option<u128> {
-    amount
-        .parse::<f64>()
-        .ok()
-        .map(|v| (v * 10f64.powi(decimals as i32)) as u128)
-}
+const NANOS_PER_HOUR: u64 = 60 * 60 * 1_000_000_000;
+const NANOS_PER_DAY: u64 = 24 * NANOS_PER_HOUR;
 
 // Helper function to parse date string "2024-09-10" to timestamp
 fn parse_date_to_timestamp(date_str: &str) -> Result<u64, Box<dyn std::error::Error>> {
@@ -30,8 +36,28 @@ fn parse_date_to_timestamp(date_str: &str) -> Result<u64, Box<dyn std::error::Er
     Ok(utc_datetime.timestamp_nanos_opt().unwrap_or(0) as u64)
 }
 
-// Helper function to determine the source of a proposal
-fn get_proposal_source(proposal: &Proposal) -> &'static str {
+// Index of the first block at or after `timestamp`. Block timestamps grow with the height,
+// so a binary search needs only a few block lookups, and those are cached for the next
+// search.
+async fn first_block_from(
+    source: &dyn ProposalSource,
+    blocks: &[u64],
+    timestamp: u64,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let (mut low, mut high) = (0, blocks.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if get_block_timestamp_cache(source, blocks[mid]).await? < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// Where a proposal acts: "intents", "lockup" or "sputnikdao"
+pub fn get_proposal_source(proposal: &Proposal) -> &'static str {
     // Check if it's a NEAR Intents proposal
     if let Some(function_call) = proposal.kind.get("FunctionCall") {
         let receiver_id = function_call
@@ -53,30 +79,269 @@ fn get_proposal_source(proposal: &Proposal) -> &'static str {
     "sputnikdao"
 }
 
-#[derive(Deserialize, FromFormField, Clone)]
+// Token and raw amount of payments, stake delegation and lockup deposits.
+// An empty token means NEAR.
+fn extract_amount(proposal: &Proposal) -> Option<(String, String)> {
+    if let Some(payment_info) = PaymentInfo::from_proposal(proposal) {
+        return Some((payment_info.token, payment_info.amount));
+    }
+    if let Some(stake_info) = StakeDelegationInfo::from_proposal(proposal) {
+        return Some((stake_info.token, stake_info.amount));
+    }
+    if LockupInfo::from_proposal(proposal).is_some() {
+        let deposit = proposal
+            .kind
+            .get("FunctionCall")?
+            .get("actions")?
+            .get(0)?
+            .get("deposit")?
+            .as_str()?;
+        return Some((String::new(), deposit.to_string()));
+    }
+    None
+}
+
+#[derive(Clone, Copy, Debug, ToSchema)]
 pub enum SortBy {
     CreationTime,
     ExpiryTime,
 }
 
+impl SortBy {
+    // Case-insensitive, as rocket parses enum query parameters
+    fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("CreationTime") {
+            Some(SortBy::CreationTime)
+        } else if value.eq_ignore_ascii_case("ExpiryTime") {
+            Some(SortBy::ExpiryTime)
+        } else {
+            None
+        }
+    }
+}
+
+const STATUSES: [&str; 7] = [
+    "InProgress",
+    "Approved",
+    "Rejected",
+    "Removed",
+    "Expired",
+    "Moved",
+    "Failed",
+];
+const SOURCES: [&str; 3] = ["sputnikdao", "intents", "lockup"];
+const STAKE_TYPES: [&str; 4] = ["stake", "unstake", "withdraw", "whitelist"];
+
+const SEARCH_MODES: [&str; 3] = ["substring", "phrase", "regex"];
+const SEARCH_FIELDS: [&str; 4] = ["title", "summary", "notes", "description"];
+const SEARCH_SCOPES: [&str; 3] = ["description", "args", "kind"];
+
+// FunctionCall args as JSON text, base64 args that aren't JSON are left out
+fn decoded_args(kind: &serde_json::Value) -> Vec<String> {
+    kind.get("FunctionCall")
+        .and_then(|call| call.get("actions"))
+        .and_then(|actions| actions.as_array())
+        .map(|actions| {
+            actions
+                .iter()
+                .filter_map(|action| action.get("args")?.as_str())
+                .filter_map(parse_args::<serde_json::Value>)
+                .map(|args| args.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Regex search runs in linear time, these keep compiling user patterns cheap
+const MAX_REGEX_LEN: usize = 256;
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+const REGEX_NEST_LIMIT: u32 = 16;
+
+fn build_search_regex(pattern: &str) -> Result<Regex, String> {
+    if pattern.len() > MAX_REGEX_LEN {
+        return Err(format!("regex longer than {} characters", MAX_REGEX_LEN));
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .nest_limit(REGEX_NEST_LIMIT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Compiled `search` or `search_not` value
+enum SearchPattern {
+    /// Comma-separated keywords, numeric ones match the proposal id
+    Keywords(Vec<String>),
+    /// The whole value with whitespace collapsed
+    Phrase(String),
+    Regex(Regex),
+    /// Invalid regex, matches nothing
+    Invalid,
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl SearchPattern {
+    fn new(query: &str, mode: Option<&str>) -> Self {
+        match mode {
+            Some("phrase") => SearchPattern::Phrase(collapse_whitespace(query)),
+            Some("regex") => build_search_regex(query)
+                .map(SearchPattern::Regex)
+                .unwrap_or(SearchPattern::Invalid),
+            _ => SearchPattern::Keywords(
+                query
+                    .split(',')
+                    .map(|k| k.trim().to_lowercase())
+                    .filter(|k| !k.is_empty())
+                    .collect(),
+            ),
+        }
+    }
+
+    fn matches(&self, proposal_id: u64, text: &str) -> bool {
+        match self {
+            SearchPattern::Keywords(keywords) => {
+                let proposal_id_str = proposal_id.to_string();
+                let text_lower = text.to_lowercase();
+                keywords.iter().any(|kw| {
+                    // If keyword is only numbers, search for exact proposal ID match
+                    if kw.chars().all(|c| c.is_ascii_digit()) {
+                        proposal_id_str == *kw
+                    } else {
+                        text_lower.contains(kw) || proposal_id_str.contains(kw)
+                    }
+                })
+            }
+            SearchPattern::Phrase(phrase) => collapse_whitespace(text).contains(phrase),
+            SearchPattern::Regex(regex) => regex.is_match(text),
+            SearchPattern::Invalid => false,
+        }
+    }
+}
+
+/// A filter value rejected with strict=true
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct InvalidParameter {
+    pub parameter: &'static str,
+    pub value: String,
+    pub reason: String,
+}
+
+// Whole token amount like "1.5"
+fn is_decimal_amount(amount: &str) -> bool {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
 pub mod categories {
     pub const PAYMENTS: &str = "payments";
     pub const LOCKUP: &str = "lockup";
     pub const ASSET_EXCHANGE: &str = "asset-exchange";
     pub const STAKE_DELEGATION: &str = "stake-delegation";
+    pub const MEMBERS: &str = "members";
+    pub const GOVERNANCE_OPS: &str = "governance-ops";
+}
+
+fn is_lockup(proposal: &Proposal) -> bool {
+    LockupInfo::from_proposal(proposal).is_some()
+}
+
+fn is_asset_exchange(proposal: &Proposal) -> bool {
+    AssetExchangeInfo::from_proposal(proposal).is_some()
+}
+
+fn is_stake_delegation(proposal: &Proposal) -> bool {
+    StakeDelegationInfo::from_proposal(proposal).is_some()
+}
+
+fn is_governance_ops(proposal: &Proposal) -> bool {
+    GovernanceOpsInfo::from_proposal(proposal).is_some()
+}
+
+fn is_members(proposal: &Proposal) -> bool {
+    MemberChangeInfo::from_proposal(proposal).is_some()
+}
+
+fn is_payment(proposal: &Proposal) -> bool {
+    PaymentInfo::from_proposal(proposal).is_some()
+}
+
+type CategoryPredicate = fn(&Proposal) -> bool;
+
+/// Most specific first. Payments come last since lockup, asset-exchange and stake proposals
+/// can look like transfers too.
+const CATEGORY_PREDICATES: [(&str, CategoryPredicate); 6] = [
+    (categories::LOCKUP, is_lockup),
+    (categories::ASSET_EXCHANGE, is_asset_exchange),
+    (categories::STAKE_DELEGATION, is_stake_delegation),
+    (categories::GOVERNANCE_OPS, is_governance_ops),
+    (categories::MEMBERS, is_members),
+    (categories::PAYMENTS, is_payment),
+];
+
+fn category_predicate(category: &str) -> Option<CategoryPredicate> {
+    CATEGORY_PREDICATES
+        .iter()
+        .find(|(name, _)| *name == category)
+        .map(|(_, predicate)| *predicate)
+}
+
+// Predicates of comma-separated categories, unknown ones are ignored
+fn category_predicates(opt: &Option<String>) -> Vec<CategoryPredicate> {
+    opt.iter()
+        .flat_map(|s| s.split(','))
+        .filter_map(|category| category_predicate(category.trim()))
+        .collect()
 }
 
-#[derive(Deserialize, FromForm, Default, Clone)]
+/// Categories a proposal belongs to, most specific first
+pub fn proposal_categories(proposal: &Proposal) -> Vec<&'static str> {
+    CATEGORY_PREDICATES
+        .iter()
+        .filter(|(_, predicate)| predicate(proposal))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+#[derive(Deserialize, FromForm, Default, Clone, Debug, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ProposalFilters {
     pub statuses: Option<String>, // comma-separated values like "Approved,Rejected"
     pub search: Option<String>,   // search the description
     pub search_not: Option<String>, // exclude proposals containing these keywords
+    pub search_mode: Option<String>, // substring (default), phrase or regex, for search and search_not
+    pub search_fields: Option<String>, // comma-separated title, summary, notes or description (default)
+    pub search_scope: Option<String>,  // comma-separated description (default), args or kind
     pub proposal_types: Option<String>, // comma-separated values like 'FunctionCall,Transfer'
-    pub sort_by: Option<SortBy>,
+    pub id_from: Option<u64>,          // proposal id, inclusive
+    pub id_to: Option<u64>,            // proposal id, inclusive
+    pub ids: Option<String>,           // comma-separated proposal ids like "1,5,9"
+    pub receiver_ids: Option<String>,  // comma-separated FunctionCall receivers
+    pub receiver_ids_not: Option<String>, // comma-separated FunctionCall receivers to exclude
+    pub method_names: Option<String>,  // comma-separated methods, any FunctionCall action may match
+    pub kind_filter: Option<String>, // comma-separated conditions on kind fields like 'FunctionCall.receiver_id=intents.near', all must match
+    #[schema(value_type = Option<SortBy>)]
+    #[param(value_type = Option<SortBy>)]
+    pub sort_by: Option<String>, // CreationTime or ExpiryTime
     pub sort_direction: Option<String>, // "asc" or "desc"
-    pub category: Option<String>,
+    pub category: Option<String>,    // comma-separated categories, OR logic
+    pub category_not: Option<String>, // comma-separated categories to exclude
     pub created_date_from: Option<String>,
     pub created_date_to: Option<String>,
+    pub expires_after: Option<String>, // voting period ends on or after this date
+    pub expires_before: Option<String>, // voting period ends on or before this date
+    pub updated_from: Option<String>,  // last action on or after this date
+    pub updated_to: Option<String>,    // last action on or before this date
+    pub expiring_within: Option<u64>, // hours, only open proposals whose voting period ends by then
 
     pub amount_min: Option<String>,
     pub amount_max: Option<String>,
@@ -89,6 +354,13 @@ pub struct ProposalFilters {
     pub approvers_not: Option<String>, // array of accounts
     pub voter_votes: Option<String>, // format: "account:vote,account:vote" where vote is "approved" or "rejected"
 
+    // Vote counts
+    pub min_votes: Option<usize>,            // accounts that voted
+    pub max_votes: Option<usize>,            // accounts that voted
+    pub approvals_min: Option<usize>,        // Approve votes
+    pub rejections_min: Option<usize>,       // Reject and Remove votes
+    pub needs_n_more_approvals: Option<u64>, // open proposals this many approvals from passing
+
     // Source filter
     pub source: Option<String>, // comma-separated values like "sputnikdao,intents,lockup"
     pub source_not: Option<String>, // comma-separated values to exclude like "sputnikdao,intents,lockup"
@@ -104,9 +376,79 @@ pub struct ProposalFilters {
     pub stake_type_not: Option<String>, // comma-separated values to exclude like "stake,unstake,withdraw"
     pub validators: Option<String>,     // comma-separated validator accounts
     pub validators_not: Option<String>, // comma-separated validator accounts to exclude
+
+    // Member change specific filters
+    pub members: Option<String>, // comma-separated accounts added to or removed from a role
+    pub members_not: Option<String>, // comma-separated accounts to exclude
     // Pagination
     pub page: Option<usize>,
     pub page_size: Option<usize>,
+    pub cursor: Option<String>, // opaque cursor returned as next_cursor, takes precedence over page
+    pub strict: Option<bool>,   // reject invalid values with 400 instead of ignoring them
+}
+
+/// Position of the last proposal of a page in the sorted result set.
+/// Paging by cursor stays stable while new proposals are being added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub sort_key: u64,
+    pub id: u64,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(format!("{}:{}", self.sort_key, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let decoded = general_purpose::URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (sort_key, id) = decoded.split_once(':')?;
+        Some(Cursor {
+            sort_key: sort_key.parse().ok()?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidCursor;
+
+/// Filters that can't match any proposal together, e.g. tokens with category=lockup
+#[derive(Debug)]
+pub struct FilterConflict(pub String);
+
+impl std::fmt::Display for FilterConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub struct Page<'a> {
+    pub proposals: Vec<&'a Proposal>,
+    pub next_cursor: Option<String>,
+}
+
+/// Result of filter_proposals_async: positions in the cached proposal set, in result
+/// order. The set itself is shared, so filtering never copies a proposal.
+#[derive(Clone)]
+pub struct FilteredProposals {
+    proposals: Arc<[Proposal]>,
+    indices: Vec<usize>,
+}
+
+impl FilteredProposals {
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Proposal> {
+        self.indices.iter().map(|&index| &self.proposals[index])
+    }
 }
 
 fn to_str_hashset(opt: &Option<String>) -> Option<HashSet<&str>> {
@@ -138,47 +480,451 @@ fn parse_voter_votes(opt: &Option<String>) -> Option<Vec<VoterVote>> {
     })
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// One `kind_filter` condition like `FunctionCall.receiver_id=intents.near`
+#[derive(Debug, Clone)]
+struct KindCondition {
+    path: Vec<String>,
+    comparator: Comparator,
+    value: String,
+}
+
+impl KindCondition {
+    fn parse(condition: &str) -> Option<Self> {
+        let op_start = condition.find(['=', '!', '<', '>'])?;
+        let (path, rest) = condition.split_at(op_start);
+        let (comparator, value) = [
+            ("!=", Comparator::Ne),
+            (">=", Comparator::Ge),
+            ("<=", Comparator::Le),
+            ("=", Comparator::Eq),
+            (">", Comparator::Gt),
+            ("<", Comparator::Lt),
+        ]
+        .into_iter()
+        .find_map(|(op, comparator)| rest.strip_prefix(op).map(|value| (comparator, value)))?;
+
+        let path: Vec<String> = path
+            .trim()
+            .split('.')
+            .map(|k| k.trim().to_string())
+            .collect();
+        if path.iter().any(|k| k.is_empty()) {
+            return None;
+        }
+        Some(KindCondition {
+            path,
+            comparator,
+            value: value.trim().to_string(),
+        })
+    }
+
+    // Walks the path through objects and array indexes. Base64 encoded JSON strings,
+    // like FunctionCall action args, are decoded when the path continues into them.
+    fn resolve(&self, kind: &serde_json::Value) -> Option<serde_json::Value> {
+        let mut current = kind.clone();
+        for key in &self.path {
+            if let serde_json::Value::String(encoded) = &current {
+                let decoded = general_purpose::STANDARD.decode(encoded).ok()?;
+                current = serde_json::from_slice(&decoded).ok()?;
+            }
+            current = match &current {
+                serde_json::Value::Array(items) => items.get(key.parse::<usize>().ok()?)?.clone(),
+                _ => current.get(key)?.clone(),
+            };
+        }
+        Some(current)
+    }
+
+    // Missing fields never match. Ordering comparators need numeric values on both sides.
+    fn matches(&self, kind: &serde_json::Value) -> bool {
+        let Some(field) = self.resolve(kind) else {
+            return false;
+        };
+        let field = match field {
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        };
+
+        let ordering = match (field.parse::<u128>(), self.value.parse::<u128>()) {
+            (Ok(field), Ok(value)) => Some(field.cmp(&value)),
+            _ => match (field.parse::<f64>(), self.value.parse::<f64>()) {
+                (Ok(field), Ok(value)) => field.partial_cmp(&value),
+                _ => None,
+            },
+        };
+
+        match self.comparator {
+            Comparator::Eq => ordering.map_or(field == self.value, |o| o == Ordering::Equal),
+            Comparator::Ne => ordering.map_or(field != self.value, |o| o != Ordering::Equal),
+            Comparator::Gt => ordering == Some(Ordering::Greater),
+            Comparator::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            Comparator::Lt => ordering == Some(Ordering::Less),
+            Comparator::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        }
+    }
+}
+
+// Invalid conditions are kept as None so they exclude every proposal instead of being ignored
+fn parse_kind_filter(opt: &Option<String>) -> Option<Vec<Option<KindCondition>>> {
+    opt.as_ref().map(|s| {
+        s.split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(KindCondition::parse)
+            .collect()
+    })
+}
+
 impl ProposalFilters {
+    fn sort_by(&self) -> Option<SortBy> {
+        self.sort_by.as_deref().and_then(SortBy::parse)
+    }
+
+    /// Values that are ignored or match nothing outside of strict mode
+    pub fn validate(&self) -> Vec<InvalidParameter> {
+        let mut invalid = Vec::new();
+        let mut reject = |parameter: &'static str, value: &str, reason: &str| {
+            invalid.push(InvalidParameter {
+                parameter,
+                value: value.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        let values = |opt: &Option<String>| -> Vec<String> {
+            opt.iter()
+                .flat_map(|s| s.split(','))
+                .map(|v| v.trim().to_string())
+                .collect()
+        };
+
+        for (parameter, date) in [
+            ("created_date_from", &self.created_date_from),
+            ("created_date_to", &self.created_date_to),
+            ("expires_after", &self.expires_after),
+            ("expires_before", &self.expires_before),
+            ("updated_from", &self.updated_from),
+            ("updated_to", &self.updated_to),
+        ] {
+            if let Some(date) = date
+                && parse_date_to_timestamp(date).is_err()
+            {
+                reject(parameter, date, "expected a date as YYYY-MM-DD");
+            }
+        }
+        for (parameter, amount) in [
+            ("amount_min", &self.amount_min),
+            ("amount_max", &self.amount_max),
+            ("amount_equal", &self.amount_equal),
+        ] {
+            if let Some(amount) = amount
+                && !is_decimal_amount(amount.trim())
+            {
+                reject(
+                    parameter,
+                    amount,
+                    "expected a non-negative decimal like 1.5",
+                );
+            }
+        }
+        if let Some(sort_by) = &self.sort_by
+            && SortBy::parse(sort_by).is_none()
+        {
+            reject("sort_by", sort_by, "expected CreationTime or ExpiryTime");
+        }
+        if let Some(direction) = &self.sort_direction
+            && !direction.eq_ignore_ascii_case("asc")
+            && !direction.eq_ignore_ascii_case("desc")
+        {
+            reject("sort_direction", direction, "expected asc or desc");
+        }
+        for (parameter, opt) in [
+            ("category", &self.category),
+            ("category_not", &self.category_not),
+        ] {
+            for category in values(opt) {
+                if category_predicate(&category).is_none() {
+                    reject(parameter, &category, "unknown category");
+                }
+            }
+        }
+        if let Some(mode) = &self.search_mode
+            && !SEARCH_MODES.contains(&mode.as_str())
+        {
+            reject("search_mode", mode, "expected substring, phrase or regex");
+        }
+        if self.search_mode.as_deref() == Some("regex") {
+            for (parameter, pattern) in [("search", &self.search), ("search_not", &self.search_not)]
+            {
+                if let Some(pattern) = pattern
+                    && let Err(e) = build_search_regex(pattern)
+                {
+                    reject(parameter, pattern, &e);
+                }
+            }
+        }
+        for field in values(&self.search_fields) {
+            if !SEARCH_FIELDS.contains(&field.as_str()) {
+                reject(
+                    "search_fields",
+                    &field,
+                    "expected title, summary, notes or description",
+                );
+            }
+        }
+        for scope in values(&self.search_scope) {
+            if !SEARCH_SCOPES.contains(&scope.as_str()) {
+                reject("search_scope", &scope, "expected description, args or kind");
+            }
+        }
+        for status in values(&self.statuses) {
+            if !STATUSES.contains(&status.as_str()) {
+                reject("statuses", &status, "unknown status");
+            }
+        }
+        for (parameter, opt) in [("source", &self.source), ("source_not", &self.source_not)] {
+            for source in values(opt) {
+                if !SOURCES.contains(&source.as_str()) {
+                    reject(parameter, &source, "expected sputnikdao, intents or lockup");
+                }
+            }
+        }
+        for (parameter, opt) in [
+            ("stake_type", &self.stake_type),
+            ("stake_type_not", &self.stake_type_not),
+        ] {
+            for stake_type in values(opt) {
+                if !STAKE_TYPES.contains(&stake_type.as_str()) {
+                    reject(
+                        parameter,
+                        &stake_type,
+                        "expected stake, unstake, withdraw or whitelist",
+                    );
+                }
+            }
+        }
+        for pair in values(&self.voter_votes) {
+            let valid = pair.split_once(':').is_some_and(|(account, vote)| {
+                !account.trim().is_empty()
+                    && matches!(vote.trim().to_lowercase().as_str(), "approved" | "rejected")
+            });
+            if !valid {
+                reject(
+                    "voter_votes",
+                    &pair,
+                    "expected account:approved or account:rejected",
+                );
+            }
+        }
+        for condition in values(&self.kind_filter) {
+            if !condition.is_empty() && KindCondition::parse(&condition).is_none() {
+                reject(
+                    "kind_filter",
+                    &condition,
+                    "expected path, comparator and value like FunctionCall.receiver_id=intents.near",
+                );
+            }
+        }
+        if let Some(cursor) = &self.cursor
+            && Cursor::decode(cursor).is_none()
+        {
+            reject("cursor", cursor, "not a cursor returned as next_cursor");
+        }
+        for id in values(&self.ids) {
+            if id.parse::<u64>().is_err() {
+                reject("ids", &id, "expected a proposal id");
+            }
+        }
+        if let (Some(from), Some(to)) = (self.id_from, self.id_to)
+            && from > to
+        {
+            reject("id_to", &to.to_string(), "must not be less than id_from");
+        }
+        if let (Some(min), Some(max)) = (self.min_votes, self.max_votes)
+            && min > max
+        {
+            reject(
+                "max_votes",
+                &max.to_string(),
+                "must not be less than min_votes",
+            );
+        }
+        if self.page_size == Some(0) {
+            reject("page_size", "0", "must be at least 1");
+        }
+        invalid
+    }
+
+    fn has_payment_filter(&self) -> bool {
+        self.recipients.is_some()
+            || self.recipients_not.is_some()
+            || self.tokens.is_some()
+            || self.tokens_not.is_some()
+    }
+
+    fn has_stake_filter(&self) -> bool {
+        self.stake_type.is_some()
+            || self.stake_type_not.is_some()
+            || self.validators.is_some()
+            || self.validators_not.is_some()
+    }
+
+    fn has_member_filter(&self) -> bool {
+        self.members.is_some() || self.members_not.is_some()
+    }
+
+    /// Payment, stake and member filters imply their category, so they can't be combined
+    /// with other categories only, an excluded category or with each other
+    pub fn check_conflicts(&self) -> Result<(), FilterConflict> {
+        let category_set = to_str_hashset(&self.category);
+        let category_not_set = to_str_hashset(&self.category_not).unwrap_or_default();
+        let requires_payment = self.recipients.is_some() || self.tokens.is_some();
+        let requires_stake = self.stake_type.is_some() || self.validators.is_some();
+        let requires_member = self.members.is_some();
+
+        if self.has_payment_filter()
+            && category_set
+                .as_ref()
+                .is_some_and(|c| !c.contains(categories::PAYMENTS))
+        {
+            return Err(FilterConflict(format!(
+                "recipients and tokens filters only apply to payments, not to category {}",
+                self.category.as_deref().unwrap_or_default()
+            )));
+        }
+        if self.has_stake_filter()
+            && category_set
+                .as_ref()
+                .is_some_and(|c| !c.contains(categories::STAKE_DELEGATION))
+        {
+            return Err(FilterConflict(format!(
+                "stake_type and validators filters only apply to stake-delegation, not to category {}",
+                self.category.as_deref().unwrap_or_default()
+            )));
+        }
+        if requires_payment && category_not_set.contains(categories::PAYMENTS) {
+            return Err(FilterConflict(
+                "recipients and tokens only match payments, which category_not excludes"
+                    .to_string(),
+            ));
+        }
+        if requires_stake && category_not_set.contains(categories::STAKE_DELEGATION) {
+            return Err(FilterConflict(
+                "stake_type and validators only match stake delegations, which category_not excludes".to_string(),
+            ));
+        }
+        if self.has_member_filter()
+            && category_set
+                .as_ref()
+                .is_some_and(|c| !c.contains(categories::MEMBERS))
+        {
+            return Err(FilterConflict(format!(
+                "members filters only apply to members, not to category {}",
+                self.category.as_deref().unwrap_or_default()
+            )));
+        }
+        if requires_member && category_not_set.contains(categories::MEMBERS) {
+            return Err(FilterConflict(
+                "members only matches member changes, which category_not excludes".to_string(),
+            ));
+        }
+        if requires_payment && requires_stake {
+            return Err(FilterConflict(
+                "recipients or tokens can't be combined with stake_type or validators, a proposal is either a payment or a stake delegation".to_string(),
+            ));
+        }
+        if requires_member && (requires_payment || requires_stake) {
+            return Err(FilterConflict(
+                "members can't be combined with recipients, tokens, stake_type or validators, a member change moves no funds".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Proposals matching the filters, sorted, picked out of `proposals` without copying
+    /// them. Lookups for the date, validator and amount filters go through `client`, the
+    /// shared RPC pool.
     pub async fn filter_proposals_async(
         &self,
-        proposals: Vec<Proposal>,
+        client: &Arc<RpcPool>,
+        proposals: &Arc<[Proposal]>,
         policy: &Policy,
         ft_metadata_cache: &FtMetadataCache,
-    ) -> Result<Vec<Proposal>, Box<dyn std::error::Error>> {
-        let client = Arc::new(JsonRpcClient::connect("https://rpc.mainnet.near.org"));
-        let staking_pool_cache = StakingPoolCache::new();
-
+        staking_pool_cache: &StakingPoolCache,
+    ) -> Result<FilteredProposals, Box<dyn std::error::Error>> {
         let statuses_set = to_str_hashset(&self.statuses);
         let proposers_set = to_str_hashset(&self.proposers);
         let proposers_not_set = to_str_hashset(&self.proposers_not);
         let approvers_set = to_str_hashset(&self.approvers);
         let approvers_not_set = to_str_hashset(&self.approvers_not);
         let voter_votes_set = parse_voter_votes(&self.voter_votes);
+        let kind_conditions = parse_kind_filter(&self.kind_filter);
         let recipients_set = to_str_hashset(&self.recipients);
         let recipients_not_set = to_str_hashset(&self.recipients_not);
         let tokens_set = to_str_hashset(&self.tokens);
         let tokens_not_set = to_str_hashset(&self.tokens_not);
         let proposal_types_set = to_str_hashset(&self.proposal_types);
+        let receiver_ids_set = to_str_hashset(&self.receiver_ids);
+        let receiver_ids_not_set = to_str_hashset(&self.receiver_ids_not);
+        let method_names_set = to_str_hashset(&self.method_names);
         let stake_type_set = to_str_hashset(&self.stake_type);
         let stake_type_not_set = to_str_hashset(&self.stake_type_not);
         let validators_set = to_str_hashset(&self.validators);
         let validators_not_set = to_str_hashset(&self.validators_not);
+        let members_set = to_str_hashset(&self.members);
+        let members_not_set = to_str_hashset(&self.members_not);
         let source_set = to_str_hashset(&self.source);
         let source_not_set = to_str_hashset(&self.source_not);
 
-        let search_keywords: Option<Vec<String>> = self.search.as_ref().map(|s| {
-            s.split(',')
-                .map(|k| k.trim().to_lowercase())
-                .filter(|k| !k.is_empty())
-                .collect()
-        });
-
-        let search_not_keywords: Option<Vec<String>> = self.search_not.as_ref().map(|s| {
-            s.split(',')
-                .map(|k| k.trim().to_lowercase())
-                .filter(|k| !k.is_empty())
-                .collect()
-        });
+        let search_mode = self.search_mode.as_deref();
+        let search_pattern = self
+            .search
+            .as_ref()
+            .map(|s| SearchPattern::new(s, search_mode));
+        let search_not_pattern = self
+            .search_not
+            .as_ref()
+            .map(|s| SearchPattern::new(s, search_mode));
+        // Unknown fields are ignored, no known field searches the whole description
+        let search_fields: Vec<&str> = self
+            .search_fields
+            .iter()
+            .flat_map(|s| s.split(','))
+            .map(|field| field.trim())
+            .filter(|field| SEARCH_FIELDS.contains(field))
+            .collect();
+        let mut search_scopes: Vec<&str> = self
+            .search_scope
+            .iter()
+            .flat_map(|s| s.split(','))
+            .map(|scope| scope.trim())
+            .filter(|scope| SEARCH_SCOPES.contains(scope))
+            .collect();
+        if search_scopes.is_empty() {
+            search_scopes.push("description");
+        }
+        let search_text = |proposal: &Proposal| -> String {
+            let mut parts = Vec::new();
+            for scope in &search_scopes {
+                match *scope {
+                    "args" => parts.extend(decoded_args(&proposal.kind)),
+                    "kind" => parts.push(proposal.kind.to_string()),
+                    _ if search_fields.is_empty() => parts.push(proposal.description.clone()),
+                    _ => parts.extend(search_fields.iter().filter_map(|field| {
+                        extract_from_description(&proposal.description, field)
+                    })),
+                }
+            }
+            parts.join("\n")
+        };
 
         let from_timestamp = self
             .created_date_from
@@ -189,21 +935,97 @@ impl ProposalFilters {
             .as_ref()
             .and_then(|d| parse_date_to_timestamp(d).ok());
 
+        let expires_after = self
+            .expires_after
+            .as_ref()
+            .and_then(|d| parse_date_to_timestamp(d).ok());
+        let expires_before = self
+            .expires_before
+            .as_ref()
+            .and_then(|d| parse_date_to_timestamp(d).ok());
+        let now = get_current_time_nanos().0;
+        let expiring_until = self
+            .expiring_within
+            .map(|hours| now.saturating_add(hours.saturating_mul(NANOS_PER_HOUR)));
+
+        let category_preds = category_predicates(&self.category);
+        let category_not_preds = category_predicates(&self.category_not);
+
+        // Unparsable ids never match
+        let ids_set: Option<HashSet<u64>> = self.ids.as_ref().map(|ids| {
+            ids.split(',')
+                .filter_map(|id| id.trim().parse().ok())
+                .collect()
+        });
+
+        let updated_from = self
+            .updated_from
+            .as_ref()
+            .and_then(|d| parse_date_to_timestamp(d).ok());
+        // The whole updated_to day is included
+        let updated_until = self
+            .updated_to
+            .as_ref()
+            .and_then(|d| parse_date_to_timestamp(d).ok())
+            .map(|ts| ts + NANOS_PER_DAY);
+
+        // The actions log only has block heights, so the dates are turned into a range of
+        // the proposals' last action blocks
+        let mut last_blocks: Vec<u64> = Vec::new();
+        if updated_from.is_some() || updated_until.is_some() {
+            last_blocks = proposals.iter().filter_map(last_action_block).collect();
+            last_blocks.sort_unstable();
+            last_blocks.dedup();
+        }
+        let source = proposal_source(client);
+        let first_updated_block = match updated_from {
+            Some(ts) => Some(first_block_from(source.as_ref(), &last_blocks, ts).await?),
+            None => None,
+        };
+        let end_updated_block = match updated_until {
+            Some(ts) => Some(first_block_from(source.as_ref(), &last_blocks, ts).await?),
+            None => None,
+        };
+        // Proposals without an actions log were last updated when submitted
+        let updated_in_range = |proposal: &Proposal| match last_action_block(proposal) {
+            Some(block) => {
+                let index = last_blocks.partition_point(|b| *b < block);
+                first_updated_block.is_none_or(|first| index >= first)
+                    && end_updated_block.is_none_or(|end| index < end)
+            }
+            None => {
+                let ts = proposal.submission_time.0;
+                updated_from.is_none_or(|from| ts >= from)
+                    && updated_until.is_none_or(|until| ts < until)
+            }
+        };
+
         let mut filtered_proposals = Vec::with_capacity(proposals.len());
 
-        for proposal in proposals {
+        for (index, proposal) in proposals.iter().enumerate() {
             let submission_time = proposal.submission_time.0;
 
-            if let Some(ref proposers) = proposers_set {
-                if !proposers.contains(proposal.proposer.as_str()) {
-                    continue;
-                }
+            if self.id_from.is_some_and(|from| proposal.id < from)
+                || self.id_to.is_some_and(|to| proposal.id > to)
+            {
+                continue;
+            }
+            if let Some(ref ids) = ids_set
+                && !ids.contains(&proposal.id)
+            {
+                continue;
             }
 
-            if let Some(ref proposers_not) = proposers_not_set {
-                if proposers_not.contains(proposal.proposer.as_str()) {
-                    continue;
-                }
+            if let Some(ref proposers) = proposers_set
+                && !proposers.contains(proposal.proposer.as_str())
+            {
+                continue;
+            }
+
+            if let Some(ref proposers_not) = proposers_not_set
+                && proposers_not.contains(proposal.proposer.as_str())
+            {
+                continue;
             }
 
             if let Some(ref approvers) = approvers_set {
@@ -224,59 +1046,57 @@ impl ProposalFilters {
                 }
             }
 
-            if let Some(from_ts) = from_timestamp {
-                if submission_time < from_ts {
-                    continue;
-                }
+            if let Some(from_ts) = from_timestamp
+                && submission_time < from_ts
+            {
+                continue;
             }
-            if let Some(to_ts) = to_timestamp {
-                if submission_time > to_ts {
-                    continue;
-                }
+            if let Some(to_ts) = to_timestamp
+                && submission_time > to_ts
+            {
+                continue;
             }
 
-            if let Some(ref statuses) = statuses_set {
-                let computed_status = get_status_display(
-                    &proposal.status,
-                    submission_time,
-                    policy.proposal_period.0,
-                    "InProgress",
-                );
-                if !statuses.contains(computed_status.as_str()) {
-                    continue;
-                }
+            let expiry_time = submission_time + policy.proposal_period.0;
+            if let Some(after_ts) = expires_after
+                && expiry_time < after_ts
+            {
+                continue;
+            }
+            // The whole expires_before day is included
+            if let Some(before_ts) = expires_before
+                && expiry_time >= before_ts + NANOS_PER_DAY
+            {
+                continue;
+            }
+            if let Some(until_ts) = expiring_until
+                && (proposal.status != ProposalStatus::InProgress
+                    || expiry_time < now
+                    || expiry_time > until_ts)
+            {
+                continue;
             }
 
-            if let Some(ref keywords) = search_keywords {
-                let proposal_id_str = proposal.id.to_string();
-                let description_lower = proposal.description.to_lowercase();
-                let proposal_id_lower = proposal_id_str.to_lowercase();
-
-                if !keywords.iter().any(|kw| {
-                    // If keyword is only numbers, search for exact proposal ID match
-                    if kw.chars().all(|c| c.is_ascii_digit()) {
-                        proposal_id_str == *kw
-                    } else {
-                        description_lower.contains(kw) || proposal_id_lower.contains(kw)
-                    }
-                }) {
-                    continue;
-                }
+            if (updated_from.is_some() || updated_until.is_some()) && !updated_in_range(proposal) {
+                continue;
             }
 
-            if let Some(ref keywords_not) = search_not_keywords {
-                let proposal_id_str = proposal.id.to_string();
-                let description_lower = proposal.description.to_lowercase();
-                let proposal_id_lower = proposal_id_str.to_lowercase();
+            if let Some(ref statuses) = statuses_set
+                && !statuses.contains(proposal.current_status().as_str())
+            {
+                continue;
+            }
 
-                if keywords_not.iter().any(|kw| {
-                    // If keyword is only numbers, search for exact proposal ID match
-                    if kw.chars().all(|c| c.is_ascii_digit()) {
-                        proposal_id_str == *kw
-                    } else {
-                        description_lower.contains(kw) || proposal_id_lower.contains(kw)
-                    }
-                }) {
+            if search_pattern.is_some() || search_not_pattern.is_some() {
+                let text = search_text(proposal);
+                if let Some(ref pattern) = search_pattern
+                    && !pattern.matches(proposal.id, &text)
+                {
+                    continue;
+                }
+                if let Some(ref pattern) = search_not_pattern
+                    && pattern.matches(proposal.id, &text)
+                {
                     continue;
                 }
             }
@@ -296,6 +1116,47 @@ impl ProposalFilters {
                 }
             }
 
+            // Receiver and method filters only match function calls, the exclusion leaves
+            // other kinds alone
+            let function_call = proposal.kind.get("FunctionCall");
+            let receiver_id = function_call
+                .and_then(|call| call.get("receiver_id"))
+                .and_then(|receiver| receiver.as_str());
+            if let Some(ref receiver_ids) = receiver_ids_set
+                && !receiver_id.is_some_and(|receiver| receiver_ids.contains(receiver))
+            {
+                continue;
+            }
+            if let Some(ref receiver_ids_not) = receiver_ids_not_set
+                && receiver_id.is_some_and(|receiver| receiver_ids_not.contains(receiver))
+            {
+                continue;
+            }
+            if let Some(ref method_names) = method_names_set {
+                let calls_method = function_call
+                    .and_then(|call| call.get("actions"))
+                    .and_then(|actions| actions.as_array())
+                    .is_some_and(|actions| {
+                        actions.iter().any(|action| {
+                            action
+                                .get("method_name")
+                                .and_then(|method| method.as_str())
+                                .is_some_and(|method| method_names.contains(method))
+                        })
+                    });
+                if !calls_method {
+                    continue;
+                }
+            }
+
+            if let Some(ref conditions) = kind_conditions
+                && !conditions
+                    .iter()
+                    .all(|c| c.as_ref().is_some_and(|c| c.matches(&proposal.kind)))
+            {
+                continue;
+            }
+
             if let Some(ref voter_votes) = voter_votes_set {
                 let mut all_voter_checks_passed = true;
                 for voter_vote in voter_votes {
@@ -323,9 +1184,36 @@ impl ProposalFilters {
                 }
             }
 
+            let votes = proposal.votes.len();
+            if self.min_votes.is_some_and(|min| votes < min)
+                || self.max_votes.is_some_and(|max| votes > max)
+            {
+                continue;
+            }
+            if self.approvals_min.is_some() || self.rejections_min.is_some() {
+                let approvals = proposal
+                    .votes
+                    .values()
+                    .filter(|vote| matches!(vote, crate::scraper::Vote::Approve))
+                    .count();
+                if self.approvals_min.is_some_and(|min| approvals < min)
+                    || self
+                        .rejections_min
+                        .is_some_and(|min| votes - approvals < min)
+                {
+                    continue;
+                }
+            }
+            if let Some(needed) = self.needs_n_more_approvals
+                && (*proposal.current_status() != ProposalStatus::InProgress
+                    || policy.approvals_needed(proposal) != Some(needed.into()))
+            {
+                continue;
+            }
+
             // Filter by source
             if let Some(ref sources) = source_set {
-                let proposal_source = get_proposal_source(&proposal);
+                let proposal_source = get_proposal_source(proposal);
                 if !sources.contains(proposal_source) {
                     continue;
                 }
@@ -333,246 +1221,147 @@ impl ProposalFilters {
 
             // Filter by source (exclusion)
             if let Some(ref sources_not) = source_not_set {
-                let proposal_source = get_proposal_source(&proposal);
+                let proposal_source = get_proposal_source(proposal);
                 if sources_not.contains(proposal_source) {
                     continue;
                 }
             }
 
-            if let Some(category) = &self.category {
-                match category.as_str() {
-                    categories::LOCKUP => {
-                        if LockupInfo::from_proposal(&proposal).is_none() {
+            if !category_preds.is_empty() && !category_preds.iter().any(|matches| matches(proposal))
+            {
+                continue;
+            }
+            if category_not_preds.iter().any(|matches| matches(proposal)) {
+                continue;
+            }
+
+            // Payment filters work without category=payments. Recipients and tokens only
+            // match payments, the exclusions leave other proposals alone.
+            if self.has_payment_filter() {
+                match PaymentInfo::from_proposal(proposal) {
+                    Some(payment_info) => {
+                        let token_to_check = if payment_info.token.is_empty() {
+                            "near"
+                        } else {
+                            payment_info.token.as_str()
+                        };
+
+                        if let Some(ref recipients) = recipients_set
+                            && !recipients.contains(payment_info.receiver.as_str())
+                        {
                             continue;
                         }
-                    }
-                    categories::ASSET_EXCHANGE => {
-                        if AssetExchangeInfo::from_proposal(&proposal).is_none() {
+
+                        if let Some(ref recipients_not) = recipients_not_set
+                            && recipients_not.contains(payment_info.receiver.as_str())
+                        {
                             continue;
                         }
-                    }
-                    categories::STAKE_DELEGATION => {
-                        if let Some(stake_info) = StakeDelegationInfo::from_proposal(&proposal) {
-                            // Filter by stake type
-                            if let Some(ref stake_types) = stake_type_set {
-                                if !stake_types.contains(stake_info.proposal_type.as_str()) {
-                                    continue;
-                                }
-                            }
-
-                            // Filter by stake type (exclusion)
-                            if let Some(ref stake_types_not) = stake_type_not_set {
-                                if stake_types_not.contains(stake_info.proposal_type.as_str()) {
-                                    continue;
-                                }
-                            }
-
-                            // For lockup proposals, we need to get the validator from RPC if not already set
-                            let mut validator_to_check = stake_info.validator.clone();
-                            if stake_info.validator.contains("lockup.near")
-                                && stake_info.proposal_type != "whitelist"
-                            {
-                                // This is a lockup proposal that's not a select_staking_pool call
-                                // We need to get the validator from the lockup contract
-                                if let Some(pool_id) = staking_pool_cache
-                                    .get_staking_pool_account_id(&client, &stake_info.validator)
-                                    .await
-                                {
-                                    validator_to_check = pool_id;
-                                }
-                            }
-
-                            // Filter by validator
-                            if let Some(ref validators) = validators_set {
-                                if !validators.contains(validator_to_check.as_str()) {
-                                    continue;
-                                }
-                            }
-
-                            // Filter by validator (exclusion)
-                            if let Some(ref validators_not) = validators_not_set {
-                                if validators_not.contains(validator_to_check.as_str()) {
-                                    continue;
-                                }
-                            }
-
-                            // Filter by amount (convert NEAR to yocto NEAR)
-                            let amount_min_ref = self.amount_min.as_ref();
-                            let amount_max_ref = self.amount_max.as_ref();
-                            let amount_equal_ref = self.amount_equal.as_ref();
-
-                            if amount_min_ref.is_some()
-                                || amount_max_ref.is_some()
-                                || amount_equal_ref.is_some()
-                            {
-                                let stake_amount = stake_info.amount.parse::<u128>().ok();
-
-                                if let Some(min_str) = amount_min_ref {
-                                    if let Some(min) = convert_to_smallest_unit(min_str, 24) {
-                                        if let Some(amount) = stake_amount {
-                                            if amount < min {
-                                                continue;
-                                            }
-                                        } else {
-                                            continue; // Invalid amount
-                                        }
-                                    } else {
-                                        continue; // Invalid amount_min input
-                                    }
-                                }
-
-                                if let Some(max_str) = amount_max_ref {
-                                    if let Some(max) = convert_to_smallest_unit(max_str, 24) {
-                                        // NEAR has 24 decimals
-                                        if let Some(amount) = stake_amount {
-                                            if amount > max {
-                                                continue;
-                                            }
-                                        } else {
-                                            continue; // Invalid amount
-                                        }
-                                    } else {
-                                        continue; // Invalid amount_max input
-                                    }
-                                }
-
-                                if let Some(equal_str) = amount_equal_ref {
-                                    if let Some(equal) = convert_to_smallest_unit(equal_str, 24) {
-                                        if let Some(amount) = stake_amount {
-                                            if amount != equal {
-                                                continue;
-                                            }
-                                        } else {
-                                            continue; // Invalid amount
-                                        }
-                                    } else {
-                                        continue; // Invalid amount_equal input
-                                    }
-                                }
-                            }
-                        } else {
-                            continue; // Not a stake delegation proposal
+
+                        if let Some(ref tokens) = tokens_set
+                            && !tokens.contains(token_to_check)
+                        {
+                            continue;
+                        }
+
+                        if let Some(ref tokens_not) = tokens_not_set
+                            && tokens_not.contains(token_to_check)
+                        {
+                            continue;
                         }
                     }
-                    categories::PAYMENTS => {
-                        if let Some(payment_info) = PaymentInfo::from_proposal(&proposal) {
-                            let token_to_check = if payment_info.token.is_empty() {
-                                "near"
-                            } else {
-                                payment_info.token.as_str()
-                            };
-
-                            if let Some(ref recipients) = recipients_set {
-                                if !recipients.contains(payment_info.receiver.as_str()) {
-                                    continue;
-                                }
-                            }
-
-                            if let Some(ref recipients_not) = recipients_not_set {
-                                if recipients_not.contains(payment_info.receiver.as_str()) {
-                                    continue;
-                                }
-                            }
-
-                            if let Some(ref tokens) = tokens_set {
-                                if !tokens.contains(token_to_check) {
-                                    continue;
-                                }
-                            }
-
-                            if let Some(ref tokens_not) = tokens_not_set {
-                                if tokens_not.contains(token_to_check) {
-                                    continue;
-                                }
-                            }
-
-                            if self.amount_equal.is_some()
-                                || self.amount_min.is_some()
-                                || self.amount_max.is_some()
-                            {
-                                // Get token metadata for amount comparison
-                                let token_id = if payment_info.token.is_empty() {
-                                    "near"
-                                } else {
-                                    &payment_info.token
-                                };
-
-                                let ft_metadata =
-                                    get_ft_metadata_cache(&client, ft_metadata_cache, token_id)
-                                        .await?;
-                                let token_decimals = ft_metadata.decimals;
-
-                                let proposal_amount = payment_info.amount.parse::<u128>().ok();
-
-                                if let Some(amount_equal_str) = &self.amount_equal {
-                                    if let Some(amount_equal) =
-                                        convert_to_smallest_unit(amount_equal_str, token_decimals)
-                                    {
-                                        if let Some(amount) = proposal_amount {
-                                            if amount != amount_equal {
-                                                continue;
-                                            }
-                                        } else {
-                                            continue; // Invalid amount
-                                        }
-                                    } else {
-                                        continue; // Invalid amount_equal input
-                                    }
-                                }
-
-                                if let Some(min_str) = &self.amount_min {
-                                    if let Some(min) =
-                                        convert_to_smallest_unit(min_str, token_decimals)
-                                    {
-                                        if let Some(amount) = proposal_amount {
-                                            if amount < min {
-                                                continue;
-                                            }
-                                        } else {
-                                            continue; // Invalid amount
-                                        }
-                                    } else {
-                                        continue; // Invalid amount_min input
-                                    }
-                                }
-
-                                if let Some(max_str) = &self.amount_max {
-                                    if let Some(max) =
-                                        convert_to_smallest_unit(max_str, token_decimals)
-                                    {
-                                        if let Some(amount) = proposal_amount {
-                                            if amount > max {
-                                                continue;
-                                            }
-                                        } else {
-                                            continue; // Invalid amount
-                                        }
-                                    } else {
-                                        continue; // Invalid amount_max input
-                                    }
-                                }
-                            } // Close the amount filters conditional block
-                        } else {
-                            continue; // Not a payment proposal
+                    None if recipients_set.is_some() || tokens_set.is_some() => continue,
+                    None => {}
+                }
+            }
+
+            // Same for the stake delegation filters
+            if self.has_stake_filter() {
+                match StakeDelegationInfo::from_proposal(proposal) {
+                    Some(stake_info) => {
+                        // Filter by stake type
+                        if let Some(ref stake_types) = stake_type_set
+                            && !stake_types.contains(stake_info.proposal_type.as_str())
+                        {
+                            continue;
+                        }
+
+                        // Filter by stake type (exclusion)
+                        if let Some(ref stake_types_not) = stake_type_not_set
+                            && stake_types_not.contains(stake_info.proposal_type.as_str())
+                        {
+                            continue;
+                        }
+
+                        // Lockup proposals are matched by the lockup's pool when it has one
+                        let validator_to_check = staking_pool_cache
+                            .resolve_validator(client, &stake_info)
+                            .await
+                            .unwrap_or_else(|| stake_info.validator.clone());
+
+                        // Filter by validator
+                        if let Some(ref validators) = validators_set
+                            && !validators.contains(validator_to_check.as_str())
+                        {
+                            continue;
+                        }
+
+                        // Filter by validator (exclusion)
+                        if let Some(ref validators_not) = validators_not_set
+                            && validators_not.contains(validator_to_check.as_str())
+                        {
+                            continue;
                         }
                     }
-                    _ => {}
+                    None if stake_type_set.is_some() || validators_set.is_some() => continue,
+                    None => {}
+                }
+            }
+
+            // Policy changes have no member, so members only matches member additions and
+            // removals
+            if self.has_member_filter() {
+                let member = MemberChangeInfo::from_proposal(proposal).and_then(|info| info.member);
+                if let Some(ref members) = members_set
+                    && !member.as_deref().is_some_and(|m| members.contains(m))
+                {
+                    continue;
+                }
+                if let Some(ref members_not) = members_not_set
+                    && member.as_deref().is_some_and(|m| members_not.contains(m))
+                {
+                    continue;
                 }
             }
 
-            filtered_proposals.push(proposal);
+            // Amounts are given in whole tokens and apply to any proposal that moves funds
+            if self.has_amount_filter() {
+                let Some((token_id, amount)) = extract_amount(proposal) else {
+                    continue;
+                };
+                let decimals =
+                    match get_ft_metadata_cache(client, ft_metadata_cache, &token_id).await {
+                        Ok(ft_metadata) => ft_metadata.decimals,
+                        Err(e) => {
+                            warn!(%token_id, error = %e, "Error fetching ft metadata");
+                            continue;
+                        }
+                    };
+                if !self.matches_amount(&amount, decimals) {
+                    continue;
+                }
+            }
+
+            filtered_proposals.push(index);
         }
 
         // Sort the proposals based on the sort_by and sort_direction parameters
-        if let Some(sort_criteria) = &self.sort_by {
-            let is_ascending = self
-                .sort_direction
-                .as_deref()
-                .map(|d| d.to_lowercase() == "asc")
-                .unwrap_or(true);
+        if let Some(sort_criteria) = self.sort_by() {
+            let is_ascending = self.is_ascending();
 
             match sort_criteria {
-                SortBy::CreationTime => filtered_proposals.sort_by(|a, b| {
+                SortBy::CreationTime => filtered_proposals.sort_by(|&a, &b| {
+                    let (a, b) = (&proposals[a], &proposals[b]);
                     let ordering = a.submission_time.cmp(&b.submission_time);
                     if is_ascending {
                         ordering
@@ -580,7 +1369,8 @@ impl ProposalFilters {
                         ordering.reverse()
                     }
                 }),
-                SortBy::ExpiryTime => filtered_proposals.sort_by(|a, b| {
+                SortBy::ExpiryTime => filtered_proposals.sort_by(|&a, &b| {
+                    let (a, b) = (&proposals[a], &proposals[b]);
                     let ordering = (a.submission_time.0 + policy.proposal_period.0)
                         .cmp(&(b.submission_time.0 + policy.proposal_period.0));
                     if is_ascending {
@@ -592,16 +1382,110 @@ impl ProposalFilters {
             }
         }
 
-        Ok(filtered_proposals)
+        Ok(FilteredProposals {
+            proposals: proposals.clone(),
+            indices: filtered_proposals,
+        })
+    }
+
+    fn has_amount_filter(&self) -> bool {
+        self.amount_min.is_some() || self.amount_max.is_some() || self.amount_equal.is_some()
+    }
+
+    // Invalid amounts or bounds never match
+    fn matches_amount(&self, amount: &str, decimals: u8) -> bool {
+        let Ok(amount) = amount.parse::<u128>() else {
+            return false;
+        };
+        let check = |bound: &Option<String>, matches: fn(u128, u128) -> bool| match bound {
+            Some(bound) => parse_units(bound, decimals).is_some_and(|bound| matches(amount, bound)),
+            None => true,
+        };
+
+        check(&self.amount_min, |amount, min| amount >= min)
+            && check(&self.amount_max, |amount, max| amount <= max)
+            && check(&self.amount_equal, |amount, equal| amount == equal)
+    }
+
+    fn is_ascending(&self) -> bool {
+        self.sort_direction
+            .as_deref()
+            .map(|d| d.to_lowercase() == "asc")
+            .unwrap_or(true)
+    }
+
+    // Value the result set is ordered by (proposal id when no sort_by is given)
+    fn sort_key(&self, proposal: &Proposal, policy: &Policy) -> u64 {
+        match self.sort_by() {
+            Some(SortBy::CreationTime) => proposal.submission_time.0,
+            Some(SortBy::ExpiryTime) => proposal.submission_time.0 + policy.proposal_period.0,
+            None => proposal.id,
+        }
+    }
+
+    // Matches the order produced by filter_proposals_async: sorting is stable,
+    // so proposals with equal sort keys keep their ascending id order
+    fn comes_after(&self, proposal: &Proposal, policy: &Policy, cursor: &Cursor) -> bool {
+        let sort_key = self.sort_key(proposal, policy);
+        let key_order = if self.sort_by().is_some() && !self.is_ascending() {
+            cursor.sort_key.cmp(&sort_key)
+        } else {
+            sort_key.cmp(&cursor.sort_key)
+        };
+        key_order.then(proposal.id.cmp(&cursor.id)) == Ordering::Greater
+    }
+
+    /// Applies cursor or page based pagination to already filtered and sorted proposals
+    pub fn paginate<'a>(
+        &self,
+        proposals: &'a FilteredProposals,
+        policy: &Policy,
+    ) -> Result<Page<'a>, InvalidCursor> {
+        let remaining: Vec<&Proposal> = match &self.cursor {
+            Some(cursor) => {
+                let cursor = Cursor::decode(cursor).ok_or(InvalidCursor)?;
+                proposals
+                    .iter()
+                    .filter(|p| self.comes_after(p, policy, &cursor))
+                    .collect()
+            }
+            None => match (self.page, self.page_size) {
+                // Frontend sends 0-based page numbers
+                (Some(page), Some(page_size)) => proposals.iter().skip(page * page_size).collect(),
+                _ => proposals.iter().collect(),
+            },
+        };
+
+        let Some(page_size) = self.page_size else {
+            return Ok(Page {
+                proposals: remaining,
+                next_cursor: None,
+            });
+        };
+
+        let has_more = remaining.len() > page_size;
+        let proposals: Vec<&Proposal> = remaining.into_iter().take(page_size).collect();
+        let next_cursor = proposals.last().filter(|_| has_more).map(|last| {
+            Cursor {
+                sort_key: self.sort_key(last, policy),
+                id: last.id,
+            }
+            .encode()
+        });
+
+        Ok(Page {
+            proposals,
+            next_cursor,
+        })
     }
 
-    pub fn filter_and_extract<T: ProposalType>(
+    pub fn filter_and_extract<'a, T: ProposalType>(
         &self,
-        proposals: Vec<Proposal>,
-    ) -> Vec<(Proposal, T)> {
+        proposals: &'a FilteredProposals,
+    ) -> Vec<(&'a Proposal, T)> {
         proposals
-            .into_iter()
-            .filter_map(|proposal| T::from_proposal(&proposal).map(|info| (proposal, info)))
+            .iter()
+            .filter_map(|proposal| T::from_proposal(proposal).map(|info| (proposal, info)))
             .collect()
     }
 }
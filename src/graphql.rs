@@ -0,0 +1,329 @@
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error, Object, Result, Schema, SimpleObject,
+};
+use async_graphql_rocket::{GraphQLQuery, GraphQLRequest, GraphQLResponse};
+use near_primitives::types::AccountId;
+use rocket::State;
+use rocket::response::content::RawHtml;
+
+use crate::auth::ApiAuth;
+use crate::cache::{
+    CachedProposals, FtMetadataCache, ProposalStore, StakingPoolCache, get_latest_dao_cache,
+};
+use crate::config::get_config;
+use crate::filters::ProposalFilters;
+use crate::policy::{RoleKind, proposal_kind_label};
+use crate::rpc_client;
+use crate::scraper::{
+    PaymentInfo, Policy, Proposal, ProposalType, StakeDelegationInfo, Vote,
+    extract_from_description,
+};
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Schema resolving DAOs and proposals from the same caches as the REST routes
+pub fn build_schema(
+    store: ProposalStore,
+    ft_metadata_cache: FtMetadataCache,
+    staking_pool_cache: StakingPoolCache,
+) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(store)
+        .data(ft_metadata_cache)
+        .data(staking_pool_cache)
+        .finish()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn dao(&self, ctx: &Context<'_>, id: String) -> Result<Dao> {
+        let dao_id: AccountId = id
+            .parse()
+            .map_err(|_| Error::new(format!("Invalid DAO id: {}", id)))?;
+        let client = rpc_client::get_rpc_client();
+        let store = ctx.data::<ProposalStore>()?;
+
+        let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "graphql");
+        let cached = get_latest_dao_cache(&client, store, &dao_id, life_time).await?;
+
+        Ok(Dao { id, cached })
+    }
+}
+
+pub struct Dao {
+    id: String,
+    cached: CachedProposals,
+}
+
+#[Object]
+impl Dao {
+    async fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.cached.config.name
+    }
+
+    async fn purpose(&self) -> &str {
+        &self.cached.config.purpose
+    }
+
+    async fn policy(&self) -> GqlPolicy {
+        GqlPolicy::from(&self.cached.policy)
+    }
+
+    async fn total_proposals(&self) -> usize {
+        self.cached.proposals.len()
+    }
+
+    async fn proposal(&self, id: u64) -> Option<GqlProposal> {
+        self.cached
+            .proposals
+            .iter()
+            .find(|proposal| proposal.id == id)
+            .map(|proposal| GqlProposal::new(proposal.clone(), &self.cached.policy))
+    }
+
+    /// Proposals matching the same filters as GET /proposals, comma-separated where the
+    /// REST parameter is
+    #[allow(clippy::too_many_arguments)]
+    async fn proposals(
+        &self,
+        ctx: &Context<'_>,
+        statuses: Option<String>,
+        category: Option<String>,
+        proposers: Option<String>,
+        search: Option<String>,
+        sort_direction: Option<String>,
+        #[graphql(default = 0)] offset: usize,
+        #[graphql(default = 50)] first: usize,
+    ) -> Result<Vec<GqlProposal>> {
+        let ft_metadata_cache = ctx.data::<FtMetadataCache>()?;
+        let staking_pool_cache = ctx.data::<StakingPoolCache>()?;
+        let filters = ProposalFilters {
+            statuses,
+            category,
+            proposers,
+            search,
+            sort_direction,
+            ..Default::default()
+        };
+
+        filters
+            .check_conflicts()
+            .map_err(|e| Error::new(e.to_string()))?;
+        let proposals = filters
+            .filter_proposals_async(
+                &rpc_client::get_rpc_client(),
+                &self.cached.proposals,
+                &self.cached.policy,
+                ft_metadata_cache,
+                staking_pool_cache,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        Ok(proposals
+            .iter()
+            .skip(offset)
+            .take(first)
+            .map(|proposal| GqlProposal::new(proposal.clone(), &self.cached.policy))
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Role")]
+pub struct GqlRole {
+    name: String,
+    /// Everyone, Member or Group
+    kind: String,
+    members: Vec<String>,
+    permissions: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Policy")]
+pub struct GqlPolicy {
+    roles: Vec<GqlRole>,
+    /// Yocto NEAR
+    proposal_bond: String,
+    /// Nanoseconds
+    proposal_period: String,
+}
+
+impl From<&Policy> for GqlPolicy {
+    fn from(policy: &Policy) -> Self {
+        GqlPolicy {
+            roles: policy
+                .roles
+                .iter()
+                .map(|role| GqlRole {
+                    name: role.name.clone(),
+                    kind: match role.kind {
+                        RoleKind::Everyone => "Everyone",
+                        RoleKind::Member(_) => "Member",
+                        RoleKind::Group(_) => "Group",
+                    }
+                    .to_string(),
+                    members: role.kind.members().to_vec(),
+                    permissions: role.permissions.clone(),
+                })
+                .collect(),
+            proposal_bond: policy.proposal_bond.clone(),
+            proposal_period: policy.proposal_period.0.to_string(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Vote")]
+pub struct GqlVote {
+    account_id: String,
+    /// Approve, Reject or Remove
+    vote: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "PaymentInfo")]
+pub struct GqlPaymentInfo {
+    receiver: String,
+    /// Token contract, empty for NEAR
+    token: String,
+    /// In the smallest unit of the token
+    amount: String,
+    is_lockup: bool,
+    /// msg of a Transfer or ft_transfer_call, passed to the receiver
+    msg: Option<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "StakeDelegationInfo")]
+pub struct GqlStakeDelegationInfo {
+    amount: String,
+    /// stake, unstake, withdraw or whitelist
+    proposal_type: String,
+    validator: String,
+    /// Token the amount is in, empty for NEAR
+    token: String,
+    /// Liquid staking contract, also the token issued for the stake
+    pool_token: Option<String>,
+}
+
+pub struct GqlProposal {
+    proposal: Proposal,
+    status: String,
+}
+
+impl GqlProposal {
+    fn new(mut proposal: Proposal, policy: &Policy) -> Self {
+        proposal.reconcile_status(policy.proposal_period.0);
+        let status = proposal.current_status().as_str().to_string();
+        GqlProposal { proposal, status }
+    }
+}
+
+#[Object(name = "Proposal")]
+impl GqlProposal {
+    async fn id(&self) -> u64 {
+        self.proposal.id
+    }
+
+    async fn proposer(&self) -> &str {
+        &self.proposal.proposer
+    }
+
+    async fn description(&self) -> &str {
+        &self.proposal.description
+    }
+
+    async fn title(&self) -> Option<String> {
+        extract_from_description(&self.proposal.description, "title")
+    }
+
+    /// Policy label of the proposal kind, e.g. "transfer" or "call"
+    async fn kind(&self) -> Option<&'static str> {
+        proposal_kind_label(&self.proposal.kind)
+    }
+
+    /// Raw kind as JSON, including base64 function call args
+    async fn kind_json(&self) -> String {
+        self.proposal.kind.to_string()
+    }
+
+    /// Display status, InProgress proposals past their voting period are Expired
+    async fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Nanoseconds
+    async fn submission_time(&self) -> String {
+        self.proposal.submission_time.0.to_string()
+    }
+
+    async fn votes(&self) -> Vec<GqlVote> {
+        let mut votes: Vec<GqlVote> = self
+            .proposal
+            .votes
+            .iter()
+            .map(|(account_id, vote)| GqlVote {
+                account_id: account_id.clone(),
+                vote: match vote {
+                    Vote::Approve => "Approve",
+                    Vote::Reject => "Reject",
+                    Vote::Remove => "Remove",
+                }
+                .to_string(),
+            })
+            .collect();
+        votes.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        votes
+    }
+
+    async fn payment(&self) -> Option<GqlPaymentInfo> {
+        PaymentInfo::from_proposal(&self.proposal).map(|info| GqlPaymentInfo {
+            receiver: info.receiver,
+            token: info.token,
+            amount: info.amount,
+            is_lockup: info.is_lockup,
+            msg: info.msg,
+        })
+    }
+
+    async fn stake_delegation(&self) -> Option<GqlStakeDelegationInfo> {
+        StakeDelegationInfo::from_proposal(&self.proposal).map(|info| GqlStakeDelegationInfo {
+            amount: info.amount,
+            proposal_type: info.proposal_type,
+            validator: info.validator,
+            token: info.token,
+            pool_token: info.pool_token,
+        })
+    }
+}
+
+#[post("/graphql", data = "<request>", format = "json")]
+pub async fn graphql_request(
+    _auth: ApiAuth,
+    schema: &State<ApiSchema>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    request.execute(schema.inner()).await
+}
+
+#[get("/graphql?<query..>")]
+pub async fn graphql_query(
+    _auth: ApiAuth,
+    schema: &State<ApiSchema>,
+    query: GraphQLQuery,
+) -> GraphQLResponse {
+    query.execute(schema.inner()).await
+}
+
+#[get("/graphiql")]
+pub fn graphiql() -> RawHtml<String> {
+    RawHtml(GraphiQLSource::build().endpoint("/graphql").finish())
+}
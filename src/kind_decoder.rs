@@ -0,0 +1,155 @@
+use base64::{Engine as _, engine::general_purpose};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::policy::PolicyDiff;
+use crate::scraper::{MemberChangeInfo, Policy, Proposal, ProposalType};
+
+/// Decodes base64 function call args, None when they aren't base64 encoded JSON of type T
+pub fn parse_args<T: DeserializeOwned>(args_base64: &str) -> Option<T> {
+    let decoded_bytes = general_purpose::STANDARD.decode(args_base64).ok()?;
+    serde_json::from_slice(&decoded_bytes).ok()
+}
+
+/// Proposal kind with the function call args decoded, returned with decode_kind=true
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(tag = "type")]
+pub enum DecodedKind {
+    Transfer {
+        /// Empty for NEAR
+        token_id: String,
+        receiver_id: String,
+        amount: String,
+        msg: Option<String>,
+    },
+    FunctionCall {
+        receiver_id: String,
+        actions: Vec<DecodedAction>,
+    },
+    AddMemberToRole {
+        member_id: String,
+        role: String,
+    },
+    RemoveMemberFromRole {
+        member_id: String,
+        role: String,
+    },
+    /// ChangePolicy and its role and vote policy variants
+    PolicyChange {
+        /// Kind name, e.g. ChangePolicyAddOrUpdateRole
+        kind: String,
+        /// Role changes relative to the current policy, e.g. "council: +alice.near"
+        role_changes: Vec<String>,
+        /// The same changes in structured form, null for legacy policies. Boxed, it is
+        /// by far the largest variant.
+        diff: Option<Box<PolicyDiff>>,
+        #[schema(value_type = Object)]
+        value: Value,
+    },
+    /// Kinds passed through as they are, e.g. Vote or UpgradeSelf
+    Other {
+        kind: String,
+        #[schema(value_type = Object)]
+        value: Value,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct DecodedAction {
+    pub method_name: String,
+    /// Arguments as JSON, or the base64 string when they aren't JSON
+    #[schema(value_type = Object)]
+    pub args: Value,
+    pub deposit: String,
+    pub gas: String,
+}
+
+fn get_string(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+// U64 gas is serialized as a string, older proposals may hold numbers
+fn get_number_string(value: &Value, key: &str) -> String {
+    match value.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn decode_action(action: &Value) -> DecodedAction {
+    let args = action
+        .get("args")
+        .and_then(|a| a.as_str())
+        .map(|args| parse_args::<Value>(args).unwrap_or_else(|| Value::String(args.to_string())))
+        .unwrap_or(Value::Null);
+    DecodedAction {
+        method_name: get_string(action, "method_name"),
+        args,
+        deposit: get_number_string(action, "deposit"),
+        gas: get_number_string(action, "gas"),
+    }
+}
+
+/// Typed form of the proposal kind. Policy changes are summarized against `policy`, the
+/// DAO's current policy.
+pub fn decode_kind(proposal: &Proposal, policy: &Policy) -> DecodedKind {
+    let (kind, value) = match &proposal.kind {
+        Value::String(kind) => (kind.clone(), Value::Null),
+        Value::Object(map) if map.len() == 1 => {
+            let (kind, value) = map.iter().next().expect("map has one entry");
+            (kind.clone(), value.clone())
+        }
+        other => {
+            return DecodedKind::Other {
+                kind: String::new(),
+                value: other.clone(),
+            };
+        }
+    };
+
+    match kind.as_str() {
+        "Transfer" => DecodedKind::Transfer {
+            token_id: get_string(&value, "token_id"),
+            receiver_id: get_string(&value, "receiver_id"),
+            amount: get_string(&value, "amount"),
+            msg: value
+                .get("msg")
+                .and_then(|v| v.as_str())
+                .map(|msg| msg.to_string()),
+        },
+        "FunctionCall" => DecodedKind::FunctionCall {
+            receiver_id: get_string(&value, "receiver_id"),
+            actions: value
+                .get("actions")
+                .and_then(|a| a.as_array())
+                .map(|actions| actions.iter().map(decode_action).collect())
+                .unwrap_or_default(),
+        },
+        "AddMemberToRole" => DecodedKind::AddMemberToRole {
+            member_id: get_string(&value, "member_id"),
+            role: get_string(&value, "role"),
+        },
+        "RemoveMemberFromRole" => DecodedKind::RemoveMemberFromRole {
+            member_id: get_string(&value, "member_id"),
+            role: get_string(&value, "role"),
+        },
+        kind if kind.starts_with("ChangePolicy") => {
+            let diff =
+                MemberChangeInfo::from_proposal(proposal).and_then(|info| info.policy_diff(policy));
+            DecodedKind::PolicyChange {
+                kind: kind.to_string(),
+                role_changes: diff.as_ref().map(|diff| diff.summary()).unwrap_or_default(),
+                diff: diff.map(Box::new),
+                value,
+            }
+        }
+        _ => DecodedKind::Other { kind, value },
+    }
+}
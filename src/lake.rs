@@ -0,0 +1,105 @@
+use anyhow::{Result, bail};
+use near_primitives::types::AccountId;
+use near_primitives::views::{ExecutionOutcomeWithIdView, ReceiptView};
+use serde::Deserialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::get_config;
+use crate::scraper::{Proposal, TxMetadata, proposal_call_signer, receipt_actions, tx_status};
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// A block in the NEAR Lake layout, as stored in the near-lake-data buckets and served
+// by mirrors such as neardata.xyz. Only the parts the transaction log needs.
+#[derive(Deserialize)]
+struct LakeBlock {
+    block: LakeBlockView,
+    shards: Vec<LakeShard>,
+}
+
+#[derive(Deserialize)]
+struct LakeBlockView {
+    header: LakeBlockHeader,
+}
+
+#[derive(Deserialize)]
+struct LakeBlockHeader {
+    timestamp: u64,
+}
+
+#[derive(Deserialize)]
+struct LakeShard {
+    receipt_execution_outcomes: Vec<LakeReceiptOutcome>,
+}
+
+#[derive(Deserialize)]
+struct LakeReceiptOutcome {
+    execution_outcome: ExecutionOutcomeWithIdView,
+    receipt: Option<ReceiptView>,
+}
+
+fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build the NEAR Lake HTTP client")
+    })
+}
+
+// None for heights without a block, which mirrors answer with null
+async fn fetch_block(block_height: u64) -> Result<Option<LakeBlock>> {
+    let url = format!(
+        "{}/{}",
+        get_config().scraper.lake_url.trim_end_matches('/'),
+        block_height
+    );
+    let response = http_client().get(&url).send().await?;
+    if !response.status().is_success() {
+        bail!(
+            "NEAR Lake answered {} for block {}",
+            response.status(),
+            block_height
+        );
+    }
+    Ok(serde_json::from_slice(&response.bytes().await?)?)
+}
+
+/// Calls on a proposal executed in a block, read from one NEAR Lake block instead of
+/// the block, its chunks and a proof per receipt over RPC. The outcomes come with the
+/// block, so no further requests are needed.
+pub async fn fetch_proposal_txs_in_block(
+    dao_id: &AccountId,
+    proposal: &Proposal,
+    block_height: u64,
+) -> Result<Vec<TxMetadata>> {
+    let Some(block) = fetch_block(block_height).await? else {
+        bail!("Block {} is not in NEAR Lake", block_height);
+    };
+    let timestamp = block.block.header.timestamp;
+
+    Ok(block
+        .shards
+        .into_iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes)
+        .filter_map(|executed| {
+            let receipt = executed.receipt?;
+            let signer_id = proposal_call_signer(&receipt, dao_id, proposal)?;
+            let outcome = executed.execution_outcome.outcome;
+            let actions = receipt_actions(&receipt);
+            Some(TxMetadata {
+                signer_id,
+                predecessor_id: receipt.predecessor_id,
+                reciept_hash: receipt.receipt_id,
+                block_height,
+                timestamp,
+                status: tx_status(&outcome.status),
+                gas_burnt: outcome.gas_burnt,
+                actions: Some(actions),
+            })
+        })
+        .collect())
+}
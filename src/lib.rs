@@ -1,547 +1,3270 @@
 #[macro_use]
 extern crate rocket;
-mod cache;
+mod admin;
+mod amounts;
+mod analytics;
+mod auth;
+pub mod cache;
+mod cache_ttl;
+mod calendar;
+mod config;
 mod csv_view;
 pub mod filters;
-mod persistence;
+mod graphql;
+mod kind_decoder;
+mod lake;
+mod logging;
+mod openapi;
+mod parquet_export;
+mod parsed_info;
+pub mod persistence;
+pub mod policy;
+mod prices;
+pub mod reports;
 pub mod rpc_client;
 pub mod scraper;
+mod snapshot;
+mod source;
+mod tracking;
+mod treasury;
+mod updates;
+mod warmup;
 
+pub use config::use_fixtures;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use dashmap::DashMap;
 use near_primitives::types::AccountId;
 use rocket::State;
+use rocket::data::{Limits, ToByteUnit};
+use rocket::form::FromFormField;
+use rocket::futures::{SinkExt, Stream, StreamExt, stream};
 
 use rocket::serde::json::Json;
 use rocket_cors::{AllowedOrigins, CorsOptions};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
-
+use std::time::Duration;
+
+use amounts::format_units;
+use analytics::{
+    DaoStats, Granularity, ProposerStats, TimelineEntry, TimeseriesMetric, TimeseriesPoint,
+    VoterParticipation, VoterSortBy, VotingHistory, approver_votes, compute_stats, proposer_counts,
+    proposer_stats, recipient_counts, recipient_payments, requested_token_counts,
+    requested_token_totals, sort_voters, timeseries, vote_timeline, voter_counts,
+    voter_participation, voting_history,
+};
+use auth::ApiAuth;
 use cache::{
-    FtMetadataCache, ProposalCache, ProposalStore, get_latest_dao_cache, get_latest_proposal_cache,
+    BountyClaimsCache, BountyStore, FtMetadataCache, LockupCache, ProposalCache, ProposalStore,
+    StakingPoolCache, get_ft_metadata_cache, get_latest_bounties_cache,
+    get_latest_bounty_claims_cache, get_latest_dao_cache, get_latest_proposal_cache,
+    get_proposal_history_cache,
 };
-
-// Helper function to get cached data with consistent error handling
+use cache_ttl::{CacheHeaders, CacheTtl};
+use calendar::voting_deadlines_ics;
+use config::get_config;
+use csv_view::{AccountingProfile, CsvDelimiter, CsvDialect, LineEnding, UTF8_BOM};
+
+// Helper function to get cached data with consistent error handling.
+// A first fetch of a large DAO can outlast client timeouts, so after
+// server.request_timeout_secs the request gets a 503 while the fetch continues.
+#[instrument(skip_all, fields(%dao_id))]
 async fn get_cached_data(
     dao_id: &AccountId,
-    client: &Arc<near_jsonrpc_client::JsonRpcClient>,
+    client: &Arc<RpcPool>,
     store: &ProposalStore,
+    life_time: Duration,
 ) -> Result<cache::CachedProposals, Status> {
-    match get_latest_dao_cache(client, store, dao_id).await {
-        Ok(cache) => Ok(cache),
-        Err(e) => {
-            eprintln!("Failed to get latest DAO cache: {:?}", e);
-            Err(Status::NotFound)
+    let fetch = tokio::spawn({
+        let (client, store, dao_id) = (client.clone(), store.clone(), dao_id.clone());
+        async move { get_latest_dao_cache(&client, &store, &dao_id, life_time).await }
+    });
+
+    match tokio::time::timeout(get_config().server.request_timeout(), fetch).await {
+        Ok(Ok(Ok(cache))) => Ok(cache),
+        Ok(Ok(Err(e))) => {
+            warn!(error = ?e, "Failed to get latest DAO cache");
+            Err(fetch_error_status(&e))
+        }
+        Ok(Err(e)) => {
+            error!(error = ?e, "DAO cache fetch panicked");
+            Err(Status::InternalServerError)
+        }
+        Err(_) => {
+            warn!("DAO is still being fetched, asking the client to retry");
+            Err(Status::ServiceUnavailable)
+        }
+    }
+}
+
+// Unknown DAOs and proposals are 404s, any other failure means RPC couldn't answer. While
+// the circuit breaker is open that is a 503, the catcher tells the client when to retry.
+pub(crate) fn fetch_error_status(error: &anyhow::Error) -> Status {
+    if cache::is_unknown_dao(error)
+        || rpc_client::is_missing_proposal(error)
+        || error.is::<source::MissingProposal>()
+    {
+        Status::NotFound
+    } else if !rpc_client::get_rpc_client().is_available() {
+        Status::ServiceUnavailable
+    } else {
+        Status::BadGateway
+    }
+}
+
+#[derive(Responder)]
+#[response(status = 503)]
+struct RetryLater {
+    body: Json<serde_json::Value>,
+    retry_after: Header<'static>,
+}
+
+/// Error with a JSON body saying what was wrong. Plain statuses are passed on to the
+/// catchers, e.g. for the Retry-After of 503s.
+#[derive(Debug)]
+pub struct ApiError {
+    status: Status,
+    message: Option<String>,
+    invalid_parameters: Vec<InvalidParameter>,
+}
+
+impl ApiError {
+    fn bad_request(message: impl ToString) -> Self {
+        ApiError {
+            status: Status::BadRequest,
+            message: Some(message.to_string()),
+            invalid_parameters: Vec::new(),
+        }
+    }
+}
+
+/// Rejects conflicting filters, and with strict=true values that would otherwise be ignored
+fn check_filters(filters: &ProposalFilters) -> Result<(), ApiError> {
+    filters.check_conflicts().map_err(ApiError::bad_request)?;
+    if filters.strict == Some(true) {
+        let invalid_parameters = filters.validate();
+        if !invalid_parameters.is_empty() {
+            return Err(ApiError {
+                invalid_parameters,
+                ..ApiError::bad_request("Invalid filter parameters")
+            });
+        }
+    }
+    Ok(())
+}
+
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        ApiError {
+            status,
+            message: None,
+            invalid_parameters: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {}", self.status, message)?,
+            None => write!(f, "{}", self.status)?,
+        }
+        for invalid in &self.invalid_parameters {
+            write!(
+                f,
+                "; {}={:?}: {}",
+                invalid.parameter, invalid.value, invalid.reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'static> {
+        match self.message {
+            Some(message) if self.invalid_parameters.is_empty() => {
+                (self.status, Json(serde_json::json!({ "error": message }))).respond_to(request)
+            }
+            Some(message) => (
+                self.status,
+                Json(serde_json::json!({
+                    "error": message,
+                    "invalid_parameters": self.invalid_parameters,
+                })),
+            )
+                .respond_to(request),
+            None => Err(self.status),
+        }
+    }
+}
+
+/// Sets Retry-After on every 503, e.g. while a DAO is still being fetched
+#[catch(503)]
+fn service_unavailable() -> RetryLater {
+    let retry_after = get_config().server.retry_after_secs;
+    RetryLater {
+        body: Json(serde_json::json!({
+            "error": "Data is not available yet, retry later",
+            "retry_after": retry_after,
+        })),
+        retry_after: Header::new("Retry-After", retry_after.to_string()),
+    }
+}
+use filters::{
+    InvalidParameter, ProposalFilters, categories, get_proposal_source, proposal_categories,
+};
+use kind_decoder::DecodedKind;
+use logging::RequestLogger;
+use parquet_export::{ParquetFile, proposals_parquet};
+use parsed_info::{ParsedInfo, parse_proposal};
+use persistence::{CachePersistence, RestoredCaches, read_cache_from_file};
+use policy::{
+    PROPOSAL_ACTIONS, ProposalOutlook, RolePermission, RoleVoteProgress, parse_kind_label,
+    proposal_kind_label,
+};
+use prices::{PriceCache, TokenPrices, get_token_prices, payment_usd_value};
+use reports::load_report_jobs;
+use rpc_client::RpcPool;
+use scraper::{
+    AccountBountyClaim, AccountingPaymentFormatter, AssetExchangeInfo,
+    AssetExchangeProposalFormatter, Bounty, DaoConfig, DateFormat, DefaultFormatter, FtMetadata,
+    GovernanceOpsInfo, GovernanceOpsProposalFormatter, LockupInfo, LockupProposalFormatter,
+    MemberChangeInfo, MemberChangeProposalFormatter, PaymentInfo, Policy,
+    PolicyChangeProposalFormatter, Proposal, ProposalCsvFormatterAsync, ProposalCsvFormatterSync,
+    ProposalReceipt, ProposalStateChange, ProposalStatus, ProposalType, StakeDelegationInfo,
+    StakeDelegationProposalFormatter, TX_LOG_HEADERS, TransferProposalFormatter, TxMetadata,
+    fetch_proposal_receipts, format_tx_log,
+};
+use source::proposal_source;
+use tracking::load_tracked_daos;
+use treasury::{
+    BalanceCache, IntentsBalances, IntentsCache, StakingCache, StakingPortfolio, TreasuryBalances,
+    get_intents_balances, get_staking_portfolio, get_treasury_balances,
+};
+use warmup::CacheWarmup;
+
+use openapi::{ApiDoc, CsvDialectQuery, DaoPath, ProposalPath};
+use rocket::Request;
+use rocket::fairing::AdHoc;
+use rocket::http::{Accept, ContentType, Header, Status};
+use rocket::response::stream::TextStream;
+use rocket::response::{Responder, Response};
+use serde::{Deserialize, Serialize};
+use tracing::{Instrument, debug, error, info_span, instrument, warn};
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ProposalOutput {
+    #[serde(flatten)]
+    pub proposal: Proposal,
+    // Same as effective_status, kept for existing clients
+    #[serde(default)]
+    pub computed_status: String,
+    #[serde(default)]
+    pub vote_progress: Vec<RoleVoteProgress>,
+    pub txs_log: Vec<TxMetadata>,
+    /// txs_log labeled with the action and vote of each call
+    #[serde(default)]
+    pub timeline: Vec<TimelineEntry>,
+    /// False while older transactions of the proposal are still being fetched
+    #[serde(default)]
+    pub txs_log_complete: bool,
+    // Only set with decode_kind=true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded_kind: Option<DecodedKind>,
+    /// Staking pool of stake delegation proposals, the lockup's pool for lockup proposals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_validator: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProposalListItem {
+    #[serde(flatten)]
+    pub proposal: Proposal,
+    // Same as effective_status, kept for existing clients
+    pub computed_status: String,
+    pub vote_progress: Vec<RoleVoteProgress>,
+    // Only set with include_usd=true for payments with a known token price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usd_value: Option<f64>,
+    // Swap details of asset-exchange proposals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_exchange: Option<AssetExchangeInfo>,
+    // Only set with decode_kind=true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded_kind: Option<DecodedKind>,
+    // Most specific of the categories the proposal belongs to, null for none
+    #[schema(value_type = Option<String>)]
+    pub category: Option<&'static str>,
+    // Staking pool of stake delegation proposals, the lockup's pool for lockup proposals.
+    // Only set with include_parsed=true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_validator: Option<String>,
+    #[schema(value_type = String)]
+    pub source: &'static str,
+    // Only filled with include_parsed=true
+    #[serde(flatten)]
+    pub parsed: ParsedInfo,
+}
+
+impl ProposalListItem {
+    pub fn new(mut proposal: Proposal, policy: &Policy) -> Self {
+        proposal.reconcile_status(policy.proposal_period.0);
+        let computed_status = proposal.current_status().as_str().to_string();
+        let vote_progress = policy.vote_progress(&proposal);
+        let asset_exchange = AssetExchangeInfo::from_proposal(&proposal);
+        let category = proposal_categories(&proposal).first().copied();
+        let source = get_proposal_source(&proposal);
+        Self {
+            proposal,
+            computed_status,
+            vote_progress,
+            usd_value: None,
+            asset_exchange,
+            decoded_kind: None,
+            category,
+            resolved_validator: None,
+            source,
+            parsed: ParsedInfo::default(),
+        }
+    }
+}
+
+// Prices are optional extras, so a failing price source must not fail the request
+async fn token_prices_or_empty(price_cache: &PriceCache) -> TokenPrices {
+    get_token_prices(price_cache).await.unwrap_or_else(|e| {
+        warn!(error = ?e, "Failed to fetch token prices");
+        TokenPrices::default()
+    })
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedProposals {
+    pub proposals: Vec<ProposalListItem>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    // Cursor for the next page, absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VoterLeaderboard {
+    pub voters: Vec<VoterParticipation>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProposerStatsResponse {
+    pub proposers: Vec<ProposerStats>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TimeseriesResponse {
+    /// Token contract the outflow is summed for, "near" for native NEAR
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub points: Vec<TimeseriesPoint>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProposersResponse {
+    pub proposers: Vec<String>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApproversResponse {
+    pub approvers: Vec<String>,
+    /// Per approver roles and votes, only with detailed=true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<ApproverDetails>>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApproverDetails {
+    pub account_id: String,
+    /// Group roles of the account in the current policy
+    pub roles: Vec<String>,
+    pub approve: usize,
+    pub reject: usize,
+    pub remove: usize,
+    /// Latest vote as RFC 3339 in UTC, null when no voted proposal has its transaction
+    /// log cached
+    pub last_vote_date: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RecipientsResponse {
+    pub recipients: Vec<String>,
+    /// Per recipient payment summary, only with detailed=true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<RecipientDetails>>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RecipientDetails {
+    pub recipient: String,
+    /// Payment proposals to the recipient, whatever their status
+    pub proposals: usize,
+    /// Approved payments summed per token
+    pub approved_amounts: Vec<TokenAmount>,
+    /// Latest approved payment as RFC 3339 in UTC, null without approved payments
+    pub last_payment_date: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TokenAmount {
+    /// Token contract, "near" for native NEAR
+    pub token_id: String,
+    /// Null when the token metadata couldn't be resolved
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    /// Smallest unit of the token
+    pub amount: String,
+    /// Whole tokens, null when the decimals are unknown
+    pub amount_normalized: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RequestedToken {
+    /// Token contract, "near" for native NEAR
+    pub token_id: String,
+    /// Null when the token metadata couldn't be resolved
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub icon: Option<String>,
+    /// Payment proposals requesting the token
+    pub proposals: usize,
+    /// Sum of the requested amounts in the smallest unit of the token
+    pub total_amount: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RequestedTokensResponse {
+    pub requested_tokens: Vec<RequestedToken>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ValidatorsResponse {
+    pub validators: Vec<String>,
+    /// Per validator stake summary, only with detailed=true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<ValidatorDetails>>,
+    pub total: usize,
+}
+
+/// Amounts in yoctoNEAR
+#[derive(Serialize, ToSchema)]
+pub struct ValidatorDetails {
+    pub validator: String,
+    /// Stake delegation proposals for the validator, whatever their status
+    pub proposals: usize,
+    /// Sum of approved stake proposals
+    pub approved_staked: String,
+    /// Sum of approved unstake proposals
+    pub approved_unstaked: String,
+    /// Current stake of the DAO and its lockup account in the pool
+    pub staked_balance: String,
+    pub unstaked_balance: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BountiesResponse {
+    pub bounties: Vec<Bounty>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BountyClaimsResponse {
+    pub bounty_id: u64,
+    pub number_of_claims: u32,
+    pub claims: Vec<AccountBountyClaim>,
+    pub total: usize,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BatchProposalsRequest {
+    pub dao_id: String,
+    #[serde(default)]
+    pub filters: ProposalFilters,
+    #[serde(default)]
+    pub include_usd: bool,
+    #[serde(default)]
+    pub decode_kind: bool,
+    #[serde(default)]
+    pub include_parsed: bool,
+    #[serde(default)]
+    pub normalize_amounts: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchProposalsResult {
+    pub dao_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<PaginatedProposals>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RolesResponse {
+    pub roles: Vec<RolePermission>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MemberRoles {
+    pub account_id: String,
+    pub roles: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MembersResponse {
+    pub members: Vec<MemberRoles>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PendingProposalsResponse {
+    pub account_id: String,
+    pub proposals: Vec<ProposalListItem>,
+    pub total: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CanActResponse {
+    pub account_id: String,
+    /// Policy label of the proposal kind, e.g. transfer
+    pub proposal_kind: String,
+    /// Roles of the account that grant any action on the proposal kind
+    pub roles: Vec<String>,
+    /// Every action a permission can grant, with whether the account may take it, null
+    /// when only token weighted roles grant it
+    pub actions: BTreeMap<String, Option<bool>>,
+    // Only set with action=
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<bool>)]
+    pub allowed: Option<Option<bool>>,
+}
+
+// Shared by the single DAO and batch endpoints
+#[allow(clippy::too_many_arguments)]
+async fn query_proposals(
+    dao_id: &AccountId,
+    filters: &ProposalFilters,
+    include_usd: bool,
+    decode_kind: bool,
+    include_parsed: bool,
+    normalize_amounts: bool,
+    store: &ProposalStore,
+    ft_metadata_cache: &FtMetadataCache,
+    staking_pool_cache: &StakingPoolCache,
+    price_cache: &PriceCache,
+    life_time: Duration,
+) -> Result<PaginatedProposals, ApiError> {
+    check_filters(filters)?;
+    let client = rpc_client::get_rpc_client();
+
+    // Get cached data
+    let cached = get_cached_data(dao_id, &client, store, life_time).await?;
+
+    // Apply filters
+    let filtered_proposals = filters
+        .filter_proposals_async(
+            &client,
+            &cached.proposals,
+            &cached.policy,
+            ft_metadata_cache,
+            staking_pool_cache,
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Error filtering proposals");
+            Status::InternalServerError
+        })?;
+    let total = filtered_proposals.len();
+
+    // Handle pagination
+    let page = filters
+        .paginate(&filtered_proposals, &cached.policy)
+        .map_err(|_| ApiError::bad_request("Invalid cursor"))?;
+
+    let mut proposals: Vec<ProposalListItem> = page
+        .proposals
+        .into_iter()
+        .map(|proposal| ProposalListItem::new(proposal.clone(), &cached.policy))
+        .collect();
+
+    if decode_kind {
+        for item in &mut proposals {
+            item.decoded_kind = Some(kind_decoder::decode_kind(&item.proposal, &cached.policy));
+        }
+    }
+
+    // Normalized amounts live in the parsed info
+    if include_parsed || normalize_amounts {
+        // Resolved together, so a cold cache costs one round of lockup lookups
+        let (stake_items, stakes): (Vec<usize>, Vec<StakeDelegationInfo>) = proposals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                StakeDelegationInfo::from_proposal(&item.proposal).map(|info| (index, info))
+            })
+            .unzip();
+        let validators = staking_pool_cache
+            .resolve_validators(&client, &stakes)
+            .await;
+        for (index, validator) in stake_items.into_iter().zip(validators) {
+            proposals[index].resolved_validator = validator;
+        }
+
+        for item in &mut proposals {
+            item.parsed = parse_proposal(
+                &client,
+                ft_metadata_cache,
+                staking_pool_cache,
+                &item.proposal,
+                normalize_amounts,
+            )
+            .await;
+        }
+    }
+
+    if include_usd {
+        let prices = token_prices_or_empty(price_cache).await;
+        for item in &mut proposals {
+            if let Some(payment_info) = PaymentInfo::from_proposal(&item.proposal) {
+                item.usd_value =
+                    payment_usd_value(&client, ft_metadata_cache, &prices, &payment_info).await;
+            }
+        }
+    }
+
+    Ok(PaginatedProposals {
+        proposals,
+        total,
+        page: filters.page.unwrap_or(0),
+        page_size: filters.page_size.unwrap_or(total),
+        next_cursor: page.next_cursor,
+    })
+}
+
+#[derive(FromFormField, Clone, Copy, PartialEq, Debug)]
+pub enum OutputFormat {
+    Json,
+    Ndjson,
+}
+
+impl OutputFormat {
+    fn content_type(self) -> ContentType {
+        match self {
+            OutputFormat::Json => ContentType::JSON,
+            OutputFormat::Ndjson => ContentType::new("application", "x-ndjson"),
+        }
+    }
+}
+
+// Serializes the page proposal by proposal instead of rendering one large string.
+// NDJSON output has one proposal per line and no pagination envelope.
+fn paginated_proposals_stream(
+    page: PaginatedProposals,
+    format: OutputFormat,
+) -> TextStream![String] {
+    TextStream! {
+        if format == OutputFormat::Json {
+            yield "{\"proposals\":[".to_string();
+        }
+        let mut separator = "";
+        for item in &page.proposals {
+            match serde_json::to_string(item) {
+                Ok(json) => match format {
+                    OutputFormat::Json => {
+                        yield format!("{}{}", separator, json);
+                        separator = ",";
+                    }
+                    OutputFormat::Ndjson => {
+                        yield format!("{}\n", json);
+                    }
+                },
+                Err(e) => {
+                    error!(proposal_id = item.proposal.id, error = %e, "Error serializing proposal");
+                }
+            }
+        }
+        if format == OutputFormat::Json {
+            let next_cursor = page
+                .next_cursor
+                .as_ref()
+                .map(|cursor| {
+                    format!(",\"next_cursor\":{}", serde_json::Value::from(cursor.as_str()))
+                })
+                .unwrap_or_default();
+            yield format!(
+                "],\"total\":{},\"page\":{},\"page_size\":{}{}}}",
+                page.total, page.page, page.page_size, next_cursor
+            );
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}",
+    tag = "proposals",
+    params(
+        DaoPath,
+        ("include_usd" = Option<bool>, Query, description = "Add usd_value to payment proposals"),
+        ("decode_kind" = Option<bool>, Query, description = "Add decoded_kind with decoded function call args"),
+        ("include_parsed" = Option<bool>, Query, description = "Add payment_info, stake_info, lockup_info or asset_exchange_info, and resolved_validator"),
+        ("normalize_amounts" = Option<bool>, Query, description = "Add amounts in whole tokens to the parsed info, implies include_parsed"),
+        ("format" = Option<String>, Query, description = "json (default) or ndjson"),
+        ProposalFilters,
+    ),
+    responses(
+        (status = 200, body = PaginatedProposals),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get(
+    "/proposals/<dao_id>?<include_usd>&<decode_kind>&<include_parsed>&<normalize_amounts>&<format>&<filters..>"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_proposals(
+    _auth: ApiAuth,
+    dao_id: &str,
+    include_usd: Option<bool>,
+    decode_kind: Option<bool>,
+    include_parsed: Option<bool>,
+    normalize_amounts: Option<bool>,
+    format: Option<OutputFormat>,
+    filters: ProposalFilters,
+    accept: Option<&Accept>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    ft_metadata_cache: &State<FtMetadataCache>,
+    staking_pool_cache: &State<StakingPoolCache>,
+    price_cache: &State<PriceCache>,
+) -> Result<(ContentType, TextStream![String]), ApiError> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+
+    // The format query parameter wins over the Accept header
+    let format = format.unwrap_or_else(|| {
+        let ndjson = OutputFormat::Ndjson.content_type();
+        match accept {
+            Some(accept) if accept.preferred().media_type() == ndjson.media_type() => {
+                OutputFormat::Ndjson
+            }
+            _ => OutputFormat::Json,
+        }
+    });
+
+    let span = info_span!("get_proposals", dao_id = %dao_id, filters = ?filters);
+    let proposals = query_proposals(
+        &dao_id,
+        &filters,
+        include_usd.unwrap_or(false),
+        decode_kind.unwrap_or(false),
+        include_parsed.unwrap_or(false),
+        normalize_amounts.unwrap_or(false),
+        store,
+        ft_metadata_cache,
+        staking_pool_cache,
+        price_cache,
+        ttl.0,
+    )
+    .instrument(span)
+    .await?;
+
+    Ok((
+        format.content_type(),
+        paginated_proposals_stream(proposals, format),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/proposals",
+    tag = "proposals",
+    request_body = Vec<BatchProposalsRequest>,
+    responses(
+        (status = 200, body = Vec<BatchProposalsResult>),
+        (status = 413, description = "Too many DAOs in one request"),
+    )
+)]
+#[post("/batch/proposals", format = "json", data = "<requests>")]
+#[instrument(skip_all)]
+pub async fn batch_proposals(
+    _auth: ApiAuth,
+    requests: Json<Vec<BatchProposalsRequest>>,
+    store: &State<ProposalStore>,
+    ft_metadata_cache: &State<FtMetadataCache>,
+    staking_pool_cache: &State<StakingPoolCache>,
+    price_cache: &State<PriceCache>,
+) -> Result<Json<Vec<BatchProposalsResult>>, Status> {
+    // Each DAO may trigger a full cache refresh
+    if requests.len() > get_config().server.max_batch_size {
+        return Err(Status::PayloadTooLarge);
+    }
+
+    let results =
+        futures::future::join_all(requests.into_inner().into_iter().map(|request| async move {
+            let result = match request.dao_id.parse::<AccountId>() {
+                Ok(dao_id) => {
+                    query_proposals(
+                        &dao_id,
+                        &request.filters,
+                        request.include_usd,
+                        request.decode_kind,
+                        request.include_parsed,
+                        request.normalize_amounts,
+                        store,
+                        ft_metadata_cache,
+                        staking_pool_cache,
+                        price_cache,
+                        get_config().cache.dao_life_time(dao_id.as_str(), "batch"),
+                    )
+                    .await
+                }
+                Err(_) => Err(Status::BadRequest.into()),
+            };
+            match result {
+                Ok(proposals) => BatchProposalsResult {
+                    dao_id: request.dao_id,
+                    result: Some(proposals),
+                    error: None,
+                },
+                Err(error) => BatchProposalsResult {
+                    dao_id: request.dao_id,
+                    result: None,
+                    error: Some(error.to_string()),
+                },
+            }
+        }))
+        .await;
+
+    Ok(Json(results))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}/pending-for/{account_id}",
+    tag = "proposals",
+    params(
+        DaoPath,
+        ("account_id" = String, Path, description = "Voter account"),
+    ),
+    responses(
+        (status = 200, body = PendingProposalsResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/proposals/<dao_id>/pending-for/<account_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_pending_for_account(
+    _auth: ApiAuth,
+    dao_id: &str,
+    account_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<Json<PendingProposalsResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let account_id: AccountId = account_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let policy = &cached.policy;
+
+    let proposals: Vec<ProposalListItem> = cached
+        .proposals
+        .iter()
+        .cloned()
+        .map(|proposal| ProposalListItem::new(proposal, policy))
+        .filter(|item| item.computed_status == "InProgress")
+        .filter(|item| !item.proposal.votes.contains_key(account_id.as_str()))
+        .filter(|item| {
+            proposal_kind_label(&item.proposal.kind)
+                .is_some_and(|label| policy.can_vote(account_id.as_str(), label))
+        })
+        .collect();
+    let total = proposals.len();
+
+    Ok(Json(PendingProposalsResponse {
+        account_id: account_id.to_string(),
+        proposals,
+        total,
+    }))
+}
+
+#[get("/ws/proposals/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn ws_proposals(
+    _auth: ApiAuth,
+    dao_id: &str,
+    ws: rocket_ws::WebSocket,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<rocket_ws::Channel<'static>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    // Only subscribe to DAOs that exist
+    get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let mut receiver = updates::subscribe(&dao_id);
+    updates::ensure_poller(client, store.inner().clone(), dao_id);
+
+    Ok(ws.channel(move |mut stream| {
+        Box::pin(async move {
+            loop {
+                tokio::select! {
+                    update = receiver.recv() => match update {
+                        Ok(update) => {
+                            let message = serde_json::to_string(&update).unwrap_or_default();
+                            stream.send(rocket_ws::Message::Text(message)).await?;
+                        }
+                        // Slow client, skip the dropped updates
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    },
+                    message = stream.next() => match message {
+                        Some(Ok(rocket_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => {}
+                    },
+                }
+            }
+            Ok(())
+        })
+    }))
+}
+
+// Avoids loading every proposal of the DAO just to know its policy
+async fn proposal_policy(
+    client: &Arc<RpcPool>,
+    store: &ProposalStore,
+    dao_id: &AccountId,
+) -> Result<Policy, Status> {
+    let cached_policy = store
+        .get(dao_id.as_str())
+        .map(|cached| cached.policy.clone());
+    match cached_policy {
+        Some(policy) => Ok(policy),
+        None => proposal_source(client).policy(dao_id).await.map_err(|e| {
+            error!(error = ?e, "Failed to fetch policy");
+            Status::InternalServerError
+        }),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposal/{dao_id}/{proposal_id}",
+    tag = "proposals",
+    params(
+        ProposalPath,
+        ("decode_kind" = Option<bool>, Query, description = "Add decoded_kind with decoded function call args"),
+    ),
+    responses(
+        (status = 200, body = ProposalOutput),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+    )
+)]
+#[get("/proposal/<dao_id>/<proposal_id>?<decode_kind>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_specific_proposal(
+    _auth: ApiAuth,
+    dao_id: &str,
+    proposal_id: u64,
+    decode_kind: Option<bool>,
+    cache: &State<ProposalCache>,
+    store: &State<ProposalStore>,
+    staking_pool_cache: &State<StakingPoolCache>,
+) -> Result<Json<ProposalOutput>, Status> {
+    let dao_id_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+    let proposal_cached = get_latest_proposal_cache(&client, cache, &dao_id_account, proposal_id)
+        .await
+        .map_err(|e| fetch_error_status(&e))?;
+    let policy = proposal_policy(&client, store, &dao_id_account).await?;
+
+    // Single proposals come from the contract directly, not from the reconciled DAO cache
+    let mut proposal = proposal_cached.proposal;
+    proposal.reconcile_status(policy.proposal_period.0);
+    let resolved_validator = match StakeDelegationInfo::from_proposal(&proposal) {
+        Some(stake_info) => {
+            staking_pool_cache
+                .resolve_validator(&client, &stake_info)
+                .await
+        }
+        None => None,
+    };
+    Ok(Json(ProposalOutput {
+        decoded_kind: decode_kind
+            .unwrap_or(false)
+            .then(|| kind_decoder::decode_kind(&proposal, &policy)),
+        resolved_validator,
+        computed_status: proposal.current_status().as_str().to_string(),
+        vote_progress: policy.vote_progress(&proposal),
+        timeline: vote_timeline(&proposal, &proposal_cached.txs_log),
+        proposal,
+        txs_log_complete: proposal_cached.backfill_block.is_none(),
+        txs_log: proposal_cached.txs_log,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposal/{dao_id}/{proposal_id}/outlook",
+    tag = "proposals",
+    params(
+        ProposalPath,
+    ),
+    responses(
+        (status = 200, body = ProposalOutlook),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "Proposal not found"),
+    )
+)]
+#[get("/proposal/<dao_id>/<proposal_id>/outlook")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_proposal_outlook(
+    _auth: ApiAuth,
+    dao_id: &str,
+    proposal_id: u64,
+    cache: &State<ProposalCache>,
+    store: &State<ProposalStore>,
+) -> Result<Json<ProposalOutlook>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+    let proposal_cached = get_latest_proposal_cache(&client, cache, &dao_id, proposal_id)
+        .await
+        .map_err(|e| fetch_error_status(&e))?;
+    let policy = proposal_policy(&client, store, &dao_id).await?;
+
+    let mut proposal = proposal_cached.proposal;
+    proposal.reconcile_status(policy.proposal_period.0);
+    Ok(Json(policy.outlook(&proposal)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposal/{dao_id}/{proposal_id}/timeline",
+    tag = "proposals",
+    params(
+        ProposalPath,
+    ),
+    responses(
+        (status = 200, body = Vec<TimelineEntry>),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "Proposal not found"),
+    )
+)]
+#[get("/proposal/<dao_id>/<proposal_id>/timeline")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_proposal_timeline(
+    _auth: ApiAuth,
+    dao_id: &str,
+    proposal_id: u64,
+    cache: &State<ProposalCache>,
+    store: &State<ProposalStore>,
+) -> Result<Json<Vec<TimelineEntry>>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+    let proposal_cached = get_latest_proposal_cache(&client, cache, &dao_id, proposal_id)
+        .await
+        .map_err(|e| fetch_error_status(&e))?;
+    let policy = proposal_policy(&client, store, &dao_id).await?;
+
+    // Same status as /proposal/<id> reports for it
+    let mut proposal = proposal_cached.proposal;
+    proposal.reconcile_status(policy.proposal_period.0);
+    Ok(Json(vote_timeline(&proposal, &proposal_cached.txs_log)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposal/{dao_id}/{proposal_id}/receipts",
+    tag = "proposals",
+    params(
+        ProposalPath,
+    ),
+    responses(
+        (status = 200, body = Vec<ProposalReceipt>),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "Proposal not found"),
+        (status = 502, description = "Receipts could not be fetched from RPC"),
+    )
+)]
+#[get("/proposal/<dao_id>/<proposal_id>/receipts")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_proposal_receipts(
+    _auth: ApiAuth,
+    dao_id: &str,
+    proposal_id: u64,
+    cache: &State<ProposalCache>,
+) -> Result<Json<Vec<ProposalReceipt>>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+    let proposal_cached = get_latest_proposal_cache(&client, cache, &dao_id, proposal_id)
+        .await
+        .map_err(|e| fetch_error_status(&e))?;
+
+    let receipts = fetch_proposal_receipts(&client, &proposal_cached.txs_log)
+        .await
+        .map_err(|e| {
+            error!(%dao_id, proposal_id, error = %e, "Error fetching proposal receipts");
+            fetch_error_status(&e)
+        })?;
+
+    Ok(Json(receipts))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposal/{dao_id}/{proposal_id}/history",
+    tag = "proposals",
+    params(
+        ProposalPath,
+    ),
+    responses(
+        (status = 200, body = Vec<ProposalStateChange>),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "Proposal not found"),
+        (status = 502, description = "History could not be fetched from RPC"),
+    )
+)]
+#[get("/proposal/<dao_id>/<proposal_id>/history")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_proposal_history(
+    _auth: ApiAuth,
+    dao_id: &str,
+    proposal_id: u64,
+    cache: &State<ProposalCache>,
+) -> Result<Json<Vec<ProposalStateChange>>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+    let history = get_proposal_history_cache(&client, cache, &dao_id, proposal_id)
+        .await
+        .map_err(|e| {
+            error!(%dao_id, proposal_id, error = %e, "Error fetching proposal history");
+            fetch_error_status(&e)
+        })?;
+
+    Ok(Json(history))
+}
+
+#[utoipa::path(
+    get,
+    path = "/policy/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = Policy),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/policy/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_policy(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<Json<Policy>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    Ok(Json(cached.policy))
+}
+
+#[utoipa::path(
+    get,
+    path = "/can-act/{dao_id}/{account_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("account_id" = String, Path, description = "Account to check"),
+        ("proposal_kind" = String, Query, description = "Proposal kind name or policy label, e.g. Transfer or transfer"),
+        ("action" = Option<String>, Query, description = "One of AddProposal, RemoveProposal, VoteApprove, VoteReject, VoteRemove, Finalize or MoveToHub"),
+    ),
+    responses(
+        (status = 200, body = CanActResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/can-act/<dao_id>/<account_id>?<proposal_kind>&<action>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_can_act(
+    _auth: ApiAuth,
+    dao_id: &str,
+    account_id: &str,
+    proposal_kind: Option<&str>,
+    action: Option<&str>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<Json<CanActResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let account_id: AccountId = account_id.parse().map_err(|_| Status::BadRequest)?;
+    let label = proposal_kind
+        .and_then(parse_kind_label)
+        .ok_or(Status::BadRequest)?;
+    if action.is_some_and(|action| !PROPOSAL_ACTIONS.contains(&action)) {
+        return Err(Status::BadRequest);
+    }
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let policy = &cached.policy;
+
+    let roles = policy
+        .roles_of(account_id.as_str())
+        .filter(|role| {
+            PROPOSAL_ACTIONS
+                .iter()
+                .any(|action| role.allows(label, action))
+        })
+        .map(|role| role.name.clone())
+        .collect();
+    let actions = PROPOSAL_ACTIONS
+        .iter()
+        .map(|action| {
+            (
+                action.to_string(),
+                policy.can_act(account_id.as_str(), label, action),
+            )
+        })
+        .collect();
+
+    Ok(Json(CanActResponse {
+        account_id: account_id.to_string(),
+        proposal_kind: label.to_string(),
+        roles,
+        actions,
+        action: action.map(str::to_string),
+        allowed: action.map(|action| policy.can_act(account_id.as_str(), label, action)),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/config/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = DaoConfig),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/config/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_config(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<Json<DaoConfig>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    Ok(Json(cached.config))
+}
+
+// Only proposals fetched individually have their transaction log cached
+fn cached_txs_logs(cache: &ProposalCache, dao_id: &AccountId) -> HashMap<u64, Vec<TxMetadata>> {
+    cache
+        .iter()
+        .filter(|cached| cached.key().0 == dao_id.as_str())
+        .map(|cached| (cached.key().1, cached.txs_log.clone()))
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = DaoStats),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/stats/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_stats(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    cache: &State<ProposalCache>,
+) -> Result<Json<DaoStats>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let txs_logs = cached_txs_logs(cache, &dao_id);
+
+    Ok(Json(compute_stats(&cached.proposals, &txs_logs)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/{dao_id}/voters",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("sort_by" = Option<String>, Query, description = "participation (default), votes, eligible or account"),
+        ("sort_direction" = Option<String>, Query, description = "asc or desc (default)"),
+        ("page" = Option<usize>, Query, description = "0-based page number"),
+        ("page_size" = Option<usize>, Query, description = "Members per page, all when absent"),
+    ),
+    responses(
+        (status = 200, body = VoterLeaderboard),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/stats/<dao_id>/voters?<sort_by>&<sort_direction>&<page>&<page_size>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_voter_leaderboard(
+    _auth: ApiAuth,
+    dao_id: &str,
+    sort_by: Option<VoterSortBy>,
+    sort_direction: Option<&str>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<Json<VoterLeaderboard>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let mut voters = voter_participation(&cached.proposals, &cached.policy);
+    let ascending = sort_direction.is_some_and(|d| d.eq_ignore_ascii_case("asc"));
+    sort_voters(&mut voters, sort_by.unwrap_or_default(), ascending);
+
+    let total = voters.len();
+    let page = page.unwrap_or(0);
+    let page_size = page_size.unwrap_or(total);
+    let voters = voters
+        .into_iter()
+        .skip(page * page_size)
+        .take(page_size)
+        .collect();
+
+    Ok(Json(VoterLeaderboard {
+        voters,
+        total,
+        page,
+        page_size,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/{dao_id}/proposers",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = ProposerStatsResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/stats/<dao_id>/proposers")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_proposer_stats(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    ft_metadata_cache: &State<FtMetadataCache>,
+) -> Result<Json<ProposerStatsResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let mut decimals: HashMap<String, u8> = HashMap::new();
+    for token in requested_token_counts(&cached.proposals).into_keys() {
+        match get_ft_metadata_cache(&client, ft_metadata_cache, &token).await {
+            Ok(metadata) => {
+                decimals.insert(token, metadata.decimals);
+            }
+            Err(e) => warn!(%token, error = %e, "Error fetching FT metadata"),
+        }
+    }
+
+    let proposers = proposer_stats(&cached.proposals, &decimals);
+    let total = proposers.len();
+
+    Ok(Json(ProposerStatsResponse { proposers, total }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/{dao_id}/timeseries",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("granularity" = Option<String>, Query, description = "month (default) or quarter"),
+        ("metric" = Option<String>, Query, description = "proposals (default) or approved_outflow"),
+        ("token" = Option<String>, Query, description = "Token contract or symbol, required for approved_outflow"),
+    ),
+    responses(
+        (status = 200, body = TimeseriesResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/stats/<dao_id>/timeseries?<granularity>&<metric>&<token>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_dao_timeseries(
+    _auth: ApiAuth,
+    dao_id: &str,
+    granularity: Option<Granularity>,
+    metric: Option<TimeseriesMetric>,
+    token: Option<&str>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    ft_metadata_cache: &State<FtMetadataCache>,
+) -> Result<Json<TimeseriesResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let metric = metric.unwrap_or_default();
+    if metric == TimeseriesMetric::ApprovedOutflow && token.is_none() {
+        return Err(Status::BadRequest);
+    }
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    // The token may be given by contract or symbol, only requested tokens can match
+    let mut outflow_token: Option<(String, u8)> = None;
+    if let Some(token) = token.filter(|_| metric == TimeseriesMetric::ApprovedOutflow) {
+        for requested in requested_token_counts(&cached.proposals).into_keys() {
+            let Ok(metadata) = get_ft_metadata_cache(&client, ft_metadata_cache, &requested).await
+            else {
+                continue;
+            };
+            if requested.eq_ignore_ascii_case(token) || metadata.symbol.eq_ignore_ascii_case(token)
+            {
+                outflow_token = Some((requested, metadata.decimals));
+                break;
+            }
         }
     }
+
+    let points = timeseries(
+        &cached.proposals,
+        granularity.unwrap_or_default(),
+        metric,
+        outflow_token
+            .as_ref()
+            .map(|(token, decimals)| (token.as_str(), *decimals)),
+    );
+
+    Ok(Json(TimeseriesResponse {
+        token: outflow_token.map(|(token, _)| token),
+        points,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/balances/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = TreasuryBalances),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/balances/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_dao_balances(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    balance_cache: &State<BalanceCache>,
+    ft_metadata_cache: &State<FtMetadataCache>,
+    lockup_cache: &State<LockupCache>,
+) -> Result<Json<TreasuryBalances>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let balances = get_treasury_balances(
+        &client,
+        balance_cache,
+        ft_metadata_cache,
+        lockup_cache,
+        &dao_id,
+        requested_token_counts(&cached.proposals).into_keys(),
+    )
+    .await
+    .map_err(|e| {
+        error!(%dao_id, error = %e, "Error fetching treasury balances");
+        Status::InternalServerError
+    })?;
+
+    Ok(Json(balances))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staking/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = StakingPortfolio),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/staking/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_dao_staking(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    staking_cache: &State<StakingCache>,
+    staking_pool_cache: &State<StakingPoolCache>,
+    lockup_cache: &State<LockupCache>,
+) -> Result<Json<StakingPortfolio>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let portfolio = get_staking_portfolio(
+        &client,
+        staking_cache,
+        staking_pool_cache,
+        lockup_cache,
+        &dao_id,
+        &cached.proposals,
+    )
+    .await
+    .map_err(|e| {
+        error!(%dao_id, error = %e, "Error fetching staking portfolio");
+        Status::InternalServerError
+    })?;
+
+    Ok(Json(portfolio))
+}
+
+#[utoipa::path(
+    get,
+    path = "/intents/{dao_id}/balances",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = IntentsBalances),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/intents/<dao_id>/balances")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_intents_balances(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    intents_cache: &State<IntentsCache>,
+    ft_metadata_cache: &State<FtMetadataCache>,
+) -> Result<Json<IntentsBalances>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let balances = get_intents_balances(
+        &client,
+        intents_cache,
+        ft_metadata_cache,
+        &dao_id,
+        &cached.proposals,
+    )
+    .await
+    .map_err(|e| {
+        error!(%dao_id, error = %e, "Error fetching intents balances");
+        Status::InternalServerError
+    })?;
+
+    Ok(Json(balances))
 }
-use filters::{ProposalFilters, categories};
-use persistence::{CachePersistence, read_cache_from_file};
-use scraper::{
-    AssetExchangeInfo, AssetExchangeProposalFormatter, DefaultFormatter, LockupInfo,
-    LockupProposalFormatter, PaymentInfo, Proposal, ProposalCsvFormatterAsync,
-    ProposalCsvFormatterSync, ProposalType, StakeDelegationInfo, StakeDelegationProposalFormatter,
-    TransferProposalFormatter, TxMetadata,
-};
 
-use rocket::Request;
-use rocket::http::{ContentType, Header, Status};
-use rocket::response::{Responder, Response};
-use serde::{Deserialize, Serialize};
-use std::io::Cursor;
+#[utoipa::path(
+    get,
+    path = "/voters/{dao_id}/{account_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("account_id" = String, Path, description = "Voter account"),
+    ),
+    responses(
+        (status = 200, body = VotingHistory),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/voters/<dao_id>/<account_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_voting_history(
+    _auth: ApiAuth,
+    dao_id: &str,
+    account_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    cache: &State<ProposalCache>,
+) -> Result<Json<VotingHistory>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let account_id: AccountId = account_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ProposalOutput {
-    #[serde(flatten)]
-    pub proposal: Proposal,
-    pub txs_log: Vec<TxMetadata>,
-}
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let txs_logs = cached_txs_logs(cache, &dao_id);
 
-#[derive(Serialize)]
-pub struct PaginatedProposals {
-    pub proposals: Vec<Proposal>,
-    pub total: usize,
-    pub page: usize,
-    pub page_size: usize,
+    Ok(Json(voting_history(
+        account_id.as_str(),
+        &cached.proposals,
+        &txs_logs,
+    )))
 }
 
-#[derive(Serialize)]
-pub struct ProposersResponse {
-    pub proposers: Vec<String>,
-    pub total: usize,
-}
+#[utoipa::path(
+    get,
+    path = "/calendar/{dao_id}.ics",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, content_type = "text/calendar", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/calendar/<file_name>")]
+#[instrument(skip_all, fields(file_name = %file_name))]
+pub async fn get_dao_calendar(
+    _auth: ApiAuth,
+    file_name: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<(ContentType, String), Status> {
+    // Calendar apps expect the .ics extension in subscription URLs
+    let dao_id = file_name.strip_suffix(".ics").ok_or(Status::NotFound)?;
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
 
-#[derive(Serialize)]
-pub struct ApproversResponse {
-    pub approvers: Vec<String>,
-    pub total: usize,
-}
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
 
-#[derive(Serialize)]
-pub struct RecipientsResponse {
-    pub recipients: Vec<String>,
-    pub total: usize,
+    Ok((
+        ContentType::new("text", "calendar"),
+        voting_deadlines_ics(dao_id, &cached.proposals, &cached.policy),
+    ))
 }
 
-#[derive(Serialize)]
-pub struct RequestedTokensResponse {
-    pub requested_tokens: Vec<String>,
-    pub total: usize,
-}
+#[utoipa::path(
+    get,
+    path = "/roles/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = RolesResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/roles/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_roles(
+    _auth: ApiAuth,
+    dao_id: &str,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<Json<RolesResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
 
-#[derive(Serialize)]
-pub struct ValidatorsResponse {
-    pub validators: Vec<String>,
-    pub total: usize,
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let roles = cached.policy.roles;
+    let total = roles.len();
+
+    Ok(Json(RolesResponse { roles, total }))
 }
 
-#[get("/proposals/<dao_id>?<filters..>")]
-pub async fn get_proposals(
+#[utoipa::path(
+    get,
+    path = "/members/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = MembersResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/members/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_members(
+    _auth: ApiAuth,
     dao_id: &str,
-    filters: ProposalFilters,
     store: &State<ProposalStore>,
-    ft_metadata_cache: &State<FtMetadataCache>,
-) -> Result<Json<PaginatedProposals>, Status> {
+    ttl: CacheTtl,
+) -> Result<Json<MembersResponse>, Status> {
     let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
 
-    // Get cached data
-    let cached = get_cached_data(&dao_id, &client, &store).await?;
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let members: Vec<MemberRoles> = cached
+        .policy
+        .members()
+        .into_iter()
+        .map(|(account_id, roles)| MemberRoles { account_id, roles })
+        .collect();
+    let total = members.len();
 
-    // Apply filters
-    let filtered_proposals = filters
-        .filter_proposals_async(cached.proposals, &cached.policy, &ft_metadata_cache)
+    Ok(Json(MembersResponse { members, total }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/bounties/{dao_id}",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = BountiesResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+    )
+)]
+#[get("/bounties/<dao_id>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_dao_bounties(
+    _auth: ApiAuth,
+    dao_id: &str,
+    bounty_store: &State<BountyStore>,
+) -> Result<Json<BountiesResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let bounties = get_latest_bounties_cache(&client, bounty_store, &dao_id)
         .await
         .map_err(|e| {
-            eprintln!("Error filtering proposals: {}", e);
-            Status::InternalServerError
+            warn!(error = ?e, "Failed to get bounties");
+            fetch_error_status(&e)
         })?;
-    let total = filtered_proposals.len();
+    let total = bounties.len();
 
-    // Handle pagination
-    let proposals = match (filters.page, filters.page_size) {
-        (Some(page), Some(page_size)) => {
-            // Frontend sends 0-based page numbers
-            let start = page * page_size;
-            let end = start + page_size;
-
-            if start < total {
-                filtered_proposals[start..filtered_proposals.len().min(end)].to_vec()
-            } else {
-                vec![]
+    Ok(Json(BountiesResponse { bounties, total }))
+}
+
+// Claims are stored per claimant, so only accounts known to the DAO can be checked:
+// role members plus proposers and receivers of BountyDone proposals for this bounty
+fn bounty_claim_candidates(cached: &cache::CachedProposals, bounty_id: u64) -> Vec<String> {
+    let mut candidates: std::collections::HashSet<String> = std::collections::HashSet::new();
+    for role in &cached.policy.roles {
+        candidates.extend(role.kind.members().iter().cloned());
+    }
+    for proposal in cached.proposals.iter() {
+        if let Some(bounty_done) = proposal
+            .kind
+            .get("BountyDone")
+            .filter(|b| b.get("bounty_id").and_then(|id| id.as_u64()) == Some(bounty_id))
+        {
+            candidates.insert(proposal.proposer.clone());
+            if let Some(receiver) = bounty_done.get("receiver_id").and_then(|r| r.as_str()) {
+                candidates.insert(receiver.to_string());
             }
         }
-        _ => filtered_proposals,
-    };
+    }
 
-    Ok(Json(PaginatedProposals {
-        proposals,
-        total,
-        page: filters.page.unwrap_or(0),
-        page_size: filters.page_size.unwrap_or(total),
-    }))
+    let mut candidates_vec: Vec<String> = candidates.into_iter().collect();
+    candidates_vec.sort_unstable();
+    candidates_vec
 }
 
-#[get("/proposal/<dao_id>/<proposal_id>")]
-pub async fn get_specific_proposal(
+#[utoipa::path(
+    get,
+    path = "/bounties/{dao_id}/{bounty_id}/claims",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("bounty_id" = u64, Path, description = "Bounty id"),
+    ),
+    responses(
+        (status = 200, body = BountyClaimsResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/bounties/<dao_id>/<bounty_id>/claims")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn get_bounty_claims(
+    _auth: ApiAuth,
     dao_id: &str,
-    proposal_id: u64,
-    cache: &State<ProposalCache>,
-) -> Result<Json<ProposalOutput>, Status> {
-    let dao_id_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    bounty_id: u64,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    claims_cache: &State<BountyClaimsCache>,
+) -> Result<Json<BountyClaimsResponse>, Status> {
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
-    let proposal_cached = get_latest_proposal_cache(&client, cache, &dao_id_account, proposal_id)
-        .await
-        .map_err(|_| Status::NotFound)?;
 
-    Ok(Json(ProposalOutput {
-        proposal: proposal_cached.proposal,
-        txs_log: proposal_cached.txs_log,
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+    let candidates = bounty_claim_candidates(&cached, bounty_id);
+
+    let bounty_claims =
+        get_latest_bounty_claims_cache(&client, claims_cache, &dao_id, bounty_id, &candidates)
+            .await
+            .map_err(|e| {
+                warn!(error = ?e, "Failed to get bounty claims");
+                fetch_error_status(&e)
+            })?;
+    let total = bounty_claims.claims.len();
+
+    Ok(Json(BountyClaimsResponse {
+        bounty_id,
+        number_of_claims: bounty_claims.number_of_claims,
+        claims: bounty_claims.claims,
+        total,
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}/proposers",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = ProposersResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
 #[get("/proposals/<dao_id>/proposers")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
 pub async fn get_dao_proposers(
+    _auth: ApiAuth,
     dao_id: &str,
     store: &State<ProposalStore>,
+    ttl: CacheTtl,
 ) -> Result<Json<ProposersResponse>, Status> {
     let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
 
-    let cached = get_cached_data(&dao_id, &client, &store).await?;
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
 
-    // Extract unique proposers from all proposals
-    let mut proposers: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for proposal in &cached.proposals {
-        proposers.insert(proposal.proposer.clone());
-    }
-
-    let mut proposers_vec: Vec<String> = proposers.into_iter().collect();
-    proposers_vec.sort_unstable(); // Sort alphabetically for consistent ordering
+    // Keys are sorted alphabetically for consistent ordering
+    let proposers: Vec<String> = proposer_counts(&cached.proposals).into_keys().collect();
+    let total = proposers.len();
 
-    let total = proposers_vec.len();
-
-    Ok(Json(ProposersResponse {
-        proposers: proposers_vec,
-        total,
-    }))
+    Ok(Json(ProposersResponse { proposers, total }))
 }
 
-#[get("/proposals/<dao_id>/approvers")]
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}/approvers",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("role" = Option<String>, Query, description = "Only approvers in this group role of the current policy, e.g. council"),
+        ("detailed" = Option<bool>, Query, description = "Add roles, vote splits and the last vote date per approver"),
+    ),
+    responses(
+        (status = 200, body = ApproversResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/proposals/<dao_id>/approvers?<role>&<detailed>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
 pub async fn get_dao_approvers(
+    _auth: ApiAuth,
     dao_id: &str,
+    role: Option<&str>,
+    detailed: Option<bool>,
     store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    cache: &State<ProposalCache>,
 ) -> Result<Json<ApproversResponse>, Status> {
     let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
 
-    let cached = get_cached_data(&dao_id, &client, &store).await?;
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
 
-    // Extract unique approvers from all proposals
-    let mut approvers: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for proposal in &cached.proposals {
-        // Add all voters from the votes HashMap
-        for (voter, _) in &proposal.votes {
-            approvers.insert(voter.clone());
-        }
-    }
+    let members = cached.policy.members();
+    let in_role = |account_id: &str| {
+        role.is_none_or(|role| {
+            members
+                .get(account_id)
+                .is_some_and(|roles| roles.iter().any(|r| r == role))
+        })
+    };
 
-    let mut approvers_vec: Vec<String> = approvers.into_iter().collect();
-    approvers_vec.sort_unstable(); // Sort alphabetically for consistent ordering
+    if !detailed.unwrap_or(false) {
+        let approvers: Vec<String> = voter_counts(&cached.proposals)
+            .into_keys()
+            .filter(|account_id| in_role(account_id))
+            .collect();
+        let total = approvers.len();
+        return Ok(Json(ApproversResponse {
+            approvers,
+            details: None,
+            total,
+        }));
+    }
 
-    let total = approvers_vec.len();
+    let txs_logs = cached_txs_logs(cache, &dao_id);
+    let mut approvers = Vec::new();
+    let mut details = Vec::new();
+    for (account_id, votes) in approver_votes(&cached.proposals, &txs_logs) {
+        if !in_role(&account_id) {
+            continue;
+        }
+        details.push(ApproverDetails {
+            roles: members.get(&account_id).cloned().unwrap_or_default(),
+            account_id: account_id.clone(),
+            approve: votes.approve,
+            reject: votes.reject,
+            remove: votes.remove,
+            last_vote_date: votes.last_vote_at.map(rfc3339_utc),
+        });
+        approvers.push(account_id);
+    }
+    let total = approvers.len();
 
     Ok(Json(ApproversResponse {
-        approvers: approvers_vec,
+        approvers,
+        details: Some(details),
         total,
     }))
 }
 
-#[get("/proposals/<dao_id>/recipients")]
+// Nanosecond timestamp as e.g. "2024-05-01T12:30:00Z"
+fn rfc3339_utc(timestamp: u64) -> String {
+    Utc.timestamp_nanos(timestamp as i64)
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}/recipients",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("detailed" = Option<bool>, Query, description = "Add proposal counts, approved amounts and the last payment date per recipient"),
+    ),
+    responses(
+        (status = 200, body = RecipientsResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/proposals/<dao_id>/recipients?<detailed>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
 pub async fn get_dao_recipients(
+    _auth: ApiAuth,
     dao_id: &str,
+    detailed: Option<bool>,
     store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    cache: &State<ProposalCache>,
+    ft_metadata_cache: &State<FtMetadataCache>,
 ) -> Result<Json<RecipientsResponse>, Status> {
     let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
 
-    let cached = get_cached_data(&dao_id, &client, &store).await?;
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
 
-    // Extract unique recipients from transfer proposals only
-    let mut recipients: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for proposal in &cached.proposals {
-        // Check if this is a transfer proposal
-        if let Some(payment_info) = scraper::PaymentInfo::from_proposal(proposal) {
-            recipients.insert(payment_info.receiver);
-        }
+    if !detailed.unwrap_or(false) {
+        let recipients: Vec<String> = recipient_counts(&cached.proposals).into_keys().collect();
+        let total = recipients.len();
+        return Ok(Json(RecipientsResponse {
+            recipients,
+            details: None,
+            total,
+        }));
     }
 
-    let mut recipients_vec: Vec<String> = recipients.into_iter().collect();
-    recipients_vec.sort_unstable(); // Sort alphabetically for consistent ordering
-
-    let total = recipients_vec.len();
+    let txs_logs = cached_txs_logs(cache, &dao_id);
+    let mut metadata: HashMap<String, Option<FtMetadata>> = HashMap::new();
+    let mut recipients = Vec::new();
+    let mut details = Vec::new();
+    for (recipient, payments) in recipient_payments(&cached.proposals, &txs_logs) {
+        let mut approved_amounts = Vec::new();
+        for (token_id, amount) in payments.approved_amounts {
+            if !metadata.contains_key(&token_id) {
+                let token_metadata =
+                    match get_ft_metadata_cache(&client, ft_metadata_cache, &token_id).await {
+                        Ok(token_metadata) => Some(token_metadata),
+                        Err(e) => {
+                            warn!(token = %token_id, error = %e, "Error fetching FT metadata");
+                            None
+                        }
+                    };
+                metadata.insert(token_id.clone(), token_metadata);
+            }
+            let token_metadata = metadata.get(&token_id).and_then(|m| m.as_ref());
+            let decimals = token_metadata.map(|m| m.decimals);
+            approved_amounts.push(TokenAmount {
+                symbol: token_metadata.map(|m| m.symbol.clone()),
+                decimals,
+                amount: amount.to_string(),
+                amount_normalized: decimals.map(|decimals| format_units(amount, decimals)),
+                token_id,
+            });
+        }
+        details.push(RecipientDetails {
+            recipient: recipient.clone(),
+            proposals: payments.proposals,
+            approved_amounts,
+            last_payment_date: payments.last_payment_at.map(rfc3339_utc),
+        });
+        recipients.push(recipient);
+    }
+    let total = recipients.len();
 
     Ok(Json(RecipientsResponse {
-        recipients: recipients_vec,
+        recipients,
+        details: Some(details),
         total,
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}/requested-tokens",
+    tag = "dao",
+    params(
+        DaoPath,
+    ),
+    responses(
+        (status = 200, body = RequestedTokensResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
 #[get("/proposals/<dao_id>/requested-tokens")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
 pub async fn get_dao_requested_tokens(
+    _auth: ApiAuth,
     dao_id: &str,
     store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    ft_metadata_cache: &State<FtMetadataCache>,
 ) -> Result<Json<RequestedTokensResponse>, Status> {
     let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
 
-    let cached = get_cached_data(&dao_id, &client, &store).await?;
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
 
-    // Extract unique request tokens from transfer proposals only
-    let mut request_tokens: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for proposal in &cached.proposals {
-        // Check if this is a transfer proposal
-        if let Some(payment_info) = scraper::PaymentInfo::from_proposal(proposal) {
-            // Map empty string to "near" for NEAR tokens
-            let token = if payment_info.token.is_empty() {
-                "near".to_string()
-            } else {
-                payment_info.token
-            };
-            request_tokens.insert(token);
-        }
+    let mut requested_tokens = Vec::new();
+    for (token_id, (proposals, total_amount)) in requested_token_totals(&cached.proposals) {
+        let metadata = match get_ft_metadata_cache(&client, ft_metadata_cache, &token_id).await {
+            Ok(metadata) => Some(metadata),
+            Err(e) => {
+                warn!(token = %token_id, error = %e, "Error fetching FT metadata");
+                None
+            }
+        };
+        requested_tokens.push(RequestedToken {
+            symbol: metadata.as_ref().map(|metadata| metadata.symbol.clone()),
+            decimals: metadata.as_ref().map(|metadata| metadata.decimals),
+            icon: metadata.and_then(|metadata| metadata.icon),
+            token_id,
+            proposals,
+            total_amount: total_amount.to_string(),
+        });
     }
-
-    let mut request_tokens_vec: Vec<String> = request_tokens.into_iter().collect();
-    request_tokens_vec.sort_unstable(); // Sort alphabetically for consistent ordering
-
-    let total = request_tokens_vec.len();
+    let total = requested_tokens.len();
 
     Ok(Json(RequestedTokensResponse {
-        requested_tokens: request_tokens_vec,
+        requested_tokens,
         total,
     }))
 }
 
-#[get("/proposals/<dao_id>/validators")]
+// Stake delegation proposals per validator, with lockup accounts resolved to their staking pool
+async fn validator_counts(
+    client: &Arc<RpcPool>,
+    staking_pool_cache: &StakingPoolCache,
+    proposals: &[Proposal],
+) -> BTreeMap<String, usize> {
+    validator_totals(client, staking_pool_cache, proposals)
+        .await
+        .into_iter()
+        .map(|(validator, totals)| (validator, totals.proposals))
+        .collect()
+}
+
+#[derive(Default)]
+struct ValidatorTotals {
+    proposals: usize,
+    approved_staked: u128,
+    approved_unstaked: u128,
+}
+
+// Proposal counts and approved stake and unstake amounts per validator
+async fn validator_totals(
+    client: &Arc<RpcPool>,
+    staking_pool_cache: &StakingPoolCache,
+    proposals: &[Proposal],
+) -> BTreeMap<String, ValidatorTotals> {
+    let (stake_proposals, stakes): (Vec<&Proposal>, Vec<StakeDelegationInfo>) = proposals
+        .iter()
+        .filter_map(|proposal| {
+            StakeDelegationInfo::from_proposal(proposal).map(|info| (proposal, info))
+        })
+        .unzip();
+    let resolved = staking_pool_cache.resolve_validators(client, &stakes).await;
+
+    let mut validators: BTreeMap<String, ValidatorTotals> = BTreeMap::new();
+    for ((proposal, stake_info), validator) in stake_proposals.into_iter().zip(stakes).zip(resolved)
+    {
+        // If the RPC call fails, still include the lockup account as fallback
+        let validator = validator.unwrap_or_else(|| stake_info.validator.clone());
+        let totals = validators.entry(validator).or_default();
+        totals.proposals += 1;
+        // The totals are in yoctoNEAR, proposals moving another token only add to the count
+        if proposal.status == ProposalStatus::Approved && stake_info.token.is_empty() {
+            let amount = stake_info.amount.parse::<u128>().unwrap_or(0);
+            match stake_info.proposal_type.as_str() {
+                "stake" => totals.approved_staked = totals.approved_staked.saturating_add(amount),
+                "unstake" => {
+                    totals.approved_unstaked = totals.approved_unstaked.saturating_add(amount)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    validators
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposals/{dao_id}/validators",
+    tag = "dao",
+    params(
+        DaoPath,
+        ("detailed" = Option<bool>, Query, description = "Add approved stake totals and the current stake per validator"),
+    ),
+    responses(
+        (status = 200, body = ValidatorsResponse),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/proposals/<dao_id>/validators?<detailed>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_dao_validators(
+    _auth: ApiAuth,
     dao_id: &str,
+    detailed: Option<bool>,
     store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    staking_pool_cache: &State<StakingPoolCache>,
+    staking_cache: &State<StakingCache>,
+    lockup_cache: &State<LockupCache>,
 ) -> Result<Json<ValidatorsResponse>, Status> {
     let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
     let client = rpc_client::get_rpc_client();
 
-    let cached = get_cached_data(&dao_id, &client, &store).await?;
-
-    // Extract unique validators from stake delegation proposals only
-    let mut validators: std::collections::HashSet<String> = std::collections::HashSet::new();
-    let staking_pool_cache = cache::StakingPoolCache::new();
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
 
-    for proposal in &cached.proposals {
-        // Check if this is a stake delegation proposal
-        if let Some(stake_info) = scraper::StakeDelegationInfo::from_proposal(proposal) {
-            // For lockup accounts, we need to resolve the validator via RPC
-            if stake_info.validator.contains(".lockup.near") {
-                // This is a lockup account, resolve the validator
-                if let Some(validator) = staking_pool_cache
-                    .get_staking_pool_account_id(&client, &stake_info.validator)
-                    .await
-                {
-                    validators.insert(validator);
-                } else {
-                    // If RPC call fails, still include the lockup account as fallback
-                    validators.insert(stake_info.validator);
-                }
-            } else {
-                // Direct validator account
-                validators.insert(stake_info.validator);
-            }
-        }
+    if !detailed.unwrap_or(false) {
+        let validators: Vec<String> =
+            validator_counts(&client, staking_pool_cache, &cached.proposals)
+                .await
+                .into_keys()
+                .collect();
+        let total = validators.len();
+        return Ok(Json(ValidatorsResponse {
+            validators,
+            details: None,
+            total,
+        }));
     }
 
-    let mut validators_vec: Vec<String> = validators.into_iter().collect();
-    validators_vec.sort_unstable(); // Sort alphabetically for consistent ordering
+    // Current stake comes from the staking portfolio, summed over the DAO and its lockup
+    let portfolio = get_staking_portfolio(
+        &client,
+        staking_cache,
+        staking_pool_cache,
+        lockup_cache,
+        &dao_id,
+        &cached.proposals,
+    )
+    .await
+    .map_err(|e| {
+        error!(%dao_id, error = %e, "Error fetching staking portfolio");
+        Status::InternalServerError
+    })?;
+    let mut balances: HashMap<&str, (u128, u128)> = HashMap::new();
+    for position in &portfolio.positions {
+        let (staked, unstaked) = balances.entry(position.validator.as_str()).or_default();
+        *staked = staked.saturating_add(position.staked_balance.0);
+        *unstaked = unstaked.saturating_add(position.unstaked_balance.0);
+    }
 
-    let total = validators_vec.len();
+    let mut validators = Vec::new();
+    let mut details = Vec::new();
+    for (validator, totals) in
+        validator_totals(&client, staking_pool_cache, &cached.proposals).await
+    {
+        let (staked, unstaked) = balances
+            .get(validator.as_str())
+            .copied()
+            .unwrap_or_default();
+        details.push(ValidatorDetails {
+            validator: validator.clone(),
+            proposals: totals.proposals,
+            approved_staked: totals.approved_staked.to_string(),
+            approved_unstaked: totals.approved_unstaked.to_string(),
+            staked_balance: staked.to_string(),
+            unstaked_balance: unstaked.to_string(),
+        });
+        validators.push(validator);
+    }
+    let total = validators.len();
 
     Ok(Json(ValidatorsResponse {
-        validators: validators_vec,
+        validators,
+        details: Some(details),
         total,
     }))
 }
 
-pub struct CsvFile {
-    pub content: String,
+pub struct CsvFile<S> {
+    pub stream: TextStream<S>,
     pub filename: String,
 }
 
-impl<'r> Responder<'r, 'static> for CsvFile {
-    fn respond_to(self, _req: &'r Request<'_>) -> rocket::response::Result<'static> {
-        Response::build()
+impl<'r, S> Responder<'r, 'r> for CsvFile<S>
+where
+    S: Stream<Item = String> + Send + 'r,
+{
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'r> {
+        Response::build_from(self.stream.respond_to(req)?)
             .header(ContentType::new("text", "csv"))
             .header(Header::new(
                 "Content-Disposition",
                 format!("attachment; filename=\"{}\"", self.filename),
             ))
-            .sized_body(self.content.len(), Cursor::new(self.content))
             .ok()
     }
 }
 
-#[get("/csv/proposals/<dao_id>?<filters..>")]
+// Creation tx, approval txs and vote times of a proposal, from its cached transaction log
+async fn tx_log_columns(
+    client: &Arc<RpcPool>,
+    proposal_cache: &ProposalCache,
+    dao_id: &AccountId,
+    proposal: &Proposal,
+    date_format: &DateFormat,
+) -> Vec<String> {
+    match get_latest_proposal_cache(client, proposal_cache, dao_id, proposal.id).await {
+        Ok(cached) => format_tx_log(proposal, &cached.txs_log, date_format),
+        Err(e) => {
+            warn!(proposal_id = proposal.id, error = %e, "Error fetching transaction log");
+            vec![String::new(); TX_LOG_HEADERS.len()]
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}",
+    tag = "csv",
+    params(
+        DaoPath,
+        ("include_usd" = Option<bool>, Query, description = "Add a USD Value column to payments"),
+        ("include_txs" = Option<bool>, Query, description = "Add transaction hash and vote time columns"),
+        ("profile" = Option<String>, Query, description = "quickbooks or xero, requires category=payments"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for dates, default UTC"),
+        ("date_format" = Option<String>, Query, description = "strftime pattern for dates"),
+        ProposalFilters,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get(
+    "/csv/proposals/<dao_id>?<include_usd>&<include_txs>&<profile>&<delimiter>&<line_ending>&<bom>&<tz>&<date_format>&<filters..>"
+)]
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(dao_id = %dao_id, filters = ?filters))]
 pub async fn csv_proposals(
+    _auth: ApiAuth,
     dao_id: &str,
+    include_usd: Option<bool>,
+    include_txs: Option<bool>,
+    profile: Option<AccountingProfile>,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    tz: Option<&str>,
+    date_format: Option<&str>,
     filters: ProposalFilters,
     store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    proposal_cache: &State<ProposalCache>,
     ft_metadata_cache: &State<FtMetadataCache>,
-) -> Result<CsvFile, Status> {
+    staking_pool_cache: &State<StakingPoolCache>,
+    lockup_cache: &State<LockupCache>,
+    price_cache: &State<PriceCache>,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, ApiError> {
     if dao_id.is_empty() {
-        return Err(Status::BadRequest);
+        return Err(Status::BadRequest.into());
     }
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
 
-    let client = rpc_client::get_rpc_client();
-    let dao_id_account = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let options = ProposalExportOptions {
+        include_usd: include_usd.unwrap_or(false),
+        include_txs: include_txs.unwrap_or(false),
+        profile,
+        tz: tz.map(str::to_string),
+        date_format: date_format.map(str::to_string),
+    };
+    let caches = ExportCaches {
+        store: store.inner().clone(),
+        proposal_cache: proposal_cache.inner().clone(),
+        ft_metadata_cache: ft_metadata_cache.inner().clone(),
+        staking_pool_cache: staking_pool_cache.inner().clone(),
+        lockup_cache: lockup_cache.inner().clone(),
+        price_cache: price_cache.inner().clone(),
+    };
+    let records = proposal_records(
+        rpc_client::get_rpc_client(),
+        caches,
+        dao_id.clone(),
+        filters,
+        options,
+        ttl.0,
+    )
+    .await?;
+
+    // Rows are formatted and sent one at a time instead of building the whole file in memory
+    let dialect = CsvDialect::new(delimiter, line_ending, bom);
+    let bom = dialect.bom.then(|| UTF8_BOM.to_string());
+    let lines = records.map(move |record| dialect.line(&record));
+
+    Ok(CsvFile {
+        stream: TextStream::from(stream::iter(bom).chain(lines)),
+        filename: format!("proposals_{}.csv", dao_id),
+    })
+}
+
+/// Layout and date options of a proposals export, shared by the CSV route and reports
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProposalExportOptions {
+    /// Add a USD Value column to payments
+    pub include_usd: bool,
+    /// Add transaction hash and vote time columns
+    pub include_txs: bool,
+    /// Accounting software layout, requires category=payments
+    pub profile: Option<AccountingProfile>,
+    /// IANA timezone for dates, UTC by default
+    pub tz: Option<String>,
+    /// strftime pattern for dates
+    pub date_format: Option<String>,
+}
+
+/// Caches a proposals export reads, cloned from the managed state
+#[derive(Clone)]
+pub struct ExportCaches {
+    pub store: ProposalStore,
+    pub proposal_cache: ProposalCache,
+    pub ft_metadata_cache: FtMetadataCache,
+    pub staking_pool_cache: StakingPoolCache,
+    pub lockup_cache: LockupCache,
+    pub price_cache: PriceCache,
+}
+
+fn header_record(headers: &[&str]) -> Vec<String> {
+    headers.iter().map(|header| header.to_string()).collect()
+}
+
+/// Header and rows of the proposals CSV in the layout of the filtered category. Rows are
+/// formatted as the stream is read.
+pub async fn proposal_records(
+    client: Arc<RpcPool>,
+    caches: ExportCaches,
+    dao_id_account: AccountId,
+    filters: ProposalFilters,
+    options: ProposalExportOptions,
+    life_time: Duration,
+) -> Result<impl Stream<Item = Vec<String>> + Send, ApiError> {
+    check_filters(&filters)?;
+    let profile = options.profile;
+    let dao_id = dao_id_account.as_str();
+
+    // Accounting profiles only describe payments
+    if profile.is_some() && filters.category.as_deref() != Some(categories::PAYMENTS) {
+        return Err(ApiError::bad_request("profile requires category=payments"));
+    }
+
+    let date_format = options
+        .date_format
+        .as_deref()
+        .or(profile.map(|profile| profile.date_pattern()));
+    let date_format = DateFormat::new(options.tz.as_deref(), date_format).map_err(|e| {
+        debug!(error = %e, "Invalid CSV date options");
+        Status::BadRequest
+    })?;
 
     // Get cached data
-    let cached = get_latest_dao_cache(&client, &store, &dao_id_account)
-        .await
-        .map_err(|_| Status::NotFound)?;
+    let cached = get_cached_data(&dao_id_account, &client, &caches.store, life_time).await?;
 
     let proposals = filters
-        .filter_proposals_async(cached.proposals, &cached.policy, &ft_metadata_cache)
+        .filter_proposals_async(
+            &client,
+            &cached.proposals,
+            &cached.policy,
+            &caches.ft_metadata_cache,
+            &caches.staking_pool_cache,
+        )
         .await
         .map_err(|e| {
-            eprintln!("Error filtering proposals for CSV: {}", e);
+            error!(error = %e, "Error filtering proposals for CSV");
             Status::InternalServerError
         })?;
 
     // Check if DAO has a lockup account (for payments or stake delegation category)
     let has_lockup_account = match filters.category.as_deref() {
-        Some(categories::PAYMENTS) | Some(categories::STAKE_DELEGATION) => {
-            rpc_client::account_to_lockup(&client, dao_id)
-                .await
-                .is_some()
-        }
+        Some(categories::PAYMENTS) | Some(categories::STAKE_DELEGATION) => caches
+            .lockup_cache
+            .get_lockup_account(&client, dao_id)
+            .await
+            .is_some(),
         _ => false,
     };
 
-    let mut wtr = csv::Writer::from_writer(vec![]);
-
-    // Helper functions to write CSV records with error handling
-    let write_headers = |wtr: &mut csv::Writer<Vec<u8>>, headers: &[&str]| -> Result<(), Status> {
-        wtr.write_record(headers)
-            .map_err(|_| Status::InternalServerError)
-    };
-
-    let write_record = |wtr: &mut csv::Writer<Vec<u8>>, record: &[String]| -> Result<(), Status> {
-        wtr.write_record(record)
-            .map_err(|_| Status::InternalServerError)
+    let prices = match (filters.category.as_deref(), options.include_usd) {
+        (Some(categories::PAYMENTS), true) => {
+            Some(token_prices_or_empty(&caches.price_cache).await)
+        }
+        _ => None,
     };
 
-    match filters.category.as_deref() {
-        Some(categories::PAYMENTS) => {
-            let extracted = filters.filter_and_extract::<PaymentInfo>(proposals);
-            let formatter = TransferProposalFormatter;
-            let mut headers = formatter.headers();
-            if !has_lockup_account {
-                if let Some(index) = headers.iter().position(|&h| h == "Treasury Wallet") {
-                    headers.remove(index);
+    let policy = cached.policy;
+    let ft_metadata_cache = caches.ft_metadata_cache;
+    let staking_pool_cache = caches.staking_pool_cache;
+    // Transaction logs are fetched per exported proposal, only when asked for
+    let include_txs = options.include_txs;
+    let proposal_cache = caches.proposal_cache;
+    let accounting = profile.map(|profile| AccountingPaymentFormatter {
+        profile,
+        date_format: date_format.clone(),
+        dao_id: dao_id_account.clone(),
+        proposal_cache: proposal_cache.clone(),
+    });
+    Ok(async_stream::stream! {
+        match filters.category.as_deref() {
+            Some(categories::PAYMENTS) if accounting.is_some() => {
+                if let Some(formatter) = accounting {
+                    // Only approved payments were actually paid out
+                    let extracted = filters
+                        .filter_and_extract::<PaymentInfo>(&proposals)
+                        .into_iter()
+                        .filter(|(proposal, _)| proposal.status == ProposalStatus::Approved);
+                    yield header_record(&formatter.headers());
+                    for (proposal, payment_info) in extracted {
+                        let record = formatter
+                            .format(
+                                &client,
+                                &ft_metadata_cache,
+                                proposal,
+                                &policy,
+                                &payment_info,
+                            )
+                            .await;
+                        if record.is_empty() {
+                            continue;
+                        }
+                        yield record;
+                    }
                 }
             }
-            write_headers(&mut wtr, &headers)?;
-            for (proposal, payment_info) in extracted {
-                let mut record = formatter
-                    .format(
-                        &client,
-                        &ft_metadata_cache,
-                        &proposal,
-                        &cached.policy,
-                        &payment_info,
-                    )
-                    .await;
-                if record.is_empty() {
-                    continue;
+            Some(categories::PAYMENTS) => {
+                let extracted = filters.filter_and_extract::<PaymentInfo>(&proposals);
+                let formatter = TransferProposalFormatter {
+                    date_format: date_format.clone(),
+                };
+                let mut headers = formatter.headers();
+                if !has_lockup_account
+                    && let Some(index) = headers.iter().position(|&h| h == "Treasury Wallet") {
+                        headers.remove(index);
+                    }
+                if prices.is_some() {
+                    headers.push("USD Value");
+                }
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
                 }
-                if !has_lockup_account && record.len() > 3 {
-                    record.remove(3);
+                yield header_record(&headers);
+                for (proposal, payment_info) in extracted {
+                    let mut record = formatter
+                        .format(
+                            &client,
+                            &ft_metadata_cache,
+                            proposal,
+                            &policy,
+                            &payment_info,
+                        )
+                        .await;
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if !has_lockup_account && record.len() > 3 {
+                        record.remove(3);
+                    }
+                    if let Some(prices) = &prices {
+                        let usd_value =
+                            payment_usd_value(&client, &ft_metadata_cache, prices, &payment_info)
+                                .await;
+                        record.push(usd_value.map(|v| format!("{:.2}", v)).unwrap_or_default());
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
                 }
-                write_record(&mut wtr, &record)?;
             }
-        }
-        Some(categories::LOCKUP) => {
-            let extracted = filters.filter_and_extract::<LockupInfo>(proposals);
-            let formatter = LockupProposalFormatter;
-            let headers = formatter.headers();
-            write_headers(&mut wtr, &headers)?;
-            for (proposal, lockup_info) in extracted {
-                let record = formatter.format(&proposal, &cached.policy, &lockup_info);
-                if record.is_empty() {
-                    continue;
+            Some(categories::LOCKUP) => {
+                let extracted = filters.filter_and_extract::<LockupInfo>(&proposals);
+                let formatter = LockupProposalFormatter {
+                    date_format: date_format.clone(),
+                };
+                let mut headers = formatter.headers();
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
+                }
+                yield header_record(&headers);
+                for (proposal, lockup_info) in extracted {
+                    let mut record = formatter.format(proposal, &policy, &lockup_info);
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
                 }
-                write_record(&mut wtr, &record)?;
             }
-        }
-        Some(categories::ASSET_EXCHANGE) => {
-            let extracted = filters.filter_and_extract::<AssetExchangeInfo>(proposals);
-            let formatter = AssetExchangeProposalFormatter;
-            let headers = formatter.headers();
-            write_headers(&mut wtr, &headers)?;
-            for (proposal, asset_info) in extracted {
-                let record = formatter
-                    .format(
-                        &client,
-                        &ft_metadata_cache,
-                        &proposal,
-                        &cached.policy,
-                        &asset_info,
-                    )
-                    .await;
-                if record.is_empty() {
-                    continue;
+            Some(categories::ASSET_EXCHANGE) => {
+                let extracted = filters.filter_and_extract::<AssetExchangeInfo>(&proposals);
+                let formatter = AssetExchangeProposalFormatter {
+                    date_format: date_format.clone(),
+                };
+                let mut headers = formatter.headers();
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
+                }
+                yield header_record(&headers);
+                for (proposal, asset_info) in extracted {
+                    let mut record = formatter
+                        .format(
+                            &client,
+                            &ft_metadata_cache,
+                            proposal,
+                            &policy,
+                            &asset_info,
+                        )
+                        .await;
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
                 }
-                write_record(&mut wtr, &record)?;
             }
-        }
-        Some(categories::STAKE_DELEGATION) => {
-            let extracted = filters.filter_and_extract::<StakeDelegationInfo>(proposals);
-            let formatter = StakeDelegationProposalFormatter;
-            let mut headers = formatter.headers();
-            if !has_lockup_account {
-                if let Some(index) = headers.iter().position(|&h| h == "Treasury Wallet") {
-                    headers.remove(index);
+            Some(categories::STAKE_DELEGATION) => {
+                let extracted = filters.filter_and_extract::<StakeDelegationInfo>(&proposals);
+                let formatter = StakeDelegationProposalFormatter {
+                    date_format: date_format.clone(),
+                    staking_pool_cache: staking_pool_cache.clone(),
+                };
+                let mut headers = formatter.headers();
+                if !has_lockup_account
+                    && let Some(index) = headers.iter().position(|&h| h == "Treasury Wallet") {
+                        headers.remove(index);
+                    }
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
+                }
+                yield header_record(&headers);
+                for (proposal, stake_info) in extracted {
+                    let mut record = formatter
+                        .format(
+                            &client,
+                            &ft_metadata_cache,
+                            proposal,
+                            &policy,
+                            &stake_info,
+                        )
+                        .await;
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if !has_lockup_account && record.len() > 3 {
+                        record.remove(3);
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
                 }
             }
-            write_headers(&mut wtr, &headers)?;
-            for (proposal, stake_info) in extracted {
-                let mut record = formatter
-                    .format(
-                        &client,
-                        &ft_metadata_cache,
-                        &proposal,
-                        &cached.policy,
-                        &stake_info,
-                    )
-                    .await;
-                if record.is_empty() {
-                    continue;
+            Some(categories::GOVERNANCE_OPS) => {
+                let extracted = filters.filter_and_extract::<GovernanceOpsInfo>(&proposals);
+                let formatter = GovernanceOpsProposalFormatter {
+                    date_format: date_format.clone(),
+                };
+                let mut headers = formatter.headers();
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
                 }
-                if !has_lockup_account && record.len() > 3 {
-                    record.remove(3);
+                yield header_record(&headers);
+                for (proposal, ops_info) in extracted {
+                    let mut record = formatter.format(proposal, &policy, &ops_info);
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
                 }
-                write_record(&mut wtr, &record)?;
             }
-        }
-        _ => {
-            // Default: use the old logic for other categories
-            let formatter = DefaultFormatter;
-            let headers = formatter.headers();
-            write_headers(&mut wtr, &headers)?;
-            for proposal in proposals {
-                let record = formatter.format(&proposal, &cached.policy, &());
-                if record.is_empty() {
-                    continue;
+            Some(categories::MEMBERS) => {
+                let extracted = filters.filter_and_extract::<MemberChangeInfo>(&proposals);
+                let formatter = MemberChangeProposalFormatter {
+                    date_format: date_format.clone(),
+                };
+                let mut headers = formatter.headers();
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
+                }
+                yield header_record(&headers);
+                for (proposal, member_info) in extracted {
+                    let mut record = formatter.format(proposal, &policy, &member_info);
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
+                }
+            }
+            _ => {
+                // Default: use the old logic for other categories
+                let formatter = DefaultFormatter {
+                    date_format: date_format.clone(),
+                };
+                let mut headers = formatter.headers();
+                if include_txs {
+                    headers.extend(TX_LOG_HEADERS);
+                }
+                yield header_record(&headers);
+                for proposal in proposals.iter() {
+                    let mut record = formatter.format(proposal, &policy, &());
+                    if record.is_empty() {
+                        continue;
+                    }
+                    if include_txs {
+                        record.extend(
+                            tx_log_columns(
+                                &client,
+                                &proposal_cache,
+                                &dao_id_account,
+                                proposal,
+                                &date_format,
+                            )
+                            .await,
+                        );
+                    }
+                    yield record;
                 }
-                write_record(&mut wtr, &record)?;
             }
         }
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/export/proposals/{dao_id}.parquet",
+    tag = "csv",
+    params(
+        DaoPath,
+        ProposalFilters,
+    ),
+    responses(
+        (status = 200, content_type = "application/vnd.apache.parquet", body = Vec<u8>,
+            description = "Columns id, proposer, status, submission_ts, category, token, amount_raw and amount_normalized"),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/export/proposals/<file>?<filters..>")]
+#[instrument(skip_all, fields(file = %file, filters = ?filters))]
+pub async fn parquet_proposals(
+    _auth: ApiAuth,
+    file: &str,
+    filters: ProposalFilters,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    ft_metadata_cache: &State<FtMetadataCache>,
+    staking_pool_cache: &State<StakingPoolCache>,
+) -> Result<ParquetFile, ApiError> {
+    // Rocket segments can't mix parameters and text, so the extension is checked here
+    let dao_id = file.strip_suffix(".parquet").ok_or(Status::NotFound)?;
+    check_filters(&filters)?;
+
+    let client = rpc_client::get_rpc_client();
+    let dao_id: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+
+    let cached = get_cached_data(&dao_id, &client, store, ttl.0).await?;
+
+    let proposals = filters
+        .filter_proposals_async(
+            &client,
+            &cached.proposals,
+            &cached.policy,
+            ft_metadata_cache,
+            staking_pool_cache,
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Error filtering proposals for Parquet");
+            Status::InternalServerError
+        })?;
+
+    let bytes = proposals_parquet(&client, ft_metadata_cache, staking_pool_cache, &proposals)
+        .await
+        .map_err(|e| {
+            error!(error = ?e, "Error writing Parquet export");
+            Status::InternalServerError
+        })?;
+
+    Ok(ParquetFile {
+        bytes,
+        filename: format!("proposals_{}.parquet", dao_id),
+    })
+}
+
+// Small enough to build up front, the stream only keeps CsvFile uniform
+fn counts_csv(
+    dao_id: &str,
+    list_name: &str,
+    headers: [&str; 2],
+    counts: BTreeMap<String, usize>,
+    dialect: CsvDialect,
+) -> CsvFile<impl Stream<Item = String> + Send + use<>> {
+    let mut lines = Vec::with_capacity(counts.len() + 2);
+    if dialect.bom {
+        lines.push(UTF8_BOM.to_string());
     }
+    lines.push(dialect.line(headers));
+    lines.extend(
+        counts
+            .into_iter()
+            .map(|(entry, count)| dialect.line([entry, count.to_string()])),
+    );
+
+    CsvFile {
+        stream: TextStream(rocket::futures::stream::iter(lines)),
+        filename: format!("{}_{}.csv", list_name, dao_id),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}/proposers",
+    tag = "csv",
+    params(
+        DaoPath,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/csv/proposals/<dao_id>/proposers?<delimiter>&<line_ending>&<bom>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn csv_dao_proposers(
+    _auth: ApiAuth,
+    dao_id: &str,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, Status> {
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
+
+    Ok(counts_csv(
+        dao_id,
+        "proposers",
+        ["Proposer", "Proposals"],
+        proposer_counts(&cached.proposals),
+        CsvDialect::new(delimiter, line_ending, bom),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}/approvers",
+    tag = "csv",
+    params(
+        DaoPath,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/csv/proposals/<dao_id>/approvers?<delimiter>&<line_ending>&<bom>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn csv_dao_approvers(
+    _auth: ApiAuth,
+    dao_id: &str,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, Status> {
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
+
+    Ok(counts_csv(
+        dao_id,
+        "approvers",
+        ["Approver", "Votes"],
+        voter_counts(&cached.proposals),
+        CsvDialect::new(delimiter, line_ending, bom),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}/recipients",
+    tag = "csv",
+    params(
+        DaoPath,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/csv/proposals/<dao_id>/recipients?<delimiter>&<line_ending>&<bom>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn csv_dao_recipients(
+    _auth: ApiAuth,
+    dao_id: &str,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, Status> {
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
 
-    let data = String::from_utf8(wtr.into_inner().map_err(|_| Status::InternalServerError)?)
-        .map_err(|_| Status::InternalServerError)?;
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
+
+    Ok(counts_csv(
+        dao_id,
+        "recipients",
+        ["Recipient", "Payment Proposals"],
+        recipient_counts(&cached.proposals),
+        CsvDialect::new(delimiter, line_ending, bom),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}/requested-tokens",
+    tag = "csv",
+    params(
+        DaoPath,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/csv/proposals/<dao_id>/requested-tokens?<delimiter>&<line_ending>&<bom>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn csv_dao_requested_tokens(
+    _auth: ApiAuth,
+    dao_id: &str,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, Status> {
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
+
+    Ok(counts_csv(
+        dao_id,
+        "requested_tokens",
+        ["Token", "Payment Proposals"],
+        requested_token_counts(&cached.proposals),
+        CsvDialect::new(delimiter, line_ending, bom),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}/validators",
+    tag = "csv",
+    params(
+        DaoPath,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/csv/proposals/<dao_id>/validators?<delimiter>&<line_ending>&<bom>")]
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn csv_dao_validators(
+    _auth: ApiAuth,
+    dao_id: &str,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+    staking_pool_cache: &State<StakingPoolCache>,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, Status> {
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
+
+    Ok(counts_csv(
+        dao_id,
+        "validators",
+        ["Validator", "Stake Delegation Proposals"],
+        validator_counts(&client, staking_pool_cache, &cached.proposals).await,
+        CsvDialect::new(delimiter, line_ending, bom),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/csv/proposals/{dao_id}/policy-changes",
+    tag = "csv",
+    params(
+        DaoPath,
+        CsvDialectQuery,
+    ),
+    responses(
+        (status = 200, content_type = "text/csv", body = String),
+        (status = 400, description = "Invalid DAO id or parameters"),
+        (status = 404, description = "DAO not found"),
+        (status = 502, description = "RPC failed to answer"),
+        (status = 503, description = "DAO is still being fetched or RPC is unavailable, retry after the Retry-After seconds"),
+    )
+)]
+#[get("/csv/proposals/<dao_id>/policy-changes?<delimiter>&<line_ending>&<bom>")]
+#[instrument(skip_all, fields(dao_id = %dao_id))]
+pub async fn csv_policy_changes(
+    _auth: ApiAuth,
+    dao_id: &str,
+    delimiter: Option<CsvDelimiter>,
+    line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    store: &State<ProposalStore>,
+    ttl: CacheTtl,
+) -> Result<CsvFile<impl Stream<Item = String> + Send>, Status> {
+    let dao_account: AccountId = dao_id.parse().map_err(|_| Status::BadRequest)?;
+    let client = rpc_client::get_rpc_client();
+
+    let cached = get_cached_data(&dao_account, &client, store, ttl.0).await?;
+    let dialect = CsvDialect::new(delimiter, line_ending, bom);
+    let formatter = PolicyChangeProposalFormatter::default();
+
+    let mut lines = Vec::new();
+    if dialect.bom {
+        lines.push(UTF8_BOM.to_string());
+    }
+    lines.push(dialect.line(formatter.headers()));
+    for proposal in cached.proposals.iter() {
+        let Some(info) = MemberChangeInfo::from_proposal(proposal) else {
+            continue;
+        };
+        if info.is_policy_change() {
+            lines.push(dialect.line(formatter.format(proposal, &cached.policy, &info)));
+        }
+    }
 
     Ok(CsvFile {
-        content: data,
-        filename: format!("proposals_{}.csv", dao_id),
+        stream: TextStream(rocket::futures::stream::iter(lines)),
+        filename: format!("policy_changes_{}.csv", dao_id),
     })
 }
 
 // This is the function your main.rs and tests should call!
 pub fn rocket() -> rocket::Rocket<rocket::Build> {
-    let proposals_store: ProposalStore = Arc::new(RwLock::new(HashMap::new()));
-    let proposal_cache: ProposalCache =
-        read_cache_from_file().unwrap_or_else(|_| Arc::new(RwLock::new(HashMap::new())));
-
-    let ft_metadata_cache: FtMetadataCache = Arc::new(RwLock::new(HashMap::new()));
+    logging::init();
+
+    let restored = read_cache_from_file().unwrap_or_else(|_| RestoredCaches {
+        store: Arc::new(DashMap::new()),
+        proposal_cache: Arc::new(DashMap::new()),
+        staking_pool_cache: StakingPoolCache::default(),
+        lockup_cache: LockupCache::default(),
+    });
+    let proposals_store: ProposalStore = restored.store;
+    let proposal_cache: ProposalCache = restored.proposal_cache;
+    let staking_pool_cache = restored.staking_pool_cache;
+    let lockup_cache = restored.lockup_cache;
+
+    let ft_metadata_cache: FtMetadataCache = Arc::new(DashMap::new());
+    let bounty_store: BountyStore = Arc::new(DashMap::new());
+    let bounty_claims_cache: BountyClaimsCache = Arc::new(DashMap::new());
+    let price_cache: PriceCache = Arc::new(RwLock::new(None));
+    let balance_cache: BalanceCache = Arc::new(RwLock::new(HashMap::new()));
+    let staking_cache: StakingCache = Arc::new(RwLock::new(HashMap::new()));
+    let intents_cache: IntentsCache = Arc::new(RwLock::new(HashMap::new()));
+
+    let graphql_schema = graphql::build_schema(
+        proposals_store.clone(),
+        ft_metadata_cache.clone(),
+        staking_pool_cache.clone(),
+    );
+
+    let tracked_daos = load_tracked_daos();
+    let report_jobs = load_report_jobs();
+    let export_caches = ExportCaches {
+        store: proposals_store.clone(),
+        proposal_cache: proposal_cache.clone(),
+        ft_metadata_cache: ft_metadata_cache.clone(),
+        staking_pool_cache: staking_pool_cache.clone(),
+        lockup_cache: lockup_cache.clone(),
+        price_cache: price_cache.clone(),
+    };
 
+    let reconcile_store = proposals_store.clone();
+    let tracked_store = proposals_store.clone();
+    let tracked_refresh = tracked_daos.clone();
+    let scheduled_reports = report_jobs.clone();
+    let report_caches = export_caches.clone();
     let cache_persistence = CachePersistence {
+        store: proposals_store.clone(),
         proposal_cache: proposal_cache.clone(),
+        staking_pool_cache: staking_pool_cache.clone(),
+        lockup_cache: lockup_cache.clone(),
     };
 
     // Configure CORS
     let cors = CorsOptions::default()
-        .allowed_origins(AllowedOrigins::some_regex(&[
-            r"https?://.*\.near\.page",
-            r"https?://near\.social",
-            r"https?://near\.org",
-            r"https?://localhost:3000",
-            r"https?://near-treasury\.vercel\.app",
-            r"https?://app\.neartreasury\.com",
-            r"https?://near-treasury-sigma\.vercel\.app",
-            r"https?://localhost:8080",
-            r"https?://localhost:5001",
-            r"https?://127\.0\.0\.1:8080",
-            r"https?://sputnik-indexer-divine-fog-3863\.fly\.dev",
-            r"https?://sputnik-indexer\.fly\.dev",
-        ]))
+        .allowed_origins(AllowedOrigins::some_regex(
+            get_config().cors.allowed_origins.as_slice(),
+        ))
         .allow_credentials(true)
         .to_cors()
         .expect("Failed to create CORS fairing");
@@ -550,30 +3273,116 @@ pub fn rocket() -> rocket::Rocket<rocket::Build> {
         .manage(proposals_store)
         .manage(proposal_cache)
         .manage(ft_metadata_cache)
+        .manage(staking_pool_cache)
+        .manage(lockup_cache)
+        .manage(bounty_store)
+        .manage(bounty_claims_cache)
+        .manage(price_cache)
+        .manage(balance_cache)
+        .manage(staking_cache)
+        .manage(intents_cache)
+        .manage(graphql_schema)
+        .manage(tracked_daos)
+        .manage(report_jobs)
+        .manage(export_caches)
+        .mount(
+            "/",
+            SwaggerUi::new("/swagger-ui/<_..>").url("/openapi.json", ApiDoc::openapi()),
+        )
         .mount(
             "/",
             routes![
                 get_proposals,
+                batch_proposals,
                 get_specific_proposal,
+                get_proposal_timeline,
+                get_proposal_outlook,
+                get_proposal_history,
+                get_proposal_receipts,
+                get_pending_for_account,
+                get_can_act,
+                ws_proposals,
+                get_dao_policy,
+                get_dao_config,
+                get_dao_stats,
+                get_voting_history,
+                get_voter_leaderboard,
+                get_proposer_stats,
+                get_dao_timeseries,
+                get_dao_balances,
+                get_dao_staking,
+                get_dao_intents_balances,
+                get_dao_calendar,
+                get_dao_roles,
+                get_dao_members,
+                get_dao_bounties,
+                get_bounty_claims,
                 get_dao_proposers,
                 get_dao_approvers,
                 get_dao_recipients,
                 get_dao_requested_tokens,
                 get_dao_validators,
-                csv_proposals
+                csv_proposals,
+                csv_dao_proposers,
+                csv_dao_approvers,
+                csv_dao_recipients,
+                csv_dao_requested_tokens,
+                csv_dao_validators,
+                csv_policy_changes,
+                parquet_proposals,
+                graphql::graphql_request,
+                graphql::graphql_query,
+                graphql::graphiql,
+                admin::purge_dao_cache,
+                admin::refresh_dao_cache,
+                admin::cache_stats,
+                admin::export_dao,
+                admin::import_dao,
+                tracking::track_dao,
+                tracking::untrack_dao,
+                tracking::get_tracked_daos,
+                reports::create_report,
+                reports::get_reports,
+                reports::delete_report,
+                reports::run_report_now
             ],
         )
+        .register("/", catchers![service_unavailable])
+        .attach(RequestLogger)
         .attach(cache_persistence)
+        .attach(CacheWarmup)
+        .attach(CacheHeaders)
+        .attach(AdHoc::on_liftoff("RPC health probe", |_| {
+            Box::pin(async { rpc_client::spawn_health_probe() })
+        }))
+        .attach(AdHoc::on_liftoff("Status reconciliation", move |_| {
+            Box::pin(async move { cache::spawn_status_reconciliation(reconcile_store) })
+        }))
+        .attach(AdHoc::on_liftoff("Tracked DAO refresh", move |_| {
+            Box::pin(async move {
+                tracking::spawn_tracked_refresh(
+                    rpc_client::get_rpc_client(),
+                    tracked_store,
+                    tracked_refresh,
+                )
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Report scheduler", move |_| {
+            Box::pin(
+                async move { reports::spawn_report_scheduler(scheduled_reports, report_caches) },
+            )
+        }))
         .attach(cors)
         .configure(
             rocket::Config::figment()
+                .merge(("port", get_config().server.port))
+                .merge(("address", get_config().server.address.as_str()))
                 .merge((
-                    "port",
-                    std::env::var("PORT")
-                        .unwrap_or("5001".to_string())
-                        .parse::<u16>()
-                        .unwrap_or(5001),
-                ))
-                .merge(("address", "0.0.0.0")),
+                    "limits",
+                    Limits::default().limit(
+                        admin::SNAPSHOT_LIMIT,
+                        get_config().server.import_limit_mb.mebibytes(),
+                    ),
+                )),
         )
 }
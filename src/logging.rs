@@ -0,0 +1,53 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Data, Request, Response};
+use std::time::Instant;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::get_config;
+
+/// Installs the global tracing subscriber. RUST_LOG takes precedence over
+/// logging.level. Later calls, e.g. from every test building a rocket, are no-ops.
+pub fn init() {
+    let logging = &get_config().logging;
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(logging.level.as_str()));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    let _ = if logging.json {
+        subscriber.json().try_init()
+    } else {
+        subscriber.try_init()
+    };
+}
+
+// Set when the request arrives
+struct RequestStart(Instant);
+
+/// Logs method, path, matched route, status and duration of every request
+pub struct RequestLogger;
+
+#[rocket::async_trait]
+impl Fairing for RequestLogger {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request logger",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let started = request.local_cache(|| RequestStart(Instant::now()));
+        info!(
+            method = %request.method(),
+            path = %request.uri().path(),
+            route = request.route().and_then(|route| route.name.as_deref()),
+            status = response.status().code,
+            elapsed_ms = started.0.elapsed().as_millis() as u64,
+            "request"
+        );
+    }
+}
@@ -1,5 +1,14 @@
 #[rocket::main]
 async fn main() {
+    // --fixtures <dir> serves the DAOs recorded in dir instead of asking RPC
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>().into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--fixtures" {
+            let dir = args.next().expect("--fixtures needs a directory");
+            sputnik_indexer::use_fixtures(&dir);
+        }
+    }
+
     sputnik_indexer::rocket()
         .launch()
         .await
@@ -0,0 +1,110 @@
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi};
+
+use crate::auth::API_KEY_HEADER;
+
+// Documents the X-API-Key header, only required when keys are configured
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+    }
+}
+
+// Parameters shared by many routes. The structs only describe them to the generator,
+// the routes take the values as separate arguments.
+
+/// Path parameter of the routes on one DAO
+#[derive(IntoParams)]
+#[allow(dead_code)]
+#[into_params(parameter_in = Path)]
+pub struct DaoPath {
+    /// DAO account, e.g. testing-astradao.sputnik-dao.near
+    pub dao_id: String,
+}
+
+/// Path parameters of the routes on one proposal
+#[derive(IntoParams)]
+#[allow(dead_code)]
+#[into_params(parameter_in = Path)]
+pub struct ProposalPath {
+    /// DAO account, e.g. testing-astradao.sputnik-dao.near
+    pub dao_id: String,
+    /// Proposal id
+    pub proposal_id: u64,
+}
+
+/// Query parameters of the CSV exports, see CsvDialect
+#[derive(IntoParams)]
+#[allow(dead_code)]
+#[into_params(parameter_in = Query)]
+pub struct CsvDialectQuery {
+    /// comma (default), semicolon or tab
+    pub delimiter: Option<String>,
+    /// lf (default) or crlf
+    pub line_ending: Option<String>,
+    /// Start with a UTF-8 byte order mark
+    pub bom: Option<bool>,
+}
+
+/// Generated from the route annotations, served at /openapi.json with Swagger UI at /swagger-ui/
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Sputnik DAO Caching API",
+        description = "Cached proposals, policies and exports of Sputnik DAOs on NEAR"
+    ),
+    paths(
+        crate::get_proposals,
+        crate::batch_proposals,
+        crate::get_pending_for_account,
+        crate::get_specific_proposal,
+        crate::get_proposal_timeline,
+        crate::get_proposal_outlook,
+        crate::get_proposal_history,
+        crate::get_proposal_receipts,
+        crate::get_dao_policy,
+        crate::get_can_act,
+        crate::get_dao_config,
+        crate::get_dao_stats,
+        crate::get_voting_history,
+        crate::get_voter_leaderboard,
+        crate::get_proposer_stats,
+        crate::get_dao_timeseries,
+        crate::get_dao_balances,
+        crate::get_dao_staking,
+        crate::get_dao_intents_balances,
+        crate::get_dao_calendar,
+        crate::get_dao_roles,
+        crate::get_dao_members,
+        crate::get_dao_bounties,
+        crate::get_bounty_claims,
+        crate::get_dao_proposers,
+        crate::get_dao_approvers,
+        crate::get_dao_recipients,
+        crate::get_dao_requested_tokens,
+        crate::get_dao_validators,
+        crate::csv_proposals,
+        crate::csv_dao_proposers,
+        crate::csv_dao_approvers,
+        crate::csv_dao_recipients,
+        crate::csv_dao_requested_tokens,
+        crate::csv_dao_validators,
+        crate::csv_policy_changes,
+        crate::parquet_proposals,
+        crate::tracking::get_tracked_daos,
+    ),
+    tags(
+        (name = "proposals", description = "Filtered and paginated proposals"),
+        (name = "dao", description = "DAO policy, members and aggregates"),
+        (name = "csv", description = "CSV and Parquet exports")
+    ),
+    modifiers(&ApiKeySecurity),
+    security((), ("api_key" = []))
+)]
+pub struct ApiDoc;
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use arrow_array::{
+    ArrayRef, Decimal128Array, RecordBatch, StringArray, TimestampNanosecondArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rocket::Request;
+use rocket::http::{ContentType, Header};
+use rocket::response::{Responder, Response};
+use std::io::Cursor;
+use std::sync::Arc;
+
+use crate::cache::{FtMetadataCache, StakingPoolCache};
+use crate::filters::{FilteredProposals, proposal_categories};
+use crate::parsed_info::parse_proposal;
+use crate::rpc_client::RpcPool;
+use crate::scraper::Proposal;
+
+// Amounts up to 38 digits fit a Decimal128, larger ones are written as null
+const AMOUNT_PRECISION: u8 = 38;
+
+/// Parquet file answered as an attachment
+pub struct ParquetFile {
+    pub bytes: Vec<u8>,
+    pub filename: String,
+}
+
+impl<'r> Responder<'r, 'static> for ParquetFile {
+    fn respond_to(self, _req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        Response::build()
+            .header(ContentType::new("application", "vnd.apache.parquet"))
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.filename),
+            ))
+            .sized_body(self.bytes.len(), Cursor::new(self.bytes))
+            .ok()
+    }
+}
+
+struct ProposalRow {
+    id: u64,
+    proposer: String,
+    status: &'static str,
+    submission_ts: i64,
+    category: Option<&'static str>,
+    token: Option<String>,
+    amount_raw: Option<i128>,
+    amount_normalized: Option<String>,
+}
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("proposer", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new(
+            "submission_ts",
+            DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("category", DataType::Utf8, true),
+        Field::new("token", DataType::Utf8, true),
+        Field::new(
+            "amount_raw",
+            DataType::Decimal128(AMOUNT_PRECISION, 0),
+            true,
+        ),
+        // A decimal string, the scale depends on the token and a float would round it
+        Field::new("amount_normalized", DataType::Utf8, true),
+    ])
+}
+
+// Token and amount of the payment, stake or swap a proposal makes, NEAR written as
+// "near". Normalized amounts are only known when the token's decimals could be resolved.
+async fn proposal_row(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    staking_pool_cache: &StakingPoolCache,
+    proposal: &Proposal,
+) -> ProposalRow {
+    let parsed = parse_proposal(
+        client,
+        ft_metadata_cache,
+        staking_pool_cache,
+        proposal,
+        true,
+    )
+    .await;
+    let (token, amount, amount_normalized) = if let Some(payment) = parsed.payment_info {
+        (
+            Some(payment.token),
+            Some(payment.amount),
+            payment.amount_normalized,
+        )
+    } else if let Some(stake) = parsed.stake_info {
+        (
+            Some(stake.token),
+            Some(stake.amount),
+            stake.amount_normalized,
+        )
+    } else if let Some(exchange) = parsed.asset_exchange_info.filter(|e| e.info.on_chain) {
+        (
+            exchange.info.token_in,
+            exchange.info.amount_in,
+            exchange.amount_in_normalized,
+        )
+    } else {
+        (None, None, None)
+    };
+
+    ProposalRow {
+        id: proposal.id,
+        proposer: proposal.proposer.clone(),
+        status: proposal.current_status().as_str(),
+        submission_ts: proposal.submission_time.0 as i64,
+        category: proposal_categories(proposal).first().copied(),
+        token: token.map(|token| {
+            if token.is_empty() {
+                "near".to_string()
+            } else {
+                token
+            }
+        }),
+        amount_raw: amount
+            .and_then(|amount| amount.parse::<i128>().ok())
+            .filter(|amount| amount.unsigned_abs() < 10u128.pow(AMOUNT_PRECISION as u32)),
+        amount_normalized,
+    }
+}
+
+/// Proposals as a Parquet file with one typed row each, for loading into DuckDB or pandas
+pub async fn proposals_parquet(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    staking_pool_cache: &StakingPoolCache,
+    proposals: &FilteredProposals,
+) -> Result<Vec<u8>> {
+    let mut rows = Vec::with_capacity(proposals.len());
+    for proposal in proposals.iter() {
+        rows.push(proposal_row(client, ft_metadata_cache, staking_pool_cache, proposal).await);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(rows.iter().map(|row| row.id))),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|row| row.proposer.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|row| row.status),
+        )),
+        Arc::new(
+            TimestampNanosecondArray::from_iter_values(rows.iter().map(|row| row.submission_ts))
+                .with_timezone("UTC"),
+        ),
+        Arc::new(StringArray::from_iter(rows.iter().map(|row| row.category))),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|row| row.token.as_deref()),
+        )),
+        Arc::new(
+            Decimal128Array::from_iter(rows.iter().map(|row| row.amount_raw))
+                .with_precision_and_scale(AMOUNT_PRECISION, 0)?,
+        ),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|row| row.amount_normalized.as_deref()),
+        )),
+    ];
+    let batch = RecordBatch::try_new(Arc::new(schema()), columns)?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut bytes = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut bytes, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(bytes)
+}
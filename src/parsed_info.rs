@@ -0,0 +1,194 @@
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::amounts::format_units;
+use crate::cache::{FtMetadataCache, StakingPoolCache, get_ft_metadata_cache};
+use crate::rpc_client::RpcPool;
+use crate::scraper::{
+    AssetExchangeInfo, FtMetadata, GovernanceOpsInfo, LockupDetails, LockupInfo, PaymentInfo,
+    Proposal, ProposalType, StakeDelegationInfo,
+};
+
+/// Category details of a proposal, added with include_parsed=true. Amounts are in the
+/// smallest unit of the token, `decimals` tells how to convert them. With
+/// normalize_amounts=true the `*_normalized` fields hold them in whole tokens.
+#[derive(Serialize, Default, Debug, ToSchema)]
+pub struct ParsedInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_info: Option<ParsedPayment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake_info: Option<ParsedStake>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lockup_info: Option<LockupDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_exchange_info: Option<ParsedAssetExchange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub governance_ops_info: Option<GovernanceOpsInfo>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ParsedPayment {
+    pub receiver: String,
+    /// Token id as in the proposal, empty for NEAR
+    pub token: String,
+    /// Null when the token metadata couldn't be resolved
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_normalized: Option<String>,
+    pub is_lockup: bool,
+    pub msg: Option<String>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ParsedStake {
+    /// stake, unstake, withdraw or whitelist
+    pub proposal_type: String,
+    /// Staking pool, resolved through the lockup contract for lockup proposals
+    pub validator: String,
+    /// Lockup contract the proposal calls, if any
+    pub lockup_account: Option<String>,
+    /// Token the amount is in, empty for NEAR
+    pub token: String,
+    /// Liquid staking contract, also the token issued for the stake
+    pub pool_token: Option<String>,
+    pub symbol: Option<String>,
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_normalized: Option<String>,
+    /// Decimals of `token`, null when its metadata couldn't be resolved. Used to be
+    /// always 24, before stakes could be in other tokens than NEAR.
+    pub decimals: Option<u8>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ParsedAssetExchange {
+    #[serde(flatten)]
+    pub info: AssetExchangeInfo,
+    pub token_in_symbol: Option<String>,
+    pub token_in_decimals: Option<u8>,
+    pub token_out_symbol: Option<String>,
+    pub token_out_decimals: Option<u8>,
+    /// Only for amounts decoded from the swap args
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_in_normalized: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount_out_normalized: Option<String>,
+}
+
+// Whole token amount, None when the amount isn't a raw integer or the decimals are unknown
+fn normalize(amount: &str, decimals: Option<u8>) -> Option<String> {
+    Some(format_units(amount.parse().ok()?, decimals?))
+}
+
+async fn token_metadata(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    token: &str,
+) -> Option<FtMetadata> {
+    match get_ft_metadata_cache(client, ft_metadata_cache, token).await {
+        Ok(metadata) => Some(metadata),
+        Err(e) => {
+            warn!(token, error = %e, "Error fetching ft metadata");
+            None
+        }
+    }
+}
+
+/// Extracts the category details of a proposal and resolves token symbols and decimals
+pub async fn parse_proposal(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    staking_pool_cache: &StakingPoolCache,
+    proposal: &Proposal,
+    normalize_amounts: bool,
+) -> ParsedInfo {
+    let mut parsed = ParsedInfo::default();
+
+    if let Some(payment) = PaymentInfo::from_proposal(proposal) {
+        let metadata = token_metadata(client, ft_metadata_cache, &payment.token).await;
+        let decimals = metadata.as_ref().map(|metadata| metadata.decimals);
+        parsed.payment_info = Some(ParsedPayment {
+            symbol: metadata.map(|metadata| metadata.symbol),
+            decimals,
+            amount_normalized: normalize_amounts
+                .then(|| normalize(&payment.amount, decimals))
+                .flatten(),
+            receiver: payment.receiver,
+            token: payment.token,
+            amount: payment.amount,
+            is_lockup: payment.is_lockup,
+            msg: payment.msg,
+        });
+    }
+
+    if let Some(stake) = StakeDelegationInfo::from_proposal(proposal) {
+        // Same resolution as the validators filter
+        let validator = staking_pool_cache
+            .resolve_validator(client, &stake)
+            .await
+            .unwrap_or_default();
+        let lockup_account = stake.lockup_account().map(str::to_string);
+        let metadata = token_metadata(client, ft_metadata_cache, &stake.token).await;
+        let decimals = metadata.as_ref().map(|metadata| metadata.decimals);
+        parsed.stake_info = Some(ParsedStake {
+            proposal_type: stake.proposal_type,
+            validator,
+            lockup_account,
+            symbol: metadata.map(|metadata| metadata.symbol),
+            amount_normalized: normalize_amounts
+                .then(|| normalize(&stake.amount, decimals))
+                .flatten(),
+            token: stake.token,
+            pool_token: stake.pool_token,
+            amount: stake.amount,
+            decimals,
+        });
+    }
+
+    if LockupInfo::from_proposal(proposal).is_some() {
+        parsed.lockup_info = Some(LockupDetails::from_proposal(proposal));
+    }
+
+    if let Some(info) = AssetExchangeInfo::from_proposal(proposal) {
+        let token_in = match &info.token_in {
+            Some(token) => token_metadata(client, ft_metadata_cache, token).await,
+            None => None,
+        };
+        let token_out = match &info.token_out {
+            Some(token) => token_metadata(client, ft_metadata_cache, token).await,
+            None => None,
+        };
+        let token_in_decimals = token_in.as_ref().map(|metadata| metadata.decimals);
+        let token_out_decimals = token_out.as_ref().map(|metadata| metadata.decimals);
+        let (amount_in_normalized, min_amount_out_normalized) =
+            if normalize_amounts && info.on_chain {
+                (
+                    info.amount_in
+                        .as_deref()
+                        .and_then(|amount| normalize(amount, token_in_decimals)),
+                    info.min_amount_out
+                        .as_deref()
+                        .and_then(|amount| normalize(amount, token_out_decimals)),
+                )
+            } else {
+                (None, None)
+            };
+        parsed.asset_exchange_info = Some(ParsedAssetExchange {
+            info,
+            token_in_symbol: token_in.map(|metadata| metadata.symbol),
+            token_in_decimals,
+            token_out_symbol: token_out.map(|metadata| metadata.symbol),
+            token_out_decimals,
+            amount_in_normalized,
+            min_amount_out_normalized,
+        });
+    }
+
+    parsed.governance_ops_info = GovernanceOpsInfo::from_proposal(proposal);
+
+    parsed
+}
@@ -1,47 +1,266 @@
-use crate::cache::{CachedProposal, ProposalCache};
-use anyhow::Result;
+use crate::cache::{
+    CachedLookup, CachedProposal, CachedProposals, LockupCache, LookupCache, ProposalCache,
+    ProposalStore, StakingPoolCache, ft_metadata_failures, restore_ft_metadata_failures,
+    spawn_restored_refresh,
+};
+use crate::config::get_config;
+use crate::rpc_client;
+use crate::scraper::{TxMetadata, TxStatus};
+use anyhow::{Result, bail};
+use borsh::{BorshDeserialize, BorshSerialize};
+use dashmap::mapref::multiple::RefMulti;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::AccountId;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::{Orbit, Rocket};
-use std::collections::HashMap;
-use std::env;
-use std::fs::File;
-use std::io::{Read, Write};
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{ErrorKind, Read};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+type ProposalCacheMap = HashMap<(String, u64), CachedProposal>;
+type ProposalStoreMap = HashMap<String, CachedProposals>;
+type LookupMap = HashMap<String, CachedLookup>;
+// Failed lookups with the unix time of the failure
+type FailureMap = HashMap<String, u64>;
+
+// Files start with MAGIC and the schema version as a little-endian u32. Files written
+// before the header was introduced are version 1.
+const MAGIC: &[u8; 4] = b"SDCC";
+// Bump when the Borsh layout of CacheSnapshot or anything inside it changes, and add a
+// migration from the previous version to decode_snapshot
+const SCHEMA_VERSION: u32 = 2;
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+struct CacheSnapshot {
+    proposal_cache: ProposalCacheMap,
+    store: ProposalStoreMap,
+    staking_pools: LookupMap,
+    lockups: LookupMap,
+    ft_metadata_failures: FailureMap,
+}
+
+// Version 1 transaction logs lack the receipt outcomes, restored entries keep their calls
+// with an unknown outcome
+#[derive(BorshDeserialize)]
+struct TxMetadataV1 {
+    signer_id: AccountId,
+    predecessor_id: AccountId,
+    reciept_hash: CryptoHash,
+    block_height: u64,
+    timestamp: u64,
+}
+
+impl From<TxMetadataV1> for TxMetadata {
+    fn from(tx: TxMetadataV1) -> Self {
+        TxMetadata {
+            signer_id: tx.signer_id,
+            predecessor_id: tx.predecessor_id,
+            reciept_hash: tx.reciept_hash,
+            block_height: tx.block_height,
+            timestamp: tx.timestamp,
+            status: TxStatus::Unknown,
+            gas_burnt: 0,
+            actions: None,
+        }
+    }
+}
+
+#[derive(BorshDeserialize)]
+struct CachedProposalV1 {
+    txs_log: Vec<TxMetadataV1>,
+}
+
+impl From<CachedProposalV1> for CachedProposal {
+    fn from(entry: CachedProposalV1) -> Self {
+        // Logs without a backfill block predate backfilling, so they are complete
+        let txs_log = entry.txs_log.into_iter().map(TxMetadata::from).collect();
+        CachedProposal::restored(txs_log, None)
+    }
+}
+
+/// Caches restored by read_cache_from_file
+pub struct RestoredCaches {
+    pub store: ProposalStore,
+    pub proposal_cache: ProposalCache,
+    pub staking_pool_cache: StakingPoolCache,
+    pub lockup_cache: LockupCache,
+}
 
 pub struct CachePersistence {
+    pub store: ProposalStore,
     pub proposal_cache: ProposalCache,
+    pub staking_pool_cache: StakingPoolCache,
+    pub lockup_cache: LockupCache,
 }
 
 pub fn get_file_path() -> String {
-    if env::var("FLY_APP_NAME").is_ok() {
-        "/data/cache.bin".to_string()
-    } else {
-        "./cache.bin".to_string()
-    }
+    get_config().cache.persistence_path()
 }
 #[rocket::async_trait]
 impl Fairing for CachePersistence {
     fn info(&self) -> Info {
         Info {
             name: "Cache Persistence",
-            kind: Kind::Shutdown,
+            kind: Kind::Liftoff | Kind::Shutdown,
         }
     }
 
+    async fn on_liftoff(&self, _rocket: &Rocket<Orbit>) {
+        spawn_restored_refresh(rpc_client::get_rpc_client(), self.store.clone());
+    }
+
     async fn on_shutdown(&self, _rocket: &Rocket<Orbit>) {
-        let cache = self.proposal_cache.read().unwrap();
-        let serialized = borsh::to_vec(&*cache).unwrap();
+        // Fixture data would overwrite the cache of real DAOs
+        if get_config().scraper.uses_fixtures() {
+            return;
+        }
+        let path = get_file_path();
+        match self.write_to(&path).await {
+            Ok(()) => info!(%path, daos = self.store.len(), "Saved caches to disk"),
+            Err(e) => error!(%path, error = %e, "Failed to save caches to disk"),
+        }
+    }
+}
+
+impl CachePersistence {
+    /// Writes the caches to `path` through `<path>.tmp`, so a crash while writing leaves
+    /// the previous file intact
+    pub async fn write_to(&self, path: &str) -> std::io::Result<()> {
+        let staking_pools = self.staking_pool_cache.0.entries().await;
+        let lockups = self.lockup_cache.0.entries().await;
+        let ft_metadata_failures: BTreeMap<_, _> = ft_metadata_failures().into_iter().collect();
+        // The entries lock their DashMap shards, they are dropped before writing
+        let serialized = {
+            let cache: Vec<_> = self.proposal_cache.iter().collect();
+            let store: Vec<_> = self.store.iter().collect();
+
+            let mut serialized = MAGIC.to_vec();
+            serialized.extend_from_slice(&SCHEMA_VERSION.to_le_bytes());
+            // Same layout as CacheSnapshot without cloning the caches, Borsh writes HashMaps
+            // sorted by key like these BTreeMaps
+            borsh::to_writer(
+                &mut serialized,
+                &(
+                    entries_by_key(&cache),
+                    entries_by_key(&store),
+                    &staking_pools,
+                    &lockups,
+                    &ft_metadata_failures,
+                ),
+            )?;
+            serialized
+        };
+
+        let tmp_path = format!("{path}.tmp");
+        tokio::fs::write(&tmp_path, serialized).await?;
+        tokio::fs::rename(&tmp_path, path).await
+    }
+}
+
+fn entries_by_key<'a, K: Ord + Hash, V>(
+    entries: &'a [RefMulti<'a, K, V>],
+) -> BTreeMap<&'a K, &'a V> {
+    entries
+        .iter()
+        .map(|entry| (entry.key(), entry.value()))
+        .collect()
+}
+
+// Version 1 only held the proposal cache, without a header
+fn migrate_v1(payload: &[u8]) -> Result<CacheSnapshot> {
+    let proposal_cache: HashMap<(String, u64), CachedProposalV1> = borsh::from_slice(payload)?;
+    Ok(CacheSnapshot {
+        proposal_cache: proposal_cache
+            .into_iter()
+            .map(|(key, entry)| (key, entry.into()))
+            .collect(),
+        ..CacheSnapshot::default()
+    })
+}
+
+fn decode_snapshot(serialized: &[u8]) -> Result<CacheSnapshot> {
+    let Some(rest) = serialized.strip_prefix(MAGIC) else {
+        return migrate_v1(serialized);
+    };
+    let Some((version, payload)) = rest.split_first_chunk::<4>() else {
+        bail!("Cache file header is truncated");
+    };
+
+    match u32::from_le_bytes(*version) {
+        SCHEMA_VERSION => Ok(borsh::from_slice(payload)?),
+        version if version > SCHEMA_VERSION => bail!(
+            "Cache file has schema version {version}, this build only reads up to {SCHEMA_VERSION}"
+        ),
+        version => bail!("Cache file has unknown schema version {version}"),
+    }
+}
+
+// Keeps a file that can't be read next to the original instead of overwriting it on
+// the next shutdown, so a newer build or a manual fix can still recover it
+fn set_aside(path: &str) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    let rejected = format!("{path}.{timestamp}.rejected");
+    match fs::rename(path, &rejected) {
+        Ok(()) => warn!(path = %rejected, "Moved unreadable cache file aside"),
+        Err(e) => error!(%path, error = %e, "Failed to move unreadable cache file aside"),
+    }
+}
 
-        let mut file = File::create(get_file_path()).expect("Failed to create a file.");
-        file.write_all(&serialized).expect("Failed write to file.");
+/// Restores the caches written on the last shutdown, see read_cache_file
+pub fn read_cache_from_file() -> Result<RestoredCaches> {
+    // Fixtures are served as recorded, without state from earlier runs
+    if get_config().scraper.uses_fixtures() {
+        return Ok(restored_caches(CacheSnapshot::default()));
     }
+    read_cache_file(&get_file_path())
 }
 
-pub fn read_cache_from_file() -> Result<ProposalCache> {
-    let mut file = File::open(get_file_path())?;
-    let mut serialized = Vec::new();
-    file.read_to_end(&mut serialized)?;
-    let map: HashMap<(String, u64), CachedProposal> = borsh::from_slice(&serialized)?;
+/// Restores the caches written to `path`. A missing file gives empty caches, a file that
+/// can't be decoded is moved aside and reported as an error.
+pub fn read_cache_file(path: &str) -> Result<RestoredCaches> {
+    let serialized = match File::open(path) {
+        Ok(mut file) => {
+            let mut serialized = Vec::new();
+            file.read_to_end(&mut serialized)?;
+            serialized
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            info!(%path, "No cache file, starting with empty caches");
+            Vec::new()
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let snapshot = if serialized.is_empty() {
+        CacheSnapshot::default()
+    } else {
+        decode_snapshot(&serialized).inspect_err(|e| {
+            error!(%path, error = %e, "Failed to decode cache file");
+            set_aside(path);
+        })?
+    };
+    info!(
+        daos = snapshot.store.len(),
+        proposals = snapshot.proposal_cache.len(),
+        lookups = snapshot.staking_pools.len() + snapshot.lockups.len(),
+        "Restored caches from disk"
+    );
+
+    Ok(restored_caches(snapshot))
+}
 
-    Ok(Arc::new(RwLock::new(map)))
+fn restored_caches(snapshot: CacheSnapshot) -> RestoredCaches {
+    restore_ft_metadata_failures(snapshot.ft_metadata_failures);
+    RestoredCaches {
+        store: Arc::new(snapshot.store.into_iter().collect()),
+        proposal_cache: Arc::new(snapshot.proposal_cache.into_iter().collect()),
+        staking_pool_cache: StakingPoolCache(LookupCache::from_entries(snapshot.staking_pools)),
+        lockup_cache: LockupCache(LookupCache::from_entries(snapshot.lockups)),
+    }
 }
@@ -0,0 +1,668 @@
+use near_sdk::json_types::{U64, U128};
+use rocket::serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use utoipa::ToSchema;
+
+use crate::rfc3339_utc;
+use crate::scraper::{Policy, Proposal, ProposalStatus, get_current_time_nanos};
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RoleKind {
+    /// Any account
+    Everyone,
+    /// Accounts holding at least this many delegated tokens
+    Member(U128),
+    /// Fixed set of accounts
+    Group(Vec<String>),
+}
+
+impl RoleKind {
+    /// Group members, empty for Everyone and token weighted roles
+    pub fn members(&self) -> &[String] {
+        match self {
+            RoleKind::Group(members) => members,
+            _ => &[],
+        }
+    }
+
+    /// Whether the account belongs to the role, None for Member roles since token
+    /// balances are not cached
+    pub fn contains(&self, account_id: &str) -> Option<bool> {
+        match self {
+            RoleKind::Everyone => Some(true),
+            RoleKind::Member(_) => None,
+            RoleKind::Group(members) => Some(members.iter().any(|m| m == account_id)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub enum WeightKind {
+    TokenWeight,
+    RoleWeight,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum WeightOrRatio {
+    Weight(U128),
+    Ratio(u64, u64),
+}
+
+impl WeightOrRatio {
+    // Same rounding as the Sputnik contract: a ratio needs strictly more than the fraction
+    pub fn to_weight(&self, total_weight: u128) -> u128 {
+        match self {
+            WeightOrRatio::Weight(weight) => weight.0.min(total_weight),
+            WeightOrRatio::Ratio(numerator, denominator) => (*numerator as u128 * total_weight)
+                .checked_div(*denominator as u128)
+                .map_or(total_weight, |weight| (weight + 1).min(total_weight)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct VotePolicy {
+    pub weight_kind: WeightKind,
+    #[schema(value_type = String)]
+    pub quorum: U128,
+    /// Either a weight as string or a [numerator, denominator] ratio
+    #[schema(value_type = Object)]
+    pub threshold: WeightOrRatio,
+}
+
+impl VotePolicy {
+    /// Short form for CSV cells, e.g. "RoleWeight 1/2, quorum 0"
+    pub fn describe(&self) -> String {
+        let threshold = match &self.threshold {
+            WeightOrRatio::Weight(weight) => weight.0.to_string(),
+            WeightOrRatio::Ratio(numerator, denominator) => {
+                format!("{}/{}", numerator, denominator)
+            }
+        };
+        format!(
+            "{:?} {}, quorum {}",
+            self.weight_kind, threshold, self.quorum.0
+        )
+    }
+}
+
+/// Bonds and periods set by a proposal, missing ones are left as they are
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PolicyParameters {
+    pub proposal_bond: Option<String>,
+    pub proposal_period: Option<U64>,
+    pub bounty_bond: Option<String>,
+    pub bounty_forgiveness_period: Option<U64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct RolePermission {
+    pub name: String,
+    /// "Everyone", {"Member": "<min balance>"} or {"Group": [accounts]}
+    #[schema(value_type = Object)]
+    pub kind: RoleKind,
+    /// Entries like "transfer:AddProposal", "*:VoteApprove" or "*:*"
+    pub permissions: Vec<String>,
+    /// Overrides of the default vote policy per proposal kind label
+    pub vote_policy: HashMap<String, VotePolicy>,
+}
+
+impl RolePermission {
+    pub fn allows(&self, label: &str, action: &str) -> bool {
+        self.permissions.iter().any(|permission| {
+            let Some((kind, permitted_action)) = permission.split_once(':') else {
+                return false;
+            };
+            (kind == "*" || kind == label)
+                && (permitted_action == "*" || permitted_action == action)
+        })
+    }
+
+    pub fn can_vote(&self, label: &str) -> bool {
+        ["VoteApprove", "VoteReject", "VoteRemove"]
+            .iter()
+            .any(|action| self.allows(label, action))
+    }
+}
+
+/// Voting progress of one role towards deciding a proposal
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct RoleVoteProgress {
+    pub role: String,
+    /// Missing when the threshold depends on token balances
+    #[schema(value_type = Option<String>)]
+    pub required_approvals: Option<U128>,
+    #[schema(value_type = Option<String>)]
+    pub required_rejections: Option<U128>,
+    #[schema(value_type = String)]
+    pub approvals: U128,
+    #[schema(value_type = String)]
+    pub rejections: U128,
+    #[schema(value_type = String)]
+    pub removals: U128,
+    /// Approvals relative to the required approvals, capped at 100
+    pub percent_complete: Option<f64>,
+}
+
+/// What one role still needs to approve a proposal
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct RoleOutlook {
+    pub role: String,
+    /// Missing when the threshold depends on token balances
+    #[schema(value_type = Option<String>)]
+    pub required_approvals: Option<U128>,
+    #[schema(value_type = String)]
+    pub approvals: U128,
+    #[schema(value_type = Option<String>)]
+    pub approvals_needed: Option<U128>,
+    /// Members allowed to approve who haven't voted, empty once voting is over and for
+    /// Everyone roles, which have no list of members
+    pub remaining_voters: Vec<String>,
+    /// Whether the remaining voters can still reach the threshold, always while voting
+    /// is open for Everyone roles and missing for other token weighted roles
+    pub can_pass: Option<bool>,
+}
+
+/// Chances of a proposal being approved given the votes cast so far
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ProposalOutlook {
+    pub proposal_id: u64,
+    pub status: String,
+    /// End of the voting period
+    pub expiry_date: String,
+    /// Seconds left in the voting period, 0 once it ended or the proposal was decided
+    pub seconds_remaining: u64,
+    /// Whether some role can still approve the proposal with the votes not cast yet.
+    /// Missing when only token weighted roles could tell.
+    pub can_pass: Option<bool>,
+    /// Fewest approvals that would approve the proposal, in a role that can still pass it
+    #[schema(value_type = Option<String>)]
+    pub approvals_needed: Option<U128>,
+    /// Members whose approval alone would approve the proposal, sorted
+    pub decisive_voters: Vec<String>,
+    pub roles: Vec<RoleOutlook>,
+}
+
+/// Actions a role permission can grant, as named by the contract
+pub const PROPOSAL_ACTIONS: [&str; 7] = [
+    "AddProposal",
+    "RemoveProposal",
+    "VoteApprove",
+    "VoteReject",
+    "VoteRemove",
+    "Finalize",
+    "MoveToHub",
+];
+
+// Proposal kind names and their policy labels
+const KIND_LABELS: [(&str, &str); 17] = [
+    ("ChangeConfig", "config"),
+    ("ChangePolicy", "policy"),
+    ("AddMemberToRole", "add_member_to_role"),
+    ("RemoveMemberFromRole", "remove_member_from_role"),
+    ("FunctionCall", "call"),
+    ("UpgradeSelf", "upgrade_self"),
+    ("UpgradeRemote", "upgrade_remote"),
+    ("Transfer", "transfer"),
+    ("SetStakingContract", "set_vote_token"),
+    ("AddBounty", "add_bounty"),
+    ("BountyDone", "bounty_done"),
+    ("Vote", "vote"),
+    ("FactoryInfoUpdate", "factory_info_update"),
+    ("ChangePolicyAddOrUpdateRole", "policy_add_or_update_role"),
+    ("ChangePolicyRemoveRole", "policy_remove_role"),
+    (
+        "ChangePolicyUpdateDefaultVotePolicy",
+        "policy_update_default_vote_policy",
+    ),
+    ("ChangePolicyUpdateParameters", "policy_update_parameters"),
+];
+
+/// Changes a policy proposal makes to the current policy
+#[derive(Serialize, Deserialize, Clone, Debug, Default, ToSchema)]
+pub struct PolicyDiff {
+    pub roles_added: Vec<RolePermission>,
+    pub roles_removed: Vec<String>,
+    /// Roles kept by the proposal whose kind, members, permissions or vote policies change
+    pub roles_changed: Vec<RoleDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_vote_policy: Option<VotePolicyChange>,
+    /// Bonds and periods, as strings
+    pub parameters: Vec<ParameterChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct RoleDiff {
+    pub role: String,
+    /// Only set when the role stops or starts being a group, or its minimum balance changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RoleKindChange>,
+    pub members_added: Vec<String>,
+    pub members_removed: Vec<String>,
+    pub permissions_added: Vec<String>,
+    pub permissions_removed: Vec<String>,
+    /// Vote policies per proposal kind label that are added, removed or changed
+    pub vote_policies: Vec<VotePolicyChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct RoleKindChange {
+    #[schema(value_type = Object)]
+    pub before: RoleKind,
+    #[schema(value_type = Object)]
+    pub after: RoleKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct VotePolicyChange {
+    /// Proposal kind label, missing for the default vote policy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Missing when the role had no vote policy for the label
+    pub before: Option<VotePolicy>,
+    /// Missing when the role's vote policy for the label is removed
+    pub after: Option<VotePolicy>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ParameterChange {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl RoleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.kind.is_none()
+            && self.members_added.is_empty()
+            && self.members_removed.is_empty()
+            && self.permissions_added.is_empty()
+            && self.permissions_removed.is_empty()
+            && self.vote_policies.is_empty()
+    }
+}
+
+impl PolicyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.roles_added.is_empty()
+            && self.roles_removed.is_empty()
+            && self.roles_changed.is_empty()
+            && self.default_vote_policy.is_none()
+            && self.parameters.is_empty()
+    }
+
+    /// One line per change, e.g. "council: +alice.near, -bob.near"
+    pub fn summary(&self) -> Vec<String> {
+        let mut changes: Vec<String> = self
+            .roles_added
+            .iter()
+            .map(|role| format!("added role {}", role.name))
+            .collect();
+
+        for role in &self.roles_changed {
+            if role.kind.is_some() {
+                changes.push(format!("{}: kind changed", role.role));
+            }
+            let member_changes: Vec<String> = role
+                .members_added
+                .iter()
+                .map(|m| format!("+{}", m))
+                .chain(role.members_removed.iter().map(|m| format!("-{}", m)))
+                .collect();
+            if !member_changes.is_empty() {
+                changes.push(format!("{}: {}", role.role, member_changes.join(", ")));
+            }
+            if !role.permissions_added.is_empty() || !role.permissions_removed.is_empty() {
+                changes.push(format!("{}: permissions changed", role.role));
+            }
+            if !role.vote_policies.is_empty() {
+                changes.push(format!("{}: vote policy changed", role.role));
+            }
+        }
+
+        changes.extend(
+            self.roles_removed
+                .iter()
+                .map(|role| format!("removed role {}", role)),
+        );
+        if self.default_vote_policy.is_some() {
+            changes.push("default vote policy changed".to_string());
+        }
+        changes.extend(self.parameters.iter().map(|parameter| {
+            format!(
+                "{}: {} -> {}",
+                parameter.name, parameter.before, parameter.after
+            )
+        }));
+        changes
+    }
+}
+
+/// Policy label of a proposal kind, as used in role permissions and vote policies
+pub fn proposal_kind_label(kind: &Value) -> Option<&'static str> {
+    let name = match kind {
+        Value::String(name) => name.as_str(),
+        Value::Object(map) => map.keys().next()?.as_str(),
+        _ => return None,
+    };
+    KIND_LABELS
+        .iter()
+        .find(|(kind_name, _)| *kind_name == name)
+        .map(|(_, label)| *label)
+}
+
+/// Policy label of a proposal kind given by name ("Transfer") or label ("transfer")
+pub fn parse_kind_label(kind: &str) -> Option<&'static str> {
+    KIND_LABELS
+        .iter()
+        .find(|(name, label)| *name == kind || *label == kind)
+        .map(|(_, label)| *label)
+}
+
+impl Policy {
+    /// Roles the account surely belongs to, without token weighted ones
+    pub fn roles_of<'a>(&'a self, account_id: &'a str) -> impl Iterator<Item = &'a RolePermission> {
+        self.roles
+            .iter()
+            .filter(move |role| role.kind.contains(account_id) == Some(true))
+    }
+
+    pub fn can_vote(&self, account_id: &str, label: &str) -> bool {
+        self.roles_of(account_id).any(|role| role.can_vote(label))
+    }
+
+    /// Whether any role of the account grants the action on proposals with this label,
+    /// None when only token weighted roles could
+    pub fn can_act(&self, account_id: &str, label: &str, action: &str) -> Option<bool> {
+        let mut allowed = Some(false);
+        for role in self.roles.iter().filter(|role| role.allows(label, action)) {
+            match role.kind.contains(account_id) {
+                Some(true) => return Some(true),
+                None => allowed = None,
+                Some(false) => {}
+            }
+        }
+        allowed
+    }
+
+    /// Vote policy a role uses for proposals with this label
+    pub fn vote_policy_for<'a>(&'a self, role: &'a RolePermission, label: &str) -> &'a VotePolicy {
+        role.vote_policy
+            .get(label)
+            .unwrap_or(&self.default_vote_policy)
+    }
+
+    /// Votes per role against the thresholds the contract uses to finalize the proposal
+    pub fn vote_progress(&self, proposal: &Proposal) -> Vec<RoleVoteProgress> {
+        let Some(label) = proposal_kind_label(&proposal.kind) else {
+            return vec![];
+        };
+
+        self.roles
+            .iter()
+            .filter(|role| role.can_vote(label))
+            .map(|role| {
+                let vote_policy = self.vote_policy_for(role, label);
+                let total_weight = match (&role.kind, &vote_policy.weight_kind) {
+                    (RoleKind::Group(members), WeightKind::RoleWeight) => {
+                        Some(members.len() as u128)
+                    }
+                    // Everyone, Member and token weighted roles use the total delegated supply
+                    _ => None,
+                };
+                let required = total_weight.map(|total| {
+                    vote_policy
+                        .threshold
+                        .to_weight(total)
+                        .max(vote_policy.quorum.0)
+                });
+
+                let counts = proposal.vote_counts.get(&role.name);
+                let count = |index: usize| counts.map(|c| c[index].value()).unwrap_or(0);
+                let (approvals, rejections, removals) = (count(0), count(1), count(2));
+
+                RoleVoteProgress {
+                    role: role.name.clone(),
+                    required_approvals: required.map(U128),
+                    required_rejections: required.map(U128),
+                    approvals: U128(approvals),
+                    rejections: U128(rejections),
+                    removals: U128(removals),
+                    percent_complete: required.map(|required| {
+                        if required == 0 {
+                            100.0
+                        } else {
+                            (approvals as f64 / required as f64 * 100.0).min(100.0)
+                        }
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Approvals still missing before the first role reaches its threshold, None when no
+    /// role has a threshold that can be computed
+    pub fn approvals_needed(&self, proposal: &Proposal) -> Option<u128> {
+        self.vote_progress(proposal)
+            .iter()
+            .filter_map(|progress| {
+                let required = progress.required_approvals?.0;
+                Some(required.saturating_sub(progress.approvals.0))
+            })
+            .min()
+    }
+
+    /// Approvals still needed per role, the members who could cast them and the time left
+    /// to do so. Every member of a role weighted group counts once, as in the contract.
+    pub fn outlook(&self, proposal: &Proposal) -> ProposalOutlook {
+        let open = *proposal.current_status() == ProposalStatus::InProgress;
+        let expiry = proposal.submission_time.0 + self.proposal_period.0;
+        let label = proposal_kind_label(&proposal.kind);
+
+        let roles: Vec<RoleOutlook> = self
+            .vote_progress(proposal)
+            .into_iter()
+            .map(|progress| {
+                // Votes count towards every role of the voter that allows the vote
+                let approvers = self
+                    .roles
+                    .iter()
+                    .find(|role| role.name == progress.role)
+                    .filter(|role| {
+                        open && label.is_some_and(|label| role.allows(label, "VoteApprove"))
+                    });
+                // Any account may still approve in an Everyone role, so whatever the
+                // threshold is, it can be reached
+                let unbounded = approvers.is_some_and(|role| role.kind == RoleKind::Everyone);
+                let remaining_voters: Vec<String> = approvers
+                    .map(|role| {
+                        role.kind
+                            .members()
+                            .iter()
+                            .filter(|member| !proposal.votes.contains_key(member.as_str()))
+                            .cloned()
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let approvals_needed = progress
+                    .required_approvals
+                    .map(|required| required.0.saturating_sub(progress.approvals.0));
+                let can_pass = match approvals_needed {
+                    _ if !open => Some(false),
+                    _ if unbounded => Some(true),
+                    Some(needed) => Some(needed <= remaining_voters.len() as u128),
+                    None => None,
+                };
+
+                RoleOutlook {
+                    role: progress.role,
+                    required_approvals: progress.required_approvals,
+                    approvals: progress.approvals,
+                    approvals_needed: approvals_needed.map(U128),
+                    remaining_voters,
+                    can_pass,
+                }
+            })
+            .collect();
+
+        let can_pass = if !open || roles.is_empty() {
+            Some(false)
+        } else if roles.iter().any(|role| role.can_pass == Some(true)) {
+            Some(true)
+        } else if roles.iter().any(|role| role.can_pass.is_none()) {
+            None
+        } else {
+            Some(false)
+        };
+        let decisive_voters: BTreeSet<&String> = roles
+            .iter()
+            .filter(|role| role.approvals_needed.is_some_and(|needed| needed.0 == 1))
+            .flat_map(|role| &role.remaining_voters)
+            .collect();
+        let seconds_remaining = if open {
+            expiry.saturating_sub(get_current_time_nanos().0) / NANOS_PER_SECOND
+        } else {
+            0
+        };
+
+        ProposalOutlook {
+            proposal_id: proposal.id,
+            status: proposal.current_status().as_str().to_string(),
+            expiry_date: rfc3339_utc(expiry),
+            seconds_remaining,
+            can_pass,
+            approvals_needed: roles
+                .iter()
+                .filter(|role| role.can_pass != Some(false))
+                .filter_map(|role| role.approvals_needed)
+                .min_by_key(|needed| needed.0),
+            decisive_voters: decisive_voters.into_iter().cloned().collect(),
+            roles,
+        }
+    }
+
+    /// Group members mapped to the names of their roles, sorted by account
+    pub fn members(&self) -> BTreeMap<String, Vec<String>> {
+        let mut members: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for role in &self.roles {
+            for member in role.kind.members() {
+                members
+                    .entry(member.clone())
+                    .or_default()
+                    .push(role.name.clone());
+            }
+        }
+        members
+    }
+}
+
+/// Strings in `to` that are not in `from`, in the order of `to`
+fn added(from: &[String], to: &[String]) -> Vec<String> {
+    to.iter().filter(|s| !from.contains(s)).cloned().collect()
+}
+
+fn diff_roles(current: &RolePermission, proposed: &RolePermission) -> RoleDiff {
+    let labels: BTreeSet<&String> = current
+        .vote_policy
+        .keys()
+        .chain(proposed.vote_policy.keys())
+        .collect();
+
+    RoleDiff {
+        role: proposed.name.clone(),
+        // Group member changes are listed one by one instead
+        kind: match (&current.kind, &proposed.kind) {
+            (RoleKind::Group(_), RoleKind::Group(_)) => None,
+            (before, after) if before != after => Some(RoleKindChange {
+                before: before.clone(),
+                after: after.clone(),
+            }),
+            _ => None,
+        },
+        members_added: added(current.kind.members(), proposed.kind.members()),
+        members_removed: added(proposed.kind.members(), current.kind.members()),
+        permissions_added: added(&current.permissions, &proposed.permissions),
+        permissions_removed: added(&proposed.permissions, &current.permissions),
+        vote_policies: labels
+            .into_iter()
+            .filter_map(|label| {
+                let before = current.vote_policy.get(label);
+                let after = proposed.vote_policy.get(label);
+                (before != after).then(|| VotePolicyChange {
+                    label: Some(label.clone()),
+                    before: before.cloned(),
+                    after: after.cloned(),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Differences between the current policy and the one a proposal would leave
+pub fn diff_policies(current: &Policy, proposed: &Policy) -> PolicyDiff {
+    let mut diff = PolicyDiff::default();
+
+    for role in &proposed.roles {
+        match current.roles.iter().find(|r| r.name == role.name) {
+            Some(existing) => {
+                let role_diff = diff_roles(existing, role);
+                if !role_diff.is_empty() {
+                    diff.roles_changed.push(role_diff);
+                }
+            }
+            None => diff.roles_added.push(role.clone()),
+        }
+    }
+    diff.roles_removed = current
+        .roles
+        .iter()
+        .filter(|role| !proposed.roles.iter().any(|r| r.name == role.name))
+        .map(|role| role.name.clone())
+        .collect();
+
+    if current.default_vote_policy != proposed.default_vote_policy {
+        diff.default_vote_policy = Some(VotePolicyChange {
+            label: None,
+            before: Some(current.default_vote_policy.clone()),
+            after: Some(proposed.default_vote_policy.clone()),
+        });
+    }
+
+    let parameters = [
+        (
+            "proposal_bond",
+            current.proposal_bond.clone(),
+            proposed.proposal_bond.clone(),
+        ),
+        (
+            "proposal_period",
+            current.proposal_period.0.to_string(),
+            proposed.proposal_period.0.to_string(),
+        ),
+        (
+            "bounty_bond",
+            current.bounty_bond.clone(),
+            proposed.bounty_bond.clone(),
+        ),
+        (
+            "bounty_forgiveness_period",
+            current.bounty_forgiveness_period.0.to_string(),
+            proposed.bounty_forgiveness_period.0.to_string(),
+        ),
+    ];
+    diff.parameters = parameters
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(name, before, after)| ParameterChange {
+            name: name.to_string(),
+            before,
+            after,
+        })
+        .collect();
+
+    diff
+}
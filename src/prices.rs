@@ -0,0 +1,103 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::cache::{FtMetadataCache, get_ft_metadata_cache};
+use crate::rpc_client::RpcPool;
+use crate::scraper::PaymentInfo;
+
+const REF_PRICES_URL: &str = "https://indexer.ref.finance/list-token-price";
+const PRICE_CACHE_LIFETIME: Duration = Duration::from_secs(60);
+// Ref Finance lists native NEAR under its wrapped token
+const WRAPPED_NEAR: &str = "wrap.near";
+
+pub type TokenPrices = Arc<HashMap<String, f64>>;
+
+pub struct CachedPrices {
+    pub prices: TokenPrices,
+    pub last_updated: Instant,
+}
+
+pub type PriceCache = Arc<RwLock<Option<CachedPrices>>>;
+
+#[derive(Deserialize)]
+struct RefTokenPrice {
+    price: String,
+}
+
+async fn fetch_token_prices() -> Result<HashMap<String, f64>> {
+    let body = reqwest::get(REF_PRICES_URL)
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let listed: HashMap<String, RefTokenPrice> = serde_json::from_str(&body)?;
+
+    Ok(listed
+        .into_iter()
+        .filter_map(|(token_id, token)| Some((token_id, token.price.parse::<f64>().ok()?)))
+        .collect())
+}
+
+/// USD prices keyed by token contract, refreshed at most once per minute
+pub async fn get_token_prices(cache: &PriceCache) -> Result<TokenPrices> {
+    {
+        let cache_read = match cache.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(cached) = cache_read.as_ref()
+            && cached.last_updated.elapsed() <= PRICE_CACHE_LIFETIME
+        {
+            return Ok(cached.prices.clone());
+        }
+    }
+
+    let prices: TokenPrices = Arc::new(fetch_token_prices().await?);
+
+    let mut cache_write = match cache.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *cache_write = Some(CachedPrices {
+        prices: prices.clone(),
+        last_updated: Instant::now(),
+    });
+
+    Ok(prices)
+}
+
+// Intents payments reference tokens as "nep141:<contract>"
+fn price_token_id(token: &str) -> &str {
+    let token = token.strip_prefix("nep141:").unwrap_or(token);
+    if token.is_empty() || token.eq_ignore_ascii_case("near") {
+        WRAPPED_NEAR
+    } else {
+        token
+    }
+}
+
+/// USD value of a payment at current prices, None when the token has no known price
+pub async fn payment_usd_value(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    prices: &TokenPrices,
+    info: &PaymentInfo,
+) -> Option<f64> {
+    let token_id = price_token_id(&info.token);
+    let price = prices.get(token_id)?;
+    let metadata_token = if token_id == WRAPPED_NEAR {
+        ""
+    } else {
+        token_id
+    };
+    let decimals = get_ft_metadata_cache(client, ft_metadata_cache, metadata_token)
+        .await
+        .ok()?
+        .decimals;
+    let amount = info.amount.parse::<f64>().ok()? / 10f64.powi(decimals as i32);
+
+    Some(amount * price)
+}
@@ -0,0 +1,828 @@
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use cron::Schedule;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, MultiPart, SinglePart};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use near_primitives::types::AccountId;
+use rocket::State;
+use rocket::futures::StreamExt;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rust_xlsxwriter::{ExcelDateTime, Format};
+use s3::creds::Credentials;
+use s3::{Bucket, Region};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, info, warn};
+
+use crate::auth::AdminAuth;
+use crate::cache::get_latest_dao_cache;
+use crate::config::get_config;
+use crate::csv_view::CsvDialect;
+use crate::filters::ProposalFilters;
+use crate::policy::proposal_kind_label;
+use crate::rpc_client;
+use crate::scraper::{Proposal, extract_from_description, get_current_time_nanos};
+use crate::{ExportCaches, ProposalExportOptions, proposal_records, rfc3339_utc};
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+const NANOS_PER_HOUR: u64 = 60 * 60 * 1_000_000_000;
+
+const MAX_TITLE_CHARS: usize = 120;
+
+// Dates in XLSX reports, unless the job sets options.date_format. Without a timezone
+// name, so the cells parse back into Excel dates.
+const XLSX_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const XLSX_DATE_CELL_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+
+// Excel keeps numbers as doubles, longer numbers would lose digits
+const MAX_XLSX_DIGITS: usize = 15;
+
+/// Registered report jobs, keyed by job id
+pub type ReportJobs = Arc<RwLock<BTreeMap<String, ReportJob>>>;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl ReportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "text/csv",
+            ReportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+}
+
+/// Proposals a report covers, submitted from the start of the previous period up to the
+/// start of the current one, in UTC
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl ReportPeriod {
+    /// First day of the previous period and first day of the current one. Weeks start
+    /// on Monday.
+    pub fn previous(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let start = match self {
+            ReportPeriod::Day => today,
+            ReportPeriod::Week => today - Days::new(today.weekday().num_days_from_monday().into()),
+            ReportPeriod::Month => today.with_day(1).unwrap_or(today),
+        };
+        let previous = match self {
+            ReportPeriod::Day => start - Days::new(1),
+            ReportPeriod::Week => start - Days::new(7),
+            ReportPeriod::Month => start - Months::new(1),
+        };
+        (previous, start)
+    }
+}
+
+/// Where a rendered report is delivered
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ReportDestination {
+    /// Uploaded as `<prefix><file name>`, credentials come from the AWS_* variables
+    S3 {
+        bucket: String,
+        region: String,
+        /// S3 compatible endpoint instead of AWS, e.g. a MinIO server
+        #[serde(default)]
+        endpoint: Option<String>,
+        #[serde(default)]
+        prefix: String,
+    },
+    /// POSTed as the request body, with the file name in Content-Disposition
+    Webhook { url: String },
+    /// Attached to an email sent through reports.smtp_url
+    Email { to: Vec<String> },
+    /// Slack incoming webhook, only for digests
+    Slack { url: String },
+}
+
+/// Turns a job into a digest of the open proposals whose voting period ends soon,
+/// sent as a message instead of a file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DigestOptions {
+    pub expiring_within_hours: u64,
+    /// Also send a digest when no proposal is about to expire
+    #[serde(default)]
+    pub send_empty: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Digest {
+    pub dao_id: String,
+    pub expiring_within_hours: u64,
+    pub proposals: Vec<DigestProposal>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DigestProposal {
+    pub id: u64,
+    pub title: String,
+    pub kind: String,
+    pub proposer: String,
+    pub expiry_date: String,
+    pub hours_left: u64,
+    /// Approvals still missing in the role closest to passing, None when it can't pass
+    /// by approvals
+    pub approvals_needed: Option<u64>,
+}
+
+impl Digest {
+    /// Plain text, also readable as Slack mrkdwn
+    fn text(&self) -> String {
+        let mut text = match self.proposals.len() {
+            0 => format!(
+                "No proposals of {} expire within {} hours.",
+                self.dao_id, self.expiring_within_hours
+            ),
+            1 => format!(
+                "1 proposal of {} expires within {} hours:",
+                self.dao_id, self.expiring_within_hours
+            ),
+            count => format!(
+                "{} proposals of {} expire within {} hours:",
+                count, self.dao_id, self.expiring_within_hours
+            ),
+        };
+        for proposal in &self.proposals {
+            text.push_str(&format!(
+                "\n• #{} {} ({} by {}), {}h left",
+                proposal.id, proposal.title, proposal.kind, proposal.proposer, proposal.hours_left
+            ));
+            if let Some(needed) = proposal.approvals_needed {
+                text.push_str(&format!(", {} more approvals needed", needed));
+            }
+        }
+        text
+    }
+}
+
+/// What a report job renders, when and where to
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportSpec {
+    /// Cron expression in UTC, five fields as in crontab or six with seconds
+    pub cron: String,
+    pub dao_id: String,
+    /// Send a digest of proposals about to expire instead of a report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<DigestOptions>,
+    /// Query string with the filter parameters of /csv/proposals, e.g.
+    /// "category=payments&statuses=Approved"
+    #[serde(default)]
+    pub filters: String,
+    #[serde(default)]
+    pub options: ProposalExportOptions,
+    #[serde(default)]
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub period: Option<ReportPeriod>,
+    pub destination: ReportDestination,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportJob {
+    #[serde(flatten)]
+    pub spec: ReportSpec,
+    /// Unix seconds
+    pub created_at: u64,
+    /// Unix seconds, None when the cron expression has no further runs
+    pub next_run_at: Option<u64>,
+    pub last_run_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReportEntry {
+    pub id: String,
+    #[serde(flatten)]
+    pub spec: ReportSpec,
+    pub next_run_date: Option<String>,
+    pub last_run_date: Option<String>,
+    /// Error of the last run, null when it succeeded
+    pub last_error: Option<String>,
+}
+
+impl ReportEntry {
+    fn new(id: &str, job: &ReportJob) -> Self {
+        ReportEntry {
+            id: id.to_string(),
+            spec: job.spec.clone(),
+            next_run_date: job
+                .next_run_at
+                .map(|secs| rfc3339_utc(secs * 1_000_000_000)),
+            last_run_date: job
+                .last_run_at
+                .map(|secs| rfc3339_utc(secs * 1_000_000_000)),
+            last_error: job.last_error.clone(),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(UPLOAD_TIMEOUT)
+            .build()
+            .expect("Failed to build the report HTTP client")
+    })
+}
+
+fn schedule(cron: &str) -> Result<Schedule> {
+    // The cron crate wants a seconds field in front of the crontab ones
+    let expression = if cron.split_whitespace().count() == 5 {
+        format!("0 {}", cron)
+    } else {
+        cron.to_string()
+    };
+    Schedule::from_str(&expression).map_err(|e| anyhow!("Invalid cron expression: {}", e))
+}
+
+/// First run of a cron expression strictly after `after`, in unix seconds. None when
+/// the expression is invalid or has no further runs.
+pub fn next_run(cron: &str, after: u64) -> Option<u64> {
+    let after = Utc.timestamp_opt(after as i64, 0).single()?;
+    let next = schedule(cron).ok()?.after(&after).next()?;
+    Some(next.timestamp() as u64)
+}
+
+/// Jobs from reports.path, empty when the file doesn't exist yet
+pub fn load_report_jobs() -> ReportJobs {
+    let path = get_config().reports.path();
+    let jobs = match fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!(%path, error = %e, "Ignoring unreadable report jobs file");
+            BTreeMap::new()
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => {
+            warn!(%path, error = %e, "Failed to read report jobs file");
+            BTreeMap::new()
+        }
+    };
+    Arc::new(RwLock::new(jobs))
+}
+
+// Serializes saves, so an older copy of the jobs can't overwrite a newer one
+static SAVE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// Written after every change, through a temporary file like the tracked DAOs. The jobs
+// are copied under the lock and written after releasing it.
+async fn save_report_jobs(jobs: &ReportJobs) {
+    let _saving = SAVE_LOCK.lock().await;
+    let jobs = match jobs.read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    let path = get_config().reports.path();
+    let tmp_path = format!("{}.tmp", path);
+    let written = async {
+        let json = serde_json::to_vec_pretty(&jobs).map_err(std::io::Error::other)?;
+        tokio::fs::write(&tmp_path, json).await?;
+        tokio::fs::rename(&tmp_path, &path).await
+    }
+    .await;
+    if let Err(e) = written {
+        error!(%path, error = %e, "Failed to save report jobs");
+    }
+}
+
+// The job's filters, with `overrides` replacing the parameters of the same name. Unlike
+// the query of /csv/proposals a bad value is an error, not ignored, so it fails the job
+// when it's registered.
+fn parse_filters(query: &str, overrides: &[(&str, String)]) -> Result<ProposalFilters> {
+    let mut parameters: Vec<String> = query
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|parameter| {
+            let name = parameter.split('=').next().unwrap_or_default();
+            !name.is_empty() && !overrides.iter().any(|(other, _)| *other == name)
+        })
+        .map(str::to_string)
+        .collect();
+    parameters.extend(
+        overrides
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value)),
+    );
+    serde_urlencoded::from_str(&parameters.join("&")).map_err(|e| anyhow!("Invalid filters: {}", e))
+}
+
+// Rejects jobs that could never run, filters that parse but are rejected by the export,
+// e.g. an unknown category, show up as last_error
+fn check_spec(spec: &ReportSpec) -> Result<()> {
+    spec.dao_id.parse::<AccountId>()?;
+    schedule(&spec.cron)?;
+    parse_filters(&spec.filters, &[])?;
+    match &spec.destination {
+        ReportDestination::Email { to } => {
+            let config = &get_config().reports;
+            if config.smtp_url.is_none() || config.email_from.is_none() {
+                bail!("Email reports need reports.smtp_url and reports.email_from");
+            }
+            if to.is_empty() {
+                bail!("Email reports need at least one recipient");
+            }
+        }
+        ReportDestination::Slack { .. } if spec.digest.is_none() => {
+            bail!("Slack only takes digests, reports are files");
+        }
+        ReportDestination::S3 { .. } if spec.digest.is_some() => {
+            bail!("Digests are messages, they can't be stored in S3");
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Plain decimals Excel can hold exactly, ids and raw amounts with more digits stay text
+fn xlsx_number(value: &str) -> Option<f64> {
+    let unsigned = value.strip_prefix('-').unwrap_or(value);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let digits = integer.len() + fraction.len();
+    let plain = !integer.is_empty()
+        && integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        && unsigned.contains('.') != fraction.is_empty()
+        && (integer == "0" || !integer.starts_with('0'));
+    if !plain || digits > MAX_XLSX_DIGITS {
+        return None;
+    }
+    value.parse().ok()
+}
+
+// Header row as text, then numbers, dates in `date_format` and text cells
+fn xlsx(records: &[Vec<String>], date_format: &str) -> Result<Vec<u8>> {
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let date_cell = Format::new().set_num_format(XLSX_DATE_CELL_FORMAT);
+    let sheet = workbook.add_worksheet();
+    for (row, record) in records.iter().enumerate() {
+        let row = row as u32;
+        for (column, value) in record.iter().enumerate() {
+            let column = column as u16;
+            if row == 0 {
+                sheet.write_string(row, column, value)?;
+            } else if let Some(number) = xlsx_number(value) {
+                sheet.write_number(row, column, number)?;
+            } else if let Ok(date) = NaiveDateTime::parse_from_str(value, date_format) {
+                let date = ExcelDateTime::from_timestamp(date.and_utc().timestamp())?;
+                sheet.write_datetime_with_format(row, column, &date, &date_cell)?;
+            } else {
+                sheet.write_string(row, column, value)?;
+            }
+        }
+    }
+    Ok(workbook.save_to_buffer()?)
+}
+
+// The file and its name, with the same rows as the /csv/proposals export
+async fn render(
+    spec: &ReportSpec,
+    caches: &ExportCaches,
+    now: DateTime<Utc>,
+) -> Result<(Vec<u8>, String)> {
+    let dao_id: AccountId = spec.dao_id.parse()?;
+    let period = spec.period.map(|period| {
+        let (from, to) = period.previous(now.date_naive());
+        [
+            ("created_date_from", from.to_string()),
+            ("created_date_to", to.to_string()),
+        ]
+    });
+    let filters = parse_filters(&spec.filters, period.as_ref().map_or(&[], |p| p.as_slice()))?;
+
+    let mut options = spec.options.clone();
+    if spec.format == ReportFormat::Xlsx && options.date_format.is_none() {
+        options.date_format = Some(XLSX_DATE_FORMAT.to_string());
+    }
+    let date_format = options.date_format.clone().unwrap_or_default();
+
+    let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "");
+    let records: Vec<Vec<String>> = proposal_records(
+        rpc_client::get_rpc_client(),
+        caches.clone(),
+        dao_id.clone(),
+        filters,
+        options,
+        life_time,
+    )
+    .await
+    .map_err(|e| anyhow!("{}", e))?
+    .collect()
+    .await;
+
+    let bytes = match spec.format {
+        ReportFormat::Csv => {
+            let dialect = CsvDialect::default();
+            records
+                .iter()
+                .map(|record| dialect.line(record))
+                .collect::<String>()
+                .into_bytes()
+        }
+        ReportFormat::Xlsx => xlsx(&records, &date_format)?,
+    };
+    let filename = format!(
+        "proposals_{}_{}.{}",
+        dao_id,
+        now.format("%Y-%m-%d"),
+        spec.format.extension()
+    );
+    Ok((bytes, filename))
+}
+
+async fn deliver(
+    destination: &ReportDestination,
+    bytes: Vec<u8>,
+    filename: &str,
+    content_type: &str,
+) -> Result<()> {
+    match destination {
+        ReportDestination::S3 {
+            bucket,
+            region,
+            endpoint,
+            prefix,
+        } => {
+            let region = match endpoint {
+                Some(endpoint) => Region::Custom {
+                    region: region.clone(),
+                    endpoint: endpoint.clone(),
+                },
+                None => region.parse()?,
+            };
+            let mut bucket = Bucket::new(bucket, region, Credentials::default()?)?;
+            // Other S3 servers rarely route buckets by host name
+            if endpoint.is_some() {
+                bucket = bucket.with_path_style();
+            }
+            let response = bucket
+                .put_object_with_content_type(
+                    format!("{}{}", prefix, filename),
+                    &bytes,
+                    content_type,
+                )
+                .await?;
+            if response.status_code() >= 300 {
+                bail!("S3 answered {} for {}", response.status_code(), filename);
+            }
+        }
+        ReportDestination::Webhook { url } => {
+            http_client()
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .header(
+                    reqwest::header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", filename),
+                )
+                .body(bytes)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        ReportDestination::Email { to } => {
+            let body = MultiPart::mixed()
+                .singlepart(SinglePart::plain(format!(
+                    "The report {} is attached.",
+                    filename
+                )))
+                .singlepart(
+                    Attachment::new(filename.to_string())
+                        .body(bytes, ContentType::parse(content_type)?),
+                );
+            send_email(to, format!("Report {}", filename), body).await?;
+        }
+        ReportDestination::Slack { .. } => bail!("Slack only takes digests"),
+    }
+    Ok(())
+}
+
+async fn send_email(to: &[String], subject: String, body: MultiPart) -> Result<()> {
+    let config = &get_config().reports;
+    let (Some(smtp_url), Some(from)) = (&config.smtp_url, &config.email_from) else {
+        bail!("Email reports need reports.smtp_url and reports.email_from");
+    };
+    let mut message = Message::builder().from(from.parse()?).subject(subject);
+    for recipient in to {
+        message = message.to(recipient.parse()?);
+    }
+    AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)?
+        .build()
+        .send(message.multipart(body)?)
+        .await?;
+    Ok(())
+}
+
+// Open proposals matching the job's filters whose voting period ends within the digest's
+// hours, soonest first
+async fn render_digest(
+    spec: &ReportSpec,
+    digest: &DigestOptions,
+    caches: &ExportCaches,
+) -> Result<Digest> {
+    let dao_id: AccountId = spec.dao_id.parse()?;
+    let filters = parse_filters(
+        &spec.filters,
+        &[("expiring_within", digest.expiring_within_hours.to_string())],
+    )?;
+
+    let client = rpc_client::get_rpc_client();
+    let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "");
+    let cached = get_latest_dao_cache(&client, &caches.store, &dao_id, life_time).await?;
+    let policy = cached.policy;
+    let filtered = filters
+        .filter_proposals_async(
+            &client,
+            &cached.proposals,
+            &policy,
+            &caches.ft_metadata_cache,
+            &caches.staking_pool_cache,
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    let mut proposals: Vec<&Proposal> = filtered.iter().collect();
+    proposals.sort_by_key(|proposal| proposal.submission_time.0);
+
+    let now = get_current_time_nanos().0;
+    let proposals = proposals
+        .iter()
+        .map(|proposal| {
+            let expiry = proposal.submission_time.0 + policy.proposal_period.0;
+            DigestProposal {
+                id: proposal.id,
+                title: proposal_title(proposal),
+                kind: proposal_kind_label(&proposal.kind)
+                    .unwrap_or("unknown")
+                    .to_string(),
+                proposer: proposal.proposer.clone(),
+                expiry_date: rfc3339_utc(expiry),
+                hours_left: expiry.saturating_sub(now) / NANOS_PER_HOUR,
+                approvals_needed: policy
+                    .approvals_needed(proposal)
+                    .map(|needed| needed as u64),
+            }
+        })
+        .collect();
+
+    Ok(Digest {
+        dao_id: dao_id.to_string(),
+        expiring_within_hours: digest.expiring_within_hours,
+        proposals,
+    })
+}
+
+// First line of the title, or of the description when there is none
+fn proposal_title(proposal: &Proposal) -> String {
+    let title = extract_from_description(&proposal.description, "title")
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| proposal.description.clone());
+    title
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_TITLE_CHARS)
+        .collect()
+}
+
+async fn deliver_digest(destination: &ReportDestination, digest: &Digest) -> Result<()> {
+    match destination {
+        ReportDestination::Slack { url } => {
+            http_client()
+                .post(url)
+                .json(&serde_json::json!({ "text": digest.text() }))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        ReportDestination::Webhook { url } => {
+            http_client()
+                .post(url)
+                .json(digest)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        ReportDestination::Email { to } => {
+            let subject = format!("Proposals of {} expiring soon", digest.dao_id);
+            let body = MultiPart::mixed().singlepart(SinglePart::plain(digest.text()));
+            send_email(to, subject, body).await?;
+        }
+        ReportDestination::S3 { .. } => bail!("Digests can't be stored in S3"),
+    }
+    Ok(())
+}
+
+async fn run_report(id: &str, spec: &ReportSpec, caches: &ExportCaches) -> Result<()> {
+    if let Some(options) = &spec.digest {
+        let digest = render_digest(spec, options, caches).await?;
+        let count = digest.proposals.len();
+        if count > 0 || options.send_empty {
+            deliver_digest(&spec.destination, &digest).await?;
+        }
+        info!(id, dao_id = %spec.dao_id, count, "Sent digest");
+        return Ok(());
+    }
+
+    let (bytes, filename) = render(spec, caches, Utc::now()).await?;
+    let size = bytes.len();
+    deliver(
+        &spec.destination,
+        bytes,
+        &filename,
+        spec.format.content_type(),
+    )
+    .await?;
+    info!(id, dao_id = %spec.dao_id, %filename, size, "Delivered report");
+    Ok(())
+}
+
+// Records the outcome of a run, unless the job was deleted meanwhile
+async fn record_run(jobs: &ReportJobs, id: &str, started_at: u64, result: &Result<()>) {
+    {
+        let mut jobs = match jobs.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let Some(job) = jobs.get_mut(id) else {
+            return;
+        };
+        job.last_run_at = Some(started_at);
+        job.last_error = match result {
+            Ok(()) => None,
+            Err(e) => {
+                warn!(id, dao_id = %job.spec.dao_id, error = ?e, "Report failed");
+                Some(e.to_string())
+            }
+        };
+    }
+    save_report_jobs(jobs).await;
+}
+
+/// Runs every job whose next run is due, checking every reports.check_interval_secs.
+/// Runs missed while the server was down happen once on startup.
+pub fn spawn_report_scheduler(jobs: ReportJobs, caches: ExportCaches) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(get_config().reports.check_interval());
+        loop {
+            interval.tick().await;
+            let now = unix_now();
+            // Due jobs move on to their next run before starting, so a slow run isn't
+            // started again by the next check
+            let due: Vec<(String, ReportSpec)> = {
+                let mut jobs = match jobs.write() {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                jobs.iter_mut()
+                    .filter(|(_, job)| job.next_run_at.is_some_and(|at| at <= now))
+                    .map(|(id, job)| {
+                        job.next_run_at = next_run(&job.spec.cron, now);
+                        (id.clone(), job.spec.clone())
+                    })
+                    .collect()
+            };
+            if !due.is_empty() {
+                save_report_jobs(&jobs).await;
+            }
+            for (id, spec) in due {
+                let (jobs, caches) = (jobs.clone(), caches.clone());
+                tokio::spawn(async move {
+                    let result = run_report(&id, &spec, &caches).await;
+                    record_run(&jobs, &id, now, &result).await;
+                });
+            }
+        }
+    });
+}
+
+#[post("/admin/reports", data = "<spec>")]
+pub async fn create_report(
+    _admin: AdminAuth,
+    spec: Json<ReportSpec>,
+    jobs: &State<ReportJobs>,
+) -> Result<Json<ReportEntry>, Status> {
+    let spec = spec.into_inner();
+    check_spec(&spec).map_err(|e| {
+        debug!(error = %e, "Invalid report job");
+        Status::BadRequest
+    })?;
+
+    let now = unix_now();
+    let job = ReportJob {
+        next_run_at: next_run(&spec.cron, now),
+        spec,
+        created_at: now,
+        last_run_at: None,
+        last_error: None,
+    };
+    let id = format!("{:016x}", rand::random::<u64>());
+    let entry = ReportEntry::new(&id, &job);
+
+    match jobs.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+    .insert(id.clone(), job);
+    save_report_jobs(jobs).await;
+    info!(%id, dao_id = %entry.spec.dao_id, cron = %entry.spec.cron, "Registered report");
+
+    Ok(Json(entry))
+}
+
+#[get("/admin/reports")]
+pub fn get_reports(_admin: AdminAuth, jobs: &State<ReportJobs>) -> Json<Vec<ReportEntry>> {
+    Json(
+        match jobs.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+        .iter()
+        .map(|(id, job)| ReportEntry::new(id, job))
+        .collect(),
+    )
+}
+
+#[delete("/admin/reports/<id>")]
+pub async fn delete_report(
+    _admin: AdminAuth,
+    id: &str,
+    jobs: &State<ReportJobs>,
+) -> Result<Json<ReportEntry>, Status> {
+    let job = match jobs.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+    .remove(id)
+    .ok_or(Status::NotFound)?;
+    save_report_jobs(jobs).await;
+    info!(%id, "Deleted report");
+
+    Ok(Json(ReportEntry::new(id, &job)))
+}
+
+/// Renders and delivers a report right away, without moving its next scheduled run
+#[post("/admin/reports/<id>/run")]
+pub async fn run_report_now(
+    _admin: AdminAuth,
+    id: &str,
+    jobs: &State<ReportJobs>,
+    caches: &State<ExportCaches>,
+) -> Result<Json<ReportEntry>, Status> {
+    let spec = match jobs.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+    .get(id)
+    .map(|job| job.spec.clone())
+    .ok_or(Status::NotFound)?;
+
+    let started_at = unix_now();
+    let result = run_report(id, &spec, caches).await;
+    record_run(jobs, id, started_at, &result).await;
+
+    let jobs = match jobs.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    jobs.get(id)
+        .map(|job| Json(ReportEntry::new(id, job)))
+        .ok_or(Status::NotFound)
+}
@@ -1,45 +1,502 @@
+use anyhow::Result;
 use hex;
+use near_jsonrpc_client::errors::{
+    JsonRpcError, JsonRpcServerError, JsonRpcServerResponseStatusError,
+};
+use near_jsonrpc_client::methods::RpcMethod;
 use near_jsonrpc_client::methods::query::RpcQueryRequest;
-use near_jsonrpc_client::{JsonRpcClient, methods};
-use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_jsonrpc_client::{JsonRpcClient, MethodCallResult, methods};
+use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryError};
 use near_primitives::types::AccountId;
 use near_primitives::types::Finality;
 use near_primitives::types::FunctionArgs;
 use near_primitives::views::QueryRequest;
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use std::env as std_env;
+
+use crate::config::get_config;
+use std::collections::VecDeque;
 use std::sync::Arc;
-use std::sync::OnceLock;
-use std::time::Duration;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::time::timeout;
+use tracing::{debug, info, instrument, warn};
+
+static RPC_CLIENT: OnceLock<Arc<RpcPool>> = OnceLock::new();
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+// Weight of the newest sample in the moving average latency
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+#[derive(Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    unhealthy: bool,
+    latency: Option<Duration>,
+}
+
+struct RpcEndpoint {
+    url: String,
+    client: JsonRpcClient,
+    health: Mutex<EndpointHealth>,
+}
+
+impl RpcEndpoint {
+    fn health(&self) -> MutexGuard<'_, EndpointHealth> {
+        match self.health.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn record_success(&self, latency: Duration) {
+        let mut health = self.health();
+        if health.unhealthy {
+            info!(endpoint = %self.url, "RPC endpoint is healthy again");
+        }
+        health.consecutive_failures = 0;
+        health.unhealthy = false;
+        health.latency = Some(match health.latency {
+            Some(average) => {
+                average.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+            }
+            None => latency,
+        });
+    }
+
+    fn record_failure(&self, failure_threshold: u32) {
+        let mut health = self.health();
+        health.consecutive_failures += 1;
+        if !health.unhealthy && health.consecutive_failures >= failure_threshold {
+            warn!(
+                endpoint = %self.url,
+                failures = health.consecutive_failures,
+                "RPC endpoint marked unhealthy"
+            );
+            health.unhealthy = true;
+        }
+    }
+}
+
+#[derive(Default)]
+struct BreakerState {
+    // Most recent call outcomes, true for success
+    outcomes: VecDeque<bool>,
+    open_until: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Stops sending calls for a while once most recent calls failed, so requests fail
+/// fast instead of each waiting for timeouts. After the cool-down a single trial
+/// call decides whether to close again.
+struct CircuitBreaker {
+    state: Mutex<BreakerState>,
+    window: usize,
+    failure_rate: f64,
+    open_duration: Duration,
+}
+
+impl CircuitBreaker {
+    fn state(&self) -> MutexGuard<'_, BreakerState> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        let state = self.state();
+        state.trial_in_flight
+            || state
+                .open_until
+                .is_some_and(|open_until| Instant::now() < open_until)
+    }
+
+    // A permit for one call, None while the breaker is open
+    fn allow(&self) -> Option<BreakerPermit<'_>> {
+        let mut state = self.state();
+        let trial = match state.open_until {
+            None => false,
+            Some(open_until) if Instant::now() < open_until => return None,
+            // Cool-down over, let one call through
+            Some(_) if !state.trial_in_flight => {
+                state.trial_in_flight = true;
+                true
+            }
+            Some(_) => return None,
+        };
+        Some(BreakerPermit {
+            breaker: self,
+            trial,
+            recorded: false,
+        })
+    }
+
+    fn record(&self, trial: bool, success: bool) {
+        let mut state = self.state();
+        if trial {
+            state.trial_in_flight = false;
+            if success {
+                info!("RPC circuit breaker closed");
+                *state = BreakerState::default();
+            } else {
+                state.open_until = Some(Instant::now() + self.open_duration);
+            }
+            return;
+        }
+
+        state.outcomes.push_back(success);
+        if state.outcomes.len() > self.window {
+            state.outcomes.pop_front();
+        }
+        let failures = state.outcomes.iter().filter(|success| !**success).count();
+        if state.open_until.is_none()
+            && state.outcomes.len() == self.window
+            && failures as f64 >= self.window as f64 * self.failure_rate
+        {
+            warn!(failures, window = self.window, "RPC circuit breaker opened");
+            state.open_until = Some(Instant::now() + self.open_duration);
+        }
+    }
+}
+
+/// Outcome of a call let through by the breaker. A trial call dropped before its outcome
+/// is recorded, e.g. by a timeout or a disconnected client, counts as failed, so the
+/// breaker doesn't wait for it forever.
+struct BreakerPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+    recorded: bool,
+}
+
+impl BreakerPermit<'_> {
+    fn record(mut self, success: bool) {
+        self.recorded = true;
+        self.breaker.record(self.trial, success);
+    }
+}
+
+impl Drop for BreakerPermit<'_> {
+    fn drop(&mut self) {
+        if self.trial && !self.recorded {
+            warn!("RPC circuit breaker trial call was cancelled");
+            self.breaker.record(true, false);
+        }
+    }
+}
+
+fn circuit_open_error<E>() -> JsonRpcError<E> {
+    JsonRpcError::ServerError(JsonRpcServerError::InternalError {
+        info: Some("RPC circuit breaker is open".to_string()),
+    })
+}
+
+// Endpoints serving one kind of query, with their own circuit breaker so an archival
+// outage doesn't stop queries on the latest state
+struct EndpointGroup {
+    endpoints: Vec<RpcEndpoint>,
+    breaker: CircuitBreaker,
+}
+
+/// NEAR RPC client over several endpoints. Calls go to the healthy endpoint with the
+/// lowest latency and fail over to the next one when an endpoint is unreachable,
+/// rate limited or erroring. Historical queries made with `call_archival` go to the
+/// archival endpoints when there are any.
+pub struct RpcPool {
+    current: EndpointGroup,
+    archival: Option<EndpointGroup>,
+    failure_threshold: u32,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+// Errors that say something about the endpoint rather than the request
+fn is_endpoint_failure<E>(error: &JsonRpcError<E>) -> bool {
+    match error {
+        JsonRpcError::TransportError(_) => true,
+        JsonRpcError::ServerError(JsonRpcServerError::InternalError { .. }) => true,
+        JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(status)) => {
+            !matches!(status, JsonRpcServerResponseStatusError::BadRequest)
+        }
+        JsonRpcError::ServerError(_) => false,
+    }
+}
+
+// Errors worth retrying after a pause: the same request may succeed once the node
+// catches up or the rate limit resets
+fn is_transient<E>(error: &JsonRpcError<E>) -> bool {
+    match error {
+        JsonRpcError::TransportError(_) => true,
+        JsonRpcError::ServerError(JsonRpcServerError::InternalError { .. }) => true,
+        JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(status)) => matches!(
+            status,
+            JsonRpcServerResponseStatusError::TooManyRequests
+                | JsonRpcServerResponseStatusError::TimeoutError
+                | JsonRpcServerResponseStatusError::ServiceUnavailable
+        ),
+        JsonRpcError::ServerError(_) => false,
+    }
+}
+
+/// Whether a failed view call was rejected by the RPC rate limit after all retries
+pub fn is_rate_limited(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<JsonRpcError<RpcQueryError>>(),
+        Some(JsonRpcError::ServerError(
+            JsonRpcServerError::ResponseStatusError(
+                JsonRpcServerResponseStatusError::TooManyRequests
+            )
+        ))
+    )
+}
+
+/// Whether a failed get_proposal call means the DAO has no proposal with that id
+pub fn is_missing_proposal(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<JsonRpcError<RpcQueryError>>() {
+        Some(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcQueryError::ContractExecutionError { vm_error, .. },
+        ))) => vm_error.contains("ERR_NO_PROPOSAL"),
+        _ => false,
+    }
+}
+
+/// Whether a failed view call means the account doesn't exist, has no contract or
+/// its contract lacks the called method
+pub fn is_missing_contract(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<JsonRpcError<RpcQueryError>>() {
+        Some(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(query_error))) => {
+            match query_error {
+                RpcQueryError::UnknownAccount { .. } | RpcQueryError::NoContractCode { .. } => true,
+                RpcQueryError::ContractExecutionError { vm_error, .. } => {
+                    vm_error.contains("MethodNotFound")
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+impl EndpointGroup {
+    fn new(urls: &[String], api_key: Option<&str>) -> Self {
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                let mut client = JsonRpcClient::connect(url);
+                if let Some(key) = api_key {
+                    let headers = client.headers_mut();
+                    headers.insert(
+                        reqwest::header::AUTHORIZATION,
+                        reqwest::header::HeaderValue::from_str(key).unwrap(),
+                    );
+                }
+                RpcEndpoint {
+                    url: url.clone(),
+                    client,
+                    health: Mutex::new(EndpointHealth::default()),
+                }
+            })
+            .collect();
+        let rpc_config = &get_config().rpc;
+        EndpointGroup {
+            endpoints,
+            breaker: CircuitBreaker {
+                state: Mutex::new(BreakerState::default()),
+                window: rpc_config.breaker_window.max(1),
+                failure_rate: rpc_config.breaker_failure_rate,
+                open_duration: rpc_config.breaker_open(),
+            },
+        }
+    }
+
+    // Healthy endpoints first, fastest first, untried endpoints count as fastest.
+    // Unhealthy endpoints stay as a last resort.
+    fn ordered_endpoints(&self) -> Vec<&RpcEndpoint> {
+        let mut endpoints: Vec<&RpcEndpoint> = self.endpoints.iter().collect();
+        endpoints.sort_by_key(|endpoint| {
+            let health = endpoint.health();
+            (health.unhealthy, health.latency.unwrap_or_default())
+        });
+        endpoints
+    }
+}
+
+impl RpcPool {
+    /// Historical queries use the archival urls, or urls when archival_urls is empty.
+    /// Panics when urls is empty.
+    pub fn new(
+        urls: &[String],
+        archival_urls: &[String],
+        api_key: Option<&str>,
+        failure_threshold: u32,
+    ) -> Self {
+        assert!(!urls.is_empty(), "At least one RPC URL is required");
+        let rpc_config = &get_config().rpc;
+        RpcPool {
+            current: EndpointGroup::new(urls, api_key),
+            archival: (!archival_urls.is_empty())
+                .then(|| EndpointGroup::new(archival_urls, api_key)),
+            failure_threshold,
+            max_attempts: rpc_config.max_attempts.max(1),
+            initial_backoff: rpc_config.initial_backoff(),
+            max_backoff: rpc_config.max_backoff(),
+        }
+    }
+
+    /// False while the circuit breaker is open and calls fail without reaching RPC
+    pub fn is_available(&self) -> bool {
+        !self.current.breaker.is_open()
+    }
+
+    /// Same as JsonRpcClient::call, retried on the next endpoint on endpoint failures
+    /// and retried with exponential backoff while the error is transient
+    #[instrument(name = "rpc_call", skip_all, fields(method = method.method_name()))]
+    pub async fn call<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: RpcMethod,
+    {
+        self.call_group(&self.current, method).await
+    }
+
+    /// Same as call, for queries on past blocks, chunks and receipts that only archival
+    /// nodes keep
+    #[instrument(name = "rpc_call", skip_all, fields(method = method.method_name(), archival = true))]
+    pub async fn call_archival<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: RpcMethod,
+    {
+        self.call_group(self.archival.as_ref().unwrap_or(&self.current), method)
+            .await
+    }
 
-static RPC_CLIENT: OnceLock<Arc<JsonRpcClient>> = OnceLock::new();
+    async fn call_group<M>(
+        &self,
+        group: &EndpointGroup,
+        method: M,
+    ) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: RpcMethod,
+    {
+        let mut attempt = 1;
+        loop {
+            let Some(permit) = group.breaker.allow() else {
+                return Err(circuit_open_error());
+            };
+            let result = self.call_endpoints(group, &method).await;
+            permit.record(!matches!(&result, Err(e) if is_endpoint_failure(e)));
+
+            match result {
+                Err(e) if attempt < self.max_attempts && is_transient(&e) => {
+                    let delay = self.backoff(attempt);
+                    warn!(
+                        ?delay,
+                        attempt,
+                        max_attempts = self.max_attempts,
+                        "Transient RPC error, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn call_endpoints<M>(
+        &self,
+        group: &EndpointGroup,
+        method: &M,
+    ) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: RpcMethod,
+    {
+        let mut last_error = None;
+        for endpoint in group.ordered_endpoints() {
+            let started = Instant::now();
+            match endpoint.client.call(method).await {
+                Err(e) if is_endpoint_failure(&e) => {
+                    warn!(
+                        endpoint = %endpoint.url,
+                        elapsed_ms = started.elapsed().as_millis() as u64,
+                        "RPC call failed, trying the next endpoint"
+                    );
+                    endpoint.record_failure(self.failure_threshold);
+                    last_error = Some(e);
+                }
+                result => {
+                    debug!(
+                        endpoint = %endpoint.url,
+                        elapsed_ms = started.elapsed().as_millis() as u64,
+                        "RPC call finished"
+                    );
+                    endpoint.record_success(started.elapsed());
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("RpcPool has at least one endpoint"))
+    }
+
+    // Doubles per attempt up to max_backoff, randomized between half and the full
+    // delay so concurrent refreshes don't retry in lockstep
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff);
+        delay.mul_f64(0.5 + rand::random::<f64>() / 2.0)
+    }
+
+    /// Sends a status request to every unhealthy endpoint so recovered ones rejoin
+    /// the rotation
+    pub async fn probe_unhealthy(&self) {
+        let archival = self.archival.iter().flat_map(|group| &group.endpoints);
+        for endpoint in self.current.endpoints.iter().chain(archival) {
+            if !endpoint.health().unhealthy {
+                continue;
+            }
+            let started = Instant::now();
+            let request = methods::status::RpcStatusRequest;
+            if let Ok(Ok(_)) = timeout(PROBE_TIMEOUT, endpoint.client.call(request)).await {
+                endpoint.record_success(started.elapsed());
+            }
+        }
+    }
+}
 
 /// Returns a shared instance of the RPC client
-pub fn get_rpc_client() -> Arc<JsonRpcClient> {
+pub fn get_rpc_client() -> Arc<RpcPool> {
     RPC_CLIENT
         .get_or_init(|| {
-            dotenvy::dotenv().ok();
-            let rpc_url = std_env::var("NEAR_RPC_URL")
-                .unwrap_or("https://archival-rpc.mainnet.fastnear.com".to_string());
-            let mut client = JsonRpcClient::connect(rpc_url);
-            if let Some(key) = std_env::var("NEAR_FAST_API_KEY").ok() {
-                let headers = client.headers_mut();
-                headers.insert(
-                    reqwest::header::AUTHORIZATION,
-                    reqwest::header::HeaderValue::from_str(&key).unwrap(),
-                );
-            }
-            Arc::new(client)
+            let rpc_config = &get_config().rpc;
+            Arc::new(RpcPool::new(
+                &rpc_config.urls(),
+                &rpc_config.archival_urls,
+                rpc_config.api_key.as_deref(),
+                rpc_config.failure_threshold,
+            ))
         })
         .clone()
 }
 
-/// Check if a DAO has a lockup account
-pub async fn account_to_lockup(client: &JsonRpcClient, account_id: &str) -> Option<String> {
+/// Re-probes unhealthy endpoints of the shared client every probe_interval_secs
+pub fn spawn_health_probe() {
+    let client = get_rpc_client();
+    let interval = get_config().rpc.probe_interval();
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            client.probe_unhealthy().await;
+        }
+    });
+}
+
+/// Lockup account of an account, None when it has none. Errors other than a missing
+/// account are returned so callers don't mistake an RPC failure for "no lockup".
+pub async fn find_lockup_account(client: &RpcPool, account_id: &str) -> Result<Option<String>> {
     if account_id.is_empty() {
-        return None;
+        return Ok(None);
     }
 
     let mut hasher = Sha256::new();
@@ -54,34 +511,34 @@ pub async fn account_to_lockup(client: &JsonRpcClient, account_id: &str) -> Opti
     let request = methods::query::RpcQueryRequest {
         block_reference: near_primitives::types::Finality::Final.into(),
         request: QueryRequest::ViewAccount {
-            account_id: lockup_account.parse().ok()?,
+            account_id: lockup_account.parse()?,
         },
     };
 
     match timeout(Duration::from_secs(5), client.call(request)).await {
-        Ok(Ok(response)) => {
-            if let QueryResponseKind::ViewAccount(account_view) = response.kind {
-                if account_view.amount > 0 {
-                    return Some(lockup_account);
-                }
+        Ok(Ok(response)) => match response.kind {
+            QueryResponseKind::ViewAccount(account_view) if account_view.amount > 0 => {
+                Ok(Some(lockup_account))
+            }
+            _ => Ok(None),
+        },
+        Ok(Err(e)) => {
+            let e = anyhow::Error::from(e);
+            if is_missing_contract(&e) {
+                Ok(None)
+            } else {
+                Err(e)
             }
         }
-        Ok(Err(_)) => {
-            // Account doesn't exist or other error
-        }
-        Err(_) => {
-            // Timeout
-        }
+        Err(_) => Err(anyhow::anyhow!(
+            "Timed out looking up lockup account {}",
+            lockup_account
+        )),
     }
-
-    None
 }
 
 /// Fetch staking_pool_account_id from a lockup contract
-pub async fn get_staking_pool_account_id(
-    client: &JsonRpcClient,
-    lockup_account: &str,
-) -> Option<String> {
+pub async fn get_staking_pool_account_id(client: &RpcPool, lockup_account: &str) -> Option<String> {
     let request = RpcQueryRequest {
         block_reference: Finality::Final.into(),
         request: QueryRequest::CallFunction {
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use near_jsonrpc_client::{JsonRpcClient, methods};
+use near_jsonrpc_client::methods;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_primitives::hash::CryptoHash;
 use near_primitives::types::AccountId;
@@ -8,39 +8,84 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64::{Engine as _, engine::general_purpose};
 use borsh::{BorshDeserialize, BorshSerialize};
+use chrono::format::{Item, StrftimeItems};
 use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
 use futures::FutureExt;
 use futures::future::BoxFuture;
-
-use crate::cache::{FtMetadataCache, get_ft_metadata_cache};
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use crate::amounts::format_units_fixed;
+use crate::cache::{
+    FtMetadataCache, ProposalCache, StakingPoolCache, get_ft_metadata_cache,
+    get_latest_proposal_cache,
+};
+use crate::config::{BridgedTokenConfig, TxLogSource, get_config};
+use crate::csv_view::AccountingProfile;
+use crate::kind_decoder::parse_args;
+use crate::lake;
+use crate::policy::{
+    PolicyDiff, PolicyParameters, RoleKind, RolePermission, VotePolicy, diff_policies,
+};
+use crate::rpc_client::{RpcPool, is_rate_limited};
 use near_jsonrpc_client::methods::query::RpcQueryRequest;
-use near_primitives::views::{ActionView, ReceiptEnumView};
+use near_jsonrpc_primitives::types::receipts::ReceiptReference;
+use near_primitives::types::TransactionOrReceiptId;
+use near_primitives::views::{ActionView, ExecutionStatusView, ReceiptEnumView, ReceiptView};
 use near_primitives::{types::FunctionArgs, views::QueryRequest};
 use near_sdk::BlockHeight;
 use near_sdk::json_types::{U64, U128};
 use rocket::form::FromFormField;
-use rocket::futures::future::try_join_all;
+use rocket::futures::future::{join_all, try_join_all};
 use rocket::serde::{Deserialize, Serialize};
 
-use serde::de::DeserializeOwned;
 use serde_json::Value;
-use serde_json::from_slice;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, ToSchema)]
 pub struct TxMetadata {
+    #[schema(value_type = String)]
     pub signer_id: AccountId,
+    #[schema(value_type = String)]
     pub predecessor_id: AccountId,
+    #[schema(value_type = String)]
     pub reciept_hash: CryptoHash,
     pub block_height: BlockHeight,
     pub timestamp: u64,
+    pub status: TxStatus,
+    pub gas_burnt: u64,
+    /// Function calls of the receipt, kept from the block walk so they don't have to be
+    /// read back. Neither persisted nor served, restored logs look the receipts up again.
+    #[borsh(skip)]
+    #[serde(skip)]
+    pub actions: Option<Vec<ReceiptAction>>,
+}
+
+impl TxMetadata {
+    /// Whether the call took effect, failed calls didn't vote or create anything. Calls
+    /// with an unknown outcome are assumed to have succeeded.
+    pub fn succeeded(&self) -> bool {
+        self.status != TxStatus::Failed
+    }
+}
+
+/// Outcome of a receipt's execution
+#[derive(
+    Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, ToSchema,
+)]
+pub enum TxStatus {
+    Succeeded,
+    Failed,
+    /// The outcome couldn't be fetched, or the call was cached before outcomes were
+    Unknown,
 }
 
-const PROPOSAL_LIMIT: u64 = 500;
 const LOG_LIMIT: usize = 20;
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
 pub enum Vote {
     Approve,
     Reject,
@@ -57,6 +102,7 @@ pub enum Vote {
     Clone,
     PartialEq,
     Eq,
+    ToSchema,
 )]
 pub enum ProposalStatus {
     InProgress,
@@ -68,6 +114,20 @@ pub enum ProposalStatus {
     Failed,
 }
 
+impl ProposalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalStatus::InProgress => "InProgress",
+            ProposalStatus::Approved => "Approved",
+            ProposalStatus::Rejected => "Rejected",
+            ProposalStatus::Removed => "Removed",
+            ProposalStatus::Expired => "Expired",
+            ProposalStatus::Moved => "Moved",
+            ProposalStatus::Failed => "Failed",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub enum Action {
     AddProposal,
@@ -79,12 +139,13 @@ pub enum Action {
     MoveToHub,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, ToSchema)]
 pub struct ProposalLog {
+    #[schema(value_type = String)]
     pub block_height: U64,
 }
 
-#[derive(BorshDeserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug)]
 pub enum StateVersion {
     V1,
     V2,
@@ -98,29 +159,110 @@ pub enum CountsVersions {
     V2(U128),
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl CountsVersions {
+    pub fn value(&self) -> u128 {
+        match self {
+            CountsVersions::V1(count) => *count as u128,
+            CountsVersions::V2(count) => count.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
 pub struct Proposal {
     pub id: u64,
     pub proposer: String,
     pub description: String,
+    #[schema(value_type = Object)]
     pub kind: Value,
     pub status: ProposalStatus,
+    /// `status` with InProgress proposals past the voting period as Expired, set by the
+    /// cache since the contract only changes the status when someone acts on the proposal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_status: Option<ProposalStatus>,
+    /// Approve, reject and remove weight per role
+    #[schema(value_type = Object)]
     pub vote_counts: HashMap<String, [CountsVersions; 3]>,
     pub votes: HashMap<String, Vote>,
+    #[schema(value_type = String)]
     pub submission_time: U64,
     pub last_actions_log: Option<Vec<ProposalLog>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl Proposal {
+    /// Effective status once reconciled, the on-chain status before
+    pub fn current_status(&self) -> &ProposalStatus {
+        self.effective_status.as_ref().unwrap_or(&self.status)
+    }
+
+    /// Effective status as of the current time
+    pub fn reconciled_status(&self, proposal_period: u64) -> ProposalStatus {
+        match self.status {
+            ProposalStatus::InProgress
+                if self.submission_time.0 + proposal_period < get_current_time_nanos().0 =>
+            {
+                ProposalStatus::Expired
+            }
+            ref status => status.clone(),
+        }
+    }
+
+    /// Recomputes the effective status against the current time, returns whether it changed
+    pub fn reconcile_status(&mut self, proposal_period: u64) -> bool {
+        let effective = self.reconciled_status(proposal_period);
+        let changed = self.effective_status.as_ref() != Some(&effective);
+        self.effective_status = Some(effective);
+        changed
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
 pub struct Policy {
-    pub roles: Vec<Value>,
-    pub default_vote_policy: Value,
+    pub roles: Vec<RolePermission>,
+    pub default_vote_policy: VotePolicy,
     pub proposal_bond: String, // u128
+    #[schema(value_type = String)]
     pub proposal_period: U64,
     pub bounty_bond: String, //u128
+    #[schema(value_type = String)]
     pub bounty_forgiveness_period: U64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct DaoConfig {
+    pub name: String,
+    pub purpose: String,
+    pub metadata: String, // base64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Bounty {
+    pub id: u64,
+    pub description: String,
+    pub token: String,
+    pub amount: String, // u128
+    pub times: u32,
+    #[schema(value_type = String)]
+    pub max_deadline: U64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct BountyClaim {
+    pub bounty_id: u64,
+    #[schema(value_type = String)]
+    pub start_time: U64,
+    #[schema(value_type = String)]
+    pub deadline: U64,
+    pub completed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct AccountBountyClaim {
+    pub account_id: String,
+    #[serde(flatten)]
+    pub claim: BountyClaim,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ActionLog {
     pub account_id: AccountId,
@@ -129,6 +271,35 @@ pub struct ActionLog {
     pub block_height: U64,
 }
 
+/// Token of a payment as written in proposals
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenId {
+    /// Empty or "near"
+    Near,
+    /// A fungible token contract, also written "nep141:<contract>" by intents payments
+    Nep141(AccountId),
+    /// A token on another chain as "<chain>:<address>", e.g. "eth:0x..."
+    Bridged(String),
+}
+
+impl TokenId {
+    pub fn parse(token: &str) -> Result<Self> {
+        if token.is_empty() || token.eq_ignore_ascii_case("near") {
+            return Ok(TokenId::Near);
+        }
+        if let Some(contract) = token.strip_prefix("nep141:") {
+            return Ok(TokenId::Nep141(contract.parse()?));
+        }
+        match token.split_once(':') {
+            Some((chain, address)) if !chain.is_empty() && !address.is_empty() => {
+                Ok(TokenId::Bridged(token.to_string()))
+            }
+            Some(_) => Err(anyhow::anyhow!("Invalid token id {}", token)),
+            None => Ok(TokenId::Nep141(token.parse()?)),
+        }
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct FtMetadata {
     pub name: String,
@@ -151,10 +322,24 @@ impl FtMetadata {
         }
     }
 
-    pub fn empty() -> Self {
+    /// Metadata from a tokens.bridged or tokens.registry entry
+    pub fn bridged(token: &BridgedTokenConfig) -> Self {
+        FtMetadata {
+            name: token.name.clone().unwrap_or_else(|| token.symbol.clone()),
+            symbol: token.symbol.clone(),
+            decimals: token.decimals,
+            icon: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    /// Stands in for tokens without metadata, so exports name the token instead of
+    /// leaving the symbol blank. Amounts stay in the smallest unit.
+    pub fn unknown(token: &str) -> Self {
         FtMetadata {
             name: "".to_string(),
-            symbol: "".to_string(),
+            symbol: format!("UNKNOWN({})", token),
             decimals: 0,
             icon: None,
             reference: None,
@@ -163,12 +348,91 @@ impl FtMetadata {
     }
 }
 
-pub struct TransferProposalFormatter;
-pub struct LockupProposalFormatter;
-pub struct StakeDelegationProposalFormatter;
-pub struct AssetExchangeProposalFormatter;
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
+
+/// Timezone and strftime pattern used for dates in CSV exports
+#[derive(Clone, Debug)]
+pub struct DateFormat {
+    pub tz: Tz,
+    pub pattern: String,
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        DateFormat {
+            tz: Tz::UTC,
+            pattern: DEFAULT_DATE_FORMAT.to_string(),
+        }
+    }
+}
+
+impl DateFormat {
+    pub fn new(tz: Option<&str>, pattern: Option<&str>) -> Result<Self> {
+        let tz = match tz {
+            Some(tz) => tz
+                .parse::<Tz>()
+                .map_err(|_| anyhow::anyhow!("Unknown timezone: {}", tz))?,
+            None => Tz::UTC,
+        };
+        let pattern = pattern.unwrap_or(DEFAULT_DATE_FORMAT);
+        // chrono panics when displaying an invalid pattern, so reject it upfront
+        if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+            return Err(anyhow::anyhow!("Invalid date format: {}", pattern));
+        }
+
+        Ok(DateFormat {
+            tz,
+            pattern: pattern.to_string(),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct TransferProposalFormatter {
+    pub date_format: DateFormat,
+}
+#[derive(Default)]
+pub struct LockupProposalFormatter {
+    pub date_format: DateFormat,
+}
+#[derive(Default)]
+pub struct StakeDelegationProposalFormatter {
+    pub date_format: DateFormat,
+    /// Resolves the pool lockup proposals delegate to
+    pub staking_pool_cache: StakingPoolCache,
+}
+#[derive(Default)]
+pub struct AssetExchangeProposalFormatter {
+    pub date_format: DateFormat,
+}
+#[derive(Default)]
+pub struct MemberChangeProposalFormatter {
+    pub date_format: DateFormat,
+}
+/// Policy proposals with their diff against the current policy spread over columns.
+/// Approved proposals are part of the current policy already, so their change columns
+/// are empty.
+#[derive(Default)]
+pub struct PolicyChangeProposalFormatter {
+    pub date_format: DateFormat,
+}
+#[derive(Default)]
+pub struct GovernanceOpsProposalFormatter {
+    pub date_format: DateFormat,
+}
+/// Approved payments in the import layout of an accounting tool
+pub struct AccountingPaymentFormatter {
+    pub profile: AccountingProfile,
+    pub date_format: DateFormat,
+    pub dao_id: AccountId,
+    /// Transaction logs give the approval date
+    pub proposal_cache: ProposalCache,
+}
 pub struct StakeDelegationroposalFormatter;
-pub struct DefaultFormatter;
+#[derive(Default)]
+pub struct DefaultFormatter {
+    pub date_format: DateFormat,
+}
 
 #[derive(Deserialize, Debug)]
 struct VestingSchedule {
@@ -202,7 +466,7 @@ pub trait ProposalCsvFormatterAsync<T>: Send + Sync {
     fn headers(&self) -> Vec<&'static str>;
     fn format<'a>(
         &'a self,
-        client: &'a Arc<JsonRpcClient>,
+        client: &'a Arc<RpcPool>,
         ft_metadata_cache: &'a FtMetadataCache,
         proposal: &'a Proposal,
         policy: &'a Policy,
@@ -210,12 +474,8 @@ pub trait ProposalCsvFormatterAsync<T>: Send + Sync {
     ) -> BoxFuture<'a, Vec<String>>;
 }
 
-pub async fn fetch_proposals(
-    client: &JsonRpcClient,
-    dao_id: &AccountId,
-) -> anyhow::Result<Vec<Proposal>> {
-    // Get the last proposal ID
-    let last_id_request = methods::query::RpcQueryRequest {
+pub async fn fetch_last_proposal_id(client: &RpcPool, dao_id: &AccountId) -> anyhow::Result<u64> {
+    let request = methods::query::RpcQueryRequest {
         block_reference: near_primitives::types::Finality::Final.into(),
         request: QueryRequest::CallFunction {
             account_id: dao_id.clone(),
@@ -223,56 +483,262 @@ pub async fn fetch_proposals(
             args: FunctionArgs::from(vec![]),
         },
     };
-    let last_id_response = client.call(last_id_request).await?;
-    let last_id = if let QueryResponseKind::CallResult(result) = last_id_response.kind {
-        serde_json::from_slice::<u64>(&result.result)?
+    let response = client.call(request).await?;
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        Ok(serde_json::from_slice::<u64>(&result.result)?)
     } else {
-        return Err(anyhow::anyhow!("Failed to get last proposal ID"));
+        Err(anyhow::anyhow!("Failed to get last proposal ID"))
+    }
+}
+
+// Splits `from_index..to_index` into (from_index, limit) pages of one get_proposals call
+fn proposal_pages(from_index: u64, to_index: u64) -> Vec<(u64, u64)> {
+    let page_size = get_config().scraper.proposal_limit;
+    (from_index..to_index)
+        .step_by(page_size as usize)
+        .map(|from| (from, page_size.min(to_index - from)))
+        .collect()
+}
+
+async fn fetch_proposals_page(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    from_index: u64,
+    limit: u64,
+) -> anyhow::Result<Vec<Proposal>> {
+    let query_args = FunctionArgs::from(
+        json!({
+            "from_index": from_index,
+            "limit": limit
+        })
+        .to_string()
+        .into_bytes(),
+    );
+    let request = methods::query::RpcQueryRequest {
+        block_reference: near_primitives::types::Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: dao_id.clone(),
+            method_name: "get_proposals".to_string(),
+            args: query_args,
+        },
     };
 
-    let mut all_proposals = Vec::new();
-    let mut current_index = 0;
+    let response = client.call(request).await?;
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        Ok(serde_json::from_slice(&result.result)?)
+    } else {
+        Err(anyhow::anyhow!(
+            "Unexpected response kind while fetching proposals batch starting at index {}",
+            from_index
+        ))
+    }
+}
 
-    // Fetch proposals in batches
-    while current_index < last_id {
-        let limit = std::cmp::min(PROPOSAL_LIMIT, last_id - current_index);
+/// Fetches the given pages with up to `scraper.fetch_concurrency` calls in flight.
+/// Pages that still hit the RPC rate limit after retries are fetched again with half
+/// the concurrency. Returns the proposals sorted by id.
+async fn fetch_proposal_pages(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    mut pages: Vec<(u64, u64)>,
+) -> anyhow::Result<Vec<Proposal>> {
+    let mut all_proposals = Vec::new();
+    let mut concurrency = get_config().scraper.fetch_concurrency;
 
-        let query_args = FunctionArgs::from(
-            json!({
-                "from_index": current_index,
-                "limit": limit
+    while !pages.is_empty() {
+        let results: Vec<_> = stream::iter(pages)
+            .map(|(from_index, limit)| async move {
+                let result = fetch_proposals_page(client, dao_id, from_index, limit).await;
+                ((from_index, limit), result)
             })
-            .to_string()
-            .into_bytes(),
-        );
-        let request = methods::query::RpcQueryRequest {
-            block_reference: near_primitives::types::Finality::Final.into(),
-            request: QueryRequest::CallFunction {
-                account_id: dao_id.clone(),
-                method_name: "get_proposals".to_string(),
-                args: query_args,
-            },
-        };
-
-        let response = client.call(request).await?;
-        if let QueryResponseKind::CallResult(result) = response.kind {
-            let proposals_batch: Vec<Proposal> = serde_json::from_slice(&result.result)?;
-            all_proposals.extend(proposals_batch);
-            current_index += limit;
-        } else {
-            return Err(anyhow::anyhow!(
-                "Unexpected response kind while fetching proposals batch starting at index {}",
-                current_index
-            ));
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+
+        pages = Vec::new();
+        for (page, result) in results {
+            match result {
+                Ok(proposals) => all_proposals.extend(proposals),
+                Err(e) if concurrency > 1 && is_rate_limited(&e) => pages.push(page),
+                Err(e) => return Err(e),
+            }
+        }
+        if !pages.is_empty() {
+            concurrency /= 2;
+            warn!(
+                %dao_id,
+                pages = pages.len(),
+                concurrency,
+                "Rate limited while fetching proposals, retrying with lower concurrency"
+            );
         }
-        // Add a small delay to avoid hitting rate limits
-        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
     }
 
+    all_proposals.sort_by_key(|p| p.id);
     Ok(all_proposals)
 }
+
+/// Fetches proposals with ids in `from_index..to_index`.
+/// Removed proposals are skipped by the contract, so fewer may be returned.
+pub async fn fetch_proposals_range(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    from_index: u64,
+    to_index: u64,
+) -> anyhow::Result<Vec<Proposal>> {
+    fetch_proposal_pages(client, dao_id, proposal_pages(from_index, to_index)).await
+}
+
+pub async fn fetch_proposals(
+    client: &RpcPool,
+    dao_id: &AccountId,
+) -> anyhow::Result<Vec<Proposal>> {
+    let (proposals, _) = fetch_proposals_delta(client, dao_id, &[], 0).await?;
+    Ok(proposals)
+}
+
+// Groups ascending proposal ids into ranges that fit in one batch
+fn id_ranges(ids: impl Iterator<Item = u64>) -> Vec<(u64, u64)> {
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for id in ids {
+        match ranges.last_mut() {
+            Some((start, end)) if id < *start + get_config().scraper.proposal_limit => {
+                *end = (*end).max(id + 1);
+            }
+            _ => ranges.push((id, id + 1)),
+        }
+    }
+    ranges
+}
+
+fn in_progress_ranges(proposals: &[Proposal]) -> Vec<(u64, u64)> {
+    id_ranges(
+        proposals
+            .iter()
+            .filter(|p| p.status == ProposalStatus::InProgress)
+            .map(|p| p.id),
+    )
+}
+
+/// Block of the newest action recorded on the proposal itself
+pub fn last_action_block(proposal: &Proposal) -> Option<u64> {
+    proposal
+        .last_actions_log
+        .as_ref()
+        .and_then(|log| log.iter().map(|entry| entry.block_height.0).max())
+}
+
+/// Ids of proposals the DAO's actions log shows activity on since they were cached,
+/// sorted. None when the log can't account for every change: it is empty, it doesn't
+/// reach back to the newest action already cached, or InProgress proposals carry no
+/// action log of their own (v1 contracts).
+fn acted_proposal_ids(actions_log: &[ActionLog], cached: &[Proposal]) -> Option<Vec<u64>> {
+    if cached
+        .iter()
+        .any(|p| p.status == ProposalStatus::InProgress && p.last_actions_log.is_none())
+    {
+        return None;
+    }
+    let newest_cached = cached.iter().filter_map(last_action_block).max()?;
+    let oldest_logged = actions_log.iter().map(|a| a.block_height.0).min()?;
+    // Actions between the two may have dropped out of the log already
+    if oldest_logged > newest_cached {
+        return None;
+    }
+
+    let cached_by_id: HashMap<u64, &Proposal> = cached.iter().map(|p| (p.id, p)).collect();
+    let mut ids: Vec<u64> = actions_log
+        .iter()
+        .filter(|action| {
+            cached_by_id
+                .get(&action.proposal_id.0)
+                .is_none_or(|proposal| {
+                    last_action_block(proposal).is_none_or(|block| action.block_height.0 > block)
+                })
+        })
+        .map(|action| action.proposal_id.0)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    Some(ids)
+}
+
+/// Fetches only the proposals that could have changed since the last fetch:
+/// everything with id >= `last_proposal_id` plus the cached ones the DAO's actions log
+/// shows activity on. Without a usable actions log every proposal still InProgress in
+/// `cached` is refetched instead.
+/// Returns the merged proposal set sorted by id and the new last proposal id.
+pub async fn fetch_proposals_delta(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    cached: &[Proposal],
+    last_proposal_id: u64,
+) -> anyhow::Result<(Vec<Proposal>, u64)> {
+    let (last_id, actions_log) = if cached.is_empty() {
+        (fetch_last_proposal_id(client, dao_id).await?, None)
+    } else {
+        let (last_id, actions_log) = tokio::join!(
+            fetch_last_proposal_id(client, dao_id),
+            fetch_actions_log(client, dao_id)
+        );
+        (last_id?, actions_log)
+    };
+
+    let ranges = delta_ranges(cached, last_proposal_id, last_id, actions_log.as_deref());
+    let pages = ranges
+        .iter()
+        .flat_map(|(from_index, to_index)| proposal_pages(*from_index, *to_index))
+        .collect();
+    let fetched = fetch_proposal_pages(client, dao_id, pages).await?;
+
+    Ok((merge_delta(cached, &ranges, fetched), last_id))
+}
+
+/// Id ranges, end exclusive, of the proposals a delta fetch downloads again: the cached
+/// ones `actions_log` shows activity on, or every InProgress one without a usable log,
+/// and the new ones from `last_proposal_id` up to `last_id`
+pub(crate) fn delta_ranges(
+    cached: &[Proposal],
+    last_proposal_id: u64,
+    last_id: u64,
+    actions_log: Option<&[ActionLog]>,
+) -> Vec<(u64, u64)> {
+    let mut ranges =
+        match actions_log.and_then(|actions_log| acted_proposal_ids(actions_log, cached)) {
+            Some(ids) => {
+                debug!(
+                    acted = ids.len(),
+                    "Refetching proposals from the actions log"
+                );
+                id_ranges(ids.into_iter().filter(|id| *id < last_proposal_id))
+            }
+            None => in_progress_ranges(cached),
+        };
+    if last_proposal_id < last_id {
+        ranges.push((last_proposal_id, last_id));
+    }
+    ranges
+}
+
+/// `cached` with the proposals inside `ranges` replaced by `fetched`, sorted by id.
+/// Cached proposals of a range that `fetched` lacks were removed and are dropped.
+pub(crate) fn merge_delta(
+    cached: &[Proposal],
+    ranges: &[(u64, u64)],
+    fetched: Vec<Proposal>,
+) -> Vec<Proposal> {
+    let mut merged: Vec<Proposal> = cached
+        .iter()
+        .filter(|p| !ranges.iter().any(|(from, to)| p.id >= *from && p.id < *to))
+        .cloned()
+        .collect();
+    merged.extend(fetched);
+    merged.sort_by_key(|p| p.id);
+    merged
+}
+
 pub async fn fetch_proposal(
-    client: &JsonRpcClient,
+    client: &RpcPool,
     dao_id: &AccountId,
     proposal_id: u64,
 ) -> anyhow::Result<Proposal> {
@@ -301,7 +767,7 @@ pub async fn fetch_proposal(
 }
 
 pub async fn fetch_proposal_at_block(
-    client: &JsonRpcClient,
+    client: &RpcPool,
     dao_id: &AccountId,
     proposal_id: u64,
     block_height: u64,
@@ -323,7 +789,7 @@ pub async fn fetch_proposal_at_block(
             args: query_args,
         },
     };
-    let response = client.call(request).await?;
+    let response = client.call_archival(request).await?;
     if let QueryResponseKind::CallResult(result) = response.kind {
         let proposal: Proposal = serde_json::from_slice(&result.result)?;
         Ok(proposal)
@@ -335,19 +801,29 @@ pub async fn fetch_proposal_at_block(
     }
 }
 
+/// Transactions of a proposal's actions newer than `block_height_limit`, walking its
+/// action log back from `start_block` (the latest block when None). At most `max_pages`
+/// log pages are walked; when history remains, the block to resume from is returned.
 pub async fn fetch_proposal_log_txs(
-    client: &JsonRpcClient,
+    client: &RpcPool,
     dao_id: &AccountId,
     proposal_id: u64,
     block_height_limit: u64,
-) -> anyhow::Result<Vec<TxMetadata>> {
-    let proposal = fetch_proposal(client, dao_id, proposal_id).await?;
-    if proposal.last_actions_log.is_none() {
-        return Ok(Vec::new());
-    }
-
-    let mut earliest_log = proposal.last_actions_log.unwrap();
+    start_block: Option<u64>,
+    max_pages: usize,
+) -> anyhow::Result<(Vec<TxMetadata>, Option<u64>)> {
+    let proposal = match start_block {
+        Some(block_height) => {
+            fetch_proposal_at_block(client, dao_id, proposal_id, block_height).await?
+        }
+        None => fetch_proposal(client, dao_id, proposal_id).await?,
+    };
+    let Some(mut earliest_log) = proposal.last_actions_log.clone() else {
+        return Ok((Vec::new(), None));
+    };
     let mut complete_log = Vec::new();
+    let mut pages = 1;
+    let mut resume_at = None;
 
     while earliest_log.len() == LOG_LIMIT {
         let earliest_block_height = earliest_log.first().unwrap().block_height.0;
@@ -358,10 +834,16 @@ pub async fn fetch_proposal_log_txs(
         // Extends in a wrong order
         complete_log.extend(earliest_log);
         let earlier_block_height = earliest_block_height - 1;
+        if pages >= max_pages {
+            resume_at = Some(earlier_block_height);
+            earliest_log = Vec::new();
+            break;
+        }
         earliest_log = fetch_proposal_at_block(client, dao_id, proposal_id, earlier_block_height)
             .await?
             .last_actions_log
             .unwrap();
+        pages += 1;
     }
     let earliest_log: Vec<ProposalLog> = earliest_log
         .iter()
@@ -376,13 +858,89 @@ pub async fn fetch_proposal_log_txs(
     let futures = complete_log
         .iter()
         .map(|l| l.block_height.0)
-        .map(|block_number| fetch_proposal_txs_in_block(client, dao_id, proposal_id, block_number));
+        .map(|block_number| proposal_txs_in_block(client, dao_id, &proposal, block_number));
     let res = try_join_all(futures).await?.into_iter().flatten().collect();
 
-    Ok(res)
+    Ok((res, resume_at))
+}
+
+/// State of a proposal right after a block that acted on it
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct ProposalStateChange {
+    pub block_height: u64,
+    pub status: ProposalStatus,
+    /// Approve, reject and remove weight per role
+    #[schema(value_type = Object)]
+    pub vote_counts: HashMap<String, [CountsVersions; 3]>,
+    pub votes: HashMap<String, Vote>,
+}
+
+// Every block in the proposal's action log, oldest first. Full log pages continue in the
+// page read just before their earliest block.
+async fn proposal_action_blocks(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    proposal: &Proposal,
+) -> anyhow::Result<Vec<u64>> {
+    let mut blocks = Vec::new();
+    let mut log = proposal.last_actions_log.clone().unwrap_or_default();
+    loop {
+        blocks.extend(log.iter().map(|l| l.block_height.0));
+        match log.first() {
+            Some(earliest) if log.len() == LOG_LIMIT => {
+                let earlier_block_height = earliest.block_height.0 - 1;
+                log = fetch_proposal_at_block(client, dao_id, proposal.id, earlier_block_height)
+                    .await?
+                    .last_actions_log
+                    .unwrap_or_default();
+            }
+            _ => break,
+        }
+    }
+    blocks.sort_unstable();
+    blocks.dedup();
+    Ok(blocks)
+}
+
+/// The proposal's status and votes after each block in its action log, oldest first.
+/// States already in `known` are reused, only the other blocks are read, with up to
+/// `scraper.fetch_concurrency` calls in flight. Empty for proposals of contract versions
+/// without an action log.
+pub async fn fetch_proposal_history(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    proposal: &Proposal,
+    known: &[ProposalStateChange],
+) -> anyhow::Result<Vec<ProposalStateChange>> {
+    let blocks = proposal_action_blocks(client, dao_id, proposal).await?;
+    let known: HashMap<u64, &ProposalStateChange> = known
+        .iter()
+        .map(|state| (state.block_height, state))
+        .collect();
+    let known = &known;
+
+    let mut history: Vec<ProposalStateChange> = stream::iter(blocks)
+        .map(|block_height| async move {
+            if let Some(state) = known.get(&block_height) {
+                return Ok((*state).clone());
+            }
+            let proposal =
+                fetch_proposal_at_block(client, dao_id, proposal.id, block_height).await?;
+            Ok::<_, anyhow::Error>(ProposalStateChange {
+                block_height,
+                status: proposal.status,
+                vote_counts: proposal.vote_counts,
+                votes: proposal.votes,
+            })
+        })
+        .buffer_unordered(get_config().scraper.fetch_concurrency)
+        .try_collect()
+        .await?;
+    history.sort_by_key(|state| state.block_height);
+    Ok(history)
 }
 
-pub async fn fetch_policy(client: &JsonRpcClient, dao_id: &AccountId) -> anyhow::Result<Policy> {
+pub async fn fetch_policy(client: &RpcPool, dao_id: &AccountId) -> anyhow::Result<Policy> {
     let request = methods::query::RpcQueryRequest {
         block_reference: near_primitives::types::Finality::Final.into(),
         request: QueryRequest::CallFunction {
@@ -402,8 +960,130 @@ pub async fn fetch_policy(client: &JsonRpcClient, dao_id: &AccountId) -> anyhow:
     }
 }
 
+pub async fn fetch_config(client: &RpcPool, dao_id: &AccountId) -> anyhow::Result<DaoConfig> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: near_primitives::types::Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: dao_id.clone(),
+            method_name: "get_config".to_string(),
+            args: FunctionArgs::from(vec![]),
+        },
+    };
+
+    let response = client.call(request).await?;
+
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        let config: DaoConfig = serde_json::from_slice(&result.result)?;
+        Ok(config)
+    } else {
+        Err(anyhow::anyhow!("Failed to get config"))
+    }
+}
+
+pub async fn fetch_bounties(client: &RpcPool, dao_id: &AccountId) -> anyhow::Result<Vec<Bounty>> {
+    let last_id_request = methods::query::RpcQueryRequest {
+        block_reference: near_primitives::types::Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: dao_id.clone(),
+            method_name: "get_last_bounty_id".to_string(),
+            args: FunctionArgs::from(vec![]),
+        },
+    };
+    let last_id_response = client.call(last_id_request).await?;
+    let last_id = if let QueryResponseKind::CallResult(result) = last_id_response.kind {
+        serde_json::from_slice::<u64>(&result.result)?
+    } else {
+        return Err(anyhow::anyhow!("Failed to get last bounty ID"));
+    };
+
+    let mut all_bounties = Vec::new();
+    let mut current_index = 0;
+
+    // Fetch bounties in batches, same as proposals
+    while current_index < last_id {
+        let limit = std::cmp::min(get_config().scraper.proposal_limit, last_id - current_index);
+
+        let query_args = FunctionArgs::from(
+            json!({
+                "from_index": current_index,
+                "limit": limit
+            })
+            .to_string()
+            .into_bytes(),
+        );
+        let request = methods::query::RpcQueryRequest {
+            block_reference: near_primitives::types::Finality::Final.into(),
+            request: QueryRequest::CallFunction {
+                account_id: dao_id.clone(),
+                method_name: "get_bounties".to_string(),
+                args: query_args,
+            },
+        };
+
+        let response = client.call(request).await?;
+        if let QueryResponseKind::CallResult(result) = response.kind {
+            let bounties_batch: Vec<Bounty> = serde_json::from_slice(&result.result)?;
+            all_bounties.extend(bounties_batch);
+            current_index += limit;
+        } else {
+            return Err(anyhow::anyhow!(
+                "Unexpected response kind while fetching bounties batch starting at index {}",
+                current_index
+            ));
+        }
+    }
+
+    Ok(all_bounties)
+}
+
+/// Claims are stored per account in the contract, so they can only be looked up by claimant
+pub async fn fetch_bounty_claims(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    account_id: &str,
+) -> anyhow::Result<Vec<BountyClaim>> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: near_primitives::types::Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: dao_id.clone(),
+            method_name: "get_bounty_claims".to_string(),
+            args: FunctionArgs::from(json!({ "account_id": account_id }).to_string().into_bytes()),
+        },
+    };
+
+    let response = client.call(request).await?;
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        let claims: Vec<BountyClaim> = serde_json::from_slice(&result.result)?;
+        Ok(claims)
+    } else {
+        Err(anyhow::anyhow!("Failed to get bounty claims"))
+    }
+}
+
+pub async fn fetch_bounty_number_of_claims(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    bounty_id: u64,
+) -> anyhow::Result<u32> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: near_primitives::types::Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: dao_id.clone(),
+            method_name: "get_bounty_number_of_claims".to_string(),
+            args: FunctionArgs::from(json!({ "id": bounty_id }).to_string().into_bytes()),
+        },
+    };
+
+    let response = client.call(request).await?;
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        Ok(serde_json::from_slice::<u32>(&result.result)?)
+    } else {
+        Err(anyhow::anyhow!("Failed to get bounty number of claims"))
+    }
+}
+
 pub async fn fetch_contract_version(
-    client: &JsonRpcClient,
+    client: &RpcPool,
     dao_id: &AccountId,
 ) -> anyhow::Result<StateVersion> {
     let request = methods::query::RpcQueryRequest {
@@ -433,10 +1113,7 @@ pub async fn fetch_contract_version(
     }
 }
 
-pub async fn fetch_actions_log(
-    client: &JsonRpcClient,
-    dao_id: &AccountId,
-) -> Option<Vec<ActionLog>> {
+pub async fn fetch_actions_log(client: &RpcPool, dao_id: &AccountId) -> Option<Vec<ActionLog>> {
     let request = methods::query::RpcQueryRequest {
         block_reference: near_primitives::types::Finality::Final.into(),
         request: QueryRequest::CallFunction {
@@ -461,10 +1138,121 @@ pub async fn fetch_actions_log(
     }
 }
 
+// Whether an add_proposal call created this proposal. The new id is only known from the
+// call's result, so the call is matched by proposer and description instead, which
+// only confuses identical proposals by the same proposer in the same block.
+fn creates_proposal(predecessor_id: &AccountId, args: &[u8], proposal: &Proposal) -> bool {
+    if predecessor_id.as_str() != proposal.proposer {
+        return false;
+    }
+    serde_json::from_slice::<Value>(args)
+        .ok()
+        .and_then(|args| {
+            args.pointer("/proposal/description")
+                .and_then(Value::as_str)
+                .map(|description| description == proposal.description)
+        })
+        .unwrap_or(false)
+}
+
+// Status and gas burnt of a receipt's execution, proven against `head`, a final block
+// after the one that executed the receipt
+async fn fetch_receipt_outcome(
+    client: &RpcPool,
+    receipt_id: CryptoHash,
+    receiver_id: &AccountId,
+    head: CryptoHash,
+) -> Result<(TxStatus, u64)> {
+    let request = methods::light_client_proof::RpcLightClientExecutionProofRequest {
+        id: TransactionOrReceiptId::Receipt {
+            receipt_id,
+            receiver_id: receiver_id.clone(),
+        },
+        light_client_head: head,
+    };
+    let outcome = client.call_archival(request).await?.outcome_proof.outcome;
+    Ok((tx_status(&outcome.status), outcome.gas_burnt))
+}
+
+/// Timestamp of a block in nanoseconds
+pub async fn fetch_block_timestamp(client: &RpcPool, block_height: u64) -> Result<u64> {
+    let block_request = methods::block::RpcBlockRequest {
+        block_reference: near_primitives::types::BlockReference::BlockId(
+            near_primitives::types::BlockId::Height(block_height),
+        ),
+    };
+    Ok(client.call_archival(block_request).await?.header.timestamp)
+}
+
+/// Signer of a receipt that acts on or creates `proposal` of `dao_id`, None for any
+/// other receipt
+pub fn proposal_call_signer(
+    receipt: &ReceiptView,
+    dao_id: &AccountId,
+    proposal: &Proposal,
+) -> Option<AccountId> {
+    if &receipt.receiver_id != dao_id {
+        return None;
+    }
+    let ReceiptEnumView::Action {
+        signer_id, actions, ..
+    } = &receipt.receipt
+    else {
+        return None;
+    };
+    let is_proposal_call = actions.iter().any(|action| match action {
+        ActionView::FunctionCall {
+            method_name, args, ..
+        } => match method_name.as_str() {
+            "act_proposal" => serde_json::from_slice::<Value>(args)
+                .ok()
+                .and_then(|args| args.get("id").and_then(Value::as_u64))
+                .is_some_and(|id| id == proposal.id),
+            // Other proposals may be created in the same block
+            "add_proposal" => creates_proposal(&receipt.predecessor_id, args, proposal),
+            _ => false,
+        },
+        _ => false,
+    });
+    is_proposal_call.then(|| signer_id.clone())
+}
+
+/// Outcome of a receipt's execution
+pub fn tx_status(status: &ExecutionStatusView) -> TxStatus {
+    match status {
+        ExecutionStatusView::SuccessValue(_) | ExecutionStatusView::SuccessReceiptId(_) => {
+            TxStatus::Succeeded
+        }
+        ExecutionStatusView::Failure(_) | ExecutionStatusView::Unknown => TxStatus::Failed,
+    }
+}
+
+// Calls on the proposal in a block, read from NEAR Lake when scraper.tx_log_source is
+// lake. Blocks Lake can't serve are walked over RPC instead.
+async fn proposal_txs_in_block(
+    client: &RpcPool,
+    dao_id: &AccountId,
+    proposal: &Proposal,
+    block_height: u64,
+) -> Result<Vec<TxMetadata>> {
+    if get_config().scraper.tx_log_source == TxLogSource::Lake {
+        match lake::fetch_proposal_txs_in_block(dao_id, proposal, block_height).await {
+            Ok(txs) => return Ok(txs),
+            Err(e) => warn!(
+                %dao_id,
+                block_height,
+                error = ?e,
+                "NEAR Lake block unavailable, falling back to RPC"
+            ),
+        }
+    }
+    fetch_proposal_txs_in_block(client, dao_id, proposal, block_height).await
+}
+
 pub async fn fetch_proposal_txs_in_block(
-    client: &JsonRpcClient,
+    client: &RpcPool,
     dao_id: &AccountId,
-    proposal_id: u64,
+    proposal: &Proposal,
     block_height: u64,
 ) -> Result<Vec<TxMetadata>> {
     let block_request = methods::block::RpcBlockRequest {
@@ -472,7 +1260,7 @@ pub async fn fetch_proposal_txs_in_block(
             near_primitives::types::BlockId::Height(block_height),
         ),
     };
-    let block_response = client.call(block_request).await?;
+    let block_response = client.call_archival(block_request).await?;
 
     let chunks_views = block_response.chunks;
     let timestamp = block_response.header.timestamp;
@@ -483,66 +1271,173 @@ pub async fn fetch_proposal_txs_in_block(
                 chunk_id: chunk_header.chunk_hash,
             },
         };
-        client.call(chunk_request)
+        client.call_archival(chunk_request)
     });
     let chunk_results = try_join_all(chunk_futures).await?;
 
-    let mut proposal_txs = Vec::new();
+    // Receipt id, signer, predecessor and actions of every call on the proposal
+    let mut proposal_calls = Vec::new();
     for chunk in chunk_results {
         for rc in &chunk.receipts {
-            if &rc.receiver_id == dao_id {
-                if let ReceiptEnumView::Action {
-                    signer_id, actions, ..
-                } = rc.receipt.clone()
-                {
-                    for action in actions {
-                        if let ActionView::FunctionCall {
-                            method_name, args, ..
-                        } = action
-                        {
-                            match method_name.as_str() {
-                                "act_proposal" => {
-                                    let args: Value = serde_json::from_slice(&args)
-                                        .expect("Couldn't deserialize args.");
-                                    let id = args
-                                        .get("id")
-                                        .expect("No id found at proposal.")
-                                        .as_u64()
-                                        .unwrap();
-                                    if proposal_id == id {
-                                        proposal_txs.push(TxMetadata {
-                                            signer_id: signer_id.clone(),
-                                            predecessor_id: rc.predecessor_id.clone(),
-                                            reciept_hash: rc.receipt_id,
-                                            block_height,
-                                            timestamp,
-                                        })
-                                    }
-                                }
-                                // There will be mismatch if two proposals are created in the same block.
-                                "add_proposal" => proposal_txs.push(TxMetadata {
-                                    signer_id: signer_id.clone(),
-                                    predecessor_id: rc.predecessor_id.clone(),
-                                    reciept_hash: rc.receipt_id,
-                                    block_height,
-                                    timestamp,
-                                }),
-                                _ => {}
-                            }
-                        }
+            if let Some(signer_id) = proposal_call_signer(rc, dao_id, proposal) {
+                proposal_calls.push((
+                    rc.receipt_id,
+                    signer_id,
+                    rc.predecessor_id.clone(),
+                    receipt_actions(rc),
+                ));
+            }
+        }
+    }
+
+    if proposal_calls.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let head = client
+        .call(methods::block::RpcBlockRequest {
+            block_reference: near_primitives::types::Finality::Final.into(),
+        })
+        .await?
+        .header
+        .hash;
+    let outcome_futures = proposal_calls.into_iter().map(
+        |(receipt_id, signer_id, predecessor_id, actions)| async move {
+            // One missing outcome shouldn't cost the whole log
+            let (status, gas_burnt) =
+                match fetch_receipt_outcome(client, receipt_id, dao_id, head).await {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        warn!(%dao_id, %receipt_id, error = ?e, "Failed to fetch receipt outcome");
+                        (TxStatus::Unknown, 0)
                     }
-                }
+                };
+            TxMetadata {
+                signer_id,
+                predecessor_id,
+                reciept_hash: receipt_id,
+                block_height,
+                timestamp,
+                status,
+                gas_burnt,
+                actions: Some(actions),
+            }
+        },
+    );
+
+    Ok(join_all(outcome_futures).await)
+}
+
+/// A call on a proposal as executed, with its function call arguments decoded
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ProposalReceipt {
+    #[schema(value_type = String)]
+    pub receipt_id: CryptoHash,
+    #[schema(value_type = String)]
+    pub signer_id: AccountId,
+    #[schema(value_type = String)]
+    pub predecessor_id: AccountId,
+    pub block_height: u64,
+    pub timestamp: u64,
+    pub status: TxStatus,
+    pub gas_burnt: u64,
+    pub actions: Vec<ReceiptAction>,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct ReceiptAction {
+    pub method_name: String,
+    /// Arguments as JSON, or the base64 string when they aren't JSON. The base64 args of
+    /// FunctionCall proposal actions inside are decoded as well.
+    #[schema(value_type = Object)]
+    pub args: Value,
+    #[schema(value_type = String)]
+    pub deposit: U128,
+    pub gas: u64,
+}
+
+fn decode_receipt_args(args: &[u8]) -> Value {
+    let Ok(mut json) = serde_json::from_slice::<Value>(args) else {
+        return Value::String(general_purpose::STANDARD.encode(args));
+    };
+    // add_proposal carries the actions of FunctionCall proposals with base64 args
+    if let Some(actions) = json
+        .pointer_mut("/proposal/kind/FunctionCall/actions")
+        .and_then(Value::as_array_mut)
+    {
+        for action in actions {
+            if let Some(decoded) = action
+                .get("args")
+                .and_then(Value::as_str)
+                .and_then(parse_args::<Value>)
+            {
+                action["args"] = decoded;
             }
         }
     }
+    json
+}
+
+/// Function call actions of a receipt, with their arguments decoded
+pub fn receipt_actions(receipt: &ReceiptView) -> Vec<ReceiptAction> {
+    let ReceiptEnumView::Action { actions, .. } = &receipt.receipt else {
+        return Vec::new();
+    };
+    actions
+        .iter()
+        .filter_map(|action| match action {
+            ActionView::FunctionCall {
+                method_name,
+                args,
+                gas,
+                deposit,
+            } => Some(ReceiptAction {
+                method_name: method_name.clone(),
+                args: decode_receipt_args(args),
+                deposit: U128(*deposit),
+                gas: *gas,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Receipts of the logged calls on a proposal. The actions kept by the block walk are
+/// reused, only calls without them (e.g. restored from disk) are read back by id.
+pub async fn fetch_proposal_receipts(
+    client: &RpcPool,
+    txs_log: &[TxMetadata],
+) -> Result<Vec<ProposalReceipt>> {
+    let futures = txs_log.iter().map(|tx| async move {
+        let actions = match &tx.actions {
+            Some(actions) => actions.clone(),
+            None => {
+                let receipt = client
+                    .call_archival(methods::EXPERIMENTAL_receipt::RpcReceiptRequest {
+                        receipt_reference: ReceiptReference {
+                            receipt_id: tx.reciept_hash,
+                        },
+                    })
+                    .await?;
+                receipt_actions(&receipt)
+            }
+        };
 
-    Ok(proposal_txs)
+        Ok::<_, anyhow::Error>(ProposalReceipt {
+            receipt_id: tx.reciept_hash,
+            signer_id: tx.signer_id.clone(),
+            predecessor_id: tx.predecessor_id.clone(),
+            block_height: tx.block_height,
+            timestamp: tx.timestamp,
+            status: tx.status.clone(),
+            gas_burnt: tx.gas_burnt,
+            actions,
+        })
+    });
+    try_join_all(futures).await
 }
 
-pub async fn fetch_ft_metadata(
-    client: &near_jsonrpc_client::JsonRpcClient,
-    contract_id: &AccountId,
-) -> Result<FtMetadata> {
+pub async fn fetch_ft_metadata(client: &RpcPool, contract_id: &AccountId) -> Result<FtMetadata> {
     let request = RpcQueryRequest {
         block_reference: near_primitives::types::Finality::Final.into(),
         request: QueryRequest::CallFunction {
@@ -562,24 +1457,30 @@ pub async fn fetch_ft_metadata(
     }
 }
 
-fn format_ns_timestamp_from_i64(ns: i64) -> Option<String> {
+fn format_ns_timestamp_from_i64(ns: i64, date_format: &DateFormat) -> Option<String> {
     let secs = ns / 1_000_000_000;
     let nsec = (ns % 1_000_000_000) as u32;
 
     let datetime_utc = Utc.timestamp_opt(secs, nsec).single()?;
 
-    Some(datetime_utc.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+    Some(
+        datetime_utc
+            .with_timezone(&date_format.tz)
+            .format(&date_format.pattern)
+            .to_string(),
+    )
 }
 
-fn format_ns_timestamp_u64(ns: u64) -> String {
-    format_ns_timestamp_from_i64(ns as i64).unwrap_or_else(|| "Invalid timestamp".to_string())
+fn format_ns_timestamp_u64(ns: u64, date_format: &DateFormat) -> String {
+    format_ns_timestamp_from_i64(ns as i64, date_format)
+        .unwrap_or_else(|| "Invalid timestamp".to_string())
 }
 
-fn format_ns_timestamp_str(ns_str: &str) -> Option<String> {
+fn format_ns_timestamp_str(ns_str: &str, date_format: &DateFormat) -> Option<String> {
     ns_str
         .parse::<i64>()
         .ok()
-        .and_then(format_ns_timestamp_from_i64)
+        .and_then(|ns| format_ns_timestamp_from_i64(ns, date_format))
 }
 
 #[derive(Debug, Default)]
@@ -650,7 +1551,7 @@ pub fn extract_from_description(desc: &str, key: &str) -> Option<String> {
     None
 }
 
-fn get_current_time_nanos() -> U64 {
+pub(crate) fn get_current_time_nanos() -> U64 {
     let nanos = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Time went backwards")
@@ -659,25 +1560,6 @@ fn get_current_time_nanos() -> U64 {
     U64::from(nanos as u64)
 }
 
-pub fn get_status_display(
-    status: &ProposalStatus,
-    submission_time: u64,
-    period: u64,
-    pending_label: &str,
-) -> String {
-    match status {
-        ProposalStatus::InProgress => {
-            let current_time = get_current_time_nanos().0;
-            if submission_time + period < current_time {
-                "Expired".to_string()
-            } else {
-                pending_label.to_string()
-            }
-        }
-        _ => format!("{:?}", status),
-    }
-}
-
 impl ProposalCsvFormatterAsync<PaymentInfo> for TransferProposalFormatter {
     fn headers(&self) -> Vec<&'static str> {
         vec![
@@ -699,28 +1581,29 @@ impl ProposalCsvFormatterAsync<PaymentInfo> for TransferProposalFormatter {
 
     fn format<'a>(
         &'a self,
-        client: &'a Arc<JsonRpcClient>,
+        client: &'a Arc<RpcPool>,
         ft_metadata_cache: &'a FtMetadataCache,
         proposal: &'a Proposal,
-        policy: &'a Policy,
+        _policy: &'a Policy,
         info: &'a PaymentInfo,
     ) -> BoxFuture<'a, Vec<String>> {
         async move {
-            let created_date = format_ns_timestamp_u64(proposal.submission_time.0);
+            let created_date =
+                format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
             let title =
                 extract_from_description(&proposal.description, "title").unwrap_or_default();
             let summary =
                 extract_from_description(&proposal.description, "summary").unwrap_or_default();
-            let notes =
-                extract_from_description(&proposal.description, "notes").unwrap_or_default();
+            // Notes from the description, followed by the msg passed to the receiver
+            let notes = extract_from_description(&proposal.description, "notes")
+                .into_iter()
+                .chain(info.msg.clone())
+                .filter(|note| !note.is_empty())
+                .collect::<Vec<_>>()
+                .join("; ");
             let description =
                 extract_from_description(&proposal.description, "description").unwrap_or_default();
-            let status: String = get_status_display(
-                &proposal.status,
-                proposal.submission_time.0,
-                policy.proposal_period.0,
-                "Pending",
-            );
+            let status: String = proposal.current_status().as_str().to_string();
             let created_by = proposal.proposer.clone();
             let formatted_votes = format_votes(&proposal.votes);
 
@@ -729,8 +1612,8 @@ impl ProposalCsvFormatterAsync<PaymentInfo> for TransferProposalFormatter {
                 match get_ft_metadata_cache(&client, &ft_metadata_cache, &info.token).await {
                     Ok(metadata) => metadata,
                     Err(e) => {
-                        eprintln!("Error fetching ft metadata: {}", e);
-                        FtMetadata::empty()
+                        warn!(error = %e, "Error fetching ft metadata");
+                        FtMetadata::unknown(&info.token)
                     }
                 };
 
@@ -753,7 +1636,7 @@ impl ProposalCsvFormatterAsync<PaymentInfo> for TransferProposalFormatter {
                 summary,
                 info.receiver.clone(),
                 ft_metadata.symbol,
-                normalize_token_amount(&info.amount, ft_metadata.decimals.into()),
+                normalize_token_amount(&info.amount, ft_metadata.decimals),
                 created_by,
                 notes,
                 formatted_votes.approved.join(", "),
@@ -764,6 +1647,101 @@ impl ProposalCsvFormatterAsync<PaymentInfo> for TransferProposalFormatter {
     }
 }
 
+impl AccountingPaymentFormatter {
+    // Time of the last logged transaction, the vote that approved the proposal
+    async fn approval_time(&self, client: &Arc<RpcPool>, proposal: &Proposal) -> u64 {
+        match get_latest_proposal_cache(client, &self.proposal_cache, &self.dao_id, proposal.id)
+            .await
+        {
+            Ok(cached) => cached
+                .txs_log
+                .iter()
+                .filter(|tx| tx.succeeded())
+                .map(|tx| tx.timestamp)
+                .max()
+                .unwrap_or(proposal.submission_time.0),
+            Err(e) => {
+                warn!(proposal_id = proposal.id, error = %e, "Error fetching transaction log");
+                proposal.submission_time.0
+            }
+        }
+    }
+}
+
+impl ProposalCsvFormatterAsync<PaymentInfo> for AccountingPaymentFormatter {
+    fn headers(&self) -> Vec<&'static str> {
+        match self.profile {
+            AccountingProfile::Quickbooks => {
+                vec!["Date", "Payee", "Amount", "Currency", "Memo", "Reference"]
+            }
+            AccountingProfile::Xero => vec![
+                "Date",
+                "Amount",
+                "Payee",
+                "Description",
+                "Reference",
+                "Currency",
+            ],
+        }
+    }
+
+    fn format<'a>(
+        &'a self,
+        client: &'a Arc<RpcPool>,
+        ft_metadata_cache: &'a FtMetadataCache,
+        proposal: &'a Proposal,
+        _policy: &'a Policy,
+        info: &'a PaymentInfo,
+    ) -> BoxFuture<'a, Vec<String>> {
+        async move {
+            let date = format_ns_timestamp_u64(
+                self.approval_time(client, proposal).await,
+                &self.date_format,
+            );
+            let memo = extract_from_description(&proposal.description, "title")
+                .or_else(|| extract_from_description(&proposal.description, "description"))
+                .unwrap_or_default();
+            let reference = format!("{} #{}", self.dao_id, proposal.id);
+
+            let ft_metadata =
+                match get_ft_metadata_cache(client, ft_metadata_cache, &info.token).await {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        warn!(error = %e, "Error fetching ft metadata");
+                        FtMetadata::unknown(&info.token)
+                    }
+                };
+            // Payments leave the treasury, so they are imported as spent money
+            let amount = normalize_token_amount(&info.amount, ft_metadata.decimals);
+            let amount = if amount.is_empty() {
+                amount
+            } else {
+                format!("-{}", amount)
+            };
+
+            match self.profile {
+                AccountingProfile::Quickbooks => vec![
+                    date,
+                    info.receiver.clone(),
+                    amount,
+                    ft_metadata.symbol,
+                    memo,
+                    reference,
+                ],
+                AccountingProfile::Xero => vec![
+                    date,
+                    amount,
+                    info.receiver.clone(),
+                    memo,
+                    reference,
+                    ft_metadata.symbol,
+                ],
+            }
+        }
+        .boxed()
+    }
+}
+
 fn extract_action_field<'a>(proposal: &'a Proposal, field: &str) -> Option<&'a str> {
     proposal
         .kind
@@ -773,22 +1751,14 @@ fn extract_action_field<'a>(proposal: &'a Proposal, field: &str) -> Option<&'a s
         .get(field)?
         .as_str()
 }
-fn parse_args<T: DeserializeOwned>(args_base64: &str) -> Option<T> {
-    let decoded_bytes = general_purpose::STANDARD.decode(args_base64).ok()?;
-    let parsed: T = from_slice(&decoded_bytes).ok()?;
-    Some(parsed)
-}
-
 fn extract_args(proposal: &Proposal) -> Option<LockupArgs> {
     let args_base64 = extract_action_field(proposal, "args").unwrap_or("");
     parse_args(args_base64)
 }
 
-fn normalize_token_amount(raw: &str, decimals: u32) -> String {
-    raw.parse::<f64>()
-        .map(|v| v / 10f64.powi(decimals as i32))
-        .map(|v| format!("{:.5}", v)) // format with 5 decimals (adjust as needed)
-        .unwrap_or_default()
+// Whole token amount with tokens.display_decimals fraction digits
+fn normalize_token_amount(raw: &str, decimals: u8) -> String {
+    format_units_fixed(raw, decimals, get_config().tokens.display_decimals)
 }
 
 impl ProposalCsvFormatterSync<LockupInfo> for LockupProposalFormatter {
@@ -811,7 +1781,7 @@ impl ProposalCsvFormatterSync<LockupInfo> for LockupProposalFormatter {
         ]
     }
 
-    fn format(&self, proposal: &Proposal, policy: &Policy, _info: &LockupInfo) -> Vec<String> {
+    fn format(&self, proposal: &Proposal, _policy: &Policy, _info: &LockupInfo) -> Vec<String> {
         let args_opt = extract_args(proposal);
         let args = args_opt.as_ref();
 
@@ -827,12 +1797,14 @@ impl ProposalCsvFormatterSync<LockupInfo> for LockupProposalFormatter {
             Some(a) => {
                 // Try simple lockup + duration first
                 if let (Some(start), Some(duration)) = (&a.lockup_timestamp, &a.release_duration) {
-                    let start_date = format_ns_timestamp_str(start).unwrap_or_default();
+                    let start_date =
+                        format_ns_timestamp_str(start, &self.date_format).unwrap_or_default();
 
                     let end_date = match (start.parse::<i64>(), duration.parse::<i64>()) {
                         (Ok(start_ns), Ok(duration_ns)) => {
                             let end_ns = start_ns.checked_add(duration_ns).unwrap_or(0);
-                            format_ns_timestamp_str(&end_ns.to_string()).unwrap_or_default()
+                            format_ns_timestamp_str(&end_ns.to_string(), &self.date_format)
+                                .unwrap_or_default()
                         }
                         _ => String::new(),
                     };
@@ -847,17 +1819,17 @@ impl ProposalCsvFormatterSync<LockupInfo> for LockupProposalFormatter {
 
                     let start_date = vesting
                         .and_then(|vs| vs.start_timestamp.as_ref())
-                        .map(|s| format_ns_timestamp_str(s).unwrap_or_default())
+                        .map(|s| format_ns_timestamp_str(s, &self.date_format).unwrap_or_default())
                         .unwrap_or_default();
 
                     let end_date = vesting
                         .and_then(|vs| vs.end_timestamp.as_ref())
-                        .map(|s| format_ns_timestamp_str(s).unwrap_or_default())
+                        .map(|s| format_ns_timestamp_str(s, &self.date_format).unwrap_or_default())
                         .unwrap_or_default();
 
                     let cliff_date = vesting
                         .and_then(|vs| vs.cliff_timestamp.as_ref())
-                        .map(|s| format_ns_timestamp_str(s).unwrap_or_default())
+                        .map(|s| format_ns_timestamp_str(s, &self.date_format).unwrap_or_default())
                         .unwrap_or_default();
 
                     (start_date, end_date, cliff_date)
@@ -884,65 +1856,261 @@ impl ProposalCsvFormatterSync<LockupInfo> for LockupProposalFormatter {
         .to_string();
 
         let formatted_votes = format_votes(&proposal.votes);
-        let created_date = format_ns_timestamp_u64(proposal.submission_time.0);
-        let status: String = get_status_display(
-            &proposal.status,
-            proposal.submission_time.0,
-            policy.proposal_period.0,
-            "Pending",
-        );
-        let created_by = proposal.proposer.clone();
-
+        let created_date = format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+        let status: String = proposal.current_status().as_str().to_string();
+        let created_by = proposal.proposer.clone();
+
+        vec![
+            proposal.id.to_string(),
+            created_date,
+            status,
+            recipient,
+            amount,
+            "NEAR".to_string(),
+            start_date,
+            end_date,
+            cliff_date,
+            allow_cancellation,
+            allow_staking,
+            created_by,
+            formatted_votes.approved.join(", "),
+            formatted_votes.rejected.join(", "),
+        ]
+    }
+}
+
+impl ProposalCsvFormatterSync<()> for DefaultFormatter {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "ID",
+            "Created Date",
+            "Status",
+            "Description",
+            "Kind",
+            "Created by",
+            "Approvers (Approved)",
+            "Approvers (Rejected/Remove)",
+        ]
+    }
+    fn format(&self, proposal: &Proposal, _policy: &Policy, _info: &()) -> Vec<String> {
+        let formatted_votes = format_votes(&proposal.votes);
+        let status: String = proposal.current_status().as_str().to_string();
+        let kind = proposal.kind.clone();
+        let created_date: String =
+            format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+        let created_by = proposal.proposer.clone();
+        vec![
+            proposal.id.to_string(),
+            created_date,
+            status,
+            proposal.description.clone(),
+            kind.to_string(),
+            created_by,
+            formatted_votes.approved.join(", "),
+            formatted_votes.rejected.join(", "),
+        ]
+    }
+}
+
+impl ProposalCsvFormatterSync<MemberChangeInfo> for MemberChangeProposalFormatter {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "ID",
+            "Created Date",
+            "Status",
+            "Action",
+            "Member",
+            "Role",
+            "Policy Changes",
+            "Description",
+            "Created by",
+            "Approvers (Approved)",
+            "Approvers (Rejected/Remove)",
+        ]
+    }
+    fn format(&self, proposal: &Proposal, policy: &Policy, info: &MemberChangeInfo) -> Vec<String> {
+        let formatted_votes = format_votes(&proposal.votes);
+        let status: String = proposal.current_status().as_str().to_string();
+        let created_date: String =
+            format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+        vec![
+            proposal.id.to_string(),
+            created_date,
+            status,
+            info.action_name().to_string(),
+            info.member.clone().unwrap_or_default(),
+            info.role.clone().unwrap_or_default(),
+            info.policy_changes(policy).join("; "),
+            proposal.description.clone(),
+            proposal.proposer.clone(),
+            formatted_votes.approved.join(", "),
+            formatted_votes.rejected.join(", "),
+        ]
+    }
+}
+
+impl ProposalCsvFormatterSync<GovernanceOpsInfo> for GovernanceOpsProposalFormatter {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "ID",
+            "Created Date",
+            "Status",
+            "Action",
+            "Target Contract",
+            "Method",
+            "Code Hash",
+            "Description",
+            "Created by",
+            "Approvers (Approved)",
+            "Approvers (Rejected/Remove)",
+        ]
+    }
+    fn format(
+        &self,
+        proposal: &Proposal,
+        _policy: &Policy,
+        info: &GovernanceOpsInfo,
+    ) -> Vec<String> {
+        let formatted_votes = format_votes(&proposal.votes);
+        let status: String = proposal.current_status().as_str().to_string();
+        let created_date: String =
+            format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+        let action = match info.action.as_str() {
+            "upgrade_self" => "Upgrade Self",
+            "upgrade_remote" => "Upgrade Remote",
+            "set_staking_contract" => "Set Staking Contract",
+            "deploy" => "Deploy",
+            _ => "Unknown",
+        };
         vec![
             proposal.id.to_string(),
             created_date,
             status,
-            recipient,
-            amount,
-            "NEAR".to_string(),
-            start_date,
-            end_date,
-            cliff_date,
-            allow_cancellation,
-            allow_staking,
-            created_by,
+            action.to_string(),
+            info.target
+                .clone()
+                .unwrap_or_else(|| "(this DAO)".to_string()),
+            info.method.clone().unwrap_or_default(),
+            info.code_hash.clone().unwrap_or_default(),
+            proposal.description.clone(),
+            proposal.proposer.clone(),
             formatted_votes.approved.join(", "),
             formatted_votes.rejected.join(", "),
         ]
     }
 }
 
-impl ProposalCsvFormatterSync<()> for DefaultFormatter {
+// "role: a, b" per role with entries, joined with "; "
+fn per_role<'a>(entries: impl Iterator<Item = (&'a str, &'a [String])>) -> String {
+    entries
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(role, values)| format!("{}: {}", role, values.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl ProposalCsvFormatterSync<MemberChangeInfo> for PolicyChangeProposalFormatter {
     fn headers(&self) -> Vec<&'static str> {
         vec![
             "ID",
             "Created Date",
             "Status",
+            "Action",
+            "Roles Added",
+            "Roles Removed",
+            "Members Added",
+            "Members Removed",
+            "Permissions Added",
+            "Permissions Removed",
+            "Vote Policy Changes",
+            "Parameter Changes",
             "Description",
-            "Kind",
             "Created by",
             "Approvers (Approved)",
             "Approvers (Rejected/Remove)",
         ]
     }
-    fn format(&self, proposal: &Proposal, policy: &Policy, _info: &()) -> Vec<String> {
+    fn format(&self, proposal: &Proposal, policy: &Policy, info: &MemberChangeInfo) -> Vec<String> {
         let formatted_votes = format_votes(&proposal.votes);
-        let status: String = get_status_display(
-            &proposal.status,
-            proposal.submission_time.0,
-            policy.proposal_period.0,
-            "Pending",
-        );
-        let kind = proposal.kind.clone();
-        let created_date: String = format_ns_timestamp_u64(proposal.submission_time.0);
-        let created_by = proposal.proposer.clone();
+        let status: String = proposal.current_status().as_str().to_string();
+        let created_date: String =
+            format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+        let diff = info.policy_diff(policy).unwrap_or_default();
+
+        // Added roles with their members, e.g. "admins (alice.near, bob.near)"
+        let roles_added = diff
+            .roles_added
+            .iter()
+            .map(|role| match role.kind.members() {
+                [] => role.name.clone(),
+                members => format!("{} ({})", role.name, members.join(", ")),
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let roles = &diff.roles_changed;
+        let describe = |vote_policy: &Option<VotePolicy>| {
+            vote_policy
+                .as_ref()
+                .map_or("none".to_string(), |vote_policy| vote_policy.describe())
+        };
+        let vote_policy_changes = diff
+            .default_vote_policy
+            .iter()
+            .map(|change| ("default".to_string(), change))
+            .chain(roles.iter().flat_map(|role| {
+                role.vote_policies.iter().map(move |change| {
+                    let label = change.label.as_deref().unwrap_or_default();
+                    (format!("{} {}", role.role, label), change)
+                })
+            }))
+            .map(|(name, change)| {
+                format!(
+                    "{}: {} -> {}",
+                    name,
+                    describe(&change.before),
+                    describe(&change.after)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let parameter_changes = diff
+            .parameters
+            .iter()
+            .map(|p| format!("{}: {} -> {}", p.name, p.before, p.after))
+            .collect::<Vec<_>>()
+            .join("; ");
+
         vec![
             proposal.id.to_string(),
             created_date,
             status,
+            info.action_name().to_string(),
+            roles_added,
+            diff.roles_removed.join(", "),
+            per_role(
+                roles
+                    .iter()
+                    .map(|role| (role.role.as_str(), role.members_added.as_slice())),
+            ),
+            per_role(
+                roles
+                    .iter()
+                    .map(|role| (role.role.as_str(), role.members_removed.as_slice())),
+            ),
+            per_role(
+                roles
+                    .iter()
+                    .map(|role| (role.role.as_str(), role.permissions_added.as_slice())),
+            ),
+            per_role(
+                roles
+                    .iter()
+                    .map(|role| (role.role.as_str(), role.permissions_removed.as_slice())),
+            ),
+            vote_policy_changes,
+            parameter_changes,
             proposal.description.clone(),
-            kind.to_string(),
-            created_by,
+            proposal.proposer.clone(),
             formatted_votes.approved.join(", "),
             formatted_votes.rejected.join(", "),
         ]
@@ -960,6 +2128,7 @@ impl ProposalCsvFormatterAsync<StakeDelegationInfo> for StakeDelegationProposalF
             "Amount",
             "Token",
             "Validator",
+            "Resolved Validator",
             "Created by",
             "Notes",
             "Approvers (Approved)",
@@ -969,10 +2138,10 @@ impl ProposalCsvFormatterAsync<StakeDelegationInfo> for StakeDelegationProposalF
 
     fn format<'a>(
         &'a self,
-        _client: &'a Arc<JsonRpcClient>,
-        _ft_metadata_cache: &'a FtMetadataCache,
+        client: &'a Arc<RpcPool>,
+        ft_metadata_cache: &'a FtMetadataCache,
         proposal: &'a Proposal,
-        policy: &'a Policy,
+        _policy: &'a Policy,
         info: &'a StakeDelegationInfo,
     ) -> BoxFuture<'a, Vec<String>> {
         async move {
@@ -1004,17 +2173,27 @@ impl ProposalCsvFormatterAsync<StakeDelegationInfo> for StakeDelegationProposalF
                 _ => "Unknown",
             };
 
-            let parsed_amount = format!("{}", normalize_token_amount(&info.amount, 24));
+            // NEAR, or the token deposited to or burnt at a liquid staking contract
+            let ft_metadata =
+                match get_ft_metadata_cache(client, ft_metadata_cache, &info.token).await {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        warn!(error = %e, "Error fetching ft metadata");
+                        FtMetadata::unknown(&info.token)
+                    }
+                };
+            let parsed_amount = normalize_token_amount(&info.amount, ft_metadata.decimals);
             let formatted_votes = format_votes(&proposal.votes);
-            let created_date = format_ns_timestamp_u64(proposal.submission_time.0);
-            let status: String = get_status_display(
-                &proposal.status,
-                proposal.submission_time.0,
-                policy.proposal_period.0,
-                "Pending",
-            );
+            let created_date =
+                format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+            let status: String = proposal.current_status().as_str().to_string();
             let created_by = proposal.proposer.clone();
-            let token = "NEAR".to_string();
+            let token = ft_metadata.symbol;
+            let resolved_validator = self
+                .staking_pool_cache
+                .resolve_validator(client, info)
+                .await
+                .unwrap_or_default();
 
             vec![
                 proposal.id.to_string(),
@@ -1025,6 +2204,7 @@ impl ProposalCsvFormatterAsync<StakeDelegationInfo> for StakeDelegationProposalF
                 parsed_amount,
                 token,
                 info.validator.clone(),
+                resolved_validator,
                 created_by,
                 notes,
                 formatted_votes.approved.join(", "),
@@ -1049,44 +2229,36 @@ impl ProposalCsvFormatterAsync<AssetExchangeInfo> for AssetExchangeProposalForma
             "Notes",
             "Approvers (Approved)",
             "Approvers (Rejected/Remove)",
+            "Min Receive Amount",
+            "Pool IDs",
         ]
     }
 
     fn format<'a>(
         &'a self,
-        client: &'a Arc<JsonRpcClient>,
+        client: &'a Arc<RpcPool>,
         ft_metadata_cache: &'a FtMetadataCache,
         proposal: &'a Proposal,
-        policy: &'a Policy,
-        _info: &'a AssetExchangeInfo,
+        _policy: &'a Policy,
+        info: &'a AssetExchangeInfo,
     ) -> BoxFuture<'a, Vec<String>> {
         async move {
             let proposal_id = proposal.id.to_string();
             let created_by = proposal.proposer.clone();
             let formatted_votes = format_votes(&proposal.votes);
 
-            let send_amount =
-                extract_from_description(&proposal.description, "amountIn").unwrap_or_default();
-            let send_token =
-                extract_from_description(&proposal.description, "tokenIn").unwrap_or_default();
-            let receive_token =
-                extract_from_description(&proposal.description, "tokenOut").unwrap_or_default();
-            let receive_amount =
-                extract_from_description(&proposal.description, "amountOut").unwrap_or_default();
+            let send_token = info.token_in.clone().unwrap_or_default();
+            let receive_token = info.token_out.clone().unwrap_or_default();
+            let receive_amount = info.amount_out.clone().unwrap_or_default();
             let notes =
                 extract_from_description(&proposal.description, "notes").unwrap_or_default();
-            let status: String = get_status_display(
-                &proposal.status,
-                proposal.submission_time.0,
-                policy.proposal_period.0,
-                "Pending",
-            );
+            let status: String = proposal.current_status().as_str().to_string();
             let ft_meta_send =
                 match get_ft_metadata_cache(&client, &ft_metadata_cache, &send_token).await {
                     Ok(metadata) => metadata,
                     Err(e) => {
-                        eprintln!("Error fetching send token ft metadata: {}", e);
-                        FtMetadata::empty()
+                        warn!(error = %e, "Error fetching send token ft metadata");
+                        FtMetadata::unknown(&send_token)
                     }
                 };
 
@@ -1094,11 +2266,30 @@ impl ProposalCsvFormatterAsync<AssetExchangeInfo> for AssetExchangeProposalForma
                 match get_ft_metadata_cache(&client, &ft_metadata_cache, &receive_token).await {
                     Ok(metadata) => metadata,
                     Err(e) => {
-                        eprintln!("Error fetching receive token ft metadata: {}", e);
-                        FtMetadata::empty()
+                        warn!(error = %e, "Error fetching receive token ft metadata");
+                        FtMetadata::unknown(&receive_token)
                     }
                 };
-            let created_date: String = format_ns_timestamp_u64(proposal.submission_time.0);
+            let created_date: String =
+                format_ns_timestamp_u64(proposal.submission_time.0, &self.date_format);
+            // Amounts from the swap args are in the smallest unit
+            let send_amount = match &info.amount_in {
+                Some(amount) if info.on_chain => {
+                    normalize_token_amount(amount, ft_meta_send.decimals)
+                }
+                amount => amount.clone().unwrap_or_default(),
+            };
+            let min_receive_amount = info
+                .min_amount_out
+                .as_deref()
+                .map(|amount| normalize_token_amount(amount, ft_meta_receive.decimals))
+                .unwrap_or_default();
+            let pool_ids = info
+                .pool_ids
+                .iter()
+                .map(|pool_id| pool_id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
             vec![
                 proposal_id,
                 created_date,
@@ -1111,12 +2302,74 @@ impl ProposalCsvFormatterAsync<AssetExchangeInfo> for AssetExchangeProposalForma
                 notes,
                 formatted_votes.approved.join(", "),
                 formatted_votes.rejected.join(", "),
+                min_receive_amount,
+                pool_ids,
             ]
         }
         .boxed()
     }
 }
 
+pub const TX_LOG_HEADERS: [&str; 3] = ["Creation Tx", "Approval Txs", "Vote Timestamps"];
+
+/// Columns for TX_LOG_HEADERS. The first logged transaction of the proposer is its creation,
+/// the later ones of each voter are matched to their current vote.
+pub fn format_tx_log(
+    proposal: &Proposal,
+    txs_log: &[TxMetadata],
+    date_format: &DateFormat,
+) -> Vec<String> {
+    let mut txs: Vec<&TxMetadata> = txs_log.iter().filter(|tx| tx.succeeded()).collect();
+    txs.sort_by_key(|tx| tx.block_height);
+
+    let creation_index = txs
+        .iter()
+        .position(|tx| tx.signer_id.as_str() == proposal.proposer);
+    let creation_tx = creation_index
+        .map(|index| txs[index].reciept_hash.to_string())
+        .unwrap_or_default();
+
+    let votes: Vec<&TxMetadata> = txs
+        .iter()
+        .enumerate()
+        .filter(|(index, tx)| {
+            Some(*index) != creation_index && proposal.votes.contains_key(tx.signer_id.as_str())
+        })
+        .map(|(_, tx)| *tx)
+        .collect();
+
+    let approval_txs: Vec<String> = votes
+        .iter()
+        .filter(|tx| proposal.votes.get(tx.signer_id.as_str()) == Some(&Vote::Approve))
+        .map(|tx| tx.reciept_hash.to_string())
+        .collect();
+
+    // A voter's last transaction is the one that cast the vote in the proposal
+    let mut vote_times: BTreeMap<&str, u64> = BTreeMap::new();
+    for tx in &votes {
+        vote_times.insert(tx.signer_id.as_str(), tx.timestamp);
+    }
+    let vote_timestamps: Vec<String> = vote_times
+        .into_iter()
+        .map(|(voter, timestamp)| {
+            format!(
+                "{}: {}",
+                voter,
+                format_ns_timestamp_u64(timestamp, date_format)
+            )
+        })
+        .collect();
+
+    vec![
+        creation_tx,
+        approval_txs.join(", "),
+        vote_timestamps.join(", "),
+    ]
+}
+
+// Receiver of ft_transfer_call payments to many recipients at once
+const BULK_PAYMENT_CONTRACT: &str = "bulkpayment.near";
+
 pub trait ProposalType {
     /// Attempts to extract proposal-specific information from a proposal.
     /// Returns None if the proposal doesn't match this type.
@@ -1134,19 +2387,207 @@ pub struct PaymentInfo {
     pub token: String,
     pub amount: String,
     pub is_lockup: bool,
+    /// msg of a Transfer kind or ft_transfer_call, passed to the receiver
+    pub msg: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LockupInfo;
 
-#[derive(Debug, Clone)]
-pub struct AssetExchangeInfo;
+/// Lockup creation as JSON, same fields as the lockup CSV. Timestamps in nanoseconds.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct LockupDetails {
+    pub owner_account_id: Option<String>,
+    /// yoctoNEAR deposited into the lockup
+    pub amount: String,
+    pub start_timestamp: Option<String>,
+    pub end_timestamp: Option<String>,
+    pub cliff_timestamp: Option<String>,
+    pub allow_cancellation: bool,
+    pub allow_staking: bool,
+}
+
+impl LockupDetails {
+    pub fn from_proposal(proposal: &Proposal) -> Self {
+        let args = extract_args(proposal);
+        let args = args.as_ref();
+        let vesting = args
+            .and_then(|a| a.vesting_schedule.as_ref())
+            .and_then(|v| v.vesting_schedule.as_ref());
+
+        // Simple lockup with a release duration first, then the vesting schedule
+        let (start_timestamp, end_timestamp, cliff_timestamp) =
+            match args.and_then(|a| a.lockup_timestamp.as_ref().zip(a.release_duration.as_ref())) {
+                Some((start, duration)) => {
+                    let end = match (start.parse::<i64>(), duration.parse::<i64>()) {
+                        (Ok(start_ns), Ok(duration_ns)) => start_ns
+                            .checked_add(duration_ns)
+                            .map(|end_ns| end_ns.to_string()),
+                        _ => None,
+                    };
+                    (Some(start.clone()), end, None)
+                }
+                None => (
+                    vesting.and_then(|vs| vs.start_timestamp.clone()),
+                    vesting.and_then(|vs| vs.end_timestamp.clone()),
+                    vesting.and_then(|vs| vs.cliff_timestamp.clone()),
+                ),
+            };
+
+        LockupDetails {
+            owner_account_id: args.and_then(|a| a.owner_account_id.clone()),
+            amount: extract_action_field(proposal, "deposit")
+                .unwrap_or("")
+                .to_string(),
+            start_timestamp,
+            end_timestamp,
+            cliff_timestamp,
+            allow_cancellation: args.and_then(|a| a.vesting_schedule.as_ref()).is_some(),
+            allow_staking: args
+                .and_then(|a| a.whitelist_account_id.as_ref())
+                .is_none_or(|id| id != "lockup-no-whitelist.near"),
+        }
+    }
+}
+
+/// Swap of an asset-exchange proposal. Values come from the ref.finance swap args when they
+/// can be decoded, the description fields (tokenIn, amountIn, ...) fill in the rest.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AssetExchangeInfo {
+    pub token_in: Option<String>,
+    pub token_out: Option<String>,
+    /// Smallest token unit when on_chain, whole tokens as written in the description otherwise
+    pub amount_in: Option<String>,
+    /// Expected output from the description, whole tokens
+    pub amount_out: Option<String>,
+    /// Smallest token unit, only known from the swap args
+    pub min_amount_out: Option<String>,
+    pub pool_ids: Vec<u64>,
+    /// Whether tokens and amount_in were decoded from the swap args
+    pub on_chain: bool,
+}
+
+// One hop of a ref.finance swap, as in the msg of ft_transfer_call or the args of swap
+#[derive(Deserialize)]
+struct RefSwapAction {
+    pool_id: u64,
+    token_in: String,
+    token_out: String,
+    #[serde(default)]
+    amount_in: Option<String>,
+    min_amount_out: String,
+}
+
+#[derive(Deserialize)]
+struct RefSwapActions {
+    actions: Vec<RefSwapAction>,
+}
+
+// Swap route of a FunctionCall: ft_transfer_call with the swap actions in msg, or a direct
+// swap call on the exchange with deposited tokens
+struct SwapRoute {
+    token_in: String,
+    token_out: String,
+    amount_in: u128,
+    min_amount_out: u128,
+    pool_ids: Vec<u64>,
+}
+
+fn decode_swap_route(function_call: &Value) -> Option<SwapRoute> {
+    let actions = function_call.get("actions")?.as_array()?;
+    actions.iter().find_map(|action| {
+        let args: Value = parse_args(action.get("args")?.as_str()?)?;
+        let (hops, amount_in) = match action.get("method_name")?.as_str()? {
+            "ft_transfer_call" => {
+                let msg = args.get("msg")?.as_str()?;
+                let hops = serde_json::from_str::<RefSwapActions>(msg).ok()?.actions;
+                let amount_in = args.get("amount")?.as_str()?.parse::<u128>().ok()?;
+                (hops, Some(amount_in))
+            }
+            "swap" => (
+                serde_json::from_value::<RefSwapActions>(args).ok()?.actions,
+                None,
+            ),
+            _ => return None,
+        };
+        let token_in = hops.first()?.token_in.clone();
+        let token_out = hops.last()?.token_out.clone();
+        // Parallel routes split the input and each end in the output token
+        let amount_in = amount_in.unwrap_or_else(|| {
+            hops.iter()
+                .filter(|hop| hop.token_in == token_in)
+                .filter_map(|hop| hop.amount_in.as_deref()?.parse::<u128>().ok())
+                .sum()
+        });
+        let min_amount_out = hops
+            .iter()
+            .filter(|hop| hop.token_out == token_out)
+            .filter_map(|hop| hop.min_amount_out.parse::<u128>().ok())
+            .sum();
+        Some(SwapRoute {
+            pool_ids: hops.iter().map(|hop| hop.pool_id).collect(),
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+        })
+    })
+}
 
 #[derive(Debug, Clone)]
 pub struct StakeDelegationInfo {
     pub amount: String,
     pub proposal_type: String,
     pub validator: String,
+    /// Token the amount is in, empty for NEAR
+    pub token: String,
+    /// Liquid staking contract, which is also the token it issues for the stake
+    pub pool_token: Option<String>,
+}
+
+impl StakeDelegationInfo {
+    /// Lockup contract whose selected staking pool the proposal delegates to. None for
+    /// select_staking_pool, which names the pool itself.
+    pub fn lockup_account(&self) -> Option<&str> {
+        (self.validator.contains("lockup.near") && self.proposal_type != "whitelist")
+            .then_some(self.validator.as_str())
+    }
+}
+
+/// Contract code upgrades and staking contract changes, which security reviewers check
+/// separately
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GovernanceOpsInfo {
+    /// upgrade_self, upgrade_remote, set_staking_contract or deploy (a FunctionCall that
+    /// deploys or upgrades code)
+    pub action: String,
+    /// Contract getting new code or set as staking contract, null for upgrade_self, which
+    /// upgrades the DAO itself
+    pub target: Option<String>,
+    /// Method called on the target, for upgrade_remote and deploy
+    pub method: Option<String>,
+    /// Base58 hash of the new code, as given in the proposal or computed from code passed
+    /// as call args
+    pub code_hash: Option<String>,
+}
+
+/// Membership and policy (governance) changes
+#[derive(Debug, Clone)]
+pub struct MemberChangeInfo {
+    /// One of add_member, remove_member, change_policy, add_or_update_role, remove_role,
+    /// update_default_vote_policy or update_parameters
+    pub action: String,
+    pub member: Option<String>,
+    pub role: Option<String>,
+    /// Full role list of a ChangePolicy proposal
+    pub proposed_roles: Option<Vec<RolePermission>>,
+    /// Role definition of a ChangePolicyAddOrUpdateRole proposal
+    pub updated_role: Option<RolePermission>,
+    /// Default vote policy set by a ChangePolicy or ChangePolicyUpdateDefaultVotePolicy
+    /// proposal
+    pub default_vote_policy: Option<VotePolicy>,
+    /// Bonds and periods set by a ChangePolicy or ChangePolicyUpdateParameters proposal
+    pub parameters: Option<PolicyParameters>,
 }
 
 impl ProposalType for PaymentInfo {
@@ -1176,6 +2617,11 @@ impl ProposalType for PaymentInfo {
                 token,
                 amount,
                 is_lockup: false,
+                msg: transfer_val
+                    .get("msg")
+                    .and_then(|v| v.as_str())
+                    .filter(|msg| !msg.is_empty())
+                    .map(|msg| msg.to_string()),
             });
         }
         // FunctionCall kind
@@ -1196,56 +2642,48 @@ impl ProposalType for PaymentInfo {
                     .and_then(|a| a.get("method_name"))
                     .and_then(|m| m.as_str())
                     == Some("ft_withdraw")
-            {
-                if let Some(args_b64) = actions
+                && let Some(args_b64) = actions
                     .get(0)
                     .and_then(|a| a.get("args"))
                     .and_then(|a| a.as_str())
-                {
-                    if let Ok(decoded_bytes) =
-                        base64::engine::general_purpose::STANDARD.decode(args_b64)
-                    {
-                        if let Ok(json_args) =
-                            serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
-                        {
-                            let token = json_args
-                                .get("token")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let amount = json_args
-                                .get("amount")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let receiver = if let Some(memo) =
-                                json_args.get("memo").and_then(|v| v.as_str())
-                            {
-                                if memo.contains("WITHDRAW_TO:") {
-                                    memo.split("WITHDRAW_TO:").nth(1).unwrap_or("").to_string()
-                                } else {
-                                    json_args
-                                        .get("receiver_id")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("")
-                                        .to_string()
-                                }
-                            } else {
-                                json_args
-                                    .get("receiver_id")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("")
-                                    .to_string()
-                            };
-                            return Some(PaymentInfo {
-                                receiver,
-                                token,
-                                amount,
-                                is_lockup: false,
-                            });
-                        }
+                && let Ok(decoded_bytes) =
+                    base64::engine::general_purpose::STANDARD.decode(args_b64)
+                && let Ok(json_args) = serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
+            {
+                let token = json_args
+                    .get("token")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let amount = json_args
+                    .get("amount")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let receiver = if let Some(memo) = json_args.get("memo").and_then(|v| v.as_str()) {
+                    if memo.contains("WITHDRAW_TO:") {
+                        memo.split("WITHDRAW_TO:").nth(1).unwrap_or("").to_string()
+                    } else {
+                        json_args
+                            .get("receiver_id")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string()
                     }
-                }
+                } else {
+                    json_args
+                        .get("receiver_id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string()
+                };
+                return Some(PaymentInfo {
+                    receiver,
+                    token,
+                    amount,
+                    is_lockup: false,
+                    msg: None,
+                });
             }
             // Lockup contract transfer
             let method_name = actions
@@ -1253,42 +2691,38 @@ impl ProposalType for PaymentInfo {
                 .and_then(|a| a.get("method_name"))
                 .and_then(|m| m.as_str())
                 .unwrap_or("");
-            if method_name == "transfer" && receiver_id.contains("lockup.near") {
-                if let Some(args_b64) = actions
+            if method_name == "transfer"
+                && receiver_id.contains("lockup.near")
+                && let Some(args_b64) = actions
                     .get(0)
                     .and_then(|a| a.get("args"))
                     .and_then(|a| a.as_str())
-                {
-                    if let Ok(decoded_bytes) =
-                        base64::engine::general_purpose::STANDARD.decode(args_b64)
-                    {
-                        if let Ok(json_args) =
-                            serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
-                        {
-                            let token = json_args
-                                .get("token_id")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let amount = json_args
-                                .get("amount")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let receiver = json_args
-                                .get("receiver_id")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            return Some(PaymentInfo {
-                                receiver,
-                                token,
-                                amount,
-                                is_lockup: true,
-                            });
-                        }
-                    }
-                }
+                && let Ok(decoded_bytes) =
+                    base64::engine::general_purpose::STANDARD.decode(args_b64)
+                && let Ok(json_args) = serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
+            {
+                let token = json_args
+                    .get("token_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let amount = json_args
+                    .get("amount")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let receiver = json_args
+                    .get("receiver_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                return Some(PaymentInfo {
+                    receiver,
+                    token,
+                    amount,
+                    is_lockup: true,
+                    msg: None,
+                });
             }
             // NEARN requests: storage_deposit + ft_transfer
             if actions.len() >= 2
@@ -1308,31 +2742,28 @@ impl ProposalType for PaymentInfo {
                     .get(1)
                     .and_then(|a| a.get("args"))
                     .and_then(|a| a.as_str())
-                {
-                    if let Ok(decoded_bytes) =
+                    && let Ok(decoded_bytes) =
                         base64::engine::general_purpose::STANDARD.decode(args_b64)
-                    {
-                        if let Ok(json_args) =
-                            serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
-                        {
-                            let receiver = json_args
-                                .get("receiver_id")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let amount = json_args
-                                .get("amount")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            return Some(PaymentInfo {
-                                receiver,
-                                token,
-                                amount,
-                                is_lockup: false,
-                            });
-                        }
-                    }
+                    && let Ok(json_args) =
+                        serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
+                {
+                    let receiver = json_args
+                        .get("receiver_id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let amount = json_args
+                        .get("amount")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    return Some(PaymentInfo {
+                        receiver,
+                        token,
+                        amount,
+                        is_lockup: false,
+                        msg: None,
+                    });
                 }
             }
             // Standard ft_transfer
@@ -1347,31 +2778,69 @@ impl ProposalType for PaymentInfo {
                     .get(0)
                     .and_then(|a| a.get("args"))
                     .and_then(|a| a.as_str())
-                {
-                    if let Ok(decoded_bytes) =
+                    && let Ok(decoded_bytes) =
                         base64::engine::general_purpose::STANDARD.decode(args_b64)
-                    {
-                        if let Ok(json_args) =
-                            serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
-                        {
-                            let receiver = json_args
-                                .get("receiver_id")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let amount = json_args
-                                .get("amount")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            return Some(PaymentInfo {
-                                receiver,
-                                token,
-                                amount,
-                                is_lockup: false,
-                            });
-                        }
-                    }
+                    && let Ok(json_args) =
+                        serde_json::from_slice::<serde_json::Value>(&decoded_bytes)
+                {
+                    let receiver = json_args
+                        .get("receiver_id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let amount = json_args
+                        .get("amount")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    return Some(PaymentInfo {
+                        receiver,
+                        token,
+                        amount,
+                        is_lockup: false,
+                        msg: None,
+                    });
+                }
+            }
+            // Single ft_transfer_call, optionally preceded by storage_deposit. Bulk payments
+            // pass the whole recipient list in msg and aren't a single payment.
+            let (_, calls): (Vec<_>, Vec<_>) = actions.iter().partition(|action| {
+                action.get("method_name").and_then(|m| m.as_str()) == Some("storage_deposit")
+            });
+            if let [call] = calls.as_slice()
+                && call.get("method_name").and_then(|m| m.as_str()) == Some("ft_transfer_call")
+                && let Some(json_args) = call
+                    .get("args")
+                    .and_then(|a| a.as_str())
+                    .and_then(parse_args::<serde_json::Value>)
+            {
+                let receiver = json_args
+                    .get("receiver_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                // Swaps and liquid staking deposits move the tokens to the
+                // DAO's own position, they aren't paid out
+                if receiver != BULK_PAYMENT_CONTRACT
+                    && decode_swap_route(function_call).is_none()
+                    && !get_config().tokens.is_liquid_staking(&receiver)
+                {
+                    let amount = json_args
+                        .get("amount")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    return Some(PaymentInfo {
+                        receiver,
+                        token: receiver_id.to_string(),
+                        amount,
+                        is_lockup: false,
+                        msg: json_args
+                            .get("msg")
+                            .and_then(|v| v.as_str())
+                            .filter(|msg| !msg.is_empty())
+                            .map(|msg| msg.to_string()),
+                    });
                 }
             }
         }
@@ -1411,14 +2880,35 @@ impl ProposalType for LockupInfo {
 
 impl ProposalType for AssetExchangeInfo {
     fn from_proposal(proposal: &Proposal) -> Option<Self> {
-        if let Some(_function_call) = proposal.kind.get("FunctionCall") {
-            if extract_from_description(&proposal.description, "proposalaction")
-                == Some("asset-exchange".to_string())
-            {
-                return Some(AssetExchangeInfo);
-            }
+        let function_call = proposal.kind.get("FunctionCall")?;
+        let route = decode_swap_route(function_call);
+        let is_asset_exchange = extract_from_description(&proposal.description, "proposalaction")
+            == Some("asset-exchange".to_string());
+        if route.is_none() && !is_asset_exchange {
+            return None;
         }
-        None
+
+        let description = |key: &str| extract_from_description(&proposal.description, key);
+        Some(match route {
+            Some(route) => AssetExchangeInfo {
+                token_in: Some(route.token_in),
+                token_out: Some(route.token_out),
+                amount_in: Some(route.amount_in.to_string()),
+                amount_out: description("amountOut"),
+                min_amount_out: Some(route.min_amount_out.to_string()),
+                pool_ids: route.pool_ids,
+                on_chain: true,
+            },
+            None => AssetExchangeInfo {
+                token_in: description("tokenIn"),
+                token_out: description("tokenOut"),
+                amount_in: description("amountIn"),
+                amount_out: description("amountOut"),
+                min_amount_out: None,
+                pool_ids: Vec::new(),
+                on_chain: false,
+            },
+        })
     }
 
     fn category_name() -> &'static str {
@@ -1426,9 +2916,73 @@ impl ProposalType for AssetExchangeInfo {
     }
 }
 
+// Deposits to and withdrawals from a liquid staking contract, called directly or, for
+// deposits of a token such as wNEAR, through ft_transfer_call
+fn liquid_staking_info(proposal: &Proposal, function_call: &Value) -> Option<StakeDelegationInfo> {
+    let receiver = function_call.get("receiver_id")?.as_str()?;
+    let action = function_call.get("actions")?.as_array()?.first()?;
+    let method_name = action.get("method_name")?.as_str()?;
+    let args: Value = action
+        .get("args")
+        .and_then(Value::as_str)
+        .and_then(|args| general_purpose::STANDARD.decode(args).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let arg = |key: &str| args.get(key).and_then(Value::as_str).map(str::to_string);
+    let tokens = &get_config().tokens;
+
+    if method_name == "ft_transfer_call" {
+        let pool = arg("receiver_id").filter(|pool| tokens.is_liquid_staking(pool))?;
+        return Some(StakeDelegationInfo {
+            amount: arg("amount").unwrap_or_default(),
+            proposal_type: "stake".to_string(),
+            validator: pool.clone(),
+            token: receiver.to_string(),
+            pool_token: Some(pool),
+        });
+    }
+    if !tokens.is_liquid_staking(receiver) {
+        return None;
+    }
+
+    let (proposal_type, amount, token) = match method_name {
+        "deposit_and_stake" => (
+            "stake",
+            action
+                .get("deposit")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            String::new(),
+        ),
+        "unstake" => ("unstake", arg("amount"), String::new()),
+        // Burns the pool token for NEAR right away, the amount is in the pool token
+        "liquid_unstake" => (
+            "unstake",
+            arg("st_near_to_burn").or_else(|| arg("amount")),
+            receiver.to_string(),
+        ),
+        "withdraw" | "withdraw_unstaked" | "withdraw_all" => (
+            "withdraw",
+            arg("amount").or_else(|| extract_from_description(&proposal.description, "amount")),
+            String::new(),
+        ),
+        _ => return None,
+    };
+    Some(StakeDelegationInfo {
+        amount: amount.unwrap_or_default(),
+        proposal_type: proposal_type.to_string(),
+        validator: receiver.to_string(),
+        token,
+        pool_token: Some(receiver.to_string()),
+    })
+}
+
 impl ProposalType for StakeDelegationInfo {
     fn from_proposal(proposal: &Proposal) -> Option<Self> {
         if let Some(function_call) = proposal.kind.get("FunctionCall") {
+            if let Some(info) = liquid_staking_info(proposal, function_call) {
+                return Some(info);
+            }
             let proposal_action = extract_from_description(&proposal.description, "proposalaction");
             let is_stake_request =
                 extract_from_description(&proposal.description, "isStakeRequest").is_some()
@@ -1517,6 +3071,8 @@ impl ProposalType for StakeDelegationInfo {
                     amount,
                     proposal_type: proposal_type.to_string(),
                     validator: validator_account,
+                    token: String::new(),
+                    pool_token: None,
                 });
             }
         }
@@ -1527,3 +3083,247 @@ impl ProposalType for StakeDelegationInfo {
         "stake-delegation"
     }
 }
+
+impl ProposalType for MemberChangeInfo {
+    fn from_proposal(proposal: &Proposal) -> Option<Self> {
+        let (kind, args) = proposal.kind.as_object()?.iter().next()?;
+        let action = match kind.as_str() {
+            "AddMemberToRole" => "add_member",
+            "RemoveMemberFromRole" => "remove_member",
+            "ChangePolicy" => "change_policy",
+            "ChangePolicyAddOrUpdateRole" => "add_or_update_role",
+            "ChangePolicyRemoveRole" => "remove_role",
+            "ChangePolicyUpdateDefaultVotePolicy" => "update_default_vote_policy",
+            "ChangePolicyUpdateParameters" => "update_parameters",
+            _ => return None,
+        };
+        let get_str = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+        let updated_role: Option<RolePermission> = match action {
+            "add_or_update_role" => args
+                .get("role")
+                .and_then(|role| serde_json::from_value(role.clone()).ok()),
+            _ => None,
+        };
+        // Legacy policies are a plain list of council members and have no roles to compare
+        let proposed_roles: Option<Vec<RolePermission>> = match action {
+            "change_policy" => args
+                .get("policy")
+                .and_then(|policy| policy.get("roles"))
+                .and_then(|roles| serde_json::from_value(roles.clone()).ok()),
+            _ => None,
+        };
+        // Legacy policies are arrays, which would deserialize into structs field by field
+        let default_vote_policy: Option<VotePolicy> = match action {
+            "change_policy" => args
+                .get("policy")
+                .and_then(|policy| policy.get("default_vote_policy")),
+            "update_default_vote_policy" => args.get("vote_policy"),
+            _ => None,
+        }
+        .filter(|vote_policy| vote_policy.is_object())
+        .and_then(|vote_policy| serde_json::from_value(vote_policy.clone()).ok());
+        let parameters: Option<PolicyParameters> = match action {
+            "change_policy" => args.get("policy"),
+            "update_parameters" => args.get("parameters"),
+            _ => None,
+        }
+        .filter(|parameters| parameters.is_object())
+        .and_then(|parameters| serde_json::from_value(parameters.clone()).ok());
+        let role = match action {
+            "add_or_update_role" => updated_role.as_ref().map(|role| role.name.clone()),
+            _ => get_str("role"),
+        };
+
+        Some(MemberChangeInfo {
+            action: action.to_string(),
+            member: get_str("member_id"),
+            role,
+            proposed_roles,
+            updated_role,
+            default_vote_policy,
+            parameters,
+        })
+    }
+
+    fn category_name() -> &'static str {
+        "members"
+    }
+}
+
+// FunctionCall methods that deploy or upgrade contract code
+const CODE_METHODS: [&str; 7] = [
+    "deploy",
+    "deploy_contract",
+    "upgrade",
+    "upgrade_contract",
+    "update_contract",
+    "store_contract",
+    "migrate",
+];
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+// Hash named in the args or, when the args are the code itself, the hash of that code
+fn args_code_hash(args_base64: &str) -> Option<String> {
+    let bytes = general_purpose::STANDARD.decode(args_base64).ok()?;
+    if bytes.starts_with(WASM_MAGIC) {
+        return Some(near_primitives::hash::hash(&bytes).to_string());
+    }
+    let args: Value = serde_json::from_slice(&bytes).ok()?;
+    ["code_hash", "hash"]
+        .iter()
+        .find_map(|key| args.get(key)?.as_str().map(str::to_string))
+}
+
+impl ProposalType for GovernanceOpsInfo {
+    fn from_proposal(proposal: &Proposal) -> Option<Self> {
+        let (kind, args) = proposal.kind.as_object()?.iter().next()?;
+        let get_str = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+        let (action, target, method, code_hash) = match kind.as_str() {
+            "UpgradeSelf" => ("upgrade_self", None, None, get_str("hash")),
+            "UpgradeRemote" => (
+                "upgrade_remote",
+                get_str("receiver_id"),
+                get_str("method_name"),
+                get_str("hash"),
+            ),
+            "SetStakingContract" => ("set_staking_contract", get_str("staking_id"), None, None),
+            "FunctionCall" => {
+                let action = args.get("actions")?.as_array()?.iter().find(|action| {
+                    action
+                        .get("method_name")
+                        .and_then(|m| m.as_str())
+                        .is_some_and(|m| CODE_METHODS.contains(&m))
+                })?;
+                (
+                    "deploy",
+                    get_str("receiver_id"),
+                    action
+                        .get("method_name")
+                        .and_then(|m| m.as_str())
+                        .map(str::to_string),
+                    action
+                        .get("args")
+                        .and_then(|a| a.as_str())
+                        .and_then(args_code_hash),
+                )
+            }
+            _ => return None,
+        };
+
+        Some(GovernanceOpsInfo {
+            action: action.to_string(),
+            target,
+            method,
+            code_hash,
+        })
+    }
+
+    fn category_name() -> &'static str {
+        "governance-ops"
+    }
+}
+
+impl MemberChangeInfo {
+    /// Roles of the policy after this proposal is applied to `current`,
+    /// None when the proposal doesn't touch roles
+    fn proposed_roles(&self, current: &[RolePermission]) -> Option<Vec<RolePermission>> {
+        if let Some(roles) = &self.proposed_roles {
+            return Some(roles.clone());
+        }
+
+        let mut roles = current.to_vec();
+        match self.action.as_str() {
+            "add_or_update_role" => {
+                let updated = self.updated_role.clone()?;
+                match roles.iter_mut().find(|r| r.name == updated.name) {
+                    Some(existing) => *existing = updated,
+                    None => roles.push(updated),
+                }
+            }
+            "remove_role" => {
+                let role = self.role.as_deref()?;
+                roles.retain(|r| r.name != role);
+            }
+            "add_member" | "remove_member" => {
+                let (member, role) = (self.member.clone()?, self.role.as_deref()?);
+                let role = roles.iter_mut().find(|r| r.name == role)?;
+                if let RoleKind::Group(members) = &mut role.kind {
+                    members.retain(|m| *m != member);
+                    if self.action == "add_member" {
+                        members.push(member);
+                    }
+                }
+            }
+            _ => return None,
+        }
+        Some(roles)
+    }
+
+    /// Policy after this proposal is applied to `current`, None when the proposal changes
+    /// nothing that can be compared
+    fn proposed_policy(&self, current: &Policy) -> Option<Policy> {
+        let roles = self.proposed_roles(&current.roles);
+        if roles.is_none() && self.default_vote_policy.is_none() && self.parameters.is_none() {
+            return None;
+        }
+
+        let mut policy = current.clone();
+        if let Some(roles) = roles {
+            policy.roles = roles;
+        }
+        if let Some(vote_policy) = &self.default_vote_policy {
+            policy.default_vote_policy = vote_policy.clone();
+        }
+        if let Some(parameters) = &self.parameters {
+            if let Some(bond) = &parameters.proposal_bond {
+                policy.proposal_bond = bond.clone();
+            }
+            if let Some(period) = parameters.proposal_period {
+                policy.proposal_period = period;
+            }
+            if let Some(bond) = &parameters.bounty_bond {
+                policy.bounty_bond = bond.clone();
+            }
+            if let Some(period) = parameters.bounty_forgiveness_period {
+                policy.bounty_forgiveness_period = period;
+            }
+        }
+        Some(policy)
+    }
+
+    /// Structured changes relative to the DAO's current policy, None for legacy policies.
+    /// Proposals that were already applied show no changes.
+    pub fn policy_diff(&self, policy: &Policy) -> Option<PolicyDiff> {
+        self.proposed_policy(policy)
+            .map(|proposed| diff_policies(policy, &proposed))
+    }
+
+    /// Summary of the policy_diff, one line per change
+    pub fn policy_changes(&self, policy: &Policy) -> Vec<String> {
+        self.policy_diff(policy)
+            .map(|diff| diff.summary())
+            .unwrap_or_default()
+    }
+
+    /// Whether the proposal changes the policy itself rather than a role's members
+    pub fn is_policy_change(&self) -> bool {
+        !matches!(self.action.as_str(), "add_member" | "remove_member")
+    }
+
+    /// Action as shown in CSV exports, e.g. "Add Member"
+    pub fn action_name(&self) -> &'static str {
+        match self.action.as_str() {
+            "add_member" => "Add Member",
+            "remove_member" => "Remove Member",
+            "change_policy" => "Change Policy",
+            "add_or_update_role" => "Add or Update Role",
+            "remove_role" => "Remove Role",
+            "update_default_vote_policy" => "Update Default Vote Policy",
+            "update_parameters" => "Update Parameters",
+            _ => "Unknown",
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::{Result, bail};
+use near_primitives::types::AccountId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::cache::{
+    CachedFtMetadata, CachedProposal, CachedProposals, FtMetadataCache, ProposalCache,
+    ProposalStore,
+};
+use crate::scraper::{
+    AssetExchangeInfo, DaoConfig, FtMetadata, PaymentInfo, Policy, Proposal, ProposalType,
+    StakeDelegationInfo, StateVersion, TokenId, TxMetadata,
+};
+
+/// Bumped whenever DaoSnapshot changes incompatibly, imports of other formats are refused
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Portable copy of everything cached about a DAO, written by GET /admin/export/<dao_id>
+/// and loaded by POST /admin/import
+#[derive(Serialize, Deserialize)]
+pub struct DaoSnapshot {
+    pub format: u32,
+    pub dao_id: String,
+    /// Unix seconds the DAO data was fetched from RPC, imports keep its age
+    pub fetched_at: u64,
+    pub version: StateVersion,
+    pub last_proposal_id: u64,
+    pub proposals: Vec<Proposal>,
+    pub policy: Policy,
+    pub config: DaoConfig,
+    /// Transaction logs of the proposals cached so far, keyed by proposal id
+    pub txs_logs: BTreeMap<u64, ProposalTxsLog>,
+    /// Metadata of the tokens the proposals transfer or swap, keyed by token contract
+    pub ft_metadata: BTreeMap<String, FtMetadata>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProposalTxsLog {
+    pub txs_log: Vec<TxMetadata>,
+    /// Block the backfill continues from, null once the log is complete
+    pub backfill_block: Option<u64>,
+}
+
+/// Entries loaded by restore_dao_snapshot, per cache
+#[derive(Serialize, Debug)]
+pub struct ImportedEntries {
+    pub dao_id: String,
+    pub proposals: usize,
+    pub txs_logs: usize,
+    /// Token metadata actually cached, other DAOs' tokens and unparsable ids are skipped
+    pub ft_metadata: usize,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+// Instant of a unix timestamp, now for timestamps in the future or before the boot
+fn instant_at(unix_secs: u64) -> Instant {
+    let age = Duration::from_secs(unix_now().saturating_sub(unix_secs));
+    Instant::now().checked_sub(age).unwrap_or_else(Instant::now)
+}
+
+// NEP-141 contracts the proposals transfer, stake or swap, NEAR and bridged tokens have no
+// cached metadata
+fn proposal_tokens(proposals: &[Proposal]) -> Vec<AccountId> {
+    proposals
+        .iter()
+        .flat_map(|proposal| {
+            let payment = PaymentInfo::from_proposal(proposal).map(|payment| payment.token);
+            let stake = StakeDelegationInfo::from_proposal(proposal).map(|stake| stake.token);
+            let exchange = AssetExchangeInfo::from_proposal(proposal)
+                .map(|info| [info.token_in, info.token_out])
+                .unwrap_or_default();
+            payment
+                .into_iter()
+                .chain(stake)
+                .chain(exchange.into_iter().flatten())
+        })
+        .filter_map(|token| match TokenId::parse(&token) {
+            Ok(TokenId::Nep141(token_id)) => Some(token_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Snapshot of a cached DAO together with its cached transaction logs and token metadata
+pub fn dao_snapshot(
+    dao_id: &AccountId,
+    cached: &CachedProposals,
+    proposal_cache: &ProposalCache,
+    ft_metadata_cache: &FtMetadataCache,
+) -> DaoSnapshot {
+    let txs_logs = proposal_cache
+        .iter()
+        .filter(|cached| cached.key().0 == dao_id.as_str())
+        .map(|cached| {
+            (
+                cached.key().1,
+                ProposalTxsLog {
+                    txs_log: cached.txs_log.clone(),
+                    backfill_block: cached.backfill_block,
+                },
+            )
+        })
+        .collect();
+
+    let ft_metadata = proposal_tokens(&cached.proposals)
+        .into_iter()
+        .filter_map(|token_id| {
+            let cached = ft_metadata_cache.get(&token_id)?;
+            Some((token_id.to_string(), cached.metadata.clone()))
+        })
+        .collect();
+
+    DaoSnapshot {
+        format: SNAPSHOT_FORMAT,
+        dao_id: dao_id.to_string(),
+        fetched_at: unix_now().saturating_sub(cached.last_updated.elapsed().as_secs()),
+        version: cached.version.clone(),
+        last_proposal_id: cached.last_proposal_id,
+        proposals: cached.proposals.to_vec(),
+        policy: cached.policy.clone(),
+        config: cached.config.clone(),
+        txs_logs,
+        ft_metadata,
+    }
+}
+
+impl DaoSnapshot {
+    /// The DAO of a snapshot this build can load
+    pub fn validate(&self) -> Result<AccountId> {
+        if self.format != SNAPSHOT_FORMAT {
+            bail!(
+                "Snapshot format {} is not supported, expected {}",
+                self.format,
+                SNAPSHOT_FORMAT
+            );
+        }
+        Ok(self.dao_id.parse()?)
+    }
+}
+
+/// Loads a snapshot checked by DaoSnapshot::validate into the caches, adding to the
+/// DAO's entries. The entries keep the age they had when exported, so expired data is
+/// refreshed as usual.
+pub fn restore_dao_snapshot(
+    dao_id: &AccountId,
+    snapshot: DaoSnapshot,
+    store: &ProposalStore,
+    proposal_cache: &ProposalCache,
+    ft_metadata_cache: &FtMetadataCache,
+) -> ImportedEntries {
+    let last_updated = instant_at(snapshot.fetched_at);
+
+    let mut imported = ImportedEntries {
+        dao_id: dao_id.to_string(),
+        proposals: snapshot.proposals.len(),
+        txs_logs: snapshot.txs_logs.len(),
+        ft_metadata: 0,
+    };
+
+    let proposals: HashMap<u64, &Proposal> = snapshot
+        .proposals
+        .iter()
+        .map(|proposal| (proposal.id, proposal))
+        .collect();
+    for (proposal_id, txs_log) in snapshot.txs_logs {
+        // Logs of proposals missing from the snapshot are kept, their proposal is
+        // fetched on first use
+        let cached = match proposals.get(&proposal_id) {
+            Some(proposal) => CachedProposal {
+                proposal: (*proposal).clone(),
+                last_updated,
+                txs_log: txs_log.txs_log,
+                backfill_block: txs_log.backfill_block,
+            },
+            None => CachedProposal::restored(txs_log.txs_log, txs_log.backfill_block),
+        };
+        proposal_cache.insert((dao_id.to_string(), proposal_id), cached);
+    }
+
+    // The metadata cache is shared by every DAO, so only the tokens of the DAO's own
+    // proposals are taken, and not over fresher metadata
+    let tokens: HashSet<AccountId> = proposal_tokens(&snapshot.proposals).into_iter().collect();
+    for (token_id, metadata) in snapshot.ft_metadata {
+        let Ok(token_id) = token_id.parse::<AccountId>() else {
+            continue;
+        };
+        let fresher = ft_metadata_cache
+            .get(&token_id)
+            .is_some_and(|cached| cached.last_updated > last_updated);
+        if !tokens.contains(&token_id) || fresher {
+            continue;
+        }
+        imported.ft_metadata += 1;
+        ft_metadata_cache.insert(
+            token_id,
+            CachedFtMetadata {
+                metadata,
+                last_updated,
+            },
+        );
+    }
+
+    store.insert(
+        dao_id.to_string(),
+        CachedProposals {
+            proposals: snapshot.proposals.into(),
+            policy: snapshot.policy,
+            config: snapshot.config,
+            last_updated,
+            version: snapshot.version,
+            last_proposal_id: snapshot.last_proposal_id,
+        },
+    );
+
+    imported
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use near_primitives::types::AccountId;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::cache::NotADao;
+use crate::config::{ProposalSourceKind, get_config};
+use crate::rpc_client::RpcPool;
+use crate::scraper::{
+    ActionLog, DaoConfig, Policy, Proposal, StateVersion, TxMetadata, delta_ranges,
+    fetch_block_timestamp, fetch_config, fetch_contract_version, fetch_policy, fetch_proposal,
+    fetch_proposal_log_txs, fetch_proposals_delta, merge_delta,
+};
+
+/// Backend the DAO cache reads proposals, policies and configs from. The cache and the
+/// routes only go through this, so another backend can replace RPC without touching them.
+#[rocket::async_trait]
+pub trait ProposalSource: Send + Sync {
+    /// All proposals of a DAO sorted by id, and its last proposal id. `cached` and
+    /// `last_proposal_id` are the result of the previous fetch, empty and 0 for the first,
+    /// so a backend can skip proposals that can't have changed.
+    async fn proposals(
+        &self,
+        dao_id: &AccountId,
+        cached: &[Proposal],
+        last_proposal_id: u64,
+    ) -> Result<(Vec<Proposal>, u64)>;
+
+    async fn proposal(&self, dao_id: &AccountId, proposal_id: u64) -> Result<Proposal>;
+
+    async fn policy(&self, dao_id: &AccountId) -> Result<Policy>;
+
+    async fn config(&self, dao_id: &AccountId) -> Result<DaoConfig>;
+
+    async fn version(&self, dao_id: &AccountId) -> Result<StateVersion>;
+
+    /// Calls on a proposal after `block_height_limit`, as fetch_proposal_log_txs returns
+    /// them: walking back from `start_block` at most `max_pages` action log pages, with
+    /// the block to resume from when older history remains
+    async fn proposal_txs(
+        &self,
+        dao_id: &AccountId,
+        proposal_id: u64,
+        block_height_limit: u64,
+        start_block: Option<u64>,
+        max_pages: usize,
+    ) -> Result<(Vec<TxMetadata>, Option<u64>)>;
+
+    /// Timestamp of a block in nanoseconds
+    async fn block_timestamp(&self, block_height: u64) -> Result<u64>;
+}
+
+/// The DAO contracts themselves, through the shared RPC pool
+pub struct RpcSource {
+    client: Arc<RpcPool>,
+}
+
+impl RpcSource {
+    pub fn new(client: Arc<RpcPool>) -> Self {
+        RpcSource { client }
+    }
+}
+
+#[rocket::async_trait]
+impl ProposalSource for RpcSource {
+    async fn proposals(
+        &self,
+        dao_id: &AccountId,
+        cached: &[Proposal],
+        last_proposal_id: u64,
+    ) -> Result<(Vec<Proposal>, u64)> {
+        fetch_proposals_delta(&self.client, dao_id, cached, last_proposal_id).await
+    }
+
+    async fn proposal(&self, dao_id: &AccountId, proposal_id: u64) -> Result<Proposal> {
+        fetch_proposal(&self.client, dao_id, proposal_id).await
+    }
+
+    async fn policy(&self, dao_id: &AccountId) -> Result<Policy> {
+        fetch_policy(&self.client, dao_id).await
+    }
+
+    async fn config(&self, dao_id: &AccountId) -> Result<DaoConfig> {
+        fetch_config(&self.client, dao_id).await
+    }
+
+    async fn version(&self, dao_id: &AccountId) -> Result<StateVersion> {
+        fetch_contract_version(&self.client, dao_id).await
+    }
+
+    async fn proposal_txs(
+        &self,
+        dao_id: &AccountId,
+        proposal_id: u64,
+        block_height_limit: u64,
+        start_block: Option<u64>,
+        max_pages: usize,
+    ) -> Result<(Vec<TxMetadata>, Option<u64>)> {
+        fetch_proposal_log_txs(
+            &self.client,
+            dao_id,
+            proposal_id,
+            block_height_limit,
+            start_block,
+            max_pages,
+        )
+        .await
+    }
+
+    async fn block_timestamp(&self, block_height: u64) -> Result<u64> {
+        fetch_block_timestamp(&self.client, block_height).await
+    }
+}
+
+/// Recorded DAOs read from `<dir>/<dao_id>/proposals.json`, `policy.json` and
+/// `config.json`, in the JSON the contract's view methods return. DAOs without a
+/// directory are unknown. Transaction logs come from the optional
+/// `<dir>/<dao_id>/txs_logs.json`, keyed by proposal id, the DAO's actions log from the
+/// optional `<dir>/<dao_id>/actions_log.json`, and block timestamps from
+/// `<dir>/blocks.json`, keyed by block height.
+pub struct FixtureSource {
+    dir: PathBuf,
+}
+
+impl FixtureSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FixtureSource { dir: dir.into() }
+    }
+
+    fn read<T: DeserializeOwned>(&self, dao_id: &AccountId, file: &str) -> Result<T> {
+        let dao_dir = self.dir.join(dao_id.as_str());
+        if !dao_dir.is_dir() {
+            return Err(NotADao(dao_id.to_string()).into());
+        }
+        read_fixture(&dao_dir.join(file))
+    }
+}
+
+/// Error for proposal ids a DAO doesn't have, the fixture counterpart of ERR_NO_PROPOSAL
+#[derive(Debug)]
+pub struct MissingProposal(pub u64);
+
+impl std::fmt::Display for MissingProposal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No proposal {}", self.0)
+    }
+}
+
+impl std::error::Error for MissingProposal {}
+
+fn read_fixture<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let json = std::fs::read(path).with_context(|| format!("No fixture at {}", path.display()))?;
+    serde_json::from_slice(&json).with_context(|| format!("Invalid fixture {}", path.display()))
+}
+
+#[rocket::async_trait]
+impl ProposalSource for FixtureSource {
+    // Picks from the recorded proposals what a delta fetch would download from the
+    // contract, so a fixture changed between fetches shows what a refresh picks up
+    async fn proposals(
+        &self,
+        dao_id: &AccountId,
+        cached: &[Proposal],
+        last_proposal_id: u64,
+    ) -> Result<(Vec<Proposal>, u64)> {
+        let mut proposals: Vec<Proposal> = self.read(dao_id, "proposals.json")?;
+        proposals.sort_by_key(|p| p.id);
+        let last_id = proposals.last().map_or(0, |p| p.id + 1);
+        if cached.is_empty() {
+            return Ok((proposals, last_id));
+        }
+
+        let path = self.dir.join(dao_id.as_str()).join("actions_log.json");
+        let actions_log: Option<Vec<ActionLog>> = if path.exists() {
+            Some(read_fixture(&path)?)
+        } else {
+            None
+        };
+        let ranges = delta_ranges(cached, last_proposal_id, last_id, actions_log.as_deref());
+        let fetched = proposals
+            .into_iter()
+            .filter(|p| ranges.iter().any(|(from, to)| p.id >= *from && p.id < *to))
+            .collect();
+        Ok((merge_delta(cached, &ranges, fetched), last_id))
+    }
+
+    async fn proposal(&self, dao_id: &AccountId, proposal_id: u64) -> Result<Proposal> {
+        let proposals: Vec<Proposal> = self.read(dao_id, "proposals.json")?;
+        proposals
+            .into_iter()
+            .find(|p| p.id == proposal_id)
+            .ok_or(MissingProposal(proposal_id))
+            .with_context(|| format!("Fixture of {}", dao_id))
+    }
+
+    async fn policy(&self, dao_id: &AccountId) -> Result<Policy> {
+        self.read(dao_id, "policy.json")
+    }
+
+    async fn config(&self, dao_id: &AccountId) -> Result<DaoConfig> {
+        self.read(dao_id, "config.json")
+    }
+
+    // Recorded DAOs are expected to be on the current contract
+    async fn version(&self, _dao_id: &AccountId) -> Result<StateVersion> {
+        Ok(StateVersion::V2)
+    }
+
+    // Recorded logs are complete, there is nothing to resume
+    async fn proposal_txs(
+        &self,
+        dao_id: &AccountId,
+        proposal_id: u64,
+        block_height_limit: u64,
+        _start_block: Option<u64>,
+        _max_pages: usize,
+    ) -> Result<(Vec<TxMetadata>, Option<u64>)> {
+        let path = self.dir.join(dao_id.as_str()).join("txs_logs.json");
+        if !path.exists() {
+            return Ok((Vec::new(), None));
+        }
+        let mut txs_logs: BTreeMap<u64, Vec<TxMetadata>> = read_fixture(&path)?;
+        let txs_log = txs_logs
+            .remove(&proposal_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|tx| tx.block_height > block_height_limit)
+            .collect();
+        Ok((txs_log, None))
+    }
+
+    async fn block_timestamp(&self, block_height: u64) -> Result<u64> {
+        let blocks: BTreeMap<u64, u64> = read_fixture(&self.dir.join("blocks.json"))?;
+        blocks
+            .get(&block_height)
+            .copied()
+            .with_context(|| format!("No fixture for block {}", block_height))
+    }
+}
+
+/// Source selected by scraper.proposal_source, RPC calls go through `client`
+pub fn proposal_source(client: &Arc<RpcPool>) -> Box<dyn ProposalSource> {
+    let scraper = &get_config().scraper;
+    match scraper.proposal_source {
+        ProposalSourceKind::Rpc => Box::new(RpcSource::new(client.clone())),
+        ProposalSourceKind::Fixtures => Box::new(FixtureSource::new(&scraper.fixtures_dir)),
+    }
+}
@@ -0,0 +1,283 @@
+use near_primitives::types::AccountId;
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::auth::{AdminAuth, ApiAuth};
+use crate::cache::{CachedProposals, ProposalStore, get_latest_dao_cache, is_unknown_dao};
+use crate::config::get_config;
+use crate::rfc3339_utc;
+use crate::rpc_client::{self, RpcPool};
+
+/// DAOs kept warm by the tracked refresher
+pub type TrackedDaos = Arc<TrackedRegistry>;
+
+/// Tracked DAOs keyed by DAO account, with whether the file is behind
+pub struct TrackedRegistry {
+    daos: RwLock<BTreeMap<String, TrackedDao>>,
+    dirty: AtomicBool,
+}
+
+impl TrackedRegistry {
+    // A panic while the lock was held leaves at worst one stale entry, so a poisoned lock
+    // is used as is
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<String, TrackedDao>> {
+        self.daos.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<String, TrackedDao>> {
+        self.daos.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    // Writes the registry if it changed since the last save. Only a copy is taken under
+    // the lock, the file is written without holding it.
+    fn save_if_dirty(&self) {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        let daos = self.read().clone();
+        if !save_tracked_daos(&daos) {
+            self.mark_dirty();
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TrackedDao {
+    /// Unix seconds
+    pub added_at: u64,
+    /// Unix seconds of the data last fetched from RPC
+    pub last_refreshed_at: Option<u64>,
+    pub proposals: usize,
+    pub last_error: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TrackRequest {
+    pub dao_id: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TrackedDaoEntry {
+    pub dao_id: String,
+    pub added_date: String,
+    /// When the cached data was last fetched from RPC, null before the first refresh
+    pub last_refresh_date: Option<String>,
+    pub proposals: usize,
+    /// Error of the last refresh, null when it succeeded
+    pub last_error: Option<String>,
+}
+
+impl TrackedDaoEntry {
+    fn new(dao_id: &str, tracked: &TrackedDao) -> Self {
+        TrackedDaoEntry {
+            dao_id: dao_id.to_string(),
+            added_date: rfc3339_utc(tracked.added_at * 1_000_000_000),
+            last_refresh_date: tracked
+                .last_refreshed_at
+                .map(|secs| rfc3339_utc(secs * 1_000_000_000)),
+            proposals: tracked.proposals,
+            last_error: tracked.last_error.clone(),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+/// Registry from cache.tracked_daos_path, empty when the file doesn't exist yet
+pub fn load_tracked_daos() -> TrackedDaos {
+    let path = get_config().cache.tracked_daos_path();
+    let daos = match fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!(%path, error = %e, "Ignoring unreadable tracked DAOs file");
+            BTreeMap::new()
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => {
+            warn!(%path, error = %e, "Failed to read tracked DAOs file");
+            BTreeMap::new()
+        }
+    };
+    Arc::new(TrackedRegistry {
+        daos: RwLock::new(daos),
+        dirty: AtomicBool::new(false),
+    })
+}
+
+// Written through a temporary file so a crash can't leave half of it behind. Returns
+// whether the file was written.
+fn save_tracked_daos(daos: &BTreeMap<String, TrackedDao>) -> bool {
+    let path = get_config().cache.tracked_daos_path();
+    let tmp_path = format!("{}.tmp", path);
+    let written = serde_json::to_vec_pretty(daos)
+        .map_err(std::io::Error::other)
+        .and_then(|json| fs::write(&tmp_path, json))
+        .and_then(|_| fs::rename(&tmp_path, &path));
+    if let Err(e) = written {
+        error!(%path, error = %e, "Failed to save tracked DAOs");
+        return false;
+    }
+    true
+}
+
+// Records the outcome of a refresh, unless the DAO was untracked meanwhile. Only a new
+// proposal count or error marks the file as behind, refresh times alone aren't worth a
+// write every tick.
+fn record_refresh(
+    tracked: &TrackedRegistry,
+    dao_id: &str,
+    refreshed: &anyhow::Result<CachedProposals>,
+) {
+    let mut daos = tracked.write();
+    let Some(dao) = daos.get_mut(dao_id) else {
+        return;
+    };
+    let (proposals, last_error) = (dao.proposals, dao.last_error.clone());
+    match refreshed {
+        Ok(cached) => {
+            dao.last_refreshed_at =
+                Some(unix_now().saturating_sub(cached.last_updated.elapsed().as_secs()));
+            dao.proposals = cached.proposals.len();
+            dao.last_error = None;
+        }
+        Err(e) => dao.last_error = Some(e.to_string()),
+    }
+    if dao.proposals != proposals || dao.last_error != last_error {
+        tracked.mark_dirty();
+    }
+}
+
+async fn refresh_tracked_dao(
+    client: &Arc<RpcPool>,
+    store: &ProposalStore,
+    tracked: &TrackedRegistry,
+    dao_id: &AccountId,
+) -> anyhow::Result<CachedProposals> {
+    let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "");
+    let refreshed = get_latest_dao_cache(client, store, dao_id, life_time).await;
+    if let Err(e) = &refreshed {
+        warn!(%dao_id, error = ?e, "Failed to refresh tracked DAO");
+    }
+    record_refresh(tracked, dao_id.as_str(), &refreshed);
+    refreshed
+}
+
+/// Refreshes every tracked DAO once its cached data expires, checking every
+/// cache.tracked_refresh_interval_secs
+pub fn spawn_tracked_refresh(client: Arc<RpcPool>, store: ProposalStore, tracked: TrackedDaos) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(get_config().cache.tracked_refresh_interval());
+        loop {
+            interval.tick().await;
+            let dao_ids: Vec<AccountId> = tracked
+                .read()
+                .keys()
+                .filter_map(|dao_id| dao_id.parse().ok())
+                .collect();
+            for dao_id in dao_ids {
+                let _ = refresh_tracked_dao(&client, &store, &tracked, &dao_id).await;
+            }
+            tracked.save_if_dirty();
+        }
+    });
+}
+
+#[post("/daos/track", data = "<request>")]
+pub async fn track_dao(
+    _admin: AdminAuth,
+    request: Json<TrackRequest>,
+    store: &State<ProposalStore>,
+    tracked: &State<TrackedDaos>,
+) -> Result<Json<TrackedDaoEntry>, Status> {
+    let dao_id: AccountId = request.dao_id.parse().map_err(|_| Status::BadRequest)?;
+    if let Some(dao) = tracked.read().get(dao_id.as_str()) {
+        return Ok(Json(TrackedDaoEntry::new(dao_id.as_str(), dao)));
+    }
+
+    // Only DAOs that can be fetched are published
+    let client = rpc_client::get_rpc_client();
+    let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "");
+    let cached = get_latest_dao_cache(&client, store, &dao_id, life_time)
+        .await
+        .map_err(|e| {
+            warn!(%dao_id, error = ?e, "Failed to fetch DAO to track");
+            if is_unknown_dao(&e) {
+                Status::NotFound
+            } else {
+                Status::BadGateway
+            }
+        })?;
+
+    let entry = {
+        let mut daos = tracked.write();
+        let dao = daos.entry(dao_id.to_string()).or_insert_with(|| {
+            info!(%dao_id, "Tracking DAO");
+            tracked.mark_dirty();
+            TrackedDao {
+                added_at: unix_now(),
+                last_refreshed_at: Some(
+                    unix_now().saturating_sub(cached.last_updated.elapsed().as_secs()),
+                ),
+                proposals: cached.proposals.len(),
+                last_error: None,
+            }
+        });
+        TrackedDaoEntry::new(dao_id.as_str(), dao)
+    };
+    tracked.save_if_dirty();
+
+    Ok(Json(entry))
+}
+
+#[delete("/daos/track/<dao_id>")]
+pub fn untrack_dao(
+    _admin: AdminAuth,
+    dao_id: &str,
+    tracked: &State<TrackedDaos>,
+) -> Result<Json<TrackedDaoEntry>, Status> {
+    let dao = tracked.write().remove(dao_id).ok_or(Status::NotFound)?;
+    tracked.mark_dirty();
+    tracked.save_if_dirty();
+    info!(%dao_id, "Stopped tracking DAO");
+
+    Ok(Json(TrackedDaoEntry::new(dao_id, &dao)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/daos/tracked",
+    tag = "dao",
+    responses(
+        (status = 200, description = "DAOs kept warm by the background refresher", body = Vec<TrackedDaoEntry>)
+    )
+)]
+#[get("/daos/tracked")]
+pub fn get_tracked_daos(
+    _auth: ApiAuth,
+    tracked: &State<TrackedDaos>,
+) -> Json<Vec<TrackedDaoEntry>> {
+    Json(
+        tracked
+            .read()
+            .iter()
+            .map(|(dao_id, dao)| TrackedDaoEntry::new(dao_id, dao))
+            .collect(),
+    )
+}
@@ -0,0 +1,570 @@
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use near_jsonrpc_client::methods;
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::types::{AccountId, Finality, FunctionArgs};
+use near_primitives::views::QueryRequest;
+use near_sdk::json_types::U128;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::cache::{FtMetadataCache, LockupCache, StakingPoolCache, get_ft_metadata_cache};
+use crate::config::get_config;
+use crate::rpc_client::RpcPool;
+use crate::scraper::{
+    PaymentInfo, Proposal, ProposalStatus, ProposalType, StakeDelegationInfo, TokenId,
+};
+
+const INTENTS_CONTRACT: &str = "intents.near";
+
+/// NEAR balance of an account in yoctoNEAR
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct AccountBalance {
+    #[schema(value_type = String)]
+    pub total: U128,
+    /// Staked by a validator account, not spendable
+    #[schema(value_type = String)]
+    pub locked: U128,
+    /// Held back to pay for the account's storage
+    #[schema(value_type = String)]
+    pub storage_locked: U128,
+    /// Total minus storage_locked
+    #[schema(value_type = String)]
+    pub available: U128,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct TokenBalance {
+    pub token: String,
+    /// Raw amount, divide by 10^decimals for whole tokens
+    #[schema(value_type = String)]
+    pub balance: U128,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct LockupBalance {
+    pub account_id: String,
+    pub near: AccountBalance,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct TreasuryBalances {
+    pub dao_id: String,
+    pub near: AccountBalance,
+    /// Tokens with a non-zero balance, sorted by contract
+    pub tokens: Vec<TokenBalance>,
+    pub lockup: Option<LockupBalance>,
+}
+
+/// Stake of one account in one staking pool, in yoctoNEAR
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct StakingPosition {
+    pub validator: String,
+    /// The DAO or its lockup account
+    pub account_id: String,
+    #[schema(value_type = String)]
+    pub staked_balance: U128,
+    #[schema(value_type = String)]
+    pub unstaked_balance: U128,
+    /// Unstaked balance whose unbonding period is over, zero while it isn't
+    #[schema(value_type = String)]
+    pub available_for_withdrawal: U128,
+    /// In progress stake delegation proposals for this pool and account
+    pub pending_proposals: Vec<PendingStakeProposal>,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct PendingStakeProposal {
+    pub proposal_id: u64,
+    /// stake, unstake, withdraw or whitelist
+    pub proposal_type: String,
+    pub amount: String,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct StakingPortfolio {
+    pub dao_id: String,
+    pub lockup_account: Option<String>,
+    /// Sorted by account and validator
+    pub positions: Vec<StakingPosition>,
+}
+
+/// Multi-token balance held in intents.near
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct IntentsTokenBalance {
+    /// Multi-token id, e.g. "nep141:usdt.tether-token.near"
+    pub token_id: String,
+    /// Raw amount, divide by 10^decimals for whole tokens
+    #[schema(value_type = String)]
+    pub balance: U128,
+    /// From the FT metadata of nep141 tokens
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct IntentsBalances {
+    pub dao_id: String,
+    /// Tokens with a non-zero balance, sorted by token id
+    pub tokens: Vec<IntentsTokenBalance>,
+}
+
+// Staking pool view of an account, from get_account
+#[derive(Deserialize)]
+struct PoolAccount {
+    unstaked_balance: U128,
+    staked_balance: U128,
+    can_withdraw: bool,
+}
+
+pub struct CachedBalances<T> {
+    pub value: T,
+    pub last_updated: Instant,
+}
+
+pub type BalanceCache = Arc<RwLock<HashMap<String, CachedBalances<TreasuryBalances>>>>;
+pub type StakingCache = Arc<RwLock<HashMap<String, CachedBalances<StakingPortfolio>>>>;
+pub type IntentsCache = Arc<RwLock<HashMap<String, CachedBalances<IntentsBalances>>>>;
+
+// Cached value of the DAO when younger than treasury.balance_life_time_secs, otherwise
+// `fetch`
+async fn get_or_fetch<T: Clone>(
+    cache: &Arc<RwLock<HashMap<String, CachedBalances<T>>>>,
+    dao_id: &AccountId,
+    fetch: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    {
+        let cache_read = match cache.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(cached) = cache_read.get(dao_id.as_str())
+            && cached.last_updated.elapsed() <= get_config().treasury.balance_life_time()
+        {
+            return Ok(cached.value.clone());
+        }
+    }
+
+    let value = fetch.await?;
+
+    let mut cache_write = match cache.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cache_write.insert(
+        dao_id.to_string(),
+        CachedBalances {
+            value: value.clone(),
+            last_updated: Instant::now(),
+        },
+    );
+
+    Ok(value)
+}
+
+pub async fn fetch_account_balance(
+    client: &RpcPool,
+    account_id: &AccountId,
+) -> Result<AccountBalance> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: Finality::Final.into(),
+        request: QueryRequest::ViewAccount {
+            account_id: account_id.clone(),
+        },
+    };
+
+    let response = client.call(request).await?;
+
+    if let QueryResponseKind::ViewAccount(account) = response.kind {
+        let storage_locked =
+            u128::from(account.storage_usage) * get_config().treasury.storage_price_per_byte.0;
+        Ok(AccountBalance {
+            total: U128(account.amount),
+            locked: U128(account.locked),
+            storage_locked: U128(storage_locked),
+            available: U128(account.amount.saturating_sub(storage_locked)),
+        })
+    } else {
+        Err(anyhow::anyhow!("Failed to view account {}", account_id))
+    }
+}
+
+pub async fn fetch_ft_balance(
+    client: &RpcPool,
+    token_id: &AccountId,
+    account_id: &AccountId,
+) -> Result<U128> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: token_id.clone(),
+            method_name: "ft_balance_of".to_string(),
+            args: FunctionArgs::from(json!({ "account_id": account_id }).to_string().into_bytes()),
+        },
+    };
+
+    let response = client.call(request).await?;
+
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        Ok(serde_json::from_slice(&result.result)?)
+    } else {
+        Err(anyhow::anyhow!("Failed to get {} balance", token_id))
+    }
+}
+
+// Token contracts to check: those requested in proposals plus treasury.tokens.
+// Bridged tokens aren't held on NEAR.
+fn token_contracts(requested_tokens: impl IntoIterator<Item = String>) -> BTreeSet<AccountId> {
+    requested_tokens
+        .into_iter()
+        .chain(get_config().treasury.tokens.iter().cloned())
+        .filter_map(|token| match TokenId::parse(&token) {
+            Ok(TokenId::Nep141(contract)) => Some(contract),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn fetch_treasury_balances(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    lockup_cache: &LockupCache,
+    dao_id: &AccountId,
+    requested_tokens: impl IntoIterator<Item = String>,
+) -> Result<TreasuryBalances> {
+    let near = fetch_account_balance(client, dao_id).await?;
+
+    // Sorted by contract like token_contracts, up to treasury.balance_concurrency at a time
+    let tokens: Vec<TokenBalance> = stream::iter(token_contracts(requested_tokens))
+        .map(|token_id| async move {
+            let balance = match fetch_ft_balance(client, &token_id, dao_id).await {
+                Ok(balance) if balance.0 > 0 => balance,
+                Ok(_) => return None,
+                Err(e) => {
+                    warn!(%dao_id, token = %token_id, error = %e, "Error fetching token balance");
+                    return None;
+                }
+            };
+            let metadata = get_ft_metadata_cache(client, ft_metadata_cache, token_id.as_str())
+                .await
+                .ok();
+            Some(TokenBalance {
+                token: token_id.to_string(),
+                balance,
+                symbol: metadata.as_ref().map(|metadata| metadata.symbol.clone()),
+                decimals: metadata.map(|metadata| metadata.decimals),
+            })
+        })
+        .buffered(get_config().treasury.balance_concurrency)
+        .filter_map(|token| async move { token })
+        .collect()
+        .await;
+
+    let lockup = match lockup_cache
+        .get_lockup_account(client, dao_id.as_str())
+        .await
+    {
+        Some(lockup_id) => {
+            let near = fetch_account_balance(client, &lockup_id.parse()?).await?;
+            Some(LockupBalance {
+                account_id: lockup_id,
+                near,
+            })
+        }
+        None => None,
+    };
+
+    Ok(TreasuryBalances {
+        dao_id: dao_id.to_string(),
+        near,
+        tokens,
+        lockup,
+    })
+}
+
+/// Balances of the DAO, its tokens and its lockup account, refreshed at most once per
+/// treasury.balance_life_time_secs. `requested_tokens` are the tokens of the DAO's
+/// payment proposals.
+pub async fn get_treasury_balances(
+    client: &Arc<RpcPool>,
+    cache: &BalanceCache,
+    ft_metadata_cache: &FtMetadataCache,
+    lockup_cache: &LockupCache,
+    dao_id: &AccountId,
+    requested_tokens: impl IntoIterator<Item = String>,
+) -> Result<TreasuryBalances> {
+    get_or_fetch(
+        cache,
+        dao_id,
+        fetch_treasury_balances(
+            client,
+            ft_metadata_cache,
+            lockup_cache,
+            dao_id,
+            requested_tokens,
+        ),
+    )
+    .await
+}
+
+async fn fetch_pool_account(
+    client: &RpcPool,
+    pool_id: &AccountId,
+    account_id: &str,
+) -> Result<PoolAccount> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: pool_id.clone(),
+            method_name: "get_account".to_string(),
+            args: FunctionArgs::from(json!({ "account_id": account_id }).to_string().into_bytes()),
+        },
+    };
+
+    let response = client.call(request).await?;
+
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        Ok(serde_json::from_slice(&result.result)?)
+    } else {
+        Err(anyhow::anyhow!("Failed to get account from {}", pool_id))
+    }
+}
+
+// Account and pool a stake delegation proposal acts on. Proposals calling the lockup
+// account name it as validator, except select_staking_pool which names the new pool.
+// The others are resolved to the lockup's selected pool.
+async fn stake_target(
+    client: &RpcPool,
+    staking_pool_cache: &StakingPoolCache,
+    dao_id: &AccountId,
+    proposal: &Proposal,
+    validator: &str,
+) -> Option<(String, String)> {
+    let receiver = proposal
+        .kind
+        .pointer("/FunctionCall/receiver_id")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if !receiver.ends_with(".lockup.near") {
+        return Some((dao_id.to_string(), validator.to_string()));
+    }
+    let pool = if validator == receiver {
+        staking_pool_cache
+            .get_staking_pool_account_id(client, receiver)
+            .await?
+    } else {
+        validator.to_string()
+    };
+    Some((receiver.to_string(), pool))
+}
+
+async fn fetch_staking_portfolio(
+    client: &Arc<RpcPool>,
+    staking_pool_cache: &StakingPoolCache,
+    lockup_cache: &LockupCache,
+    dao_id: &AccountId,
+    proposals: &[Proposal],
+) -> Result<StakingPortfolio> {
+    // Pending proposals per account and pool, every pool ever delegated to is checked
+    let mut targets: BTreeMap<(String, String), Vec<PendingStakeProposal>> = BTreeMap::new();
+    for proposal in proposals {
+        let Some(info) = StakeDelegationInfo::from_proposal(proposal) else {
+            continue;
+        };
+        let Some(target) = stake_target(
+            client,
+            staking_pool_cache,
+            dao_id,
+            proposal,
+            &info.validator,
+        )
+        .await
+        else {
+            continue;
+        };
+        let pending = targets.entry(target).or_default();
+        if *proposal.current_status() == ProposalStatus::InProgress {
+            pending.push(PendingStakeProposal {
+                proposal_id: proposal.id,
+                proposal_type: info.proposal_type,
+                amount: info.amount,
+            });
+        }
+    }
+
+    let lockup_account = lockup_cache
+        .get_lockup_account(client, dao_id.as_str())
+        .await;
+    if let Some(lockup_id) = &lockup_account
+        && let Some(pool) = staking_pool_cache
+            .get_staking_pool_account_id(client, lockup_id)
+            .await
+    {
+        targets.entry((lockup_id.clone(), pool)).or_default();
+    }
+
+    let mut positions = Vec::new();
+    for ((account_id, validator), pending_proposals) in targets {
+        let Ok(pool_id) = validator.parse::<AccountId>() else {
+            continue;
+        };
+        let pool_account = match fetch_pool_account(client, &pool_id, &account_id).await {
+            Ok(pool_account) => pool_account,
+            Err(e) => {
+                warn!(%dao_id, %validator, error = %e, "Error fetching staking pool account");
+                continue;
+            }
+        };
+        if pool_account.staked_balance.0 == 0
+            && pool_account.unstaked_balance.0 == 0
+            && pending_proposals.is_empty()
+        {
+            continue;
+        }
+        positions.push(StakingPosition {
+            validator,
+            account_id,
+            staked_balance: pool_account.staked_balance,
+            unstaked_balance: pool_account.unstaked_balance,
+            available_for_withdrawal: if pool_account.can_withdraw {
+                pool_account.unstaked_balance
+            } else {
+                U128(0)
+            },
+            pending_proposals,
+        });
+    }
+
+    Ok(StakingPortfolio {
+        dao_id: dao_id.to_string(),
+        lockup_account,
+        positions,
+    })
+}
+
+/// Stake of the DAO and its lockup account in every pool they delegated to, refreshed at
+/// most once per treasury.balance_life_time_secs
+pub async fn get_staking_portfolio(
+    client: &Arc<RpcPool>,
+    cache: &StakingCache,
+    staking_pool_cache: &StakingPoolCache,
+    lockup_cache: &LockupCache,
+    dao_id: &AccountId,
+    proposals: &[Proposal],
+) -> Result<StakingPortfolio> {
+    get_or_fetch(
+        cache,
+        dao_id,
+        fetch_staking_portfolio(client, staking_pool_cache, lockup_cache, dao_id, proposals),
+    )
+    .await
+}
+
+// Multi-token ids to check: tokens withdrawn from intents.near by the DAO's proposals,
+// every requested fungible token and wNEAR as nep141 tokens, plus treasury.intents_tokens
+fn intents_token_ids(proposals: &[Proposal]) -> BTreeSet<String> {
+    let mut token_ids: BTreeSet<String> = get_config()
+        .treasury
+        .intents_tokens
+        .iter()
+        .cloned()
+        .collect();
+    token_ids.insert("nep141:wrap.near".to_string());
+    for proposal in proposals {
+        let Some(payment) = PaymentInfo::from_proposal(proposal) else {
+            continue;
+        };
+        if payment.token.is_empty() || payment.token.eq_ignore_ascii_case("near") {
+            continue;
+        }
+        if payment.token.contains(':') {
+            token_ids.insert(payment.token);
+        } else {
+            token_ids.insert(format!("nep141:{}", payment.token));
+        }
+    }
+    token_ids
+}
+
+pub async fn fetch_intents_balances_of(
+    client: &RpcPool,
+    account_id: &AccountId,
+    token_ids: &[String],
+) -> Result<Vec<U128>> {
+    let request = methods::query::RpcQueryRequest {
+        block_reference: Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: INTENTS_CONTRACT.parse()?,
+            method_name: "mt_batch_balance_of".to_string(),
+            args: FunctionArgs::from(
+                json!({ "account_id": account_id, "token_ids": token_ids })
+                    .to_string()
+                    .into_bytes(),
+            ),
+        },
+    };
+
+    let response = client.call(request).await?;
+
+    if let QueryResponseKind::CallResult(result) = response.kind {
+        Ok(serde_json::from_slice(&result.result)?)
+    } else {
+        Err(anyhow::anyhow!("Failed to get intents balances"))
+    }
+}
+
+async fn fetch_intents_balances(
+    client: &Arc<RpcPool>,
+    ft_metadata_cache: &FtMetadataCache,
+    dao_id: &AccountId,
+    proposals: &[Proposal],
+) -> Result<IntentsBalances> {
+    let token_ids: Vec<String> = intents_token_ids(proposals).into_iter().collect();
+    let balances = fetch_intents_balances_of(client, dao_id, &token_ids).await?;
+
+    let mut tokens = Vec::new();
+    for (token_id, balance) in token_ids.into_iter().zip(balances) {
+        if balance.0 == 0 {
+            continue;
+        }
+        let metadata = get_ft_metadata_cache(client, ft_metadata_cache, &token_id)
+            .await
+            .ok();
+        tokens.push(IntentsTokenBalance {
+            token_id,
+            balance,
+            symbol: metadata.as_ref().map(|metadata| metadata.symbol.clone()),
+            decimals: metadata.map(|metadata| metadata.decimals),
+        });
+    }
+
+    Ok(IntentsBalances {
+        dao_id: dao_id.to_string(),
+        tokens,
+    })
+}
+
+/// The DAO's multi-token holdings in intents.near, refreshed at most once per
+/// treasury.balance_life_time_secs
+pub async fn get_intents_balances(
+    client: &Arc<RpcPool>,
+    cache: &IntentsCache,
+    ft_metadata_cache: &FtMetadataCache,
+    dao_id: &AccountId,
+    proposals: &[Proposal],
+) -> Result<IntentsBalances> {
+    get_or_fetch(
+        cache,
+        dao_id,
+        fetch_intents_balances(client, ft_metadata_cache, dao_id, proposals),
+    )
+    .await
+}
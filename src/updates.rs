@@ -0,0 +1,142 @@
+use dashmap::DashMap;
+use near_primitives::types::AccountId;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::broadcast;
+
+use crate::cache::{ProposalStore, get_latest_dao_cache};
+use crate::config::get_config;
+use crate::rpc_client::RpcPool;
+use crate::scraper::Proposal;
+use tracing::error;
+
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateKind {
+    NewProposal,
+    StatusChanged,
+    VotesChanged,
+    Removed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProposalUpdate {
+    pub dao_id: String,
+    pub update: UpdateKind,
+    pub proposal_id: u64,
+    // Missing for removed proposals
+    pub proposal: Option<Proposal>,
+}
+
+struct DaoChannel {
+    sender: broadcast::Sender<ProposalUpdate>,
+    poller_running: AtomicBool,
+}
+
+static CHANNELS: Lazy<DashMap<String, Arc<DaoChannel>>> = Lazy::new(DashMap::new);
+
+fn channel(dao_id: &AccountId) -> Arc<DaoChannel> {
+    CHANNELS
+        .entry(dao_id.to_string())
+        .or_insert_with(|| {
+            Arc::new(DaoChannel {
+                sender: broadcast::channel(CHANNEL_CAPACITY).0,
+                poller_running: AtomicBool::new(false),
+            })
+        })
+        .clone()
+}
+
+fn has_subscribers(dao_id: &AccountId) -> bool {
+    CHANNELS
+        .get(dao_id.as_str())
+        .is_some_and(|c| c.sender.receiver_count() > 0)
+}
+
+pub fn subscribe(dao_id: &AccountId) -> broadcast::Receiver<ProposalUpdate> {
+    channel(dao_id).sender.subscribe()
+}
+
+/// Keeps the DAO cache refreshing while there are subscribers, so updates are
+/// published even when nobody polls the HTTP endpoints.
+pub fn ensure_poller(client: Arc<RpcPool>, store: ProposalStore, dao_id: AccountId) {
+    let channel = channel(&dao_id);
+    if channel
+        .poller_running
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return;
+    }
+
+    let life_time = get_config().cache.dao_life_time(dao_id.as_str(), "ws");
+    tokio::spawn(async move {
+        loop {
+            while channel.sender.receiver_count() > 0 {
+                tokio::time::sleep(life_time).await;
+                if let Err(e) = get_latest_dao_cache(&client, &store, &dao_id, life_time).await {
+                    error!(%dao_id, error = ?e, "Failed to refresh subscribed DAO");
+                }
+            }
+            channel.poller_running.store(false, Ordering::SeqCst);
+
+            // A subscriber may have arrived between the last check and the flag reset
+            if channel.sender.receiver_count() == 0
+                || channel
+                    .poller_running
+                    .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// Compares two snapshots of a DAO's proposals and notifies subscribers about the differences
+pub fn publish_changes(dao_id: &AccountId, previous: &[Proposal], current: &[Proposal]) {
+    if !has_subscribers(dao_id) {
+        return;
+    }
+    let channel = channel(dao_id);
+
+    let previous_by_id: HashMap<u64, &Proposal> = previous.iter().map(|p| (p.id, p)).collect();
+    let mut updates = Vec::new();
+
+    for proposal in current {
+        let update = match previous_by_id.get(&proposal.id) {
+            None => Some(UpdateKind::NewProposal),
+            Some(old) if old.status != proposal.status => Some(UpdateKind::StatusChanged),
+            Some(old) if old.votes != proposal.votes => Some(UpdateKind::VotesChanged),
+            Some(_) => None,
+        };
+        if let Some(update) = update {
+            updates.push(ProposalUpdate {
+                dao_id: dao_id.to_string(),
+                update,
+                proposal_id: proposal.id,
+                proposal: Some(proposal.clone()),
+            });
+        }
+    }
+
+    let current_ids: std::collections::HashSet<u64> = current.iter().map(|p| p.id).collect();
+    for proposal in previous.iter().filter(|p| !current_ids.contains(&p.id)) {
+        updates.push(ProposalUpdate {
+            dao_id: dao_id.to_string(),
+            update: UpdateKind::Removed,
+            proposal_id: proposal.id,
+            proposal: None,
+        });
+    }
+
+    for update in updates {
+        // Sending only fails when every receiver is gone
+        let _ = channel.sender.send(update);
+    }
+}
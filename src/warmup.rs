@@ -0,0 +1,108 @@
+use futures::stream::{self, StreamExt};
+use near_primitives::types::AccountId;
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::{Build, Rocket};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info, warn};
+
+use crate::analytics::requested_token_counts;
+use crate::cache::{FtMetadataCache, ProposalStore, get_ft_metadata_cache, get_latest_dao_cache};
+use crate::config::get_config;
+use crate::rpc_client::{self, RpcPool};
+
+/// Fetches the DAOs in warmup.daos, plus the restored ones with warmup.persisted,
+/// before the server starts listening
+pub struct CacheWarmup;
+
+#[rocket::async_trait]
+impl Fairing for CacheWarmup {
+    fn info(&self) -> Info {
+        Info {
+            name: "Cache warm-up",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let (Some(store), Some(ft_metadata_cache)) = (
+            rocket.state::<ProposalStore>().cloned(),
+            rocket.state::<FtMetadataCache>().cloned(),
+        ) else {
+            return Ok(rocket);
+        };
+
+        let warmup = &get_config().warmup;
+        let mut dao_ids = warmup.daos.clone();
+        if warmup.persisted {
+            dao_ids.extend(store.iter().map(|cached| cached.key().clone()));
+        }
+        dao_ids.sort();
+        dao_ids.dedup();
+        if dao_ids.is_empty() {
+            return Ok(rocket);
+        }
+
+        let started = Instant::now();
+        info!(daos = dao_ids.len(), "Warming up caches");
+        let warm_up = warm_up_daos(&dao_ids, &store, &ft_metadata_cache);
+        match tokio::time::timeout(warmup.timeout(), warm_up).await {
+            Ok(warmed) => info!(
+                warmed,
+                daos = dao_ids.len(),
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "Cache warm-up finished"
+            ),
+            Err(_) => warn!(
+                timeout_secs = warmup.timeout_secs,
+                "Cache warm-up timed out, starting anyway"
+            ),
+        }
+
+        Ok(rocket)
+    }
+}
+
+// Number of DAOs that were fetched successfully
+async fn warm_up_daos(
+    dao_ids: &[String],
+    store: &ProposalStore,
+    ft_metadata_cache: &FtMetadataCache,
+) -> usize {
+    let client = rpc_client::get_rpc_client();
+    stream::iter(dao_ids.iter().cloned())
+        .map(|dao_id| warm_up_dao(&client, store, ft_metadata_cache, dao_id))
+        .buffer_unordered(get_config().warmup.concurrency)
+        .filter(|warmed| std::future::ready(*warmed))
+        .count()
+        .await
+}
+
+async fn warm_up_dao(
+    client: &Arc<RpcPool>,
+    store: &ProposalStore,
+    ft_metadata_cache: &FtMetadataCache,
+    dao_id: String,
+) -> bool {
+    let Ok(dao_id) = dao_id.parse::<AccountId>() else {
+        warn!(%dao_id, "Skipping invalid DAO id in warm-up");
+        return false;
+    };
+
+    let life_time = get_config().cache.life_time();
+    let cached = match get_latest_dao_cache(client, store, &dao_id, life_time).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            warn!(%dao_id, error = ?e, "Failed to warm up DAO");
+            return false;
+        }
+    };
+
+    // Payment listings need the decimals of every requested token
+    for token in requested_token_counts(&cached.proposals).keys() {
+        if let Err(e) = get_ft_metadata_cache(client, ft_metadata_cache, token).await {
+            warn!(%dao_id, %token, error = ?e, "Failed to warm up FT metadata");
+        }
+    }
+    true
+}
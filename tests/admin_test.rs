@@ -0,0 +1,555 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use mockito::{Mock, Server, ServerGuard};
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use sputnik_indexer::reports::{ReportPeriod, next_run};
+use sputnik_indexer::rocket;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once};
+
+// Recorded under tests/fixtures, so these tests run without RPC
+const FIXTURE_DAO_ID: &str = "fixture-dao.sputnik-dao.near";
+// Token of the fixture DAO's USDC transfer
+const USDC_TOKEN_ID: &str = "17208628f84f5d6ad33f0da3bbbeb27ffcb398eac501a31bd6ad2011e36133a1";
+
+const USER_KEY: &str = "user-key";
+const ADMIN_KEY: &str = "admin-key";
+
+const AUTH_CONFIG: &str = r#"
+[auth]
+keys = [{ key = "user-key" }, { key = "admin-key", admin = true }]
+public_paths = ["/config/fixture-dao.sputnik-dao.near"]
+"#;
+
+static USE_FIXTURES: Once = Once::new();
+
+fn fixture_client() -> Client {
+    // Settings are loaded once per process, before any rocket is built
+    USE_FIXTURES.call_once(|| {
+        let path = std::env::temp_dir().join(format!("admin_test_{}.toml", std::process::id()));
+        std::fs::write(&path, AUTH_CONFIG).expect("write test config");
+        // SAFETY: the other tests wait on USE_FIXTURES, nothing reads the environment meanwhile
+        unsafe {
+            std::env::set_var("CONFIG_PATH", &path);
+            std::env::set_var("TRACKED_DAOS_PATH", tracked_daos_path());
+            std::env::set_var(
+                "REPORTS_PATH",
+                std::env::temp_dir()
+                    .join(format!("admin_test_reports_{}.json", std::process::id())),
+            );
+        }
+        sputnik_indexer::use_fixtures("tests/fixtures");
+    });
+    Client::tracked(rocket()).expect("valid rocket instance")
+}
+
+fn tracked_daos_path() -> PathBuf {
+    std::env::temp_dir().join(format!("admin_test_tracked_{}.json", std::process::id()))
+}
+
+fn api_key(key: &str) -> Header<'static> {
+    Header::new("X-API-Key", key.to_string())
+}
+
+fn create_report(client: &Client, job: serde_json::Value) -> (Status, serde_json::Value) {
+    let response = client
+        .post("/admin/reports")
+        .header(api_key(ADMIN_KEY))
+        .header(ContentType::JSON)
+        .body(job.to_string())
+        .dispatch();
+    let status = response.status();
+    (status, response.into_json().unwrap_or_default())
+}
+
+// A webhook that keeps the body of the last request it got
+fn webhook(server: &mut ServerGuard) -> (Mock, Arc<Mutex<Vec<u8>>>) {
+    let body = Arc::new(Mutex::new(Vec::new()));
+    let received = body.clone();
+    let mock = server
+        .mock("POST", "/report")
+        .with_body_from_request(move |request| {
+            *received.lock().unwrap() = request.body().unwrap().clone();
+            Vec::new()
+        })
+        .create();
+    (mock, body)
+}
+
+// Creates the job, runs it right away and removes it
+fn run_report(client: &Client, job: serde_json::Value) -> serde_json::Value {
+    let (status, entry) = create_report(client, job);
+    assert_eq!(status, Status::Ok);
+    let id = entry["id"].as_str().expect("report id");
+    let response = client
+        .post(format!("/admin/reports/{}/run", id))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let entry: serde_json::Value = response.into_json().expect("report entry");
+    let response = client
+        .delete(format!("/admin/reports/{}", id))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    entry
+}
+
+#[test]
+fn test_known_key_is_accepted() {
+    let client = fixture_client();
+
+    let response = client
+        .get(format!("/proposals/{}", FIXTURE_DAO_ID))
+        .header(api_key(USER_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .get("/admin/cache/stats")
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn test_unknown_key_is_rejected() {
+    let client = fixture_client();
+
+    for key in ["wrong-key", "user-ke", "user-key2", ""] {
+        let response = client
+            .get(format!("/proposals/{}", FIXTURE_DAO_ID))
+            .header(api_key(key))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized, "key {:?}", key);
+    }
+
+    let response = client
+        .get("/admin/cache/stats")
+        .header(api_key(USER_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_missing_key_is_rejected() {
+    let client = fixture_client();
+
+    let response = client
+        .get(format!("/proposals/{}", FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client.get("/admin/cache/stats").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_public_paths_match_exactly() {
+    let client = fixture_client();
+
+    let response = client.get(format!("/config/{}", FIXTURE_DAO_ID)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    // Sharing a prefix with a public path doesn't make a path public
+    let response = client
+        .get(format!("/config/{}x.sputnik-dao.near", FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_tracked_daos() {
+    let client = fixture_client();
+
+    let response = client
+        .post("/daos/track")
+        .header(api_key(ADMIN_KEY))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"dao_id": "{}"}}"#, FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .get("/daos/tracked")
+        .header(api_key(USER_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let tracked: serde_json::Value = response.into_json().expect("tracked DAOs");
+    let dao = tracked
+        .as_array()
+        .expect("tracked DAOs array")
+        .iter()
+        .find(|dao| dao["dao_id"] == FIXTURE_DAO_ID)
+        .expect("fixture DAO is tracked");
+    assert!(dao["added_date"].is_string());
+    assert!(dao["last_refresh_date"].is_string());
+    assert_eq!(dao["proposals"].as_u64(), Some(6));
+    assert!(dao["last_error"].is_null());
+
+    let saved = std::fs::read_to_string(tracked_daos_path()).expect("tracked DAOs file");
+    assert!(saved.contains(FIXTURE_DAO_ID));
+
+    let response = client
+        .delete(format!("/daos/track/{}", FIXTURE_DAO_ID))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let saved = std::fs::read_to_string(tracked_daos_path()).expect("tracked DAOs file");
+    assert!(!saved.contains(FIXTURE_DAO_ID));
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let client = fixture_client();
+    // Caches the transaction log of proposal 0, so the snapshot carries one
+    let response = client
+        .get(format!("/proposal/{}/0", FIXTURE_DAO_ID))
+        .header(api_key(USER_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .get(format!("/admin/export/{}", FIXTURE_DAO_ID))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let snapshot: serde_json::Value = response.into_json().expect("snapshot");
+    assert_eq!(snapshot["dao_id"], FIXTURE_DAO_ID);
+    assert!(snapshot["txs_logs"].get("0").is_some());
+
+    // A snapshot in another format is refused without touching the cache
+    let mut unsupported = snapshot.clone();
+    unsupported["format"] = serde_json::json!(99);
+    let response = client
+        .post("/admin/import")
+        .header(api_key(ADMIN_KEY))
+        .header(ContentType::JSON)
+        .body(unsupported.to_string())
+        .dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let response = client
+        .get("/admin/cache/stats")
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    let stats: serde_json::Value = response.into_json().expect("cache stats");
+    assert!(
+        stats["daos"]
+            .as_array()
+            .expect("cached DAOs")
+            .iter()
+            .any(|dao| dao["dao_id"] == FIXTURE_DAO_ID)
+    );
+
+    // Metadata of tokens the DAO's proposals don't move would be served to other DAOs
+    let mut with_metadata = snapshot.clone();
+    let metadata = |symbol: &str| {
+        serde_json::json!({
+            "name": symbol,
+            "symbol": symbol,
+            "decimals": 6,
+            "icon": null,
+            "reference": null,
+            "reference_hash": null,
+        })
+    };
+    with_metadata["ft_metadata"] = serde_json::json!({
+        USDC_TOKEN_ID: metadata("USDC"),
+        "wrap.near": metadata("FAKE"),
+        "Not An Account": metadata("BAD"),
+    });
+    let response = client
+        .post("/admin/import")
+        .header(api_key(ADMIN_KEY))
+        .header(ContentType::JSON)
+        .body(with_metadata.to_string())
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let imported: serde_json::Value = response.into_json().expect("imported entries");
+    assert_eq!(imported["dao_id"], FIXTURE_DAO_ID);
+    assert_eq!(
+        imported["proposals"].as_u64(),
+        Some(snapshot["proposals"].as_array().unwrap().len() as u64)
+    );
+    assert_eq!(imported["ft_metadata"].as_u64(), Some(1));
+    let response = client
+        .get(format!("/admin/export/{}", FIXTURE_DAO_ID))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    let exported: serde_json::Value = response.into_json().expect("snapshot");
+    assert_eq!(
+        exported["ft_metadata"],
+        serde_json::json!({ USDC_TOKEN_ID: metadata("USDC") })
+    );
+
+    let response = client
+        .get(format!("/admin/export/{}", FIXTURE_DAO_ID))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    let exported_again: serde_json::Value = response.into_json().expect("snapshot");
+    assert_eq!(exported_again["proposals"], snapshot["proposals"]);
+    assert_eq!(exported_again["policy"], snapshot["policy"]);
+    assert_eq!(exported_again["config"], snapshot["config"]);
+    assert_eq!(exported_again["txs_logs"], snapshot["txs_logs"]);
+    // Imported entries keep their age, up to rounding to whole seconds
+    let fetched_at = snapshot["fetched_at"].as_u64().unwrap();
+    assert!(
+        exported_again["fetched_at"]
+            .as_u64()
+            .unwrap()
+            .abs_diff(fetched_at)
+            <= 1
+    );
+}
+
+#[test]
+fn test_report_periods() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+    assert_eq!(
+        ReportPeriod::Day.previous(date(2024, 3, 1)),
+        (date(2024, 2, 29), date(2024, 3, 1))
+    );
+    // Wednesday, the week started on Monday
+    assert_eq!(
+        ReportPeriod::Week.previous(date(2024, 1, 3)),
+        (date(2023, 12, 25), date(2024, 1, 1))
+    );
+    assert_eq!(
+        ReportPeriod::Week.previous(date(2024, 1, 1)),
+        (date(2023, 12, 25), date(2024, 1, 1))
+    );
+    assert_eq!(
+        ReportPeriod::Month.previous(date(2024, 3, 31)),
+        (date(2024, 2, 1), date(2024, 3, 1))
+    );
+    assert_eq!(
+        ReportPeriod::Month.previous(date(2024, 1, 15)),
+        (date(2023, 12, 1), date(2024, 1, 1))
+    );
+}
+
+#[test]
+fn test_report_next_run() {
+    let at = |h, m, s| {
+        Utc.with_ymd_and_hms(2024, 1, 1, h, m, s)
+            .unwrap()
+            .timestamp() as u64
+    };
+
+    // Crontab fields, strictly after the given time
+    assert_eq!(next_run("0 6 * * *", at(5, 0, 0)), Some(at(6, 0, 0)));
+    assert_eq!(
+        next_run("0 6 * * *", at(6, 0, 0)),
+        Some(at(6, 0, 0) + 24 * 60 * 60)
+    );
+    // Seconds in front
+    assert_eq!(next_run("30 0 6 * * *", at(5, 0, 0)), Some(at(6, 0, 30)));
+    assert_eq!(next_run("every day", at(5, 0, 0)), None);
+}
+
+#[test]
+fn test_invalid_report_jobs_are_rejected() {
+    let client = fixture_client();
+    let webhook = serde_json::json!({ "type": "webhook", "url": "http://localhost/report" });
+
+    let invalid = [
+        serde_json::json!({ "cron": "every day", "dao_id": FIXTURE_DAO_ID, "destination": webhook }),
+        serde_json::json!({ "cron": "0 6 * * *", "dao_id": "Not A DAO", "destination": webhook }),
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "filters": "id_from=first",
+            "destination": webhook,
+        }),
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "destination": { "type": "slack", "url": "http://localhost/slack" },
+        }),
+        // No reports.smtp_url in the test config
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "destination": { "type": "email", "to": ["treasury@example.com"] },
+        }),
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "digest": { "expiring_within_hours": 24 },
+            "destination": { "type": "s3", "bucket": "reports", "region": "eu-central-1" },
+        }),
+    ];
+    for job in invalid {
+        let (status, _) = create_report(&client, job.clone());
+        assert_eq!(status, Status::BadRequest, "job {}", job);
+    }
+
+    let (status, entry) = create_report(
+        &client,
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "filters": "statuses=Approved&category=payments",
+            "destination": webhook,
+        }),
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(entry["filters"], "statuses=Approved&category=payments");
+    assert!(
+        entry["next_run_date"]
+            .as_str()
+            .expect("next run date")
+            .ends_with("T06:00:00Z")
+    );
+    let response = client
+        .delete(format!("/admin/reports/{}", entry["id"].as_str().unwrap()))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn test_report_csv() {
+    let client = fixture_client();
+    let mut server = Server::new();
+    let (mock, body) = webhook(&mut server);
+    let destination =
+        serde_json::json!({ "type": "webhook", "url": format!("{}/report", server.url()) });
+
+    let entry = run_report(
+        &client,
+        serde_json::json!({ "cron": "0 6 * * *", "dao_id": FIXTURE_DAO_ID, "destination": destination }),
+    );
+    assert!(entry["last_error"].is_null(), "{}", entry["last_error"]);
+    assert!(entry["last_run_date"].is_string());
+    let csv = String::from_utf8(body.lock().unwrap().clone()).expect("CSV report");
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert!(lines[0].starts_with("ID,Created Date"));
+
+    let entry = run_report(
+        &client,
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "filters": "statuses=Approved",
+            "destination": destination,
+        }),
+    );
+    assert!(entry["last_error"].is_null(), "{}", entry["last_error"]);
+    let csv = String::from_utf8(body.lock().unwrap().clone()).expect("CSV report");
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.lines().nth(1).unwrap().starts_with("0,"));
+
+    // The fixture proposals were submitted in November 2023, long before yesterday
+    let entry = run_report(
+        &client,
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "period": "day",
+            "destination": destination,
+        }),
+    );
+    assert!(entry["last_error"].is_null(), "{}", entry["last_error"]);
+    let csv = String::from_utf8(body.lock().unwrap().clone()).expect("CSV report");
+    assert_eq!(csv.lines().count(), 1);
+
+    mock.expect(3).assert();
+}
+
+#[test]
+fn test_report_xlsx() {
+    let client = fixture_client();
+    let mut server = Server::new();
+    let (mock, body) = webhook(&mut server);
+
+    let entry = run_report(
+        &client,
+        serde_json::json!({
+            "cron": "0 6 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "format": "xlsx",
+            "destination": { "type": "webhook", "url": format!("{}/report", server.url()) },
+        }),
+    );
+    assert!(entry["last_error"].is_null(), "{}", entry["last_error"]);
+    mock.assert();
+
+    let bytes = body.lock().unwrap().clone();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("XLSX archive");
+    let mut sheet = String::new();
+    archive
+        .by_name("xl/worksheets/sheet1.xml")
+        .expect("first sheet")
+        .read_to_string(&mut sheet)
+        .expect("sheet XML");
+
+    // Header cells are text, ids numbers and submission times formatted dates
+    assert!(sheet.contains(r#"<c r="A1" t="s">"#));
+    assert!(sheet.contains(r#"<c r="A2"><v>0</v></c>"#));
+    assert!(sheet.contains(r#"<c r="A4"><v>2</v></c>"#));
+    assert!(sheet.contains(r#"<c r="B2" s="1"><v>"#));
+}
+
+#[test]
+fn test_report_digest() {
+    let client = fixture_client();
+    let mut server = Server::new();
+    let (mock, body) = webhook(&mut server);
+
+    // Proposal 2 expired in 2023, proposal 3 is submitted in 2100 and still open
+    let entry = run_report(
+        &client,
+        serde_json::json!({
+            "cron": "0 9 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "digest": { "expiring_within_hours": 1_000_000 },
+            "destination": { "type": "webhook", "url": format!("{}/report", server.url()) },
+        }),
+    );
+    assert!(entry["last_error"].is_null(), "{}", entry["last_error"]);
+    mock.assert();
+
+    let digest: serde_json::Value =
+        serde_json::from_slice(&body.lock().unwrap()).expect("digest JSON");
+    assert_eq!(digest["dao_id"], FIXTURE_DAO_ID);
+    assert_eq!(digest["expiring_within_hours"].as_u64(), Some(1_000_000));
+    let proposals = digest["proposals"].as_array().expect("digest proposals");
+    assert_eq!(proposals.len(), 1);
+    let proposal = &proposals[0];
+    assert_eq!(proposal["id"].as_u64(), Some(3));
+    assert_eq!(proposal["title"], "Renew the audit retainer");
+    assert_eq!(proposal["kind"], "vote");
+    assert_eq!(proposal["proposer"], "dave.near");
+    assert_eq!(proposal["expiry_date"], "2100-01-08T00:00:00Z");
+    assert!(proposal["hours_left"].as_u64().unwrap() > 0);
+    // Half of the three council members plus one, alice approved already
+    assert_eq!(proposal["approvals_needed"].as_u64(), Some(1));
+
+    // Nothing expires within an hour, so no digest is sent
+    let (status, entry) = create_report(
+        &client,
+        serde_json::json!({
+            "cron": "0 9 * * *",
+            "dao_id": FIXTURE_DAO_ID,
+            "digest": { "expiring_within_hours": 1 },
+            "destination": { "type": "webhook", "url": format!("{}/report", server.url()) },
+        }),
+    );
+    assert_eq!(status, Status::Ok);
+    let id = entry["id"].as_str().unwrap();
+    let response = client
+        .post(format!("/admin/reports/{}/run", id))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+    let entry: serde_json::Value = response.into_json().expect("report entry");
+    assert!(entry["last_error"].is_null(), "{}", entry["last_error"]);
+    mock.assert();
+    client
+        .delete(format!("/admin/reports/{}", id))
+        .header(api_key(ADMIN_KEY))
+        .dispatch();
+}
@@ -26,9 +26,9 @@ mod test {
         assert_eq!(response.status(), Status::Ok);
         let body = response.into_string().expect("response body");
         let lines: Vec<&str> = body.lines().collect();
-        let expected_headers = "ID,Created Date,Status,Type,Amount,Token,Validator,Created by,Notes,Approvers (Approved),Approvers (Rejected/Remove)";
+        let expected_headers = "ID,Created Date,Status,Type,Amount,Token,Validator,Resolved Validator,Created by,Notes,Approvers (Approved),Approvers (Rejected/Remove)";
         assert_eq!(lines[0], expected_headers, "Headers do not match");
-        let expected_first_row = "70,2024-10-04 09:10:17 UTC,Approved,Stake,1.00000,NEAR,astro-stakers.poolv1.near,megha19.near,Testing Stake,megha19.near,";
+        let expected_first_row = "70,2024-10-04 09:10:17 UTC,Approved,Stake,1.00000,NEAR,astro-stakers.poolv1.near,astro-stakers.poolv1.near,megha19.near,Testing Stake,megha19.near,";
         assert_eq!(
             lines[1], expected_first_row,
             "First data row does not match"
@@ -56,13 +56,32 @@ mod test {
         assert_eq!(response.status(), Status::Ok);
         let body = response.into_string().expect("response body");
         let lines: Vec<&str> = body.lines().collect();
-        let expected_headers = "ID,Created Date,Status,Send Amount,Send Token,Receive Amount,Receive Token,Created By,Notes,Approvers (Approved),Approvers (Rejected/Remove)";
+        let expected_headers = "ID,Created Date,Status,Send Amount,Send Token,Receive Amount,Receive Token,Created By,Notes,Approvers (Approved),Approvers (Rejected/Remove),Min Receive Amount,Pool IDs";
         assert_eq!(lines[0], expected_headers, "Headers do not match");
-        let expected_first_row = "193,2025-02-28 12:38:54 UTC,Approved,0.1,USDC,0.10007,USDt,megha19.near,,megha19.near,";
+        // Swaps decoded from the ref.finance call are listed whatever their description
+        // says, so the swap is looked up by id rather than expected first
+        let row = lines
+            .iter()
+            .find(|line| line.starts_with("193,"))
+            .expect("swap 193 should be listed");
+        let fields: Vec<&str> = row.split(',').collect();
+        assert_eq!(fields.len(), 13, "Unexpected number of columns: {}", row);
         assert_eq!(
-            lines[1], expected_first_row,
-            "First data row does not match"
+            fields[..3],
+            ["193", "2025-02-28 12:38:54 UTC", "Approved"],
+            "Swap 193 does not match: {}",
+            row
+        );
+        // The send amount comes from the swap args, normalized with the token decimals
+        assert_eq!(
+            fields[3].parse::<f64>().ok(),
+            Some(0.1),
+            "Send Amount: {}",
+            row
         );
+        assert_eq!(fields[4], "USDC", "Send Token: {}", row);
+        assert_eq!(fields[6], "USDt", "Receive Token: {}", row);
+        assert_eq!(fields[7], "megha19.near", "Created By: {}", row);
 
         // Test 5: Payments
         let response = client
@@ -78,5 +97,128 @@ mod test {
             lines[1], expected_first_row,
             "First data row does not match"
         );
+
+        // Test 6: Payments with USD values
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?category=payments&include_usd=true")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        let lines: Vec<&str> = body.lines().collect();
+        let expected_headers = "ID,Created Date,Status,Title,Summary,Recipient,Requested Token,Funding Ask,Created by,Notes,Approvers (Approved),Approvers (Rejected/Remove),USD Value";
+        assert_eq!(lines[0], expected_headers, "Headers do not match");
+
+        // Test 7: Semicolon dialect with BOM and CRLF
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?delimiter=semicolon&line_ending=crlf&bom=true")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        assert!(body.starts_with('\u{FEFF}'), "CSV should start with a BOM");
+        let first_line = body
+            .trim_start_matches('\u{FEFF}')
+            .split("\r\n")
+            .next()
+            .unwrap();
+        let expected_headers = "ID;Created Date;Status;Description;Kind;Created by;Approvers (Approved);Approvers (Rejected/Remove)";
+        assert_eq!(first_line, expected_headers, "Headers do not match");
+
+        // Test 8: Timezone and date format
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?tz=Europe/Berlin&date_format=%25d.%25m.%25Y%20%25H:%25M%20%25Z")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        let created_date = body
+            .lines()
+            .nth(1)
+            .and_then(|row| row.split(',').nth(1))
+            .expect("CSV should have a data row");
+        assert!(
+            created_date.ends_with("CET") || created_date.ends_with("CEST"),
+            "Created Date should be in Berlin time: {}",
+            created_date
+        );
+        assert_eq!(created_date.split('.').count(), 3, "Unexpected date format");
+
+        // Test 9: Unknown timezone
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?tz=Mars/Olympus")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        // Test 10: Member and policy changes
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?category=members")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        let lines: Vec<&str> = body.lines().collect();
+        let expected_headers = "ID,Created Date,Status,Action,Member,Role,Policy Changes,Description,Created by,Approvers (Approved),Approvers (Rejected/Remove)";
+        assert_eq!(lines[0], expected_headers, "Headers do not match");
+        assert!(lines.len() > 1, "Expected member change proposals");
+
+        // Test 11: Transaction log columns
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?category=stake-delegation&include_txs=true")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        let lines: Vec<&str> = body.lines().collect();
+        let expected_headers = "ID,Created Date,Status,Type,Amount,Token,Validator,Resolved Validator,Created by,Notes,Approvers (Approved),Approvers (Rejected/Remove),Creation Tx,Approval Txs,Vote Timestamps";
+        assert_eq!(lines[0], expected_headers, "Headers do not match");
+        assert!(
+            lines[1].contains("megha19.near: 2024-10-04"),
+            "Vote timestamp of the approver should be listed: {}",
+            lines[1]
+        );
+
+        // Test 12: Accounting profiles
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?category=payments&profile=quickbooks")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(
+            lines[0], "Date,Payee,Amount,Currency,Memo,Reference",
+            "Headers do not match"
+        );
+        for line in &lines[1..] {
+            let fields: Vec<&str> = line.split(',').collect();
+            assert_eq!(fields[0].len(), 10, "Date should be MM/DD/YYYY: {}", line);
+            assert!(
+                fields[2].starts_with('-'),
+                "Amount should be negative: {}",
+                line
+            );
+        }
+
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near?profile=xero")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        // Test 13: Proposers list with counts
+        let response = client
+            .get("/csv/proposals/testing-astradao.sputnik-dao.near/proposers")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().expect("response body");
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines[0], "Proposer,Proposals", "Headers do not match");
+        assert!(
+            lines[1..]
+                .iter()
+                .any(|line| line.starts_with("megha19.near,")),
+            "megha19.near should be listed as a proposer"
+        );
+        for line in &lines[1..] {
+            let count = line.rsplit(',').next().unwrap();
+            assert!(
+                count.parse::<usize>().unwrap() > 0,
+                "Counts should be positive"
+            );
+        }
     }
 }
@@ -0,0 +1,471 @@
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use sputnik_indexer::rocket;
+
+const TEST_DAO_ID: &str = "testing-astradao.sputnik-dao.near";
+
+fn get_json(client: &Client, url: &str) -> serde_json::Value {
+    let response = client.get(url).dispatch();
+    assert_eq!(
+        response.status(),
+        Status::Ok,
+        "Request to {} should succeed",
+        url
+    );
+    let body = response.into_string().expect("response body");
+    serde_json::from_str(&body).expect("Response should be valid JSON")
+}
+
+#[test]
+fn test_dao_policy() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let policy = get_json(&client, &format!("/policy/{}", TEST_DAO_ID));
+    for field in [
+        "roles",
+        "default_vote_policy",
+        "proposal_bond",
+        "proposal_period",
+        "bounty_bond",
+        "bounty_forgiveness_period",
+    ] {
+        assert!(
+            policy.get(field).is_some(),
+            "Policy should have {} field",
+            field
+        );
+    }
+    assert!(
+        policy["roles"]
+            .as_array()
+            .is_some_and(|roles| !roles.is_empty()),
+        "Policy should have at least one role"
+    );
+}
+
+#[test]
+fn test_dao_config() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let config = get_json(&client, &format!("/config/{}", TEST_DAO_ID));
+    for field in ["name", "purpose", "metadata"] {
+        assert!(
+            config.get(field).and_then(|v| v.as_str()).is_some(),
+            "Config should have {} string field",
+            field
+        );
+    }
+}
+
+#[test]
+fn test_dao_bounties() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = get_json(&client, &format!("/bounties/{}", TEST_DAO_ID));
+    let bounties = response["bounties"].as_array().expect("bounties array");
+    assert_eq!(response["total"].as_u64(), Some(bounties.len() as u64));
+    for bounty in bounties {
+        for field in [
+            "id",
+            "description",
+            "token",
+            "amount",
+            "times",
+            "max_deadline",
+        ] {
+            assert!(
+                bounty.get(field).is_some(),
+                "Bounty should have {} field",
+                field
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pending_for_account() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+    let account_id = "megha19.near";
+
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}/pending-for/{}", TEST_DAO_ID, account_id),
+    );
+    let proposals = response["proposals"].as_array().expect("proposals array");
+    assert_eq!(response["total"].as_u64(), Some(proposals.len() as u64));
+    for proposal in proposals {
+        assert_eq!(proposal["computed_status"].as_str(), Some("InProgress"));
+        assert!(
+            proposal["votes"].get(account_id).is_none(),
+            "Pending proposals should not have a vote from {}",
+            account_id
+        );
+    }
+}
+
+#[test]
+fn test_dao_roles_and_members() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = get_json(&client, &format!("/roles/{}", TEST_DAO_ID));
+    let roles = response["roles"].as_array().expect("roles array");
+    assert_eq!(response["total"].as_u64(), Some(roles.len() as u64));
+    let group_roles: Vec<&str> = roles
+        .iter()
+        .filter(|role| role["kind"].get("Group").is_some())
+        .filter_map(|role| role["name"].as_str())
+        .collect();
+
+    let response = get_json(&client, &format!("/members/{}", TEST_DAO_ID));
+    let members = response["members"].as_array().expect("members array");
+    assert_eq!(response["total"].as_u64(), Some(members.len() as u64));
+    for member in members {
+        assert!(member["account_id"].as_str().is_some());
+        let member_roles = member["roles"].as_array().expect("member roles array");
+        assert!(
+            member_roles
+                .iter()
+                .all(|role| group_roles.contains(&role.as_str().unwrap_or_default())),
+            "Members should only belong to group roles"
+        );
+    }
+}
+
+#[test]
+fn test_vote_progress() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?statuses=Approved&page_size=20", TEST_DAO_ID),
+    );
+    let proposals = response["proposals"].as_array().expect("proposals array");
+    for proposal in proposals {
+        let progress = proposal["vote_progress"]
+            .as_array()
+            .expect("vote_progress array");
+        for role in progress {
+            assert!(role["role"].as_str().is_some());
+            let approvals: u128 = role["approvals"].as_str().unwrap().parse().unwrap();
+            if let Some(required) = role["required_approvals"].as_str() {
+                let required: u128 = required.parse().unwrap();
+                let percent = role["percent_complete"].as_f64().expect("percent_complete");
+                assert!((0.0..=100.0).contains(&percent));
+                if approvals >= required {
+                    assert_eq!(percent, 100.0);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dao_stats() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let stats = get_json(&client, &format!("/stats/{}", TEST_DAO_ID));
+    let total = stats["total_proposals"].as_u64().expect("total_proposals");
+    assert!(total > 0, "Test DAO should have proposals");
+
+    let sum = |field: &str| -> u64 {
+        stats[field]
+            .as_object()
+            .unwrap_or_else(|| panic!("{} should be an object", field))
+            .values()
+            .filter_map(|count| count.as_u64())
+            .sum()
+    };
+    assert_eq!(sum("by_status"), total, "Status counts should add up");
+    assert_eq!(sum("by_month"), total, "Monthly counts should add up");
+    assert!(stats["unique_proposers"].as_u64().unwrap() <= total);
+    assert!(stats["unique_voters"].as_u64().is_some());
+}
+
+#[test]
+fn test_batch_proposals() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let body = serde_json::json!([
+        { "dao_id": TEST_DAO_ID, "filters": { "statuses": "Approved", "page_size": 5 } },
+        { "dao_id": "not a valid account" }
+    ]);
+    let response = client
+        .post("/batch/proposals")
+        .header(rocket::http::ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let results: serde_json::Value =
+        serde_json::from_str(&response.into_string().expect("response body")).unwrap();
+    let results = results.as_array().expect("results array");
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0]["dao_id"].as_str(), Some(TEST_DAO_ID));
+    let proposals = results[0]["result"]["proposals"]
+        .as_array()
+        .expect("proposals array");
+    assert!(proposals.len() <= 5);
+    for proposal in proposals {
+        assert_eq!(proposal["status"].as_str(), Some("Approved"));
+    }
+
+    assert!(results[1]["result"].is_null());
+    assert_eq!(results[1]["error"].as_str(), Some("400 Bad Request"));
+}
+
+#[test]
+fn test_proposals_ndjson() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = client
+        .get(format!(
+            "/proposals/{}?format=ndjson&page_size=5",
+            TEST_DAO_ID
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.content_type().map(|c| c.to_string()),
+        Some("application/x-ndjson".to_string())
+    );
+    let body = response.into_string().expect("response body");
+    let lines: Vec<&str> = body.lines().collect();
+    assert!(lines.len() <= 5);
+    for line in lines {
+        let proposal: serde_json::Value =
+            serde_json::from_str(line).expect("Each line should be a JSON object");
+        assert!(proposal.get("id").is_some());
+    }
+}
+
+#[test]
+fn test_graphql_proposals() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let query = format!(
+        r#"{{ dao(id: "{}") {{ id policy {{ roles {{ name }} }} proposals(statuses: "Approved", category: "payments", first: 3) {{ id status payment {{ receiver token amount }} }} }} }}"#,
+        TEST_DAO_ID
+    );
+    let response = client
+        .post("/graphql")
+        .header(rocket::http::ContentType::JSON)
+        .body(serde_json::json!({ "query": query }).to_string())
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body: serde_json::Value =
+        serde_json::from_str(&response.into_string().expect("response body")).unwrap();
+    assert!(body["errors"].is_null(), "Unexpected errors: {}", body);
+
+    let dao = &body["data"]["dao"];
+    assert_eq!(dao["id"].as_str(), Some(TEST_DAO_ID));
+    assert!(!dao["policy"]["roles"].as_array().unwrap().is_empty());
+    let proposals = dao["proposals"].as_array().expect("proposals array");
+    assert!(!proposals.is_empty() && proposals.len() <= 3);
+    for proposal in proposals {
+        assert_eq!(proposal["status"].as_str(), Some("Approved"));
+        assert!(proposal["payment"]["receiver"].is_string());
+        // Only requested fields are returned
+        assert!(proposal.get("description").is_none());
+    }
+}
+
+#[test]
+fn test_openapi_document() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let doc = get_json(&client, "/openapi.json");
+    assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+    let paths = doc["paths"].as_object().expect("paths object");
+    for path in [
+        "/proposals/{dao_id}",
+        "/proposal/{dao_id}/{proposal_id}",
+        "/csv/proposals/{dao_id}",
+    ] {
+        assert!(paths.contains_key(path), "{} should be documented", path);
+    }
+    let parameters = doc["paths"]["/proposals/{dao_id}"]["get"]["parameters"]
+        .as_array()
+        .expect("parameters");
+    assert!(
+        parameters
+            .iter()
+            .any(|parameter| parameter["name"] == "kind_filter"),
+        "Filter parameters should be documented"
+    );
+    let parameters = doc["paths"]["/proposal/{dao_id}/{proposal_id}"]["get"]["parameters"]
+        .as_array()
+        .expect("parameters");
+    for name in ["dao_id", "proposal_id"] {
+        assert!(
+            parameters
+                .iter()
+                .any(|parameter| parameter["name"] == name && parameter["in"] == "path"),
+            "{} should be documented as a path parameter",
+            name
+        );
+    }
+    let parameters = doc["paths"]["/csv/proposals/{dao_id}"]["get"]["parameters"]
+        .as_array()
+        .expect("parameters");
+    assert!(
+        parameters
+            .iter()
+            .any(|parameter| parameter["name"] == "delimiter" && parameter["in"] == "query"),
+        "CSV dialect parameters should be documented"
+    );
+    assert!(doc["components"]["schemas"]["PaginatedProposals"].is_object());
+}
+
+#[test]
+fn test_voting_deadline_calendar() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = client
+        .get(format!("/calendar/{}.ics", TEST_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.content_type().map(|c| c.to_string()),
+        Some("text/calendar".to_string())
+    );
+    let body = response.into_string().expect("response body");
+    assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(
+        body.matches("BEGIN:VEVENT").count(),
+        body.matches("END:VEVENT").count()
+    );
+    for line in body.split("\r\n") {
+        assert!(line.len() <= 75, "Line should be folded: {}", line);
+    }
+
+    let response = client.get(format!("/calendar/{}", TEST_DAO_ID)).dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn test_cache_headers() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = client.get(format!("/policy/{}", TEST_DAO_ID)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let header_secs = |name: &str| -> u64 {
+        response
+            .headers()
+            .get_one(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| panic!("{} should be a number of seconds", name))
+    };
+    let ttl = header_secs("X-Cache-TTL");
+    let age = header_secs("X-Cache-Age");
+    assert!(
+        age <= ttl,
+        "Freshly fetched DAO should not be older than its TTL"
+    );
+
+    // Routes not served from the DAO cache have no cache headers
+    let response = client.get("/openapi.json").dispatch();
+    assert!(response.headers().get_one("X-Cache-TTL").is_none());
+}
+
+#[test]
+fn test_unknown_dao_not_found() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    // The second request is answered from the negative cache
+    for _ in 0..2 {
+        let response = client
+            .get("/policy/this-dao-does-not-exist.sputnik-dao.near")
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
+
+#[test]
+fn test_admin_requires_key() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let response = client.get("/admin/cache/stats").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/admin/cache/purge/testing-astradao.sputnik-dao.near")
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/daos/track")
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"dao_id": "{}"}}"#, TEST_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .delete(format!("/daos/track/{}", TEST_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .get(format!("/admin/export/{}", TEST_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/admin/import")
+        .header(ContentType::JSON)
+        .body("{}")
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client.get("/admin/reports").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/admin/reports")
+        .header(ContentType::JSON)
+        .body("{}")
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_proposal_history() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let history = get_json(&client, &format!("/proposal/{}/0/history", TEST_DAO_ID));
+    let history = history.as_array().expect("history array");
+    assert!(!history.is_empty());
+    let block_heights: Vec<u64> = history
+        .iter()
+        .map(|state| state["block_height"].as_u64().unwrap())
+        .collect();
+    assert!(block_heights.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let proposal = get_json(&client, &format!("/proposal/{}/0", TEST_DAO_ID));
+    assert_eq!(history.last().unwrap()["status"], proposal["status"]);
+}
+
+#[test]
+fn test_voter_leaderboard() {
+    let client = Client::tracked(rocket()).expect("valid rocket instance");
+
+    let leaderboard = get_json(
+        &client,
+        &format!("/stats/{}/voters?sort_by=votes&page_size=2", TEST_DAO_ID),
+    );
+    let voters = leaderboard["voters"].as_array().expect("voters array");
+    assert!(voters.len() <= 2);
+    assert!(leaderboard["total"].as_u64().unwrap() >= voters.len() as u64);
+    let votes: Vec<u64> = voters
+        .iter()
+        .map(|voter| voter["votes"].as_u64().unwrap())
+        .collect();
+    assert!(votes.windows(2).all(|pair| pair[0] >= pair[1]));
+    for voter in voters {
+        let split = ["approve", "reject", "remove"]
+            .iter()
+            .map(|kind| voter[kind].as_u64().unwrap())
+            .sum::<u64>();
+        assert_eq!(voter["votes"].as_u64(), Some(split));
+    }
+}
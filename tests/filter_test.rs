@@ -536,6 +536,23 @@ async fn test_all_filters() {
     )
     .await;
 
+    run_filter_test(
+        &client,
+        "expired status filter",
+        &format!("/proposals/{}?statuses=Expired", TEST_DAO_ID),
+        |proposals| {
+            for proposal in proposals {
+                assert_eq!(proposal["status"].as_str(), Some("InProgress"));
+                assert_eq!(
+                    proposal["computed_status"].as_str(),
+                    Some("Expired"),
+                    "Expired proposals should expose computed_status"
+                );
+            }
+        },
+    )
+    .await;
+
     // Test 2: Search filter
     run_filter_test(
         &client,
@@ -688,6 +705,39 @@ async fn test_all_filters() {
         );
     }
 
+    // Cursor pagination should continue exactly where the offset page ends
+    if let Some(next_cursor) = response.get("next_cursor").and_then(|c| c.as_str()) {
+        let next_page = make_request_and_parse(
+            &client,
+            &format!(
+                "/proposals/{}?page_size=5&cursor={}",
+                TEST_DAO_ID, next_cursor
+            ),
+        )
+        .await;
+        let offset_page = make_request_and_parse(
+            &client,
+            &format!("/proposals/{}?page=1&page_size=5", TEST_DAO_ID),
+        )
+        .await;
+        let ids = |page: &serde_json::Value| -> Vec<u64> {
+            get_proposals_array(page)
+                .map(|proposals| proposals.iter().filter_map(|p| p["id"].as_u64()).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(
+            ids(&next_page),
+            ids(&offset_page),
+            "Cursor page should match the next offset page"
+        );
+    }
+
+    let response = client
+        .get(format!("/proposals/{}?cursor=not-a-cursor", TEST_DAO_ID))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+
     // Test 13: Multiple filters
     run_filter_test(
         &client,
@@ -731,7 +781,20 @@ async fn test_all_filters() {
     // Verify tokens array
     if let Some(tokens_array) = response.get("requested_tokens").and_then(|t| t.as_array()) {
         for token in tokens_array {
-            assert!(token.is_string(), "Each token should be a string");
+            assert!(
+                token["token_id"].is_string(),
+                "Each token should have an id"
+            );
+            assert!(
+                token["proposals"].as_u64().is_some_and(|count| count > 0),
+                "Each token should be requested at least once"
+            );
+            assert!(
+                token["total_amount"]
+                    .as_str()
+                    .is_some_and(|amount| amount.parse::<u128>().is_ok()),
+                "Total amount should be a raw integer string"
+            );
         }
     }
 
@@ -838,6 +901,45 @@ async fn test_all_filters() {
     )
     .await;
 
+    // Test 18b: Expiry date filters
+    let policy = make_request_and_parse(&client, &format!("/policy/{}", TEST_DAO_ID)).await;
+    let proposal_period = policy["proposal_period"]
+        .as_str()
+        .unwrap()
+        .parse::<u64>()
+        .unwrap();
+    run_filter_test(
+        &client,
+        "expiry date filters",
+        &format!(
+            "/proposals/{}?expires_after=2024-01-01&expires_before=2024-12-31",
+            TEST_DAO_ID
+        ),
+        |proposals| {
+            for proposal in proposals {
+                let submission_time = proposal["submission_time"]
+                    .as_str()
+                    .unwrap()
+                    .parse::<u64>()
+                    .unwrap();
+                let expiry_time = submission_time + proposal_period;
+                assert!(
+                    (1704067200000000000..=1735689599999999999).contains(&expiry_time),
+                    "All proposals should expire within the specified date range"
+                );
+            }
+        },
+    )
+    .await;
+
+    run_filter_test(
+        &client,
+        "expiring within filter",
+        &format!("/proposals/{}?expiring_within=24", TEST_DAO_ID),
+        |proposals| verify_proposal_status(proposals, "InProgress"),
+    )
+    .await;
+
     // Test 19: Multiple voter votes filter
     run_filter_test(
         &client,
@@ -1222,5 +1324,453 @@ async fn test_all_filters() {
     )
     .await;
 
+    // Test 42: Amount filter without a category
+    run_filter_test(
+        &client,
+        "amount filter without category",
+        &format!("/proposals/{}?tokens=near&amount_min=1.0", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(
+                proposals,
+                "Amount filter without category should return proposals",
+            );
+            for proposal in proposals {
+                verify_payment_amount(proposal, Some(1000000000000000000000000), None, None);
+            }
+        },
+    )
+    .await;
+
+    // Test 43: Kind field filter
+    run_filter_test(
+        &client,
+        "kind field filter",
+        &format!(
+            "/proposals/{}?kind_filter=FunctionCall.receiver_id=intents.near",
+            TEST_DAO_ID
+        ),
+        |proposals| {
+            for proposal in proposals {
+                assert_eq!(
+                    proposal["kind"]["FunctionCall"]["receiver_id"].as_str(),
+                    Some("intents.near"),
+                    "All proposals should call intents.near"
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 44: Members category
+    run_filter_test(
+        &client,
+        "members category",
+        &format!("/proposals/{}?category=members", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(proposals, "Members category should return proposals");
+            for proposal in proposals {
+                let kind = proposal["kind"]
+                    .as_object()
+                    .and_then(|kind| kind.keys().next())
+                    .expect("proposal kind");
+                assert!(
+                    kind == "AddMemberToRole"
+                        || kind == "RemoveMemberFromRole"
+                        || kind.starts_with("ChangePolicy"),
+                    "Unexpected kind {} in members category",
+                    kind
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 45: Recipients filter without a category
+    run_filter_test(
+        &client,
+        "recipients filter without category",
+        &format!("/proposals/{}?recipients=megha19.near", TEST_DAO_ID),
+        |proposals| verify_payment_recipients(proposals, &["megha19.near"], false),
+    )
+    .await;
+
+    // Test 46: Payment filters contradict another category
+    let response = client
+        .get(format!(
+            "/proposals/{}?category=stake-delegation&tokens=near",
+            TEST_DAO_ID
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let body: serde_json::Value =
+        serde_json::from_str(&response.into_string().await.expect("response body"))
+            .expect("JSON error body");
+    assert!(body["error"].is_string(), "Conflict should be explained");
+
+    // Test 47: Strict mode rejects values that are otherwise ignored
+    let response = client
+        .get(format!(
+            "/proposals/{}?strict=true&created_date_from=invalid-date&sort_by=Invalid",
+            TEST_DAO_ID
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let body: serde_json::Value =
+        serde_json::from_str(&response.into_string().await.expect("response body"))
+            .expect("JSON error body");
+    let parameters: Vec<&str> = body["invalid_parameters"]
+        .as_array()
+        .expect("invalid_parameters array")
+        .iter()
+        .filter_map(|invalid| invalid["parameter"].as_str())
+        .collect();
+    assert_eq!(parameters, ["created_date_from", "sort_by"]);
+
+    // Test 48: Proposal id list
+    run_filter_test(
+        &client,
+        "ids filter",
+        &format!("/proposals/{}?ids=1,5,9", TEST_DAO_ID),
+        |proposals| {
+            let ids: Vec<u64> = proposals
+                .iter()
+                .filter_map(|proposal| proposal["id"].as_u64())
+                .collect();
+            assert_eq!(
+                ids,
+                [1, 5, 9],
+                "Only the listed proposals should be returned"
+            );
+        },
+    )
+    .await;
+
+    // Test 49: Proposal id range
+    run_filter_test(
+        &client,
+        "id range filter",
+        &format!("/proposals/{}?id_from=10&id_to=19", TEST_DAO_ID),
+        |proposals| {
+            assert_eq!(proposals.len(), 10, "Range should include both ends");
+            for proposal in proposals {
+                let id = proposal["id"].as_u64().expect("proposal id");
+                assert!((10..=19).contains(&id), "Proposal {} is out of range", id);
+            }
+        },
+    )
+    .await;
+
+    // Test 50: Vote count range
+    run_filter_test(
+        &client,
+        "vote count filter",
+        &format!("/proposals/{}?min_votes=2&max_votes=3", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(proposals, "Vote count filter should return proposals");
+            for proposal in proposals {
+                let votes = proposal["votes"].as_object().expect("votes").len();
+                assert!((2..=3).contains(&votes), "Proposal has {} votes", votes);
+            }
+        },
+    )
+    .await;
+
+    // Test 51: Approval count
+    run_filter_test(
+        &client,
+        "approvals filter",
+        &format!("/proposals/{}?approvals_min=2", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(proposals, "Approvals filter should return proposals");
+            for proposal in proposals {
+                let approvals = proposal["votes"]
+                    .as_object()
+                    .expect("votes")
+                    .values()
+                    .filter(|vote| vote.as_str() == Some("Approve"))
+                    .count();
+                assert!(approvals >= 2, "Proposal has {} approvals", approvals);
+            }
+        },
+    )
+    .await;
+
+    // Test 52: Open proposals one approval away from passing
+    run_filter_test(
+        &client,
+        "needs one more approval filter",
+        &format!("/proposals/{}?needs_n_more_approvals=1", TEST_DAO_ID),
+        |proposals| {
+            for proposal in proposals {
+                assert_eq!(proposal["status"].as_str(), Some("InProgress"));
+            }
+        },
+    )
+    .await;
+
+    // Test 53: Several categories
+    run_filter_test(
+        &client,
+        "multiple categories",
+        &format!("/proposals/{}?category=payments,members", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(proposals, "Multiple categories should return proposals");
+            for proposal in proposals {
+                let kind = proposal["kind"]
+                    .as_object()
+                    .and_then(|kind| kind.keys().next())
+                    .expect("proposal kind");
+                assert_ne!(kind, "UpgradeSelf", "Unexpected kind {}", kind);
+            }
+        },
+    )
+    .await;
+
+    // Test 54: Category exclusion
+    run_filter_test(
+        &client,
+        "category exclusion",
+        &format!("/proposals/{}?category_not=members", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(proposals, "Category exclusion should return proposals");
+            for proposal in proposals {
+                let kind = proposal["kind"]
+                    .as_object()
+                    .and_then(|kind| kind.keys().next())
+                    .expect("proposal kind");
+                assert!(
+                    kind != "AddMemberToRole"
+                        && kind != "RemoveMemberFromRole"
+                        && !kind.starts_with("ChangePolicy"),
+                    "Members proposal {} should be excluded",
+                    kind
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 55: Function call receiver and method
+    run_filter_test(
+        &client,
+        "receiver and method filter",
+        &format!(
+            "/proposals/{}?receiver_ids=intents.near&method_names=mt_transfer,ft_withdraw",
+            TEST_DAO_ID
+        ),
+        |proposals| {
+            for proposal in proposals {
+                let call = &proposal["kind"]["FunctionCall"];
+                assert_eq!(call["receiver_id"].as_str(), Some("intents.near"));
+                let methods: Vec<&str> = call["actions"]
+                    .as_array()
+                    .expect("actions")
+                    .iter()
+                    .filter_map(|action| action["method_name"].as_str())
+                    .collect();
+                assert!(
+                    methods
+                        .iter()
+                        .any(|method| *method == "mt_transfer" || *method == "ft_withdraw"),
+                    "Unexpected methods {:?}",
+                    methods
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 56: Receiver exclusion keeps other kinds
+    run_filter_test(
+        &client,
+        "receiver exclusion",
+        &format!("/proposals/{}?receiver_ids_not=intents.near", TEST_DAO_ID),
+        |proposals| {
+            verify_proposals_returned(proposals, "Receiver exclusion should return proposals");
+            for proposal in proposals {
+                assert_ne!(
+                    proposal["kind"]["FunctionCall"]["receiver_id"].as_str(),
+                    Some("intents.near")
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 57: Nothing was updated in the future
+    run_filter_test(
+        &client,
+        "updated_from filter",
+        &format!("/proposals/{}?updated_from=2099-01-01", TEST_DAO_ID),
+        |proposals| assert!(proposals.is_empty(), "No proposal was updated after 2099"),
+    )
+    .await;
+
+    // Test 58: Regex search
+    run_filter_test(
+        &client,
+        "regex search",
+        &format!(
+            "/proposals/{}?search=pay(ment|out)&search_mode=regex",
+            TEST_DAO_ID
+        ),
+        |proposals| {
+            verify_proposals_returned(proposals, "Regex search should return proposals");
+            for proposal in proposals {
+                let description = proposal["description"]
+                    .as_str()
+                    .expect("description")
+                    .to_lowercase();
+                assert!(
+                    description.contains("payment") || description.contains("payout"),
+                    "Description doesn't match: {}",
+                    description
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 59: Invalid regex with strict mode
+    let response = client
+        .get(format!(
+            "/proposals/{}?search=(unclosed&search_mode=regex&strict=true",
+            TEST_DAO_ID
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+
+    // Test 60: Search inside decoded FunctionCall args
+    run_filter_test(
+        &client,
+        "search in args",
+        &format!(
+            "/proposals/{}?search=megha19.near&search_scope=args&receiver_ids=usdt.tether-token.near",
+            TEST_DAO_ID
+        ),
+        |proposals| {
+            for proposal in proposals {
+                let actions = proposal["kind"]["FunctionCall"]["actions"]
+                    .as_array()
+                    .expect("actions");
+                assert!(
+                    actions.iter().any(|action| {
+                        let args = action["args"].as_str().unwrap_or_default();
+                        String::from_utf8(
+                            STANDARD
+                                .decode(args)
+                                .unwrap_or_default(),
+                        )
+                        .unwrap_or_default()
+                        .contains("megha19.near")
+                    }),
+                    "Args should mention megha19.near"
+                );
+            }
+        },
+    )
+    .await;
+
+    // Test 61: Recipients with payment details
+    println!("Testing detailed recipients endpoint...");
+    let response = make_request_and_parse(
+        &client,
+        &format!("/proposals/{}/recipients?detailed=true", TEST_DAO_ID),
+    )
+    .await;
+    verify_response_fields(&response, &["recipients", "details", "total"]);
+    let details = response["details"].as_array().expect("details array");
+    assert_eq!(
+        details.len() as u64,
+        response["total"].as_u64().unwrap_or(0)
+    );
+    for detail in details {
+        assert!(
+            detail["recipient"].is_string(),
+            "Each entry should have a recipient"
+        );
+        assert!(
+            detail["proposals"].as_u64().is_some_and(|count| count > 0),
+            "Each recipient should have a payment proposal"
+        );
+        for amount in detail["approved_amounts"]
+            .as_array()
+            .expect("approved amounts")
+        {
+            assert!(amount["token_id"].is_string());
+            assert!(
+                amount["amount"]
+                    .as_str()
+                    .is_some_and(|amount| amount.parse::<u128>().is_ok())
+            );
+        }
+    }
+
+    // Test 62: Validators with stake totals
+    println!("Testing detailed validators endpoint...");
+    let response = make_request_and_parse(
+        &client,
+        &format!("/proposals/{}/validators?detailed=true", TEST_DAO_ID),
+    )
+    .await;
+    verify_response_fields(&response, &["validators", "details", "total"]);
+    for detail in response["details"].as_array().expect("details array") {
+        assert!(detail["validator"].is_string());
+        for field in [
+            "approved_staked",
+            "approved_unstaked",
+            "staked_balance",
+            "unstaked_balance",
+        ] {
+            assert!(
+                detail[field]
+                    .as_str()
+                    .is_some_and(|amount| amount.parse::<u128>().is_ok()),
+                "{} should be a yoctoNEAR string",
+                field
+            );
+        }
+    }
+
+    // Test 63: Approvers with vote splits, scoped by role
+    println!("Testing detailed approvers endpoint...");
+    let response = make_request_and_parse(
+        &client,
+        &format!("/proposals/{}/approvers?detailed=true", TEST_DAO_ID),
+    )
+    .await;
+    verify_response_fields(&response, &["approvers", "details", "total"]);
+    let details = response["details"].as_array().expect("details array");
+    for detail in details {
+        let votes: u64 = ["approve", "reject", "remove"]
+            .iter()
+            .map(|vote| detail[*vote].as_u64().expect("vote count"))
+            .sum();
+        assert!(votes > 0, "Approvers should have voted");
+    }
+    let role = details
+        .iter()
+        .find_map(|detail| detail["roles"][0].as_str())
+        .expect("an approver with a role");
+    let response = make_request_and_parse(
+        &client,
+        &format!(
+            "/proposals/{}/approvers?role={}&detailed=true",
+            TEST_DAO_ID, role
+        ),
+    )
+    .await;
+    for detail in response["details"].as_array().expect("details array") {
+        let roles = detail["roles"].as_array().expect("roles");
+        assert!(
+            roles.iter().any(|r| r.as_str() == Some(role)),
+            "Approvers should be in role {}",
+            role
+        );
+    }
+
     println!("All filter tests completed successfully!");
 }
@@ -0,0 +1,502 @@
+use arrow_array::{Array, Decimal128Array, RecordBatch, StringArray, UInt64Array};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rocket::http::Status;
+use rocket::local::blocking::Client;
+use sputnik_indexer::rocket;
+use std::sync::Once;
+
+// Recorded under tests/fixtures, so these tests run without RPC
+const FIXTURE_DAO_ID: &str = "fixture-dao.sputnik-dao.near";
+const STAKING_DAO_ID: &str = "staking-dao.sputnik-dao.near";
+
+// Metadata of the tokens the staking DAO moves, so their amounts normalize without RPC
+const TOKENS_CONFIG: &str = r#"
+[tokens.registry."wrap.near"]
+symbol = "wNEAR"
+decimals = 24
+
+[tokens.registry."meta-pool.near"]
+symbol = "STNEAR"
+decimals = 24
+"#;
+
+static USE_FIXTURES: Once = Once::new();
+
+fn fixture_client() -> Client {
+    // Settings are loaded once per process, before any rocket is built
+    USE_FIXTURES.call_once(|| {
+        let path =
+            std::env::temp_dir().join(format!("fixture_source_test_{}.toml", std::process::id()));
+        std::fs::write(&path, TOKENS_CONFIG).expect("write test config");
+        // SAFETY: the other tests wait on USE_FIXTURES, nothing reads the environment meanwhile
+        unsafe { std::env::set_var("CONFIG_PATH", &path) };
+        sputnik_indexer::use_fixtures("tests/fixtures");
+    });
+    Client::tracked(rocket()).expect("valid rocket instance")
+}
+
+fn get_json(client: &Client, url: &str) -> serde_json::Value {
+    let response = client.get(url).dispatch();
+    assert_eq!(
+        response.status(),
+        Status::Ok,
+        "Request to {} should succeed",
+        url
+    );
+    let body = response.into_string().expect("response body");
+    serde_json::from_str(&body).expect("Response should be valid JSON")
+}
+
+#[test]
+fn test_fixture_proposals() {
+    let client = fixture_client();
+
+    let response = get_json(&client, &format!("/proposals/{}", FIXTURE_DAO_ID));
+    let proposals = response["proposals"].as_array().expect("proposals array");
+    assert_eq!(response["total"].as_u64(), Some(6));
+    let ids: Vec<u64> = proposals
+        .iter()
+        .map(|proposal| proposal["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?statuses=Approved", FIXTURE_DAO_ID),
+    );
+    assert_eq!(response["total"].as_u64(), Some(1));
+    assert_eq!(response["proposals"][0]["id"].as_u64(), Some(0));
+}
+
+#[test]
+fn test_fixture_policy_and_config() {
+    let client = fixture_client();
+
+    let policy = get_json(&client, &format!("/policy/{}", FIXTURE_DAO_ID));
+    let roles = policy["roles"].as_array().expect("roles array");
+    assert!(roles.iter().any(|role| role["name"] == "council"));
+
+    let config = get_json(&client, &format!("/config/{}", FIXTURE_DAO_ID));
+    assert_eq!(config["name"], "fixture-dao");
+}
+
+#[test]
+fn test_fixture_unknown_dao() {
+    let client = fixture_client();
+
+    let response = client
+        .get("/proposals/not-recorded.sputnik-dao.near")
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn test_fixture_proposal_txs_log() {
+    let client = fixture_client();
+
+    let proposal = get_json(&client, &format!("/proposal/{}/0", FIXTURE_DAO_ID));
+    let txs_log = proposal["txs_log"].as_array().expect("txs_log array");
+    assert_eq!(txs_log.len(), 3);
+    assert_eq!(proposal["txs_log_complete"], true);
+    assert_eq!(txs_log[2]["signer_id"], "bob.near");
+
+    // No recorded calls, but the proposal itself is known
+    let proposal = get_json(&client, &format!("/proposal/{}/2", FIXTURE_DAO_ID));
+    assert!(proposal["txs_log"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_fixture_proposal_timeline() {
+    let client = fixture_client();
+
+    for proposal_id in [0, 2] {
+        let proposal = get_json(
+            &client,
+            &format!("/proposal/{}/{}", FIXTURE_DAO_ID, proposal_id),
+        );
+        let timeline = get_json(
+            &client,
+            &format!("/proposal/{}/{}/timeline", FIXTURE_DAO_ID, proposal_id),
+        );
+        assert_eq!(timeline, proposal["timeline"], "proposal {}", proposal_id);
+    }
+
+    let response = client
+        .get(format!("/proposal/{}/42/timeline", FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn test_fixture_block_timestamps() {
+    let client = fixture_client();
+
+    // Block 100000020 is recorded on 2023-11-21, proposals 2 to 5 have no log and were
+    // submitted on 2023-11-17, in 2100, on 2023-11-18 and on 2023-11-19
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?updated_from=2023-11-17", FIXTURE_DAO_ID),
+    );
+    let ids: Vec<u64> = response["proposals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|proposal| proposal["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_fixture_parquet_export() {
+    let client = fixture_client();
+
+    // NEAR transfer and member change, no token metadata to resolve
+    let response = client
+        .get(format!(
+            "/export/proposals/{}.parquet?ids=0,1",
+            FIXTURE_DAO_ID
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get_one("Content-Type"),
+        Some("application/vnd.apache.parquet")
+    );
+    let bytes = response.into_bytes().expect("Parquet body");
+    assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+
+    let path = std::env::temp_dir().join(format!("fixture_export_{}.parquet", std::process::id()));
+    std::fs::write(&path, &bytes).expect("write Parquet file");
+    let file = std::fs::File::open(&path).expect("open Parquet file");
+    let batches: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(file)
+        .expect("valid Parquet file")
+        .build()
+        .expect("Parquet reader")
+        .collect::<Result<_, _>>()
+        .expect("Parquet rows");
+    std::fs::remove_file(&path).ok();
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 2);
+    let column = |name: &str| batch.column_by_name(name).expect(name).clone();
+    let ids = column("id");
+    let ids = ids.as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!(ids.values(), &[0, 1]);
+    let tokens = column("token");
+    let tokens = tokens.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(tokens.value(0), "near");
+    assert!(tokens.is_null(1));
+    let amounts = column("amount_raw");
+    let amounts = amounts.as_any().downcast_ref::<Decimal128Array>().unwrap();
+    assert_eq!(amounts.value(0), 5_000_000_000_000_000_000_000_000);
+    assert!(amounts.is_null(1));
+    let normalized = column("amount_normalized");
+    let normalized = normalized.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(normalized.value(0), "5");
+    assert!(normalized.is_null(1));
+
+    let response = client
+        .get(format!("/export/proposals/{}.csv", FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn test_fixture_proposal_outlook() {
+    let client = fixture_client();
+
+    // Proposal 2 got no votes and its voting period ended in 2023
+    let outlook = get_json(&client, &format!("/proposal/{}/2/outlook", FIXTURE_DAO_ID));
+    assert_eq!(outlook["status"], "Expired");
+    assert_eq!(outlook["can_pass"], false);
+    assert_eq!(outlook["seconds_remaining"].as_u64(), Some(0));
+    assert_eq!(outlook["expiry_date"], "2023-11-24T05:46:40Z");
+
+    // Only the council votes, half of its three members plus one
+    let roles = outlook["roles"].as_array().expect("roles array");
+    assert_eq!(roles.len(), 1);
+    assert_eq!(roles[0]["role"], "council");
+    assert_eq!(roles[0]["required_approvals"], "2");
+    assert_eq!(roles[0]["approvals_needed"], "2");
+    assert!(roles[0]["remaining_voters"].as_array().unwrap().is_empty());
+
+    // Proposal 3 is open until 2100, alice approved already
+    let outlook = get_json(&client, &format!("/proposal/{}/3/outlook", FIXTURE_DAO_ID));
+    assert_eq!(outlook["status"], "InProgress");
+    assert_eq!(outlook["can_pass"], true);
+    assert_eq!(outlook["expiry_date"], "2100-01-08T00:00:00Z");
+    assert!(outlook["seconds_remaining"].as_u64().unwrap() > 0);
+    assert_eq!(outlook["approvals_needed"], "1");
+    assert_eq!(
+        outlook["decisive_voters"],
+        serde_json::json!(["bob.near", "carol.near"])
+    );
+
+    // Anyone may approve polls, so the threshold of the all role is always in reach
+    let roles = outlook["roles"].as_array().expect("roles array");
+    assert_eq!(roles.len(), 2);
+    assert_eq!(roles[0]["role"], "all");
+    assert!(roles[0]["required_approvals"].is_null());
+    assert!(roles[0]["remaining_voters"].as_array().unwrap().is_empty());
+    assert_eq!(roles[0]["can_pass"], true);
+    assert_eq!(roles[1]["role"], "council");
+    assert_eq!(roles[1]["approvals_needed"], "1");
+    assert_eq!(
+        roles[1]["remaining_voters"],
+        serde_json::json!(["bob.near", "carol.near"])
+    );
+    assert_eq!(roles[1]["can_pass"], true);
+
+    let response = client
+        .get(format!("/proposal/{}/7/outlook", FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn test_fixture_can_act() {
+    let client = fixture_client();
+
+    // Everyone may propose, only the council votes
+    let response = get_json(
+        &client,
+        &format!(
+            "/can-act/{}/dave.near?proposal_kind=Transfer&action=VoteApprove",
+            FIXTURE_DAO_ID
+        ),
+    );
+    assert_eq!(response["proposal_kind"], "transfer");
+    assert_eq!(response["allowed"], false);
+    assert_eq!(response["actions"]["AddProposal"], true);
+    assert_eq!(response["roles"], serde_json::json!(["all"]));
+
+    // Only the token weighted holders role finalizes transfers besides the council, and
+    // balances are not cached
+    let response = get_json(
+        &client,
+        &format!(
+            "/can-act/{}/dave.near?proposal_kind=Transfer&action=Finalize",
+            FIXTURE_DAO_ID
+        ),
+    );
+    assert!(response["allowed"].is_null());
+    assert_eq!(response["action"], "Finalize");
+    assert!(response["actions"]["Finalize"].is_null());
+    assert_eq!(response["actions"]["VoteApprove"], false);
+    assert_eq!(response["roles"], serde_json::json!(["all"]));
+
+    let response = get_json(
+        &client,
+        &format!(
+            "/can-act/{}/alice.near?proposal_kind=add_member_to_role&action=Finalize",
+            FIXTURE_DAO_ID
+        ),
+    );
+    assert_eq!(response["allowed"], true);
+    assert_eq!(response["roles"], serde_json::json!(["all", "council"]));
+
+    for query in [
+        "proposal_kind=Unknown",
+        "proposal_kind=Transfer&action=Vote",
+        "action=VoteApprove",
+    ] {
+        let response = client
+            .get(format!("/can-act/{}/alice.near?{}", FIXTURE_DAO_ID, query))
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest, "{}", query);
+    }
+}
+
+#[test]
+fn test_fixture_policy_changes_csv() {
+    let client = fixture_client();
+
+    // The recorded member change is not a policy change, so only proposal 4 is listed
+    let response = client
+        .get(format!("/csv/proposals/{}/policy-changes", FIXTURE_DAO_ID))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_string().expect("CSV body");
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let headers = reader.headers().expect("CSV headers").clone();
+    let rows: Vec<csv::StringRecord> = reader.records().map(|row| row.unwrap()).collect();
+    assert_eq!(rows.len(), 1);
+    let column = |name: &str| {
+        let index = headers.iter().position(|header| header == name).unwrap();
+        rows[0][index].to_string()
+    };
+
+    // Diffed against the current policy, which the proposal would change
+    assert_eq!(column("ID"), "4");
+    assert_eq!(column("Status"), "Expired");
+    assert_eq!(column("Action"), "Change Policy");
+    assert_eq!(column("Roles Added"), "auditors (dave.near)");
+    assert_eq!(column("Roles Removed"), "");
+    assert_eq!(column("Members Added"), "council: dave.near");
+    assert_eq!(column("Members Removed"), "");
+    assert_eq!(column("Permissions Added"), "");
+    assert_eq!(column("Permissions Removed"), "");
+    assert_eq!(
+        column("Vote Policy Changes"),
+        "default: RoleWeight 1/2, quorum 0 -> RoleWeight 2/3, quorum 0"
+    );
+    assert_eq!(
+        column("Parameter Changes"),
+        "proposal_period: 604800000000000 -> 1209600000000000"
+    );
+    assert_eq!(column("Created by"), "alice.near");
+}
+
+#[test]
+fn test_fixture_members_filter() {
+    let client = fixture_client();
+
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?members=erin.near", FIXTURE_DAO_ID),
+    );
+    assert_eq!(response["total"].as_u64(), Some(1));
+    assert_eq!(response["proposals"][0]["id"].as_u64(), Some(1));
+
+    // Proposals that change no member are kept by members_not
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?members_not=erin.near", FIXTURE_DAO_ID),
+    );
+    assert_eq!(response["total"].as_u64(), Some(5));
+
+    let response = client
+        .get(format!(
+            "/proposals/{}?category=payments&members=erin.near",
+            FIXTURE_DAO_ID
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_fixture_governance_ops_category() {
+    let client = fixture_client();
+
+    // Proposal 5 upgrades a contract with the Wasm code as the call args
+    let response = get_json(
+        &client,
+        &format!(
+            "/proposals/{}?category=governance-ops&strict=true",
+            FIXTURE_DAO_ID
+        ),
+    );
+    assert_eq!(response["total"].as_u64(), Some(1));
+    assert_eq!(response["proposals"][0]["id"].as_u64(), Some(5));
+
+    let response = client
+        .get(format!(
+            "/csv/proposals/{}?category=governance-ops",
+            FIXTURE_DAO_ID
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_string().expect("CSV body");
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("ID,Created Date,Status,Action,Target Contract,Method,Code Hash"));
+    // Base58 sha256 of the 8 byte module, as view_code shows it
+    let row: Vec<&str> = lines[1].split(',').collect();
+    assert_eq!(row[0], "5");
+    assert_eq!(
+        &row[3..7],
+        [
+            "Deploy",
+            "app.fixture.near",
+            "upgrade",
+            "AwLEfgaHQguPVVLGUV9Sf5QKGrMMMr2N6MVSjBj9dJAh"
+        ]
+    );
+}
+
+#[test]
+fn test_fixture_liquid_staking() {
+    let client = fixture_client();
+
+    let response = get_json(
+        &client,
+        &format!(
+            "/proposals/{}?category=stake-delegation&include_parsed=true",
+            STAKING_DAO_ID
+        ),
+    );
+    let proposals = response["proposals"].as_array().expect("proposals array");
+    let stakes: Vec<&serde_json::Value> = proposals
+        .iter()
+        .map(|proposal| &proposal["stake_info"])
+        .collect();
+    assert_eq!(stakes.len(), 3);
+
+    // NEAR deposited with the call
+    assert_eq!(stakes[0]["proposal_type"], "stake");
+    assert_eq!(stakes[0]["validator"], "meta-pool.near");
+    assert_eq!(stakes[0]["token"], "");
+    assert_eq!(stakes[0]["pool_token"], "meta-pool.near");
+    assert_eq!(stakes[0]["amount"], "10000000000000000000000000");
+    assert_eq!(stakes[0]["decimals"].as_u64(), Some(24));
+
+    // wNEAR sent to the contract with ft_transfer_call
+    assert_eq!(stakes[1]["proposal_type"], "stake");
+    assert_eq!(stakes[1]["validator"], "linear-protocol.near");
+    assert_eq!(stakes[1]["token"], "wrap.near");
+    assert_eq!(stakes[1]["pool_token"], "linear-protocol.near");
+    assert_eq!(stakes[1]["amount"], "5000000000000000000000000");
+    assert_eq!(stakes[1]["decimals"].as_u64(), Some(24));
+
+    // stNEAR burnt for NEAR, the amount is in the pool token
+    assert_eq!(stakes[2]["proposal_type"], "unstake");
+    assert_eq!(stakes[2]["validator"], "meta-pool.near");
+    assert_eq!(stakes[2]["token"], "meta-pool.near");
+    assert_eq!(stakes[2]["amount"], "3000000000000000000000000");
+    let resolved: Vec<&serde_json::Value> = proposals
+        .iter()
+        .map(|proposal| &proposal["resolved_validator"])
+        .collect();
+    assert_eq!(
+        resolved,
+        vec!["meta-pool.near", "linear-protocol.near", "meta-pool.near"]
+    );
+
+    // Validators are only resolved for the parsed output
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?category=stake-delegation", STAKING_DAO_ID),
+    );
+    let proposals = response["proposals"].as_array().expect("proposals array");
+    assert!(
+        proposals
+            .iter()
+            .all(|proposal| proposal.get("resolved_validator").is_none())
+    );
+
+    // The wNEAR deposit isn't a payment to the staking contract, only paying Dave is
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}?category=payments", STAKING_DAO_ID),
+    );
+    assert_eq!(response["total"].as_u64(), Some(1));
+    assert_eq!(response["proposals"][0]["id"].as_u64(), Some(3));
+
+    let response = get_json(
+        &client,
+        &format!("/proposals/{}/recipients", STAKING_DAO_ID),
+    );
+    assert_eq!(response["recipients"], serde_json::json!(["dave.near"]));
+
+    let response = get_json(&client, &format!("/stats/{}/proposers", STAKING_DAO_ID));
+    let alice = response["proposers"]
+        .as_array()
+        .expect("proposers array")
+        .iter()
+        .find(|proposer| proposer["account_id"] == "alice.near")
+        .expect("alice's stats");
+    assert_eq!(alice["approved"].as_u64(), Some(3));
+    assert_eq!(
+        alice["approved_payments"],
+        serde_json::json!({ "wrap.near": "1" })
+    );
+}
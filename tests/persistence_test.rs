@@ -0,0 +1,219 @@
+use borsh::BorshSerialize;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::AccountId;
+use sputnik_indexer::cache::{
+    CachedLookup, CachedProposal, CachedProposals, LockupCache, LookupCache, StakingPoolCache,
+};
+use sputnik_indexer::persistence::{CachePersistence, read_cache_file};
+use sputnik_indexer::scraper::{DaoConfig, Policy, Proposal, StateVersion, TxMetadata, TxStatus};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+const FIXTURE_DAO_ID: &str = "fixture-dao.sputnik-dao.near";
+
+// Layout of the cache file before it had a header: the proposal cache alone, with the
+// transaction logs of the time
+#[derive(BorshSerialize)]
+struct BaselineTx {
+    signer_id: AccountId,
+    predecessor_id: AccountId,
+    reciept_hash: CryptoHash,
+    block_height: u64,
+    timestamp: u64,
+}
+
+#[derive(BorshSerialize)]
+struct BaselineEntry {
+    txs_log: Vec<BaselineTx>,
+}
+
+// A file of its own per test, tests run in parallel
+fn cache_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!(
+        "persistence_test_{}_{}.bin",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
+
+fn rejected_files(path: &str) -> Vec<PathBuf> {
+    let path = PathBuf::from(path);
+    let prefix = format!("{}.", path.file_name().unwrap().to_string_lossy());
+    std::fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|rejected| {
+            let name = rejected.file_name().unwrap().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".rejected")
+        })
+        .collect()
+}
+
+fn fixture<T: serde::de::DeserializeOwned>(file: &str) -> T {
+    let path = format!("tests/fixtures/{}/{}", FIXTURE_DAO_ID, file);
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn tx(block_height: u64) -> TxMetadata {
+    TxMetadata {
+        signer_id: "alice.near".parse().unwrap(),
+        predecessor_id: "alice.near".parse().unwrap(),
+        reciept_hash: CryptoHash::default(),
+        block_height,
+        timestamp: block_height * 1_000_000_000,
+        status: TxStatus::Succeeded,
+        gas_burnt: 2_428_000_000_000,
+        actions: None,
+    }
+}
+
+#[test]
+fn test_missing_file_gives_empty_caches() {
+    let path = cache_path("missing");
+
+    let restored = read_cache_file(&path).expect("empty caches");
+    assert!(restored.store.is_empty());
+    assert!(restored.proposal_cache.is_empty());
+}
+
+#[test]
+fn test_decode_baseline_file() {
+    let path = cache_path("baseline");
+    let baseline = HashMap::from([(
+        (FIXTURE_DAO_ID.to_string(), 3u64),
+        BaselineEntry {
+            txs_log: vec![BaselineTx {
+                signer_id: "alice.near".parse().unwrap(),
+                predecessor_id: "alice.near".parse().unwrap(),
+                reciept_hash: CryptoHash::default(),
+                block_height: 100,
+                timestamp: 1_700_000_000_000_000_000,
+            }],
+        },
+    )]);
+    std::fs::write(&path, borsh::to_vec(&baseline).unwrap()).unwrap();
+
+    let restored = read_cache_file(&path).expect("baseline file decodes");
+    assert!(restored.store.is_empty());
+    assert_eq!(restored.proposal_cache.len(), 1);
+    let entry = restored
+        .proposal_cache
+        .get(&(FIXTURE_DAO_ID.to_string(), 3))
+        .expect("restored entry");
+    assert_eq!(entry.txs_log.len(), 1);
+    assert_eq!(entry.txs_log[0].block_height, 100);
+    assert_eq!(entry.txs_log[0].timestamp, 1_700_000_000_000_000_000);
+    // Outcomes weren't cached yet, and the logs predate backfilling
+    assert_eq!(entry.txs_log[0].status, TxStatus::Unknown);
+    assert_eq!(entry.backfill_block, None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[rocket::async_test]
+async fn test_round_trip_current_version() {
+    let path = cache_path("round_trip");
+    let proposals: Vec<Proposal> = fixture("proposals.json");
+    let policy: Policy = fixture("policy.json");
+    let config: DaoConfig = fixture("config.json");
+    let proposal = proposals[3].clone();
+
+    let caches = CachePersistence {
+        store: Arc::new(
+            [(
+                FIXTURE_DAO_ID.to_string(),
+                CachedProposals {
+                    proposals: proposals.into(),
+                    policy,
+                    config,
+                    last_updated: Instant::now(),
+                    version: StateVersion::V2,
+                    last_proposal_id: 6,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        ),
+        proposal_cache: Arc::new(
+            [(
+                (FIXTURE_DAO_ID.to_string(), 3),
+                CachedProposal {
+                    proposal,
+                    last_updated: Instant::now(),
+                    txs_log: vec![tx(100), tx(120)],
+                    backfill_block: Some(90),
+                },
+            )]
+            .into_iter()
+            .collect(),
+        ),
+        staking_pool_cache: StakingPoolCache(LookupCache::from_entries(HashMap::from([(
+            "alice.near".to_string(),
+            CachedLookup {
+                value: Some("pool.poolv1.near".to_string()),
+                fetched_at: 1_700_000_000,
+            },
+        )]))),
+        lockup_cache: LockupCache::default(),
+    };
+    caches.write_to(&path).await.expect("cache file written");
+    assert!(!PathBuf::from(format!("{}.tmp", path)).exists());
+
+    let restored = read_cache_file(&path).expect("cache file decodes");
+    let dao = restored.store.get(FIXTURE_DAO_ID).expect("restored DAO");
+    let ids: Vec<u64> = dao.proposals.iter().map(|p| p.id).collect();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(dao.last_proposal_id, 6);
+    assert!(matches!(dao.version, StateVersion::V2));
+    drop(dao);
+
+    let entry = restored
+        .proposal_cache
+        .get(&(FIXTURE_DAO_ID.to_string(), 3))
+        .expect("restored proposal");
+    // Only the transaction log is saved, the proposal is fetched again
+    let heights: Vec<u64> = entry.txs_log.iter().map(|tx| tx.block_height).collect();
+    assert_eq!(heights, vec![100, 120]);
+    assert_eq!(entry.txs_log[0].status, TxStatus::Succeeded);
+    assert_eq!(entry.txs_log[0].gas_burnt, 2_428_000_000_000);
+    assert_eq!(entry.backfill_block, Some(90));
+    drop(entry);
+
+    let staking_pools = restored.staking_pool_cache.0.entries().await;
+    assert_eq!(
+        staking_pools["alice.near"].value.as_deref(),
+        Some("pool.poolv1.near")
+    );
+    assert_eq!(restored.lockup_cache.0.count().await, 0);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_unreadable_files_are_set_aside() {
+    let newer = [b"SDCC".as_slice(), &99u32.to_le_bytes()].concat();
+    let unknown = [b"SDCC".as_slice(), &0u32.to_le_bytes()].concat();
+    let truncated = b"SDCC\x02".to_vec();
+    let corrupt = [b"SDCC".as_slice(), &2u32.to_le_bytes(), &[7, 7, 7]].concat();
+
+    for (name, serialized, message) in [
+        ("newer", newer, "schema version 99"),
+        ("unknown", unknown, "unknown schema version 0"),
+        ("truncated", truncated, "header is truncated"),
+        ("corrupt", corrupt, ""),
+    ] {
+        let path = cache_path(name);
+        std::fs::write(&path, &serialized).unwrap();
+
+        let error = read_cache_file(&path).err().expect("unreadable file");
+        assert!(error.to_string().contains(message), "{name}: {error}");
+        // Kept for recovery instead of being overwritten on the next shutdown
+        assert!(!PathBuf::from(&path).exists(), "{name}");
+        let rejected = rejected_files(&path);
+        assert_eq!(rejected.len(), 1, "{name}");
+        assert_eq!(std::fs::read(&rejected[0]).unwrap(), serialized, "{name}");
+        std::fs::remove_file(&rejected[0]).unwrap();
+    }
+}
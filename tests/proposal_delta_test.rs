@@ -0,0 +1,150 @@
+use near_primitives::types::AccountId;
+use serde_json::{Value, json};
+use sputnik_indexer::cache::{CachedProposals, ProposalStore, get_latest_dao_cache};
+use sputnik_indexer::rpc_client::get_rpc_client;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::time::Duration;
+
+// Copied from tests/fixtures, so the recorded proposals can change between refreshes
+const FIXTURE_DAO_ID: &str = "fixture-dao.sputnik-dao.near";
+// The fixture DAO with an actions log, as on current contracts
+const LOGGED_DAO_ID: &str = "logged-dao.sputnik-dao.near";
+
+static USE_FIXTURES: Once = Once::new();
+
+fn fixtures_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("proposal_delta_test_{}", std::process::id()))
+}
+
+fn use_fixtures() {
+    // Settings are loaded once per process, before any DAO is fetched
+    USE_FIXTURES.call_once(|| {
+        let dir = fixtures_dir();
+        let _ = std::fs::remove_dir_all(&dir);
+        copy_dir(Path::new("tests/fixtures"), &dir);
+        copy_dir(
+            &Path::new("tests/fixtures").join(FIXTURE_DAO_ID),
+            &dir.join(LOGGED_DAO_ID),
+        );
+        sputnik_indexer::use_fixtures(dir.to_str().unwrap());
+    });
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            std::fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+fn read_fixture(dao_id: &str, file: &str) -> Value {
+    let path = fixtures_dir().join(dao_id).join(file);
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn write_fixture(dao_id: &str, file: &str, value: &Value) {
+    let path = fixtures_dir().join(dao_id).join(file);
+    std::fs::write(path, serde_json::to_vec_pretty(value).unwrap()).unwrap();
+}
+
+// Every request refreshes, with the previous fetch as the base of the delta
+async fn refresh(store: &ProposalStore, dao_id: &str) -> CachedProposals {
+    let dao_id: AccountId = dao_id.parse().unwrap();
+    get_latest_dao_cache(&get_rpc_client(), store, &dao_id, Duration::ZERO)
+        .await
+        .expect("DAO refreshed")
+}
+
+fn summary(cached: &CachedProposals) -> Vec<(u64, String, String)> {
+    cached
+        .proposals
+        .iter()
+        .map(|p| (p.id, p.description.clone(), format!("{:?}", p.status)))
+        .collect()
+}
+
+// A copy of proposal 3 under a new id, still open
+fn new_proposal(proposals: &[Value], id: u64) -> Value {
+    let mut proposal = proposals[3].clone();
+    proposal["id"] = json!(id);
+    proposal["description"] = json!("New proposal");
+    proposal["status"] = json!("InProgress");
+    proposal
+}
+
+#[rocket::async_test]
+async fn test_refetches_in_progress_proposals_without_actions_log() {
+    use_fixtures();
+    let store = ProposalStore::default();
+
+    let first = refresh(&store, FIXTURE_DAO_ID).await;
+    assert_eq!(first.last_proposal_id, 6);
+    let before = summary(&first);
+
+    let mut proposals: Vec<Value> =
+        serde_json::from_value(read_fixture(FIXTURE_DAO_ID, "proposals.json")).unwrap();
+    // Decided since, and a change to a decided proposal, which can't happen on chain
+    // and only shows that decided proposals aren't downloaded again
+    proposals[3]["status"] = json!("Approved");
+    proposals[0]["description"] = json!("Edited");
+    proposals.push(new_proposal(&proposals, 6));
+    write_fixture(FIXTURE_DAO_ID, "proposals.json", &json!(proposals));
+
+    let second = refresh(&store, FIXTURE_DAO_ID).await;
+    assert_eq!(second.last_proposal_id, 7);
+    let mut merged = before;
+    merged[3].2 = "Approved".to_string();
+    merged.push((6, "New proposal".to_string(), "InProgress".to_string()));
+    assert_eq!(summary(&second), merged);
+    assert_eq!(
+        second.proposals[0].description,
+        first.proposals[0].description
+    );
+}
+
+#[rocket::async_test]
+async fn test_refetches_acted_proposals_from_actions_log() {
+    use_fixtures();
+    let store = ProposalStore::default();
+
+    // Every InProgress proposal carries its own log on current contracts
+    let mut proposals: Vec<Value> =
+        serde_json::from_value(read_fixture(LOGGED_DAO_ID, "proposals.json")).unwrap();
+    for (proposal, block_height) in proposals[2..].iter_mut().zip(100000030u64..) {
+        proposal["last_actions_log"] = json!([{ "block_height": block_height.to_string() }]);
+    }
+    write_fixture(LOGGED_DAO_ID, "proposals.json", &json!(proposals));
+
+    let first = refresh(&store, LOGGED_DAO_ID).await;
+    assert_eq!(first.last_proposal_id, 6);
+    let before = summary(&first);
+
+    // Only proposal 4 was voted on since, the log reaches back past the cached actions
+    let actions_log = json!([
+        { "account_id": "alice.near", "proposal_id": "3", "action": "VoteApprove", "block_height": "100000031" },
+        { "account_id": "bob.near", "proposal_id": "4", "action": "VoteApprove", "block_height": "100000040" },
+        { "account_id": "bob.near", "proposal_id": "6", "action": "AddProposal", "block_height": "100000041" },
+    ]);
+    write_fixture(LOGGED_DAO_ID, "actions_log.json", &actions_log);
+    proposals[4]["status"] = json!("Approved");
+    proposals[4]["last_actions_log"] = json!([{ "block_height": "100000040" }]);
+    // The log shows nothing new on proposal 3, so it isn't downloaded again
+    proposals[3]["status"] = json!("Rejected");
+    proposals.push(new_proposal(&proposals, 6));
+    write_fixture(LOGGED_DAO_ID, "proposals.json", &json!(proposals));
+
+    let second = refresh(&store, LOGGED_DAO_ID).await;
+    assert_eq!(second.last_proposal_id, 7);
+    let mut merged = before;
+    merged[4].2 = "Approved".to_string();
+    merged.push((6, "New proposal".to_string(), "InProgress".to_string()));
+    assert_eq!(summary(&second), merged);
+    assert_eq!(summary(&second)[3].2, "InProgress");
+}
@@ -0,0 +1,173 @@
+use mockito::{Matcher, Mock, Server, ServerGuard};
+use near_jsonrpc_client::methods::query::RpcQueryRequest;
+use near_primitives::types::{Finality, FunctionArgs};
+use near_primitives::views::QueryRequest;
+use sputnik_indexer::rpc_client::RpcPool;
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+// Short backoffs and a small circuit breaker window, so retries and the breaker can be
+// observed within a test
+const RPC_CONFIG: &str = r#"
+[rpc]
+max_attempts = 3
+initial_backoff_ms = 40
+max_backoff_ms = 80
+breaker_window = 4
+breaker_failure_rate = 1.0
+breaker_open_secs = 1
+"#;
+
+const CALL_RESULT: &str = r#"{"jsonrpc":"2.0","id":"dontcare","result":{"result":[],"logs":[],"block_height":1,"block_hash":"11111111111111111111111111111111"}}"#;
+
+static CONFIG: Once = Once::new();
+
+// Settings are loaded once per process, on the first RpcPool
+fn use_test_config() {
+    CONFIG.call_once(|| {
+        let path = std::env::temp_dir().join(format!("rpc_pool_test_{}.toml", std::process::id()));
+        std::fs::write(&path, RPC_CONFIG).expect("write test config");
+        // SAFETY: the other tests wait on CONFIG, nothing reads the environment meanwhile
+        unsafe { std::env::set_var("CONFIG_PATH", &path) };
+    });
+}
+
+fn pool(servers: &[&ServerGuard]) -> RpcPool {
+    use_test_config();
+    let urls: Vec<String> = servers.iter().map(|server| server.url()).collect();
+    RpcPool::new(&urls, &[], None, 1)
+}
+
+fn view_call() -> RpcQueryRequest {
+    RpcQueryRequest {
+        block_reference: Finality::Final.into(),
+        request: QueryRequest::CallFunction {
+            account_id: "fixture-dao.sputnik-dao.near".parse().unwrap(),
+            method_name: "get_config".to_string(),
+            args: FunctionArgs::from(vec![]),
+        },
+    }
+}
+
+async fn respond(server: &mut ServerGuard, status: usize, hits: usize) -> Mock {
+    server
+        .mock("POST", Matcher::Any)
+        .with_status(status)
+        .with_header("content-type", "application/json")
+        .with_body(if status == 200 { CALL_RESULT } else { "" })
+        .expect(hits)
+        .create_async()
+        .await
+}
+
+#[rocket::async_test]
+async fn test_failover_to_next_endpoint() {
+    let mut failing = Server::new_async().await;
+    let mut healthy = Server::new_async().await;
+    let unavailable = respond(&mut failing, 503, 1).await;
+    let ok = respond(&mut healthy, 200, 2).await;
+    let pool = pool(&[&failing, &healthy]);
+
+    assert!(pool.call(view_call()).await.is_ok());
+    // The failed endpoint is unhealthy now, so the healthy one is asked first
+    assert!(pool.call(view_call()).await.is_ok());
+
+    unavailable.assert_async().await;
+    ok.assert_async().await;
+}
+
+#[rocket::async_test]
+async fn test_transient_errors_are_retried_with_backoff() {
+    let mut server = Server::new_async().await;
+    let rate_limited = respond(&mut server, 429, 2).await;
+    let ok = respond(&mut server, 200, 1).await;
+    let pool = pool(&[&server]);
+
+    let started = Instant::now();
+    assert!(pool.call(view_call()).await.is_ok());
+    // Randomized between half and the full delay: 20-40ms, then 40-80ms
+    assert!(started.elapsed() >= Duration::from_millis(60));
+
+    rate_limited.assert_async().await;
+    ok.assert_async().await;
+}
+
+#[rocket::async_test]
+async fn test_request_errors_are_not_retried() {
+    let mut server = Server::new_async().await;
+    let bad_request = respond(&mut server, 400, 1).await;
+    let pool = pool(&[&server]);
+
+    assert!(pool.call(view_call()).await.is_err());
+
+    bad_request.assert_async().await;
+}
+
+#[rocket::async_test]
+async fn test_circuit_breaker_opens_and_closes() {
+    let mut server = Server::new_async().await;
+    // A call makes 3 attempts, the 4th failure in the window opens the breaker
+    let unavailable = respond(&mut server, 503, 4).await;
+    let pool = pool(&[&server]);
+
+    assert!(pool.call(view_call()).await.is_err());
+    assert!(pool.is_available());
+    assert!(pool.call(view_call()).await.is_err());
+    assert!(!pool.is_available());
+
+    // Open: fails without reaching the endpoint
+    assert!(pool.call(view_call()).await.is_err());
+    unavailable.assert_async().await;
+
+    // Half-open after the cool-down: one failed trial call opens it again
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(pool.call(view_call()).await.is_err());
+    let unavailable = unavailable.expect(5);
+    unavailable.assert_async().await;
+    assert!(!pool.is_available());
+
+    // A successful trial call closes it
+    unavailable.remove_async().await;
+    let ok = respond(&mut server, 200, 2).await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(pool.call(view_call()).await.is_ok());
+    assert!(pool.is_available());
+    assert!(pool.call(view_call()).await.is_ok());
+    ok.assert_async().await;
+}
+
+#[rocket::async_test]
+async fn test_cancelled_trial_call_reopens_the_breaker() {
+    let mut server = Server::new_async().await;
+    let unavailable = respond(&mut server, 503, 4).await;
+    let pool = pool(&[&server]);
+
+    assert!(pool.call(view_call()).await.is_err());
+    assert!(pool.call(view_call()).await.is_err());
+    assert!(!pool.is_available());
+    unavailable.assert_async().await;
+    unavailable.remove_async().await;
+
+    // The trial call outlives its caller's timeout
+    let slow = server
+        .mock("POST", Matcher::Any)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|_| {
+            std::thread::sleep(Duration::from_millis(500));
+            CALL_RESULT.into()
+        })
+        .create_async()
+        .await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let cancelled = tokio::time::timeout(Duration::from_millis(100), pool.call(view_call())).await;
+    assert!(cancelled.is_err());
+    assert!(!pool.is_available());
+    slow.remove_async().await;
+
+    // The cancelled trial counts as failed, the next cool-down lets a new trial through
+    let ok = respond(&mut server, 200, 1).await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(pool.call(view_call()).await.is_ok());
+    assert!(pool.is_available());
+    ok.assert_async().await;
+}